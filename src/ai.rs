@@ -1,140 +1,1288 @@
-use rand::{thread_rng, Rng, rngs::ThreadRng, seq::SliceRandom};
+use std::cmp::Reverse;
+use std::time::{Duration, Instant};
 
-use crate::{Reversi, TilePos, Piece};
+use rand::{thread_rng, Rng, seq::SliceRandom};
+
+use crate::{Reversi, TilePos, Move, Piece, TranspositionTable, TableEntry, Bound, OpeningBook, Evaluator, WeightedEvaluator, ENDGAME_EMPTIES, solve_endgame, CancelToken};
+
+/// A score larger than any score the evaluation can produce
+const INFINITY: i32 = i32::MAX;
+
+/// The result of searching for the best move for the current player
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    /// The best move that was found
+    pub best_move: TilePos,
+    /// The score of the best move, from the perspective of the current player
+    pub score: i32,
+    /// The depth of the deepest search that was completed
+    pub depth: usize,
+    /// The line of play the search expects, starting with `best_move`
+    ///
+    /// This may be shorter than `depth` if the search did not keep track of the whole line.
+    pub pv: Vec<Move>,
+    /// The number of positions visited during the search
+    pub nodes: u64,
+    /// The amount of time the search took
+    pub elapsed: Duration,
+    /// More detailed measurements of how the search went
+    pub stats: SearchStats,
+}
+
+/// Counters collected while searching, useful for measuring how well the search is working
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SearchStats {
+    /// The number of times the rest of the moves in a position were skipped because one of the
+    /// moves was already too good for the opponent to allow
+    pub cutoffs: u64,
+    /// The number of positions that were cut off by ProbCut because a shallow search predicted
+    /// that the deeper search would fall outside the window
+    pub probcuts: u64,
+    /// The number of times an iteration had to be searched again because its score fell outside
+    /// of the aspiration window around the score of the previous iteration
+    pub aspiration_researches: u64,
+    /// The number of positions that were looked up in the transposition table
+    pub tt_probes: u64,
+    /// The number of lookups that found an entry for the position
+    pub tt_hits: u64,
+    /// The number of positions visited by each completed iteration of the search, starting with
+    /// the iteration of depth 1
+    pub iteration_nodes: Vec<u64>,
+}
+
+impl SearchStats {
+    /// Returns the fraction of transposition table lookups that found an entry, or zero if there
+    /// were no lookups
+    pub fn tt_hit_rate(&self) -> f64 {
+        if self.tt_probes == 0 {
+            return 0.0;
+        }
+
+        self.tt_hits as f64 / self.tt_probes as f64
+    }
+
+    /// Returns the effective branching factor: how many times more positions the last completed
+    /// iteration visited than the one before it
+    ///
+    /// Returns `None` if fewer than two iterations were completed.
+    pub fn effective_branching_factor(&self) -> Option<f64> {
+        match self.iteration_nodes[..] {
+            [.., previous, last] if previous > 0 => Some(last as f64 / previous as f64),
+            _ => None,
+        }
+    }
+
+    /// Adds the counters from the other stats to these stats
+    fn add_counters(&mut self, other: &SearchStats) {
+        self.cutoffs += other.cutoffs;
+        self.probcuts += other.probcuts;
+        self.tt_probes += other.tt_probes;
+        self.tt_hits += other.tt_hits;
+    }
+}
+
+impl SearchResult {
+    /// Creates a result for a move that was chosen without searching
+    fn unsearched(best_move: TilePos) -> Self {
+        Self {
+            best_move,
+            score: 0,
+            depth: 0,
+            pv: vec![Move::Place(best_move)],
+            nodes: 0,
+            elapsed: Duration::default(),
+            stats: SearchStats::default(),
+        }
+    }
+}
+
+/// Controls how strong the negamax AI plays
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SearchOptions<'a> {
+    /// The deepest the search is allowed to go, or `None` to search as deep as time allows
+    pub max_depth: Option<usize>,
+    /// The largest amount of random noise added to the score of each move at the root. Higher
+    /// values cause the AI to make more mistakes and 0 makes it play deterministically.
+    pub noise: i32,
+    /// If true, the AI plays moves from the opening book when there are any
+    pub use_book: bool,
+    /// If true, the AI solves the game exactly once there are few enough empty tiles left
+    pub use_endgame_solver: bool,
+    /// If true, positions are pruned when a shallow search predicts that a deeper search would
+    /// fall outside of the window (see `Search::probcut`)
+    pub probcut: bool,
+    /// If set, the search is abandoned as soon as this is canceled, returning the best move found
+    /// so far
+    pub cancel: Option<&'a CancelToken>,
+}
+
+impl Default for SearchOptions<'_> {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            noise: 100,
+            use_book: true,
+            use_endgame_solver: true,
+            probcut: false,
+            cancel: None,
+        }
+    }
+}
 
 /// Returns a move for the current player computed automatically
-pub fn compute_ai_move(game: &Reversi, valid_moves: &[TilePos]) -> TilePos {
+///
+/// The search stops once the given amount of time has passed, returning the best move found by
+/// the deepest search that was completed. Positions in the builtin opening book are played from
+/// the book without searching.
+pub fn compute_ai_move(game: &Reversi, valid_moves: &[TilePos], budget: Duration) -> SearchResult {
+    compute_ai_move_with_book(game, valid_moves, budget, OpeningBook::builtin())
+}
+
+/// Returns a move for the current player computed automatically, consulting the given opening
+/// book before searching
+pub fn compute_ai_move_with_book(
+    game: &Reversi,
+    valid_moves: &[TilePos],
+    budget: Duration,
+    book: &OpeningBook,
+) -> SearchResult {
+    let evaluator = WeightedEvaluator::default();
+    let options = SearchOptions::default();
+    search_with_options(&mut thread_rng(), &evaluator, game, valid_moves, budget, book, &options)
+}
+
+/// Returns a move for the current player computed automatically using the given options
+///
+/// All of the randomness in the search comes from the given random number generator and positions
+/// are scored with the given evaluator.
+pub(crate) fn search_with_options<R: Rng + ?Sized>(
+    rng: &mut R,
+    evaluator: &(dyn Evaluator + Sync),
+    game: &Reversi,
+    valid_moves: &[TilePos],
+    budget: Duration,
+    book: &OpeningBook,
+    options: &SearchOptions,
+) -> SearchResult {
+    #[allow(dead_code)]
     enum AIType {
         Random,
         Negamax,
     }
 
-    let mut rng = thread_rng();
-    match AIType::Negamax {
-        AIType::Random => random_ai(&mut rng, game, valid_moves),
-        AIType::Negamax => negamax_ai(&mut rng, game, valid_moves),
-    }
+    let start = Instant::now();
+    let mut result = match AIType::Negamax {
+        AIType::Random => random_ai(rng, game, valid_moves),
+        AIType::Negamax => {
+            let book_move = if options.use_book { book.choose_move(rng, game) } else { None };
+            match book_move {
+                Some(best_move) => SearchResult::unsearched(best_move),
+                None => negamax_ai(rng, evaluator, game, valid_moves, budget, options),
+            }
+        },
+    };
+    result.elapsed = start.elapsed();
+
+    result
 }
 
 /// Randomly chooses a move from the set of valid moves
-fn random_ai(rng: &mut ThreadRng, _game: &Reversi, valid_moves: &[TilePos]) -> TilePos {
-    *valid_moves.choose(rng).expect("bug: no valid moves to choose from")
+fn random_ai<R: Rng + ?Sized>(rng: &mut R, _game: &Reversi, valid_moves: &[TilePos]) -> SearchResult {
+    let best_move = *valid_moves.choose(rng).expect("bug: no valid moves to choose from");
+    SearchResult::unsearched(best_move)
+}
+
+/// Chooses a move based on the negamax algorithm, using iterative deepening to search as deep as
+/// possible within the given time budget
+fn negamax_ai<R: Rng + ?Sized>(
+    rng: &mut R,
+    evaluator: &(dyn Evaluator + Sync),
+    game: &Reversi,
+    valid_moves: &[TilePos],
+    budget: Duration,
+    options: &SearchOptions,
+) -> SearchResult {
+    let cancel = options.cancel.cloned().unwrap_or_default()
+        .with_deadline(Instant::now() + budget);
+
+    // Near the end of the game, it is possible to search all the way to the end instead of
+    // relying on the evaluation function
+    if options.use_endgame_solver && game.grid().open_count() <= ENDGAME_EMPTIES {
+        if let Some(solution) = solve_endgame(game, &cancel) {
+            return SearchResult {
+                best_move: solution.best_move,
+                score: solution.score,
+                depth: game.grid().open_count(),
+                pv: vec![Move::Place(solution.best_move)],
+                nodes: solution.nodes,
+                elapsed: Duration::default(),
+                stats: SearchStats::default(),
+            };
+        }
+    }
+
+    // The noise is only added at the root, and stays the same for every iteration so that the
+    // iterations agree on which moves it favors
+    let root_noise: Vec<_> = valid_moves.iter().map(|&pmove| {
+        let noise = if options.noise > 0 { rng.gen_range(-options.noise, options.noise) } else { 0 };
+        (pmove, noise)
+    }).collect();
+    let mut root = RootSearch::new(evaluator, &root_noise, options.probcut, game);
+
+    let mut result = SearchResult::unsearched(
+        *valid_moves.first().expect("bug: no valid moves to choose from"));
+    let depth_limit = options.max_depth.unwrap_or(usize::MAX);
+    for max_depth in 1..=depth_limit {
+        // The best move from the previous iteration is the most likely to still be the best
+        let pv_move = if max_depth == 1 { None } else { Some(result.best_move) };
+        // The first iteration always runs to completion so there is always a move to return
+        let stop = if max_depth == 1 { None } else { Some(&cancel) };
+        let guess = if max_depth == 1 { None } else { Some(result.score) };
+        let nodes_before = result.nodes;
+
+        let iteration = root.search(max_depth, pv_move, stop, guess, &mut result.nodes, &mut result.stats);
+        let reached_max_depth = match iteration {
+            Some((Some(best_move), score, reached_max_depth)) => {
+                result.pv = principal_variation(game, best_move, max_depth, |hash| root.best_move(hash));
+                result.best_move = best_move;
+                result.score = score;
+                result.depth = max_depth;
+                result.stats.iteration_nodes.push(result.nodes - nodes_before);
+                reached_max_depth
+            },
+
+            // Ran out of time, so this iteration has to be thrown away
+            _ => break,
+        };
+
+        // If no part of the search was cut short by the depth limit, the search reached the end of
+        // the game everywhere and searching any deeper would not change anything
+        if !reached_max_depth || cancel.is_canceled() {
+            break;
+        }
+    }
+
+    result
+}
+
+/// Searches the moves at the root of the tree, keeping the state that carries over from one
+/// iteration of `negamax_ai` to the next
+struct RootSearch<'a> {
+    evaluator: &'a (dyn Evaluator + Sync),
+    /// The random noise added to the score of each move at the root
+    root_noise: &'a [(TilePos, i32)],
+    /// If true, positions are pruned using ProbCut
+    probcut: bool,
+    /// The position at the root
+    #[cfg(feature = "parallel")]
+    game: &'a Reversi,
+    /// The copy of the game that the search makes and unmakes moves on
+    #[cfg(not(feature = "parallel"))]
+    search_game: Reversi,
+    /// The results of previously searched positions, shared between iterations so each one
+    /// benefits from the previous ones
+    #[cfg(not(feature = "parallel"))]
+    table: TranspositionTable,
+    #[cfg(not(feature = "parallel"))]
+    heuristics: MoveHeuristics,
+    /// One transposition table for each search thread
+    #[cfg(feature = "parallel")]
+    tables: Vec<TranspositionTable>,
 }
 
-/// Chooses a move based on the negamax algorithm
-fn negamax_ai(rng: &mut ThreadRng, game: &Reversi, valid_moves: &[TilePos]) -> TilePos {
-    let (pmove, _score) = negamax(rng, game, valid_moves, false, 0);
-    pmove.unwrap()
+impl<'a> RootSearch<'a> {
+    fn new(
+        evaluator: &'a (dyn Evaluator + Sync),
+        root_noise: &'a [(TilePos, i32)],
+        probcut: bool,
+        game: &'a Reversi,
+    ) -> Self {
+        Self {
+            evaluator,
+            root_noise,
+            probcut,
+            #[cfg(feature = "parallel")]
+            game,
+            #[cfg(not(feature = "parallel"))]
+            search_game: game.clone(),
+            #[cfg(not(feature = "parallel"))]
+            table: TranspositionTable::default(),
+            #[cfg(not(feature = "parallel"))]
+            heuristics: MoveHeuristics::default(),
+            #[cfg(feature = "parallel")]
+            tables: parallel::new_tables(),
+        }
+    }
+
+    /// Searches every move at the root to the given depth, returning the best move, its score and
+    /// whether any position was cut short by the depth limit, or `None` if the search was stopped
+    ///
+    /// Aspiration windows: the score is unlikely to change much from the guess (e.g. the score of
+    /// the previous iteration), so a narrow window around it is searched first. A score outside of
+    /// the window is only a bound, so the search is repeated with that side of the window opened
+    /// up. Without a guess, the full window is searched right away.
+    ///
+    /// The number of positions visited is added to `nodes` and the counters are added to `stats`,
+    /// even if the search was stopped.
+    fn search(
+        &mut self,
+        max_depth: usize,
+        pv_move: Option<TilePos>,
+        stop: Option<&CancelToken>,
+        guess: Option<i32>,
+        nodes: &mut u64,
+        stats: &mut SearchStats,
+    ) -> Option<(Option<TilePos>, i32, bool)> {
+        let (mut alpha, mut beta) = match guess {
+            Some(guess) => (shift(guess, ASPIRATION_WINDOW), shift(guess, -ASPIRATION_WINDOW)),
+            None => (-INFINITY, INFINITY),
+        };
+        let mut reached_max_depth = false;
+        loop {
+            let (best_move, score, reached) = self.search_window(alpha, beta, max_depth, pv_move, stop, nodes, stats)?;
+            reached_max_depth |= reached;
+
+            if score <= alpha && alpha > -INFINITY {
+                alpha = -INFINITY;
+            } else if score >= beta && beta < INFINITY {
+                beta = INFINITY;
+            } else {
+                break Some((best_move, score, reached_max_depth));
+            }
+            stats.aspiration_researches += 1;
+        }
+    }
+
+    /// Searches every move at the root with the window (alpha, beta), returning the same as
+    /// `search`
+    ///
+    /// A score that is less than or equal to alpha is an upper bound of the real score and a
+    /// score that is greater than or equal to beta is a lower bound.
+    #[allow(clippy::too_many_arguments)]
+    fn search_window(
+        &mut self,
+        alpha: i32,
+        beta: i32,
+        max_depth: usize,
+        pv_move: Option<TilePos>,
+        stop: Option<&CancelToken>,
+        nodes: &mut u64,
+        stats: &mut SearchStats,
+    ) -> Option<(Option<TilePos>, i32, bool)> {
+        #[cfg(not(feature = "parallel"))]
+        {
+            let mut search = Search {
+                evaluator: self.evaluator,
+                table: &mut self.table,
+                heuristics: &mut self.heuristics,
+                root_noise: self.root_noise,
+                probcut: self.probcut,
+                pv_move,
+                stop,
+                max_depth,
+                reached_max_depth: false,
+                nodes: 0,
+                stats: SearchStats::default(),
+            };
+
+            let outcome = search.negamax(&mut self.search_game, alpha, beta, false, 0);
+            *nodes += search.nodes;
+            stats.add_counters(&search.stats);
+            outcome.map(|(best_move, score)| (best_move, score, search.reached_max_depth))
+        }
+
+        #[cfg(feature = "parallel")]
+        parallel::search_root(self.evaluator, self.root_noise, self.probcut, self.game, &mut self.tables,
+            pv_move, stop, (alpha, beta), max_depth, nodes, stats)
+    }
+
+    /// Returns the best move stored by the search for the position with the given hash (if any)
+    fn best_move(&self, hash: u64) -> Option<TilePos> {
+        #[cfg(not(feature = "parallel"))]
+        return self.table.probe(hash).and_then(|entry| entry.best_move);
+
+        #[cfg(feature = "parallel")]
+        return self.tables.iter().find_map(|table| table.probe(hash).and_then(|entry| entry.best_move));
+    }
 }
 
-/// The negamax algorithm
+/// Follows the best moves stored by the search to find the line of play it expects, starting with
+/// the given best move and going at most `depth` moves deep
 ///
-/// Based on: https://en.wikipedia.org/wiki/Negamax
-fn negamax(
-    rng: &mut ThreadRng,
+/// The line stops early if the best move for a position is not known (e.g. because it was
+/// replaced in the transposition table).
+fn principal_variation(
     game: &Reversi,
-    valid_moves: &[TilePos],
-    skipped: bool,
+    best_move: TilePos,
     depth: usize,
-) -> (Option<TilePos>, i32) {
-    const MAX_DEPTH: usize = 4;
+    probe: impl Fn(u64) -> Option<TilePos>,
+) -> Vec<Move> {
+    let mut game = game.clone();
+    let mut pv = Vec::new();
+    let mut pmove = Move::Place(best_move);
+    loop {
+        let _ = game.apply(pmove);
+        pv.push(pmove);
+        if pv.len() >= depth || game.is_game_over() {
+            break;
+        }
+
+        pmove = if game.valid_moves().is_empty() {
+            Move::Pass
+        } else {
+            match probe(game.hash()) {
+                Some(pos) if game.valid_moves().contains(&pos) => Move::Place(pos),
+                _ => break,
+            }
+        };
+    }
+
+    pv
+}
+
+/// Scores every legal move for the current player by searching each of them to the given depth,
+/// returning the moves ranked from best to worst
+///
+/// Scores are from the perspective of the current player. Unlike the AI, the analysis never uses
+/// the opening book or adds any random noise, so every move is scored on the same terms. If the
+/// current player has no moves, the only move is `Move::Pass`. Returns no moves if the game is
+/// over.
+pub fn analyze(game: &Reversi, depth: usize) -> Vec<(Move, i32)> {
+    analyze_with_evaluator(&WeightedEvaluator::default(), game, depth)
+}
 
-    if depth >= MAX_DEPTH || game.grid().is_full() || (skipped && valid_moves.is_empty()) {
-        let score = negamax_score(rng, game, game.current_player());
-        return (None, score);
+/// Same as `analyze`, but scores positions with the given evaluator
+pub fn analyze_with_evaluator(
+    evaluator: &(dyn Evaluator + Sync),
+    game: &Reversi,
+    depth: usize,
+) -> Vec<(Move, i32)> {
+    if game.is_game_over() {
+        return Vec::new();
     }
 
-    // No valid moves, so skip the turn
-    if valid_moves.is_empty() {
-        let mut mgame = game.clone();
-        mgame.advance_turn();
-        let mvalid_moves = mgame.valid_moves();
-        return negamax(rng, &mgame, &mvalid_moves, true, depth + 1);
+    let mut moves: Vec<_> = game.valid_moves().iter().map(|&pos| Move::Place(pos)).collect();
+    if moves.is_empty() {
+        moves.push(Move::Pass);
     }
 
-    let mut max_move = None;
-    let mut max_score = i32::min_value();
-    for &pmove in valid_moves {
-        let mut mgame = game.clone();
-        mgame.make_move(pmove);
-        let mvalid_moves = mgame.valid_moves();
+    let mut game = game.clone();
+    let mut table = TranspositionTable::default();
+    let mut heuristics = MoveHeuristics::default();
+    let mut scores: Vec<_> = moves.into_iter().map(|pmove| {
+        let mut search = Search {
+            evaluator,
+            table: &mut table,
+            heuristics: &mut heuristics,
+            root_noise: &[],
+            probcut: false,
+            pv_move: None,
+            stop: None,
+            max_depth: depth.max(1),
+            reached_max_depth: false,
+            nodes: 0,
+            stats: SearchStats::default(),
+        };
+
+        let token = game.apply(pmove);
+        let skipped = pmove == Move::Pass;
+        let (_, score) = search.negamax(&mut game, -INFINITY, INFINITY, skipped, 1)
+            .expect("bug: search without a stop token was stopped");
+        game.unapply(token);
 
-        // Skipped is always false because we just made a move
-        let (_, score) = negamax(rng, &mgame, &mvalid_moves, false, depth + 1);
         // Negate score because the returned score is from the perspective of the opponent
-        // We want to find the score that is *lowest* from their perspective
-        let score = -score;
-        if score > max_score {
-            max_move = Some(pmove);
-            max_score = score;
+        (pmove, -score)
+    }).collect();
+
+    // The sort is stable, so ties keep the order of the valid moves
+    scores.sort_by_key(|&(_, score)| Reverse(score));
+    scores
+}
+
+/// The state of a single depth-limited negamax search
+struct Search<'a> {
+    /// Scores the positions at the end of the search
+    evaluator: &'a (dyn Evaluator + Sync),
+    /// The results of previously searched positions
+    table: &'a mut TranspositionTable,
+    /// The moves that caused cutoffs so far
+    heuristics: &'a mut MoveHeuristics,
+    /// The random noise added to the score of each move at the root
+    root_noise: &'a [(TilePos, i32)],
+    /// If true, positions are pruned using ProbCut
+    probcut: bool,
+    /// The best move at the root found by the previous iteration (if any), searched first
+    pv_move: Option<TilePos>,
+    /// The search is abandoned as soon as this is canceled
+    stop: Option<&'a CancelToken>,
+    /// The depth at which positions are evaluated instead of searched further
+    max_depth: usize,
+    /// True if any position was evaluated because it reached `max_depth` (as opposed to being the
+    /// end of the game)
+    reached_max_depth: bool,
+    /// The number of positions visited so far
+    nodes: u64,
+    stats: SearchStats,
+}
+
+impl<'a> Search<'a> {
+    /// The negamax algorithm with alpha-beta pruning
+    ///
+    /// Only scores within the window (alpha, beta) are computed exactly. A returned score that is
+    /// less than or equal to alpha is an upper bound of the real score and a returned score that
+    /// is greater than or equal to beta is a lower bound.
+    ///
+    /// Returns `None` if the search was stopped before it could be completed. The game is
+    /// left in the same state it was in when this function was called.
+    ///
+    /// Based on: https://en.wikipedia.org/wiki/Negamax
+    fn negamax(
+        &mut self,
+        game: &mut Reversi,
+        mut alpha: i32,
+        mut beta: i32,
+        skipped: bool,
+        depth: usize,
+    ) -> Option<(Option<TilePos>, i32)> {
+        if self.stop.is_some_and(|stop| stop.is_canceled()) {
+            return None;
+        }
+        self.nodes += 1;
+
+        let nmoves = game.valid_moves().len();
+        if game.grid().is_full() || (skipped && nmoves == 0) {
+            let score = negamax_score(self.evaluator, game, game.current_player());
+            return Some((None, score));
+        }
+        if depth >= self.max_depth {
+            self.reached_max_depth = true;
+            let score = negamax_score(self.evaluator, game, game.current_player());
+            return Some((None, score));
+        }
+
+        let remaining_depth = self.max_depth - depth;
+        let alpha_orig = alpha;
+        // The move to search first, since it was the best move last time this position was seen
+        let mut best_move_hint = None;
+        if depth == 0 {
+            // The root is never looked up so that a move is always returned for it
+            best_move_hint = self.pv_move;
+        } else if let Some(entry) = self.probe(game.hash()) {
+            best_move_hint = entry.best_move;
+            if entry.depth >= remaining_depth {
+                match entry.bound {
+                    Bound::Exact => return Some((entry.best_move, entry.score)),
+                    Bound::Lower => alpha = alpha.max(entry.score),
+                    Bound::Upper => beta = beta.min(entry.score),
+                }
+
+                if alpha >= beta {
+                    return Some((entry.best_move, entry.score));
+                }
+            }
+        }
+
+        // No valid moves, so skip the turn
+        if nmoves == 0 {
+            let token = game.apply(Move::Pass);
+            let result = self.negamax(game, -beta, -alpha, true, depth + 1);
+            game.unapply(token);
+            // The returned score is from the perspective of the opponent
+            let (_, score) = result?;
+            return Some((None, -score));
+        }
+
+        if self.probcut && depth > 0 {
+            if let Some(score) = self.probcut(game, alpha, beta, skipped, depth) {
+                return Some((None, score));
+            }
+        }
+
+        // Copied into a fixed-size buffer so the moves can be reordered without allocating
+        let mut moves = [TilePos {row: 0, col: 0}; MAX_MOVES];
+        let moves = &mut moves[..nmoves];
+        moves.copy_from_slice(game.valid_moves());
+        self.heuristics.order_moves(game, moves, best_move_hint, depth);
+
+        let mut max_move = None;
+        let mut max_score = -INFINITY;
+        for &pmove in moves.iter() {
+            let noise = if depth == 0 { self.root_noise(pmove) } else { 0 };
+
+            let token = game.apply(Move::Place(pmove));
+            // Skipped is always false because we just made a move. The window is shifted by the
+            // noise so that the score with the noise added is compared against alpha and beta.
+            let result = self.negamax(game, -shift(beta, noise), -shift(alpha, noise), false, depth + 1);
+            game.unapply(token);
+            let (_, score) = result?;
+
+            // Negate score because the returned score is from the perspective of the opponent
+            // We want to find the score that is *lowest* from their perspective
+            let score = -score + noise;
+            if score > max_score {
+                max_move = Some(pmove);
+                max_score = score;
+            }
+
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                // The opponent will never allow this position to be reached
+                self.stats.cutoffs += 1;
+                self.heuristics.record_cutoff(depth, pmove, remaining_depth);
+                break;
+            }
+        }
+
+        let bound = if max_score <= alpha_orig {
+            Bound::Upper
+        } else if max_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.store(TableEntry {
+            hash: game.hash(),
+            depth: remaining_depth,
+            score: max_score,
+            bound,
+            best_move: max_move,
+        });
+
+        Some((max_move, max_score))
+    }
+
+    /// Multi-ProbCut: predicts the result of searching the given position to the full remaining
+    /// depth with one or more much shallower searches
+    ///
+    /// The score of a deep search is usually close to the score of a shallow search of the same
+    /// position. If a shallow search scores the position far enough above beta (or below alpha),
+    /// the deep search would very likely fail high (or low) too, so it can be skipped. Returns the
+    /// bound to return from the position if it was cut off, or `None` if it needs to be searched.
+    ///
+    /// Each check is only tried if there is enough depth left to make it worthwhile, and checks
+    /// are tried from the shallowest (cheapest) to the deepest (most reliable).
+    ///
+    /// Based on: https://skatgame.net/mburo/ps/mpc.pdf
+    fn probcut(
+        &mut self,
+        game: &mut Reversi,
+        alpha: i32,
+        beta: i32,
+        skipped: bool,
+        depth: usize,
+    ) -> Option<i32> {
+        let remaining_depth = self.max_depth - depth;
+        // Windows that are still unbounded cannot be cut off
+        if alpha == -INFINITY || beta == INFINITY {
+            return None;
         }
+
+        for &(min_depth, shallow_depth, sigma) in PROBCUT_CHECKS {
+            if remaining_depth < min_depth {
+                break;
+            }
+
+            let margin = (PROBCUT_THRESHOLD * sigma as f64) as i32;
+            let high = beta.saturating_add(margin).min(INFINITY - 1);
+            let low = alpha.saturating_sub(margin).max(-INFINITY + 1);
+
+            // Search to the shallow depth by temporarily moving the depth limit
+            let max_depth = self.max_depth;
+            self.max_depth = depth + shallow_depth;
+            let fail_high = self.negamax(game, high - 1, high, skipped, depth)
+                .map(|(_, score)| score >= high);
+            let fail_low = match fail_high {
+                Some(false) => self.negamax(game, low, low + 1, skipped, depth)
+                    .map(|(_, score)| score <= low),
+                _ => Some(false),
+            };
+            self.max_depth = max_depth;
+
+            // If the shallow search was stopped, the deep search will be stopped too
+            if fail_high? {
+                self.stats.probcuts += 1;
+                return Some(beta);
+            }
+            if fail_low? {
+                self.stats.probcuts += 1;
+                return Some(alpha);
+            }
+        }
+
+        None
     }
 
-    (max_move, max_score)
+    /// Returns the random noise added to the score of the given move at the root
+    fn root_noise(&self, pmove: TilePos) -> i32 {
+        self.root_noise.iter()
+            .find(|&&(root_move, _)| root_move == pmove)
+            .map_or(0, |&(_, noise)| noise)
+    }
+
+    /// Looks up the given position in the transposition table, keeping track of the hit rate
+    fn probe(&mut self, hash: u64) -> Option<TableEntry> {
+        self.stats.tt_probes += 1;
+        let entry = self.table.probe(hash).copied();
+        if entry.is_some() {
+            self.stats.tt_hits += 1;
+        }
+
+        entry
+    }
 }
 
-/// Computes the negamax score for the given player. A higher score means that the current state of
-/// the board is better for the given player.
-fn negamax_score(rng: &mut ThreadRng, game: &Reversi, player: Piece) -> i32 {
-    // Computes the normal score of the game, then awards bonuses for corners and sides. Corners
-    // are more important than sides so they get a bigger bonus.
-    const CORNER_BONUS: i32 = 4;
-    const SIDE_BONUS: i32 = 2;
+/// Splits the search of the moves at the root across multiple threads
+#[cfg(feature = "parallel")]
+mod parallel {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicI32, AtomicU64, AtomicUsize, Ordering};
+    use std::thread;
 
-    let (x_score, o_score) = game.scores();
+    use crate::{Reversi, TilePos, Move, TranspositionTable, Evaluator, CancelToken};
 
-    let mut score = if player == Piece::X {
-        x_score as i32 - o_score as i32
-    } else {
-        o_score as i32 - x_score as i32
-    };
+    use super::{Search, SearchStats, MoveHeuristics, INFINITY, order_moves, shift};
 
-    // Adds the given value to the score. Setting the sign of the value based on whether the piece
-    // this value is being awarded for is the current player or the opponent.
-    let mut add_score = |piece: Piece, value: i32| if piece == player {
-        score += value;
-    } else {
-        score -= value;
-    };
+    /// Creates one transposition table for every thread that will be used by the search
+    pub(super) fn new_tables() -> Vec<TranspositionTable> {
+        let nthreads = thread::available_parallelism().map_or(1, |n| n.get());
+        (0..nthreads).map(|_| TranspositionTable::default()).collect()
+    }
 
-    let grid = game.grid();
+    /// Searches every move at the root to the given depth with the window (alpha, beta), with each
+    /// thread taking the next unsearched move until there are none left
+    ///
+    /// The best score found so far is shared between the threads so that moves searched later
+    /// can be cut off sooner, and no more moves are started once a move fails high. Returns the
+    /// best move, its score and whether any position was cut short by the depth limit, or `None`
+    /// if the search was stopped. Scores outside of the window are bounds, like the scores
+    /// returned by `Search::negamax`.
+    ///
+    /// The number of positions visited is added to `nodes` and the counters are added to `stats`,
+    /// even if the search was stopped.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn search_root(
+        evaluator: &(dyn Evaluator + Sync),
+        root_noise: &[(TilePos, i32)],
+        probcut: bool,
+        game: &Reversi,
+        tables: &mut [TranspositionTable],
+        pv_move: Option<TilePos>,
+        stop: Option<&CancelToken>,
+        (alpha, beta): (i32, i32),
+        max_depth: usize,
+        nodes: &mut u64,
+        stats: &mut SearchStats,
+    ) -> Option<(Option<TilePos>, i32, bool)> {
+        let mut moves = game.valid_moves().to_vec();
+        order_moves(game, &mut moves, pv_move);
 
-    // Adds to score based on the piece at the given position (if any)
-    let mut add_tile_score = |pos, value| if let Some(piece) = grid.tile(pos) {
-        add_score(piece, value);
-    };
+        // Starting from alpha means that scores below the window are only computed as bounds
+        let best_score = AtomicI32::new(alpha);
+        let next_move = AtomicUsize::new(0);
+        let nodes_visited = AtomicU64::new(0);
+        let thread_stats = Mutex::new(SearchStats::default());
+        // The score of each root move, in the same order as `moves`
+        let scores = Mutex::new(vec![None; moves.len()]);
+
+        let completed = thread::scope(|scope| {
+            let handles: Vec<_> = tables.iter_mut().map(|table| {
+                let game = game.clone();
+                let shared = Shared {
+                    evaluator,
+                    root_noise,
+                    probcut,
+                    moves: &moves,
+                    next_move: &next_move,
+                    best_score: &best_score,
+                    beta,
+                    scores: &scores,
+                    nodes: &nodes_visited,
+                    stats: &thread_stats,
+                };
+
+                scope.spawn(move || search_moves(game, table, shared, stop, max_depth))
+            }).collect();
+
+            handles.into_iter()
+                .map(|handle| handle.join().expect("bug: a search thread panicked"))
+                .collect::<Option<Vec<bool>>>()
+        });
+        *nodes += nodes_visited.into_inner();
+        stats.add_counters(&thread_stats.into_inner().expect("bug: a search thread panicked"));
+        let completed = completed?;
+        let reached_max_depth = completed.into_iter().any(|reached| reached);
+
+        // Ties go to the move that was ordered first. Moves are only left unsearched after another
+        // move failed high.
+        let scores = scores.into_inner().expect("bug: a search thread panicked");
+        let mut max_move = None;
+        let mut max_score = -INFINITY;
+        for (&pmove, score) in moves.iter().zip(scores) {
+            let score = match score {
+                Some(score) => score,
+                None => continue,
+            };
+            if max_move.is_none() || score > max_score {
+                max_move = Some(pmove);
+                max_score = score;
+            }
+        }
+
+        Some((max_move, max_score, reached_max_depth))
+    }
+
+    /// The state shared between all of the search threads
+    #[derive(Clone, Copy)]
+    struct Shared<'a> {
+        /// Scores the positions at the end of the search
+        evaluator: &'a (dyn Evaluator + Sync),
+        /// The random noise added to the score of each move at the root
+        root_noise: &'a [(TilePos, i32)],
+        /// If true, positions are pruned using ProbCut
+        probcut: bool,
+        /// The moves at the root, in the order they should be searched
+        moves: &'a [TilePos],
+        /// The index of the next move in `moves` that no thread has started searching yet
+        next_move: &'a AtomicUsize,
+        /// The best score found for any root move so far, or alpha if no move has beaten it yet
+        best_score: &'a AtomicI32,
+        /// The upper bound of the window at the root
+        beta: i32,
+        /// The score of each root move, in the same order as `moves`
+        scores: &'a Mutex<Vec<Option<i32>>>,
+        /// The total number of positions visited by all threads
+        nodes: &'a AtomicU64,
+        /// The counters collected by all threads
+        stats: &'a Mutex<SearchStats>,
+    }
+
+    /// Repeatedly takes the next unsearched root move and searches it until there are none left
+    ///
+    /// Returns whether any position was cut short by the depth limit, or `None` if the search was
+    /// stopped.
+    fn search_moves(
+        mut game: Reversi,
+        table: &mut TranspositionTable,
+        shared: Shared,
+        stop: Option<&CancelToken>,
+        max_depth: usize,
+    ) -> Option<bool> {
+        let mut reached_max_depth = false;
+        let mut heuristics = MoveHeuristics::default();
+
+        loop {
+            // Once a move fails high, the other moves cannot change the result
+            if shared.best_score.load(Ordering::SeqCst) >= shared.beta {
+                break Some(reached_max_depth);
+            }
+
+            let index = shared.next_move.fetch_add(1, Ordering::SeqCst);
+            let pmove = match shared.moves.get(index) {
+                Some(&pmove) => pmove,
+                None => break Some(reached_max_depth),
+            };
+
+            let mut search = Search {
+                evaluator: shared.evaluator,
+                table: &mut *table,
+                heuristics: &mut heuristics,
+                root_noise: shared.root_noise,
+                probcut: shared.probcut,
+                pv_move: None,
+                stop,
+                max_depth,
+                reached_max_depth: false,
+                nodes: 0,
+                stats: SearchStats::default(),
+            };
+
+            let alpha = shared.best_score.load(Ordering::SeqCst);
+            let noise = search.root_noise(pmove);
+            let token = game.apply(Move::Place(pmove));
+            let result = search.negamax(&mut game, -shift(shared.beta, noise), -shift(alpha, noise), false, 1);
+            game.unapply(token);
+            reached_max_depth |= search.reached_max_depth;
+            shared.nodes.fetch_add(search.nodes, Ordering::SeqCst);
+            shared.stats.lock().expect("bug: a search thread panicked").add_counters(&search.stats);
+
+            // Negate score because the returned score is from the perspective of the opponent
+            let score = -result?.1 + noise;
+            shared.best_score.fetch_max(score, Ordering::SeqCst);
+            shared.scores.lock().expect("bug: a search thread panicked")[index] = Some(score);
+        }
+    }
+}
+
+/// The most valid moves any position can have (one for every tile on the grid)
+const MAX_MOVES: usize = 8 * 8;
+
+/// Sorts the given moves so that the moves most likely to be good are searched first
+///
+/// Alpha-beta pruning cuts off much more of the search when the best move is searched first. The
+/// hinted move (e.g. the best move from a previous search) always goes first, followed by corners,
+/// then all other moves, with the squares next to corners last since they usually give the
+/// opponent a corner.
+pub(crate) fn order_moves(game: &Reversi, moves: &mut [TilePos], hint: Option<TilePos>) {
+    moves.sort_unstable_by_key(|&pos| {
+        if Some(pos) == hint {
+            return 0;
+        }
+
+        1 + square_priority(game, pos)
+    });
+}
+
+/// Returns how early a move to the given tile should be searched based only on where it is on the
+/// board: 0 for corners, 2 for C-squares, 3 for X-squares and 1 for everything else
+///
+/// The corners are those of the playable part of the board, so smaller boards are ordered the
+/// same way as the full board.
+pub(crate) fn square_priority(game: &Reversi, pos: TilePos) -> u8 {
+    let (first, last) = game.playable_bounds();
+
+    // Distance from the nearest edge in each axis
+    let row_dist = (pos.row - first.row).min(last.row - pos.row);
+    let col_dist = (pos.col - first.col).min(last.col - pos.col);
+    match (row_dist, col_dist) {
+        // Corner
+        (0, 0) => 0,
+        // X-square (diagonally adjacent to a corner)
+        (1, 1) => 3,
+        // C-square (adjacent to a corner along an edge)
+        (0, 1) | (1, 0) => 2,
+        _ => 1,
+    }
+}
+
+/// The number of killer moves remembered for each ply
+const KILLERS_PER_PLY: usize = 2;
+
+/// Remembers which moves caused cutoffs so that they can be searched earlier in other positions
+#[derive(Debug, Default, Clone)]
+struct MoveHeuristics {
+    /// The most recent moves that caused a cutoff at each ply, most recent first
+    ///
+    /// Sibling positions are often similar, so a move that refuted one of them will likely refute
+    /// the others too.
+    killers: Vec<[Option<TilePos>; KILLERS_PER_PLY]>,
+    /// How good each move has been at causing cutoffs anywhere in the search, with cutoffs deeper
+    /// in the tree counting for less
+    history: [[u32; 8]; 8],
+}
+
+impl MoveHeuristics {
+    /// Records that the given move caused a cutoff at the given ply
+    fn record_cutoff(&mut self, ply: usize, pmove: TilePos, remaining_depth: usize) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; KILLERS_PER_PLY]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(pmove) {
+            killers.rotate_right(1);
+            killers[0] = Some(pmove);
+        }
+
+        let remaining_depth = remaining_depth.min(u16::MAX as usize) as u32;
+        let history = &mut self.history[pmove.row][pmove.col];
+        *history = history.saturating_add(remaining_depth * remaining_depth);
+    }
+
+    /// Sorts the given moves at the given ply so that the moves most likely to be good are
+    /// searched first
+    ///
+    /// This is the same as `order_moves`, except that killer moves are searched right after
+    /// corners and moves are ordered by their history within each kind of square.
+    fn order_moves(&self, game: &Reversi, moves: &mut [TilePos], hint: Option<TilePos>, ply: usize) {
+        let killers = self.killers.get(ply);
+        moves.sort_unstable_by_key(|&pos| {
+            let priority = if Some(pos) == hint {
+                0
+            } else if square_priority(game, pos) == 0 {
+                1
+            } else if killers.is_some_and(|killers| killers.contains(&Some(pos))) {
+                2
+            } else {
+                3 + square_priority(game, pos)
+            };
+
+            (priority, Reverse(self.history[pos.row][pos.col]))
+        });
+    }
+}
+
+/// How far on either side of the previous iteration's score the aspiration window reaches
+const ASPIRATION_WINDOW: i32 = 50;
+
+/// The ProbCut checks tried at each position, from the shallowest to the deepest: the remaining
+/// depth needed to try the check, the depth of the shallow search and the standard deviation of
+/// the difference between the shallow and deep scores
+///
+/// The standard deviations are rough estimates for the default evaluation. They are larger for
+/// shallower searches since those are worse at predicting the deep score.
+const PROBCUT_CHECKS: &[(usize, usize, i32)] = &[
+    (4, 1, 120),
+    (6, 2, 90),
+    (8, 4, 70),
+];
+
+/// How many standard deviations past the window a shallow score needs to be for the position to be
+/// cut off. Larger values prune less but make fewer mistakes.
+const PROBCUT_THRESHOLD: f64 = 1.5;
+
+/// Computes the negamax score for the given player using the given evaluator. A higher score
+/// means that the current state of the board is better for the given player.
+fn negamax_score(evaluator: &(dyn Evaluator + Sync), game: &Reversi, player: Piece) -> i32 {
+    evaluator.evaluate(game, player)
+}
+
+/// Shifts a bound of the search window down by the given amount of noise, staying within
+/// (-INFINITY, INFINITY) so that the bound can still be negated
+fn shift(bound: i32, noise: i32) -> i32 {
+    bound.saturating_sub(noise).clamp(-INFINITY, INFINITY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::Variant;
+
+    /// Plain negamax without any pruning, move ordering or transposition table, following the same
+    /// rules as `Search::negamax` for when to stop searching
+    fn minimax(evaluator: &dyn Evaluator, game: &Reversi, skipped: bool, depth: usize, max_depth: usize) -> i32 {
+        let moves = game.valid_moves();
+        if game.grid().is_full() || (skipped && moves.is_empty()) || depth >= max_depth {
+            return evaluator.evaluate(game, game.current_player());
+        }
+
+        if moves.is_empty() {
+            let mut next = game.clone();
+            next.pass();
+            return -minimax(evaluator, &next, true, depth + 1, max_depth);
+        }
+
+        moves.iter().map(|&pos| {
+            let mut next = game.clone();
+            next.make_move(pos);
+            -minimax(evaluator, &next, false, depth + 1, max_depth)
+        }).max().unwrap()
+    }
+
+    /// Returns the positions reached by playing random moves from the start of a game for a random
+    /// number of turns, skipping any game that ended before then
+    fn random_positions(rng: &mut StdRng, count: usize) -> Vec<Reversi> {
+        let mut positions = Vec::new();
+        while positions.len() < count {
+            let variant = if rng.gen() { Variant::Standard } else { Variant::Anti };
+            let mut game = Reversi::new(variant);
+            for _ in 0..rng.gen_range(0, 50) {
+                match game.valid_moves() {
+                    [] => game.pass(),
+                    moves => {
+                        let pos = moves[rng.gen_range(0, moves.len())];
+                        game.make_move(pos);
+                    },
+                }
+            }
 
-    let nrows = grid.col_len();
-    let ncols = grid.row_len();
+            if !game.is_game_over() {
+                positions.push(game);
+            }
+        }
+
+        positions
+    }
+
+    #[test]
+    fn analyze_matches_plain_minimax() {
+        let evaluator = WeightedEvaluator::default();
+        let mut rng = StdRng::seed_from_u64(1539);
+        for (i, game) in random_positions(&mut rng, 12).into_iter().enumerate() {
+            // Deeper searches are much slower without pruning, so only some positions use them
+            let depth = i % 4 + 1;
+            let analysis = analyze(&game, depth);
+            assert!(!analysis.is_empty());
+
+            for (pmove, score) in analysis {
+                let mut next = game.clone();
+                let skipped = pmove == Move::Pass;
+                let _ = next.apply(pmove);
+                let expected = -minimax(&evaluator, &next, skipped, 1, depth);
+                assert_eq!(score, expected, "{:?} at depth {} in {:?}", pmove, depth, game.history());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_search_agrees_with_serial_search() {
+        let evaluator = WeightedEvaluator::default();
+        let mut rng = StdRng::seed_from_u64(1541);
+        let positions = random_positions(&mut rng, 12).into_iter()
+            .filter(|game| !game.valid_moves().is_empty());
+        for (i, game) in positions.enumerate() {
+            let depth = i % 4 + 1;
+            let mut tables = parallel::new_tables();
+            let (mut nodes, mut stats) = (0, SearchStats::default());
+            let (best_move, score, _) = parallel::search_root(&evaluator, &[], false, &game, &mut tables,
+                None, None, (-INFINITY, INFINITY), depth, &mut nodes, &mut stats).unwrap();
+
+            // Serial scores for every move, searched without noise to the same depth
+            let analysis = analyze(&game, depth);
+            assert_eq!(score, analysis[0].1, "depth {} in {:?}", depth, game.history());
+            let best_move = Move::Place(best_move.unwrap());
+            assert!(analysis.contains(&(best_move, score)), "depth {} in {:?}", depth, game.history());
+            assert!(nodes > 0);
+        }
+    }
+
+    /// Searches the given game to the given depth with a full window, returning the score and the
+    /// number of positions visited
+    fn search_to_depth(game: &Reversi, table: &mut TranspositionTable, depth: usize) -> (i32, u64) {
+        let mut heuristics = MoveHeuristics::default();
+        let mut search = Search {
+            evaluator: &WeightedEvaluator::default(),
+            table,
+            heuristics: &mut heuristics,
+            root_noise: &[],
+            probcut: false,
+            pv_move: None,
+            stop: None,
+            max_depth: depth,
+            reached_max_depth: false,
+            nodes: 0,
+            stats: SearchStats::default(),
+        };
+
+        let (_, score) = search.negamax(&mut game.clone(), -INFINITY, INFINITY, false, 0).unwrap();
+        (score, search.nodes)
+    }
+
+    #[test]
+    fn transpositions_share_search_work() {
+        // Two different orders of the same moves that reach the same position
+        let mut games = vec![Reversi::default()];
+        for _ in 0..3 {
+            games = games.iter().flat_map(|game| game.valid_moves().iter().map(move |&pos| {
+                let mut next = game.clone();
+                next.make_move(pos);
+                next
+            })).collect();
+        }
+        let (first, second) = games.iter().enumerate()
+            .find_map(|(i, first)| games[i + 1..].iter()
+                .find(|second| second.grid() == first.grid())
+                .map(|second| (first, second)))
+            .unwrap();
+        assert_ne!(first.history(), second.history());
+
+        let depth = 4;
+        let (fresh_score, fresh_nodes) = search_to_depth(second, &mut TranspositionTable::default(), depth);
 
-    let corners = &[
-        TilePos {row: 0, col: 0},
-        TilePos {row: 0, col: ncols - 1},
-        TilePos {row: nrows - 1, col: 0},
-        TilePos {row: nrows - 1, col: ncols - 1},
-    ];
-    for &corner in corners {
-        add_tile_score(corner, CORNER_BONUS);
+        // Once the first order has been searched, the second order finds its positions in the table
+        let mut table = TranspositionTable::default();
+        let (first_score, _) = search_to_depth(first, &mut table, depth);
+        let (shared_score, shared_nodes) = search_to_depth(second, &mut table, depth);
+        assert_eq!(first_score, fresh_score);
+        assert_eq!(shared_score, fresh_score);
+        assert!(shared_nodes < fresh_nodes, "{} >= {}", shared_nodes, fresh_nodes);
     }
 
-    for row in 0..nrows {
-        let side = TilePos {row, col: 0};
-        add_tile_score(side, SIDE_BONUS);
+    #[test]
+    fn aspiration_re_search_matches_full_window() {
+        let evaluator = WeightedEvaluator::default();
+        let mut rng = StdRng::seed_from_u64(1568);
+        let positions = random_positions(&mut rng, 12).into_iter()
+            .filter(|game| !game.valid_moves().is_empty());
+        for (i, game) in positions.enumerate() {
+            let depth = i % 3 + 2;
+            let search = |guess| {
+                let mut root = RootSearch::new(&evaluator, &[], false, &game);
+                let (mut nodes, mut stats) = (0, SearchStats::default());
+                let (best_move, score, _) = root.search(depth, None, None, guess, &mut nodes, &mut stats).unwrap();
+                (best_move.unwrap(), score, stats.aspiration_researches)
+            };
 
-        let side = TilePos {row, col: ncols - 1};
-        add_tile_score(side, SIDE_BONUS);
+            let (best_move, score, researches) = search(None);
+            assert_eq!(researches, 0);
+            // Every move that ties for the best score, since any of them may be chosen
+            let best_moves: Vec<_> = analyze(&game, depth).into_iter()
+                .filter(|&(_, move_score)| move_score == score)
+                .map(|(pmove, _)| pmove)
+                .collect();
+            assert!(best_moves.contains(&Move::Place(best_move)));
+
+            // A guess far above the real score fails low and one far below fails high, so both
+            // need to search again with the window opened up
+            for guess in [score + 10 * ASPIRATION_WINDOW, score - 10 * ASPIRATION_WINDOW] {
+                let (re_searched_move, re_searched_score, researches) = search(Some(guess));
+                let context = format!("guess {} at depth {} in {:?}", guess, depth, game.history());
+                assert_eq!(researches, 1, "{}", context);
+                assert_eq!(re_searched_score, score, "{}", context);
+                if best_moves.len() == 1 {
+                    assert_eq!(re_searched_move, best_move, "{}", context);
+                } else {
+                    assert!(best_moves.contains(&Move::Place(re_searched_move)), "{}", context);
+                }
+            }
+
+            // A guess close to the real score is found within the window
+            let (_, close_score, researches) = search(Some(score + 1));
+            assert_eq!((close_score, researches), (score, 0));
+        }
     }
 
-    for col in 0..ncols {
-        let side = TilePos {row: 0, col};
-        add_tile_score(side, SIDE_BONUS);
+    /// Returns a position from the middle of a game played with random moves
+    fn midgame_position(rng: &mut StdRng) -> Reversi {
+        loop {
+            let mut game = Reversi::new(Variant::Standard);
+            for _ in 0..20 {
+                if let Some(&pos) = game.valid_moves().choose(rng) {
+                    game.make_move(pos);
+                }
+            }
 
-        let side = TilePos {row: nrows - 1, col};
-        add_tile_score(side, SIDE_BONUS);
+            if !game.valid_moves().is_empty() {
+                return game;
+            }
+        }
+    }
+
+    /// Searches exactly to the given depth without noise, the book or the endgame solver
+    fn search_fixed_depth(game: &Reversi, depth: usize, probcut: bool) -> SearchResult {
+        let options = SearchOptions {
+            max_depth: Some(depth),
+            noise: 0,
+            use_book: false,
+            use_endgame_solver: false,
+            probcut,
+            cancel: None,
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        search_with_options(&mut rng, &WeightedEvaluator::default(), game, game.valid_moves(),
+            Duration::from_secs(600), OpeningBook::builtin(), &options)
     }
 
-    // A perfectly deterministic AI is pretty boring...
-    let score_error = rng.gen_range(-100, 100);
+    #[test]
+    fn probcut_prunes_midgame_positions() {
+        let mut rng = StdRng::seed_from_u64(1566);
+        let mut total_probcuts = 0;
+        for _ in 0..3 {
+            let game = midgame_position(&mut rng);
+            let depth = 5;
 
-    score + score_error
+            let pruned = search_fixed_depth(&game, depth, true);
+            assert!(game.valid_moves().contains(&pruned.best_move));
+            assert_eq!(pruned.depth, depth);
+            total_probcuts += pruned.stats.probcuts;
+
+            // Without ProbCut the search is exact, so it agrees with scoring every move separately
+            let exact = search_fixed_depth(&game, depth, false);
+            assert_eq!(exact.stats.probcuts, 0);
+            assert_eq!(exact.score, analyze(&game, depth)[0].1, "{:?}", game.history());
+        }
+        assert!(total_probcuts > 0);
+    }
 }
@@ -0,0 +1,231 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use thiserror::Error;
+
+use crate::{Reversi, GameRecord, NotationError};
+
+/// The environment variable that overrides the directory returned by `GameArchive::default_dir`
+pub const ARCHIVE_DIR_VAR: &str = "BOARD_GAMES_ARCHIVE";
+
+/// The extension of the file of each game in an archive
+const GAME_EXTENSION: &str = "txt";
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+    #[error("Invalid game in `{}`: {error}", .path.display())]
+    InvalidGame {
+        path: PathBuf,
+        error: NotationError,
+    },
+    #[error("There is no game #{0} in the archive")]
+    NotFound(usize),
+}
+
+/// A game stored in an archive
+#[derive(Debug, Clone)]
+pub struct ArchivedGame {
+    /// The number of the game in the archive, starting at 1 for the first game added to it
+    pub id: usize,
+    /// The file the game is stored in
+    pub path: PathBuf,
+    /// The game with its metadata (see `GameArchive::add_game`)
+    pub record: GameRecord,
+}
+
+/// A directory of finished games, each stored in its own file as a `GameRecord`
+///
+/// Games are numbered in the order they were added, which is also the order of their file names
+/// (e.g. `game-00012.txt`), so the files can be browsed and copied elsewhere like any other game
+/// records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameArchive {
+    dir: PathBuf,
+}
+
+impl GameArchive {
+    /// Creates an archive stored in the given directory, which is created when the first game is
+    /// added to it
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {dir: dir.into()}
+    }
+
+    /// Returns the directory games are archived in unless another one is chosen: the directory
+    /// given by the `BOARD_GAMES_ARCHIVE` environment variable, or `.board-games/archive` in the
+    /// user's home directory
+    ///
+    /// Returns `None` if neither the variable nor the home directory is set.
+    pub fn default_dir() -> Option<PathBuf> {
+        if let Some(dir) = env::var_os(ARCHIVE_DIR_VAR).filter(|dir| !dir.is_empty()) {
+            return Some(PathBuf::from(dir));
+        }
+
+        let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+        Some(Path::new(&home).join(".board-games").join("archive"))
+    }
+
+    /// Returns the directory the games are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Adds the record of the given game to the archive, along with the date it ended and how long
+    /// it took (the `Date` and `Duration` tags), returning its number in the archive
+    ///
+    /// The players and AI difficulty are recorded if they were set with `Reversi::set_players`.
+    pub fn add_game(&self, game: &Reversi, duration: Duration) -> Result<usize, ArchiveError> {
+        let secs = duration.as_secs();
+        let record = GameRecord::from_game(game)
+            .with_tag("Date", date_tag(SystemTime::now()))
+            .with_tag("Duration", format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60));
+        self.add(&record)
+    }
+
+    /// Adds the given record to the archive as it is, returning its number in the archive
+    pub fn add(&self, record: &GameRecord) -> Result<usize, ArchiveError> {
+        fs::create_dir_all(&self.dir)?;
+        let id = self.ids()?.last().map_or(1, |id| id + 1);
+        fs::write(self.path(id), record.to_string())?;
+        Ok(id)
+    }
+
+    /// Returns every game in the archive, in the order they were added
+    pub fn list(&self) -> Result<Vec<ArchivedGame>, ArchiveError> {
+        self.ids()?.into_iter().map(|id| self.get(id)).collect()
+    }
+
+    /// Returns the game with the given number
+    pub fn get(&self, id: usize) -> Result<ArchivedGame, ArchiveError> {
+        let path = self.path(id);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Err(ArchiveError::NotFound(id)),
+            Err(err) => return Err(err.into()),
+        };
+
+        match text.parse() {
+            Ok(record) => Ok(ArchivedGame {id, path, record}),
+            Err(error) => Err(ArchiveError::InvalidGame {path, error}),
+        }
+    }
+
+    /// Returns the file that the game with the given number is stored in
+    fn path(&self, id: usize) -> PathBuf {
+        self.dir.join(format!("game-{:05}.{}", id, GAME_EXTENSION))
+    }
+
+    /// Returns the number of every game in the archive, in ascending order
+    ///
+    /// Other files in the directory are ignored. An archive whose directory does not exist yet is
+    /// empty.
+    fn ids(&self) -> Result<Vec<usize>, ArchiveError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut ids = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(GAME_EXTENSION) {
+                continue;
+            }
+            let id = path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.strip_prefix("game-"))
+                .and_then(|id| id.parse().ok());
+            if let Some(id) = id {
+                ids.push(id);
+            }
+        }
+        ids.sort_unstable();
+
+        Ok(ids)
+    }
+}
+
+/// Returns the date of the given time in UTC, formatted the way dates are written in game records
+/// (e.g. "2026.10.16")
+fn date_tag(time: SystemTime) -> String {
+    let days = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() / 86_400;
+    let (year, month, day) = civil_from_days(days as i64);
+    format!("{}.{:02}.{:02}", year, month, day)
+}
+
+/// Converts a number of days since 1970-01-01 into a (year, month, day) date in the Gregorian
+/// calendar
+///
+/// This is the `civil_from_days` algorithm from Howard Hinnant's "chrono-Compatible Low-Level
+/// Date Algorithms".
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Variant, Piece, GameResult};
+
+    /// Returns an empty directory for an archive that is only used by the test with the given name
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("board-games-archive-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn adds_and_lists_games() {
+        let dir = test_dir("adds");
+        let archive = GameArchive::new(&dir);
+        assert!(archive.list().unwrap().is_empty());
+
+        let mut game = Reversi::new(Variant::Standard);
+        game.make_move(game.valid_moves()[0]);
+        game.resign(Piece::O);
+        assert_eq!(archive.add_game(&game, Duration::from_secs(75)).unwrap(), 1);
+        assert_eq!(archive.add(&GameRecord::new().with_tag("Event", "Second")).unwrap(), 2);
+        // Other files are left alone
+        fs::write(dir.join("notes.txt"), "not a game").unwrap();
+
+        let games = archive.list().unwrap();
+        assert_eq!(games.iter().map(|game| game.id).collect::<Vec<_>>(), vec![1, 2]);
+        let first = &games[0].record;
+        assert_eq!(first.tag("Duration"), Some("0:01:15"));
+        assert!(first.tag("Date").is_some());
+        assert_eq!(first.result, GameResult::Win(Piece::X));
+        assert_eq!(first.to_game().unwrap().history(), game.history());
+        assert_eq!(archive.get(2).unwrap().record.tag("Event"), Some("Second"));
+        assert!(matches!(archive.get(3), Err(ArchiveError::NotFound(3))));
+
+        fs::write(dir.join("game-00003.txt"), "1. E3 {unfinished").unwrap();
+        assert_eq!(archive.add(&GameRecord::new()).unwrap(), 4);
+        assert!(matches!(archive.list(), Err(ArchiveError::InvalidGame {..})));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dates_of_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(59), (1970, 3, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20_742), (2026, 10, 16));
+        assert_eq!(date_tag(UNIX_EPOCH + Duration::from_secs(86_400 * 365)), "1971.01.01");
+    }
+}
@@ -0,0 +1,886 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::{SeedableRng, rngs::StdRng};
+use thiserror::Error;
+
+use crate::{Piece, Game, Heuristic, Player, expectiminimax, pick_outcome};
+
+/// The number of checkers each player has
+pub const BACKGAMMON_CHECKERS: usize = 15;
+
+/// The number of points on the board
+const POINTS: usize = 24;
+
+/// The number of points in each player's home board, where their checkers have to be before they
+/// can be borne off
+const HOME_POINTS: usize = 6;
+
+/// The point number that a player's checkers on the bar move from
+const BAR: usize = 25;
+
+/// The point number that a player's checkers are borne off to
+const OFF: usize = 0;
+
+/// The highest value of the doubling cube
+const MAX_CUBE: u32 = 64;
+
+/// A checker moving by the number on one die, from and to points numbered from the perspective of
+/// the player moving it (see `Backgammon`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BackgammonStep {
+    /// The point the checker moves from, or 25 if it enters from the bar
+    pub from: usize,
+    /// The point the checker moves to, or 0 if it is borne off
+    pub to: usize,
+}
+
+/// Writes the step in the usual notation, e.g. "24/21", "bar/22" or "3/off"
+impl fmt::Display for BackgammonStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.from {
+            BAR => write!(f, "bar/")?,
+            from => write!(f, "{}/", from)?,
+        }
+        match self.to {
+            OFF => write!(f, "off"),
+            to => write!(f, "{}", to),
+        }
+    }
+}
+
+/// A move in Backgammon, which includes deciding whether to double and rolling the dice
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackgammonMove {
+    /// Rolling the dice without offering to double
+    Roll,
+    /// Offering to double the stakes of the game before rolling
+    Double,
+    /// Accepting the opponent's offer to double the stakes, which gives control of the cube
+    Take,
+    /// Refusing the opponent's offer to double the stakes, which loses the game
+    Drop,
+    /// The numbers the dice came up with, higher number first (the outcome of a roll)
+    Dice(u8, u8),
+    /// Moving checkers by the numbers rolled, or passing if no checkers can be moved
+    Play(Vec<BackgammonStep>),
+}
+
+/// Writes the move in the usual notation, e.g. "6-1" for a roll or "13/7 8/7" for a play
+impl fmt::Display for BackgammonMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BackgammonMove::Roll => write!(f, "roll"),
+            BackgammonMove::Double => write!(f, "double"),
+            BackgammonMove::Take => write!(f, "take"),
+            BackgammonMove::Drop => write!(f, "drop"),
+            BackgammonMove::Dice(high, low) => write!(f, "{}-{}", high, low),
+            BackgammonMove::Play(steps) if steps.is_empty() => write!(f, "pass"),
+            BackgammonMove::Play(steps) => {
+                let steps: Vec<_> = steps.iter().map(BackgammonStep::to_string).collect();
+                write!(f, "{}", steps.join(" "))
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid move: `{0}`. Expected checkers to move like `24/21 13/9`, `roll`, `double`, `take`, `drop` or `pass`")]
+pub struct ParseBackgammonMoveError(pub String);
+
+impl FromStr for BackgammonMove {
+    type Err = ParseBackgammonMoveError;
+
+    /// Parses a move written the same way as it is displayed. A checker can also be moved by both
+    /// dice at once (e.g. "13/9" for 3-1), and the dice can be written in either order.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseBackgammonMoveError(s.to_string());
+
+        let input = s.trim().to_lowercase();
+        match input.as_str() {
+            "roll" => return Ok(BackgammonMove::Roll),
+            "double" => return Ok(BackgammonMove::Double),
+            "take" => return Ok(BackgammonMove::Take),
+            "drop" => return Ok(BackgammonMove::Drop),
+            "pass" => return Ok(BackgammonMove::Play(Vec::new())),
+            _ => {},
+        }
+
+        let die = |value: &str| value.parse::<u8>().ok().filter(|die| (1..=6).contains(die));
+        if let Some((first, second)) = input.split_once('-') {
+            let (first, second) = (die(first).ok_or_else(err)?, die(second).ok_or_else(err)?);
+            return Ok(BackgammonMove::Dice(first.max(second), first.min(second)));
+        }
+
+        let point = |value: &str, special: &str, special_point: usize| match value {
+            _ if value == special => Some(special_point),
+            _ => value.parse().ok().filter(|point| (1..=POINTS).contains(point)),
+        };
+        let steps = input.split_whitespace()
+            .map(|step| {
+                let (from, to) = step.split_once('/')?;
+                let step = BackgammonStep {from: point(from, "bar", BAR)?, to: point(to, "off", OFF)?};
+                Some(step).filter(|step| step.to < step.from)
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(err)?;
+        if steps.is_empty() {
+            return Err(err());
+        }
+        Ok(BackgammonMove::Play(steps))
+    }
+}
+
+/// What happens next in a turn of Backgammon
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    /// The current player decides whether to double before rolling
+    Deciding,
+    /// The current player is about to roll the dice (a chance node)
+    Rolling,
+    /// The current player decides whether to accept their opponent's offer to double
+    Responding,
+    /// The current player moves their checkers by the given dice
+    Moving(u8, u8),
+}
+
+/// A game of Backgammon, played for the stakes on the doubling cube
+///
+/// Each player races their 15 checkers around the board into their home board and then bears
+/// them off, moving by the numbers on two dice (four times for doubles). Both numbers have to be
+/// used if possible, or else the higher one. A checker cannot land on a point with two or more of
+/// the opponent's checkers, and landing on a single one (a blot) sends it to the bar, from where
+/// it has to enter the opponent's home board before any other checker can move. The first player
+/// to bear off all of their checkers wins.
+///
+/// Points are numbered from 1 to 24 from the perspective of the player moving, starting in their
+/// home board. `Piece::X` starts by rolling the dice. Before rolling, a player can offer to double
+/// the stakes if their opponent does not own the doubling cube. The opponent either takes, and
+/// then owns the cube, or drops and loses. Winning before the loser has borne off any checkers is
+/// a gammon, worth twice the stakes, or a backgammon (three times) if the loser still has a
+/// checker on the bar or in the winner's home board.
+///
+/// The dice are rolled with a random number generator owned by the game, so a game created with a
+/// seed always rolls the same way (see `with_seed`).
+#[derive(Debug, Clone)]
+pub struct Backgammon {
+    /// The checkers on each point, numbered from X's side: positive numbers are X's checkers and
+    /// negative numbers are O's checkers
+    points: [i32; POINTS],
+    /// The number of checkers each player (X first) has on the bar
+    bar: [usize; 2],
+    /// The number of checkers each player (X first) has borne off
+    borne_off: [usize; 2],
+    /// The player whose turn it is currently
+    current_player: Piece,
+    /// What happens next in the current turn
+    phase: Phase,
+    /// The value of the doubling cube
+    cube: u32,
+    /// The player who owns the doubling cube, or `None` if either player can double
+    cube_owner: Option<Piece>,
+    /// The winner and the points they won once the game is over
+    result: Option<(Piece, u32)>,
+    /// Rolls the dice
+    rng: StdRng,
+}
+
+impl Default for Backgammon {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backgammon {
+    /// Creates a new game with the checkers in their starting positions, rolling the dice with a
+    /// random seed
+    pub fn new() -> Self {
+        let mut game = Self {
+            points: [0; POINTS],
+            bar: [0; 2],
+            borne_off: [0; 2],
+            current_player: Piece::X,
+            phase: Phase::Rolling,
+            cube: 1,
+            cube_owner: None,
+            result: None,
+            rng: StdRng::from_entropy(),
+        };
+        for &(point, count) in &[(24, 2), (13, 5), (8, 3), (6, 5)] {
+            for &player in &[Piece::X, Piece::O] {
+                game.add_checkers(player, point, count);
+            }
+        }
+        game
+    }
+
+    /// Rolls the dice with a random number generator created from the given seed, so that the
+    /// game always rolls the same way
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns the number of checkers the given player has on the given point, numbered from
+    /// their perspective
+    pub fn checkers(&self, player: Piece, point: usize) -> usize {
+        let count = self.points[index(player, point)];
+        match player {
+            Piece::X => count.max(0) as usize,
+            Piece::O => (-count).max(0) as usize,
+        }
+    }
+
+    /// Returns the number of checkers the given player has on the bar
+    pub fn bar(&self, player: Piece) -> usize {
+        self.bar[player_index(player)]
+    }
+
+    /// Returns the number of checkers the given player has borne off
+    pub fn borne_off(&self, player: Piece) -> usize {
+        self.borne_off[player_index(player)]
+    }
+
+    /// Returns the total number of points the given player's checkers still have to move to bear
+    /// them all off
+    pub fn pip_count(&self, player: Piece) -> usize {
+        let on_board: usize = (1..=POINTS).map(|point| point * self.checkers(player, point)).sum();
+        on_board + BAR * self.bar(player)
+    }
+
+    /// Returns the value of the doubling cube
+    pub fn cube(&self) -> u32 {
+        self.cube
+    }
+
+    /// Returns the player who owns the doubling cube, or `None` if either player can double
+    pub fn cube_owner(&self) -> Option<Piece> {
+        self.cube_owner
+    }
+
+    /// Returns the dice the current player is moving by, if they have rolled
+    pub fn dice(&self) -> Option<(u8, u8)> {
+        match self.phase {
+            Phase::Moving(high, low) if self.result.is_none() => Some((high, low)),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the current player has been offered to double and has to take or drop
+    pub fn is_double_offered(&self) -> bool {
+        self.result.is_none() && self.phase == Phase::Responding
+    }
+
+    /// Returns the number of points won by the winner once the game is over
+    pub fn points_won(&self) -> Option<u32> {
+        self.result.map(|(_, points)| points)
+    }
+
+    /// Returns true if the current player can offer to double
+    pub fn can_double(&self) -> bool {
+        self.result.is_none()
+            && matches!(self.phase, Phase::Deciding | Phase::Rolling)
+            && self.cube < MAX_CUBE
+            && self.cube_owner != Some(self.current_player.opposite())
+    }
+
+    /// Returns every way that the current player can move their checkers by the dice they rolled,
+    /// or no plays if they have not rolled or cannot move any checkers
+    ///
+    /// Plays that lead to the same position are only listed once.
+    pub fn plays(&self) -> Vec<Vec<BackgammonStep>> {
+        let (high, low) = match self.dice() {
+            Some(dice) => dice,
+            None => return Vec::new(),
+        };
+        let orders = if high == low {
+            vec![vec![high; 4]]
+        } else {
+            vec![vec![high, low], vec![low, high]]
+        };
+
+        let player = self.current_player;
+        let mut plays = Vec::new();
+        for dice in orders {
+            self.find_plays(player, &dice, &mut Vec::new(), &mut plays);
+        }
+
+        // Both dice have to be used if possible, or else the higher one
+        let most_steps = plays.iter().map(|(steps, _)| steps.len()).max().unwrap_or(0);
+        plays.retain(|(steps, _)| steps.len() == most_steps);
+        if most_steps == 1 && plays.iter().any(|(_, first_die)| *first_die == high) {
+            plays.retain(|(_, first_die)| *first_die == high);
+        }
+
+        let mut positions = Vec::new();
+        plays.into_iter()
+            .filter(|(steps, _)| {
+                let position = self.after_steps(player, steps).map(|game| game.position());
+                if positions.contains(&position) {
+                    false
+                } else {
+                    positions.push(position);
+                    true
+                }
+            })
+            .map(|(steps, _)| steps)
+            .collect()
+    }
+
+    /// Adds every sequence of steps that uses the given dice in order (or as many of them as
+    /// possible) after the given steps, along with the first die used
+    fn find_plays(&self, player: Piece, dice: &[u8], steps: &mut Vec<BackgammonStep>, plays: &mut Vec<(Vec<BackgammonStep>, u8)>) {
+        let first_die = || dice.first().copied().unwrap_or(0);
+        let die = match dice.get(steps.len()) {
+            Some(&die) => die,
+            None => {
+                plays.push((steps.clone(), first_die()));
+                return;
+            },
+        };
+
+        let game = self.after_steps(player, steps).expect("bug: found steps should always be valid");
+        let mut moved = false;
+        for from in (1..=BAR).rev() {
+            if let Some(step) = game.step(player, from, die) {
+                moved = true;
+                steps.push(step);
+                self.find_plays(player, dice, steps, plays);
+                steps.pop();
+            }
+        }
+        if !moved && !steps.is_empty() {
+            plays.push((steps.clone(), first_die()));
+        }
+    }
+
+    /// Returns the step that moves the given player's checker on the given point by the given die,
+    /// if it can be moved
+    fn step(&self, player: Piece, from: usize, die: u8) -> Option<BackgammonStep> {
+        let die = die as usize;
+        let on_bar = self.bar(player) > 0;
+        if from == BAR {
+            if !on_bar {
+                return None;
+            }
+        } else if on_bar || self.checkers(player, from) == 0 {
+            return None;
+        }
+
+        if from > die {
+            let to = from - die;
+            return Some(BackgammonStep {from, to}).filter(|_| self.checkers(player.opposite(), BAR - to) < 2);
+        }
+
+        // Bearing off with a higher number than needed is only allowed from the highest point
+        let is_highest = ((from + 1)..=HOME_POINTS).all(|point| self.checkers(player, point) == 0);
+        if self.all_home(player) && (from == die || is_highest) {
+            Some(BackgammonStep {from, to: OFF})
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if all of the given player's checkers left on the board are in their home
+    /// board
+    fn all_home(&self, player: Piece) -> bool {
+        self.bar(player) == 0 && ((HOME_POINTS + 1)..=POINTS).all(|point| self.checkers(player, point) == 0)
+    }
+
+    /// Returns the game after moving the given player's checkers by the given steps, or `None` if
+    /// a step moves a checker that is not there or lands on a point held by the opponent
+    ///
+    /// The steps do not have to match the dice, so that a checker moving by both dice can be
+    /// given as a single step.
+    fn after_steps(&self, player: Piece, steps: &[BackgammonStep]) -> Option<Self> {
+        let mut game = self.clone();
+        for &step in steps {
+            let has_checker = match step.from {
+                BAR => game.bar(player) > 0,
+                from => game.checkers(player, from) > 0,
+            };
+            if !has_checker || step.to >= step.from || (step.to != OFF && game.checkers(player.opposite(), BAR - step.to) >= 2) {
+                return None;
+            }
+            game.apply_step(player, step);
+        }
+        Some(game)
+    }
+
+    /// Moves a checker by the given step, hitting a blot it lands on
+    fn apply_step(&mut self, player: Piece, step: BackgammonStep) {
+        match step.from {
+            BAR => self.bar[player_index(player)] -= 1,
+            from => self.add_checkers(player, from, -1),
+        }
+
+        if step.to == OFF {
+            self.borne_off[player_index(player)] += 1;
+            return;
+        }
+
+        let opponent = player.opposite();
+        if self.checkers(opponent, BAR - step.to) == 1 {
+            self.add_checkers(opponent, BAR - step.to, -1);
+            self.bar[player_index(opponent)] += 1;
+        }
+        self.add_checkers(player, step.to, 1);
+    }
+
+    /// Adds the given number of checkers (or takes them away if negative) to the given player's
+    /// point, numbered from their perspective
+    fn add_checkers(&mut self, player: Piece, point: usize, count: i32) {
+        self.points[index(player, point)] += match player {
+            Piece::X => count,
+            Piece::O => -count,
+        };
+    }
+
+    /// Returns everything about where the checkers are
+    fn position(&self) -> ([i32; POINTS], [usize; 2], [usize; 2]) {
+        (self.points, self.bar, self.borne_off)
+    }
+
+    /// Returns true if the current player can make the given move
+    ///
+    /// A play is valid if it leads to the same position as one of the plays returned by `plays`.
+    pub fn is_valid_move(&self, pmove: &BackgammonMove) -> bool {
+        if self.result.is_some() {
+            return false;
+        }
+
+        match (self.phase, pmove) {
+            (Phase::Deciding, BackgammonMove::Roll) => true,
+            (Phase::Deciding, BackgammonMove::Double) => self.can_double(),
+            (Phase::Rolling, BackgammonMove::Dice(high, low)) => {
+                (1..=6).contains(low) && low <= high && *high <= 6
+            },
+            (Phase::Responding, BackgammonMove::Take) | (Phase::Responding, BackgammonMove::Drop) => true,
+            (Phase::Moving(..), BackgammonMove::Play(steps)) => {
+                let plays = self.plays();
+                if steps.is_empty() {
+                    return plays.is_empty();
+                }
+
+                let player = self.current_player;
+                let position = match self.after_steps(player, steps) {
+                    Some(game) => game.position(),
+                    None => return false,
+                };
+                plays.iter().any(|play| self.after_steps(player, play).map(|game| game.position()) == Some(position))
+            },
+            _ => false,
+        }
+    }
+
+    /// Makes the given move for the current player
+    ///
+    /// # Panics
+    ///
+    /// Panics if the move is not valid.
+    pub fn make_move(&mut self, pmove: BackgammonMove) {
+        assert!(self.is_valid_move(&pmove), "bug: attempt to make the move `{}`, which is not valid", pmove);
+
+        let player = self.current_player;
+        match pmove {
+            BackgammonMove::Roll => self.phase = Phase::Rolling,
+            BackgammonMove::Double => {
+                self.phase = Phase::Responding;
+                self.current_player = player.opposite();
+            },
+            BackgammonMove::Take => {
+                self.cube *= 2;
+                self.cube_owner = Some(player);
+                self.phase = Phase::Rolling;
+                self.current_player = player.opposite();
+            },
+            BackgammonMove::Drop => self.result = Some((player.opposite(), self.cube)),
+            BackgammonMove::Dice(high, low) => self.phase = Phase::Moving(high, low),
+            BackgammonMove::Play(steps) => {
+                for step in steps {
+                    self.apply_step(player, step);
+                }
+
+                if self.borne_off(player) == BACKGAMMON_CHECKERS {
+                    self.result = Some((player, self.cube * self.win_multiplier(player)));
+                    return;
+                }
+                self.current_player = player.opposite();
+                self.phase = Phase::Rolling;
+                if self.can_double() {
+                    self.phase = Phase::Deciding;
+                }
+            },
+        }
+    }
+
+    /// Returns how many times the stakes the given player wins by bearing off all of their
+    /// checkers: 1 for a single game, 2 for a gammon and 3 for a backgammon
+    fn win_multiplier(&self, winner: Piece) -> u32 {
+        let loser = winner.opposite();
+        if self.borne_off(loser) > 0 {
+            1
+        } else if self.bar(loser) > 0 || ((POINTS - HOME_POINTS + 1)..=POINTS).any(|point| self.checkers(loser, point) > 0) {
+            3
+        } else {
+            2
+        }
+    }
+}
+
+/// Returns the index in `Backgammon::points` of the given player's point, numbered from their
+/// perspective
+fn index(player: Piece, point: usize) -> usize {
+    match player {
+        Piece::X => point - 1,
+        Piece::O => POINTS - point,
+    }
+}
+
+/// Returns the index of the given player in arrays with an entry for each player
+fn player_index(player: Piece) -> usize {
+    match player {
+        Piece::X => 0,
+        Piece::O => 1,
+    }
+}
+
+impl Game for Backgammon {
+    type Move = BackgammonMove;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<BackgammonMove> {
+        if self.result.is_some() {
+            return Vec::new();
+        }
+
+        match self.phase {
+            Phase::Deciding => vec![BackgammonMove::Roll, BackgammonMove::Double],
+            Phase::Rolling => self.chance_outcomes().into_iter().map(|(dice, _)| dice).collect(),
+            Phase::Responding => vec![BackgammonMove::Take, BackgammonMove::Drop],
+            Phase::Moving(..) => {
+                let plays = self.plays();
+                if plays.is_empty() {
+                    return vec![BackgammonMove::Play(Vec::new())];
+                }
+                plays.into_iter().map(BackgammonMove::Play).collect()
+            },
+        }
+    }
+
+    fn play_move(&mut self, pmove: BackgammonMove) {
+        self.make_move(pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        self.result.map(|(winner, _)| winner)
+    }
+
+    fn is_pass(&self, pmove: &BackgammonMove) -> bool {
+        *pmove == BackgammonMove::Play(Vec::new())
+    }
+
+    fn is_chance_node(&self) -> bool {
+        self.result.is_none() && self.phase == Phase::Rolling
+    }
+
+    /// Returns the 21 different rolls of the dice: each double has a 1 in 36 chance and each
+    /// other roll has a 2 in 36 chance
+    fn chance_outcomes(&self) -> Vec<(BackgammonMove, f64)> {
+        let mut outcomes = Vec::new();
+        for high in 1..=6 {
+            for low in 1..=high {
+                let probability = if high == low { 1.0 / 36.0 } else { 2.0 / 36.0 };
+                outcomes.push((BackgammonMove::Dice(high, low), probability));
+            }
+        }
+        outcomes
+    }
+
+    fn play_chance(&mut self) -> BackgammonMove {
+        assert!(self.is_chance_node(), "bug: attempt to roll the dice when it is not time to roll");
+        let outcomes = self.chance_outcomes();
+        let dice = pick_outcome(&mut self.rng, &outcomes);
+        self.make_move(dice.clone());
+        dice
+    }
+}
+
+/// The number of rows of checkers drawn on each point before the rest are written as a number
+const DRAWN_ROWS: usize = 5;
+
+/// Draws the board from X's side, with the points numbered from the perspective of the current
+/// player. O's home board is at the top right and X's home board is at the bottom right.
+impl fmt::Display for Backgammon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The points of each half, from left to right, numbered from X's side
+        let top: Vec<usize> = (13..=POINTS).collect();
+        let bottom: Vec<usize> = (1..=12).rev().collect();
+        let label = |point: usize| match self.current_player {
+            Piece::X => point,
+            Piece::O => BAR - point,
+        };
+        let write_labels = |f: &mut fmt::Formatter, points: &[usize]| {
+            for (i, &point) in points.iter().enumerate() {
+                let gap = if i == HOME_POINTS { "  " } else { "" };
+                write!(f, "{}{:>3}", gap, label(point))?;
+            }
+            writeln!(f)
+        };
+        let write_row = |f: &mut fmt::Formatter, points: &[usize], row: usize| {
+            for (i, &point) in points.iter().enumerate() {
+                if i == HOME_POINTS {
+                    write!(f, " |")?;
+                }
+                let (piece, count) = match (self.checkers(Piece::X, point), self.checkers(Piece::O, BAR - point)) {
+                    (0, 0) => {
+                        write!(f, "{}", if row == 0 { "  ." } else { "   " })?;
+                        continue;
+                    },
+                    (0, count) => (Piece::O, count),
+                    (count, _) => (Piece::X, count),
+                };
+                if row == DRAWN_ROWS - 1 && count > DRAWN_ROWS {
+                    write!(f, "{:>3}", count)?;
+                } else if count > row {
+                    write!(f, "  {}", piece)?;
+                } else {
+                    write!(f, "   ")?;
+                }
+            }
+            writeln!(f)
+        };
+
+        write_labels(f, &top)?;
+        for row in 0..DRAWN_ROWS {
+            write_row(f, &top, row)?;
+        }
+
+        writeln!(f)?;
+        write!(f, " Bar: {} {}, {} {}", Piece::X, self.bar(Piece::X), Piece::O, self.bar(Piece::O))?;
+        write!(f, "  Off: {} {}, {} {}", Piece::X, self.borne_off(Piece::X), Piece::O, self.borne_off(Piece::O))?;
+        match self.cube_owner {
+            Some(owner) => write!(f, "  Cube: {} ({})", self.cube, owner)?,
+            None => write!(f, "  Cube: {}", self.cube)?,
+        }
+        if let Some((high, low)) = self.dice() {
+            write!(f, "  Dice: {}-{}", high, low)?;
+        }
+        writeln!(f)?;
+        writeln!(f)?;
+
+        for row in (0..DRAWN_ROWS).rev() {
+            write_row(f, &bottom, row)?;
+        }
+        write_labels(f, &bottom)
+    }
+}
+
+/// An AI that plays Backgammon by searching a number of moves ahead, averaging over the rolls of
+/// the dice (see `expectiminimax`)
+///
+/// Every decision counts as a move, including whether to double, so searching the opponent's
+/// replies to every roll takes a depth of 3. Positions are evaluated by the difference in pip
+/// counts, with penalties for blots and bonuses for points made in the home board. The AI doubles
+/// and takes based on how far ahead that evaluation says it is.
+#[derive(Debug, Clone, Copy)]
+pub struct BackgammonAi {
+    /// How many moves ahead to search
+    depth: usize,
+}
+
+impl Default for BackgammonAi {
+    fn default() -> Self {
+        Self {depth: 1}
+    }
+}
+
+/// The evaluation at which the AI offers to double
+const DOUBLE_THRESHOLD: i32 = 80;
+
+/// The evaluation below which the AI drops when the opponent doubles
+const DROP_THRESHOLD: i32 = -120;
+
+impl BackgammonAi {
+    /// Creates an AI that searches the given number of moves ahead
+    pub fn new(depth: usize) -> Self {
+        Self {depth}
+    }
+
+    /// Returns how many moves ahead the AI searches
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the move the AI would play for the current player, or `None` if the game is over
+    /// or the dice are about to be rolled
+    pub fn choose_move(&self, game: &Backgammon) -> Option<BackgammonMove> {
+        expectiminimax(game, self.depth, self).map(|(pmove, _)| pmove)
+    }
+}
+
+impl Heuristic<Backgammon> for BackgammonAi {
+    fn evaluate(&self, game: &Backgammon, player: Piece) -> i32 {
+        let opponent = player.opposite();
+        let mut score = 4 * (game.pip_count(opponent) as i32 - game.pip_count(player) as i32);
+
+        for point in 1..=POINTS {
+            for &(piece, sign) in &[(player, 1), (opponent, -1)] {
+                let count = game.checkers(piece, point);
+                if count == 1 {
+                    score -= sign * 12;
+                } else if count >= 2 && point <= HOME_POINTS {
+                    score += sign * 15;
+                }
+            }
+        }
+        score
+    }
+
+    /// Searches only the AI's own choice of whether to double or take, rather than every option
+    fn candidate_moves(&self, game: &Backgammon) -> Vec<BackgammonMove> {
+        let player = game.current_player();
+        match game.phase {
+            _ if game.is_game_over() => Vec::new(),
+            Phase::Deciding if self.evaluate(game, player) >= DOUBLE_THRESHOLD => vec![BackgammonMove::Double],
+            Phase::Deciding => vec![BackgammonMove::Roll],
+            Phase::Responding if self.evaluate(game, player) < DROP_THRESHOLD => vec![BackgammonMove::Drop],
+            Phase::Responding => vec![BackgammonMove::Take],
+            _ => game.legal_moves(),
+        }
+    }
+}
+
+/// Plays the move chosen by `BackgammonAi::choose_move`
+impl Player<Backgammon> for BackgammonAi {
+    fn choose_move(&mut self, game: &Backgammon) -> BackgammonMove {
+        BackgammonAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pmove(s: &str) -> BackgammonMove {
+        s.parse().unwrap()
+    }
+
+    /// Creates a game with only the given checkers on the board (as points and counts from each
+    /// player's perspective), the rest borne off, and X about to move by the given dice
+    fn setup(x: &[(usize, usize)], o: &[(usize, usize)], dice: &str) -> Backgammon {
+        let mut game = Backgammon::new().with_seed(1);
+        game.points = [0; POINTS];
+        for &(checkers, player) in &[(x, Piece::X), (o, Piece::O)] {
+            for &(point, count) in checkers {
+                game.add_checkers(player, point, count as i32);
+            }
+            let on_board: usize = checkers.iter().map(|&(_, count)| count).sum();
+            game.borne_off[player_index(player)] = BACKGAMMON_CHECKERS - on_board;
+        }
+        game.make_move(pmove(dice));
+        game
+    }
+
+    #[test]
+    fn opening_plays() {
+        let mut game = Backgammon::new();
+        game.make_move(pmove("3-1"));
+        assert!(game.is_valid_move(&pmove("8/5 6/5")));
+        assert!(game.is_valid_move(&pmove("24/21 24/23")));
+        // A checker can be moved by both dice at once
+        assert!(game.is_valid_move(&pmove("13/9")));
+        // Both dice have to be used
+        assert!(!game.is_valid_move(&pmove("24/21")));
+        // O holds its 6 point (X's 19 point)
+        assert!(!game.is_valid_move(&pmove("24/20 24/23")));
+
+        game.make_move(pmove("8/5 6/5"));
+        assert_eq!(game.current_player(), Piece::O);
+        assert_eq!(game.checkers(Piece::X, 5), 2);
+        assert_eq!(game.pip_count(Piece::X), 167 - 4);
+    }
+
+    #[test]
+    fn doubles_move_four_times() {
+        let mut game = Backgammon::new();
+        game.make_move(pmove("6-6"));
+        assert!(game.is_valid_move(&pmove("24/18 24/18 13/7 13/7")));
+        assert!(!game.is_valid_move(&pmove("24/18 24/18 13/7")));
+    }
+
+    #[test]
+    fn must_play_higher_die() {
+        // Either die can be played, but not both, since O holds X's 2 point
+        let game = setup(&[(13, 1)], &[(23, 2)], "6-5");
+        assert_eq!(game.plays(), vec![vec![BackgammonStep {from: 13, to: 7}]]);
+        assert!(!game.is_valid_move(&pmove("13/8")));
+    }
+
+    #[test]
+    fn hit_checkers_enter_first() {
+        let mut game = setup(&[(6, 2)], &[(22, 1), (1, 2)], "3-1");
+        game.make_move(pmove("6/3 6/5"));
+        assert_eq!(game.bar(Piece::O), 1);
+
+        game.make_move(BackgammonMove::Roll);
+        game.make_move(pmove("4-2"));
+        assert!(game.plays().iter().all(|play| play[0].from == BAR));
+        assert!(game.is_valid_move(&pmove("bar/21 21/19")));
+        assert!(game.is_valid_move(&pmove("bar/19")));
+    }
+
+    #[test]
+    fn bearing_off() {
+        let mut game = setup(&[(3, 1), (2, 1)], &[(24, 1), (6, 14)], "6-5");
+        // Higher numbers than needed bear off from the highest point
+        assert!(game.is_valid_move(&pmove("3/off 2/off")));
+        game.make_move(pmove("3/off 2/off"));
+        assert_eq!(Game::winner(&game), Some(Piece::X));
+        // O has not borne off any checkers and still has one in X's home board
+        assert_eq!(game.points_won(), Some(3));
+    }
+
+    #[test]
+    fn doubling_cube() {
+        let mut game = setup(&[(13, 1)], &[(24, 15)], "2-1");
+        game.make_move(pmove("13/10"));
+        game.make_move(BackgammonMove::Double);
+        assert_eq!(game.current_player(), Piece::X);
+        game.make_move(BackgammonMove::Take);
+        assert_eq!((game.cube(), game.cube_owner()), (2, Some(Piece::X)));
+        // Only the owner of the cube can double it again
+        assert!(game.is_chance_node());
+        game.make_move(pmove("1-1"));
+        game.make_move(pmove("24/23 24/23 24/23 24/23"));
+        assert!(game.can_double());
+
+        let mut game = setup(&[(13, 1)], &[(24, 15)], "2-1");
+        game.make_move(pmove("13/10"));
+        game.make_move(BackgammonMove::Double);
+        game.make_move(BackgammonMove::Drop);
+        assert_eq!((Game::winner(&game), game.points_won()), (Some(Piece::O), Some(1)));
+    }
+
+    #[test]
+    fn ai_plays_full_game() {
+        let mut game = Backgammon::new().with_seed(7);
+        let ai = BackgammonAi::new(1);
+        while !game.is_game_over() {
+            if game.is_chance_node() {
+                game.play_chance();
+            } else {
+                let pmove = ai.choose_move(&game).unwrap();
+                game.make_move(pmove);
+            }
+        }
+        assert!(game.points_won().unwrap() >= 1);
+    }
+}
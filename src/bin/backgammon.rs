@@ -0,0 +1,144 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    Backgammon,
+    BackgammonAi,
+    BackgammonMove,
+    Game,
+    Piece,
+    Player,
+    PromptPlayer,
+    TextUi,
+    run_game,
+    disable_color_if_unsupported,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// Which players are played by the AI, X first (`--players <human|ai>,<human|ai>`)
+    ai_players: [bool; 2],
+    /// How many moves ahead the AI searches (`--depth <n>`)
+    depth: usize,
+    /// The seed for the dice, used to replay a game exactly (`--seed <number>`)
+    seed: Option<u64>,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let mut game = Backgammon::new();
+    if let Some(seed) = options.seed {
+        game = game.with_seed(seed);
+    }
+    let status = |game: &Backgammon| {
+        let mut status = format!("Pips: {} {}, {} {}", Piece::X, game.pip_count(Piece::X), Piece::O, game.pip_count(Piece::O));
+        if let (Some(winner), Some(points)) = (Game::winner(game), game.points_won()) {
+            status += &format!("\n{} won {} point(s)", winner, points);
+        } else if game.is_double_offered() {
+            status += &format!("\n{} offered to double to {}", game.current_player().opposite(), 2 * game.cube());
+        }
+        status
+    };
+    let player = |is_ai: bool| -> Box<dyn Player<Backgammon>> {
+        if is_ai {
+            Box::new(BackgammonAi::new(options.depth))
+        } else {
+            Box::new(PromptPlayer::new().with_parser(parse_move).with_message(move_message))
+        }
+    };
+    let players = [player(options.ai_players[0]), player(options.ai_players[1])];
+    run_game(game, players, TextUi::new().with_status(status).with_announced(options.ai_players));
+}
+
+/// Returns the text to prompt for the current player's move with, which depends on what they have
+/// to decide
+fn move_message(game: &Backgammon) -> String {
+    if game.is_double_offered() {
+        "Enter `take` or `drop`: ".to_string()
+    } else if game.dice().is_none() {
+        "Enter `roll` or `double`: ".to_string()
+    } else {
+        match game.legal_moves().first() {
+            Some(example) => format!("Enter your move (e.g. {}): ", example),
+            None => "Enter your move: ".to_string(),
+        }
+    }
+}
+
+/// Parses a move entered by the current player, returning why it is not valid if it cannot be
+/// made
+fn parse_move(game: &Backgammon, input: &str) -> Result<BackgammonMove, String> {
+    match input.parse() {
+        Ok(pmove) if game.is_valid_move(&pmove) => Ok(pmove),
+        Ok(BackgammonMove::Double) if game.dice().is_none() => {
+            Err("You cannot double while your opponent owns the cube.".to_string())
+        },
+        Ok(pmove @ BackgammonMove::Play(_)) => {
+            Err(format!("Invalid move: `{}`. Use both dice if you can, or else the higher one. Enter `moves` to list the valid moves.", pmove))
+        },
+        Ok(pmove) => Err(format!("Invalid move: `{}`. Enter `moves` to list the valid moves.", pmove)),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        ai_players: [false, true],
+        depth: BackgammonAi::default().depth(),
+        seed: None,
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = match value.split(',').collect::<Vec<_>>()[..] {
+                    [x, o] => [is_ai(x)?, is_ai(o)?],
+                    _ => return Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+                };
+            },
+
+            "--depth" => {
+                let value = args.next().ok_or("Missing value for `--depth`")?;
+                options.depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
+            },
+
+            "--seed" => {
+                let value = args.next().ok_or("Missing value for `--seed`")?;
+                let seed = value.parse().map_err(|_| format!("Invalid seed: `{}`", value))?;
+                options.seed = Some(seed);
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,245 @@
+use std::env;
+use std::fmt;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    Game,
+    Player,
+    PromptPlayer,
+    TextUi,
+    StdConsole,
+    run_game,
+    prompt,
+    disable_color_if_unsupported,
+    AiDifficulty,
+    AiPlayer,
+    Reversi,
+    Gomoku,
+    GomokuAi,
+    Checkers,
+    CheckersAi,
+    Morris,
+    MorrisAi,
+    Kalah,
+    KalahAi,
+    DotsAndBoxes,
+    DotsAi,
+    Go,
+    Nim,
+    NimAi,
+    Breakthrough,
+    BreakthroughAi,
+    LinesOfAction,
+    LinesOfActionAi,
+    Backgammon,
+    BackgammonAi,
+    ChineseCheckers,
+    ChineseCheckersAi,
+};
+
+/// A game that can be chosen from the menu
+struct GameEntry {
+    /// The name the game is chosen by on the command line, which is also the name of its own
+    /// binary with all of its options
+    name: &'static str,
+    /// The name of the game shown in the menu
+    title: &'static str,
+    /// False if the game can only be played by humans
+    has_ai: bool,
+}
+
+/// Every game that can be launched, in the order they are listed in the menu
+const GAMES: &[GameEntry] = &[
+    GameEntry {name: "reversi", title: "Reversi", has_ai: true},
+    GameEntry {name: "gomoku", title: "Gomoku", has_ai: true},
+    GameEntry {name: "checkers", title: "Checkers", has_ai: true},
+    GameEntry {name: "morris", title: "Nine Men's Morris", has_ai: true},
+    GameEntry {name: "kalah", title: "Kalah", has_ai: true},
+    GameEntry {name: "dots", title: "Dots and Boxes", has_ai: true},
+    GameEntry {name: "go", title: "Go", has_ai: false},
+    GameEntry {name: "nim", title: "Nim", has_ai: true},
+    GameEntry {name: "breakthrough", title: "Breakthrough", has_ai: true},
+    GameEntry {name: "loa", title: "Lines of Action", has_ai: true},
+    GameEntry {name: "backgammon", title: "Backgammon", has_ai: true},
+    GameEntry {name: "chinese-checkers", title: "Chinese Checkers", has_ai: true},
+];
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// The name of the game to play, or `None` to choose it from a menu (`<game>`)
+    game: Option<String>,
+    /// Which players are played by the AI, X first, if chosen (`--players <human|ai>,<human|ai>`)
+    ai_players: Option<[bool; 2]>,
+    /// How strong the AI plays (`--difficulty <beginner|easy|medium|hard|expert>`)
+    difficulty: AiDifficulty,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let entry = match &options.game {
+        Some(name) => match GAMES.iter().find(|entry| entry.name == name) {
+            Some(entry) => entry,
+            None => {
+                let names: Vec<_> = GAMES.iter().map(|entry| entry.name).collect();
+                eprintln!("Error: Unknown game: `{}`. Expected one of: {}", name, names.join(", "));
+                process::exit(1);
+            },
+        },
+        None => match prompt_game() {
+            Some(entry) => entry,
+            None => return,
+        },
+    };
+
+    let ai_players = match options.ai_players {
+        Some(ai_players) if !entry.has_ai && ai_players.contains(&true) => {
+            eprintln!("Error: {} can only be played by humans. Use `--players human,human`.", entry.title);
+            process::exit(1);
+        },
+        Some(ai_players) => ai_players,
+        None => [false, entry.has_ai],
+    };
+
+    println!("Playing {} ({} AI). Run the `{}` binary for more options.", entry.title, options.difficulty, entry.name);
+    let difficulty = options.difficulty;
+    match entry.name {
+        "reversi" => play(Reversi::default(), ai_players, || Box::new(AiPlayer::new(difficulty))),
+        "gomoku" => play(Gomoku::default(), ai_players, || Box::new(GomokuAi::new(depth_for(GomokuAi::default().depth(), difficulty)))),
+        "checkers" => play(Checkers::new(), ai_players, || Box::new(CheckersAi::new(depth_for(CheckersAi::default().depth(), difficulty)))),
+        "morris" => play(Morris::new(), ai_players, || Box::new(MorrisAi::new(depth_for(MorrisAi::default().depth(), difficulty)))),
+        "kalah" => play(Kalah::default(), ai_players, || Box::new(KalahAi::new(depth_for(KalahAi::default().depth(), difficulty)))),
+        "dots" => play(DotsAndBoxes::default(), ai_players, || Box::new(DotsAi::new(depth_for(DotsAi::default().depth(), difficulty)))),
+        // Go has no AI, so both of its players are always human
+        "go" => play(Go::default(), ai_players, || Box::new(PromptPlayer::new())),
+        // The Nim AI always plays perfectly
+        "nim" => play(Nim::default(), ai_players, || Box::new(NimAi)),
+        "breakthrough" => play(Breakthrough::new(), ai_players, || Box::new(BreakthroughAi::new(depth_for(BreakthroughAi::default().depth(), difficulty)))),
+        "loa" => play(LinesOfAction::new(), ai_players, || Box::new(LinesOfActionAi::new(depth_for(LinesOfActionAi::default().depth(), difficulty)))),
+        "backgammon" => play(Backgammon::new(), ai_players, || Box::new(BackgammonAi::new(depth_for(BackgammonAi::default().depth(), difficulty)))),
+        // The launcher only plays two-player games, so more players need the game's own binary
+        "chinese-checkers" => play(ChineseCheckers::new(2), ai_players, || Box::new(ChineseCheckersAi::new(depth_for(ChineseCheckersAi::default().depth(), difficulty)))),
+        name => unreachable!("bug: no way to launch the game `{}`", name),
+    }
+}
+
+/// Returns how many moves ahead an AI searches at the given difficulty, given how far it searches
+/// by default
+///
+/// The default depth is used for the default difficulty (`Medium`).
+fn depth_for(default_depth: usize, difficulty: AiDifficulty) -> usize {
+    match difficulty {
+        AiDifficulty::Beginner => 1,
+        AiDifficulty::Easy => (default_depth / 2).max(1),
+        AiDifficulty::Medium => default_depth,
+        AiDifficulty::Hard => default_depth + 1,
+        AiDifficulty::Expert => default_depth + 2,
+    }
+}
+
+/// Plays the given game with an AI created by `new_ai` playing the players chosen to be played by
+/// the AI (X first) and humans entering the moves of the others
+fn play<G, F>(game: G, ai_players: [bool; 2], new_ai: F)
+    where G: Game + fmt::Display + 'static,
+          F: Fn() -> Box<dyn Player<G>>,
+{
+    let player = |is_ai: bool| -> Box<dyn Player<G>> {
+        if is_ai {
+            new_ai()
+        } else {
+            Box::new(PromptPlayer::new())
+        }
+    };
+    let players = [player(ai_players[0]), player(ai_players[1])];
+    // The human players already know their own moves
+    run_game(game, players, TextUi::new().with_announced(ai_players));
+}
+
+/// Lists every game and prompts for the game to play until a valid choice is entered, returning
+/// `None` at the end of input or if the user quits
+fn prompt_game() -> Option<&'static GameEntry> {
+    println!("Games:");
+    for (i, entry) in GAMES.iter().enumerate() {
+        println!("{:>3}. {} ({})", i + 1, entry.title, entry.name);
+    }
+
+    loop {
+        let line = match prompt(StdConsole, "Choose a game by its number or name: ") {
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                return None;
+            },
+        };
+        let input = line.trim();
+        if line.is_empty() || input.eq_ignore_ascii_case("quit") {
+            println!();
+            return None;
+        }
+
+        let entry = match input.parse::<usize>() {
+            Ok(number) => number.checked_sub(1).and_then(|index| GAMES.get(index)),
+            Err(_) => GAMES.iter().find(|entry| entry.name.eq_ignore_ascii_case(input) || entry.title.eq_ignore_ascii_case(input)),
+        };
+        match entry {
+            Some(entry) => return Some(entry),
+            None => println!("Invalid choice: `{}`. Enter a number from 1 to {}.\n", input, GAMES.len()),
+        }
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        game: None,
+        ai_players: None,
+        difficulty: AiDifficulty::default(),
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = match value.split(',').collect::<Vec<_>>()[..] {
+                    [x, o] => Some([is_ai(x)?, is_ai(o)?]),
+                    _ => return Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+                };
+            },
+
+            "--difficulty" => {
+                let value = args.next().ok_or("Missing value for `--difficulty`")?;
+                options.difficulty = value.parse().map_err(|err| format!("{}", err))?;
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ if !arg.starts_with('-') && options.game.is_none() => options.game = Some(arg),
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
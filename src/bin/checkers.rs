@@ -0,0 +1,121 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    Checkers,
+    CheckersAi,
+    CheckersMove,
+    Game,
+    Player,
+    PromptPlayer,
+    TextUi,
+    run_game,
+    disable_color_if_unsupported,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// Which players are played by the AI, X first (`--players <human|ai>,<human|ai>`)
+    ai_players: [bool; 2],
+    /// How many moves ahead the AI searches (`--depth <n>`)
+    depth: usize,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let status = |game: &Checkers| {
+        if game.is_game_over() && Game::winner(game).is_none() {
+            "No piece was captured or man moved in a long time.".to_string()
+        } else {
+            String::new()
+        }
+    };
+    let player = |is_ai: bool| -> Box<dyn Player<Checkers>> {
+        if is_ai {
+            Box::new(CheckersAi::new(options.depth))
+        } else {
+            Box::new(PromptPlayer::new().with_parser(parse_move))
+        }
+    };
+    let players = [player(options.ai_players[0]), player(options.ai_players[1])];
+    run_game(Checkers::new(), players, TextUi::new().with_status(status).with_announced(options.ai_players));
+}
+
+/// Parses a move entered by the current player, returning why it is not valid if it cannot be
+/// made
+///
+/// A capture can be entered with only its first and last squares if no other capture starts and
+/// ends on the same squares.
+fn parse_move(game: &Checkers, input: &str) -> Result<CheckersMove, String> {
+    let pmove = input.parse::<CheckersMove>().map_err(|err| err.to_string())?;
+    if game.valid_moves().contains(&pmove) {
+        return Ok(pmove);
+    }
+
+    let shortened: Vec<_> = game.valid_moves().iter()
+        .filter(|valid| pmove.path().len() == 2 && valid.from() == pmove.from() && valid.to() == pmove.to())
+        .collect();
+    match shortened[..] {
+        [valid] => Ok(valid.clone()),
+        [] if game.valid_moves().iter().any(CheckersMove::is_capture) && !pmove.is_capture() => {
+            Err(format!("Invalid move: `{}`. You must capture when you can. Enter `moves` to list the valid moves.", pmove))
+        },
+        [] => Err(format!("Invalid move: `{}`. Enter `moves` to list the valid moves.", pmove)),
+        _ => Err(format!("Ambiguous move: `{}`. Enter every square the piece jumps to.", pmove)),
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        ai_players: [false, true],
+        depth: CheckersAi::default().depth(),
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = match value.split(',').collect::<Vec<_>>()[..] {
+                    [x, o] => [is_ai(x)?, is_ai(o)?],
+                    _ => return Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+                };
+            },
+
+            "--depth" => {
+                let value = args.next().ok_or("Missing value for `--depth`")?;
+                options.depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
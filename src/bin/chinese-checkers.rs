@@ -0,0 +1,121 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    ChineseCheckers,
+    ChineseCheckersAi,
+    ChineseCheckersMove,
+    Game,
+    Seat,
+    Player,
+    PromptPlayer,
+    TextUi,
+    run_game,
+    disable_color_if_unsupported,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// Which players are played by the AI, in the order of their seats, with one entry for each of
+    /// the 2 to 6 players (`--players <human|ai>,<human|ai>,...`)
+    ai_players: Vec<bool>,
+    /// How many of its own moves in a row the AI looks ahead (`--depth <n>`)
+    depth: usize,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let game = ChineseCheckers::new(options.ai_players.len());
+    let status = |game: &ChineseCheckers| {
+        let home: Vec<_> = (0..game.player_count())
+            .map(|index| format!("{} {}", game.seat_name(Seat(index)), game.pieces_in_goal(Seat(index))))
+            .collect();
+        format!("Pieces home: {}", home.join(", "))
+    };
+    let players: Vec<Box<dyn Player<ChineseCheckers>>> = options.ai_players.iter()
+        .map(|&is_ai| -> Box<dyn Player<ChineseCheckers>> {
+            if is_ai {
+                Box::new(ChineseCheckersAi::new(options.depth))
+            } else {
+                Box::new(PromptPlayer::new().with_parser(parse_move))
+            }
+        })
+        .collect();
+    run_game(game, players, TextUi::new().with_status(status).with_announced(options.ai_players.clone()));
+}
+
+/// Parses a move entered by the current player, returning why it is not valid if it cannot be
+/// made
+///
+/// A single hole lists the holes that the piece in it can move to instead.
+fn parse_move(game: &ChineseCheckers, input: &str) -> Result<ChineseCheckersMove, String> {
+    if let Some(from) = ChineseCheckers::holes().into_iter().find(|&hole| ChineseCheckers::hole_name(hole).eq_ignore_ascii_case(input)) {
+        let moves: Vec<_> = game.valid_moves().into_iter().filter(|pmove| pmove.from() == Some(from)).collect();
+        if moves.is_empty() {
+            return Err(format!("Invalid move: `{}`. Choose one of your pieces that can move.", input));
+        }
+        let moves: Vec<_> = moves.iter().map(ChineseCheckersMove::to_string).collect();
+        return Err(format!("The piece in {} can move: {}", ChineseCheckers::hole_name(from), moves.join(", ")));
+    }
+
+    match input.parse() {
+        Ok(pmove) if game.is_valid_move(&pmove) => Ok(pmove),
+        Ok(pmove) => Err(format!("Invalid move: `{}`. Pieces step to a neighboring hole or hop over neighboring pieces. Enter a hole to list the moves of the piece in it.", pmove)),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        ai_players: vec![false, true],
+        depth: ChineseCheckersAi::default().depth(),
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = value.split(',').map(is_ai).collect::<Result<_, _>>()?;
+                if !(2..=6).contains(&options.ai_players.len()) {
+                    return Err(format!("Invalid players: `{}`. Expected 2 to 6 players like `human,ai,ai`", value));
+                }
+            },
+
+            "--depth" => {
+                let value = args.next().ok_or("Missing value for `--depth`")?;
+                options.depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,123 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    DotsAndBoxes,
+    DotsAi,
+    DotsEdge,
+    Piece,
+    Player,
+    PromptPlayer,
+    TextUi,
+    run_game,
+    disable_color_if_unsupported,
+    DOTS_DEFAULT_SIZE,
+    DOTS_MAX_SIZE,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// The number of rows and columns of boxes (`--size <rows>x<cols>`)
+    size: (usize, usize),
+    /// Which players are played by the AI, X first (`--players <human|ai>,<human|ai>`)
+    ai_players: [bool; 2],
+    /// How many moves ahead the AI searches (`--depth <n>`)
+    depth: usize,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let status = |game: &DotsAndBoxes| {
+        format!("Score: {} {}, {} {}", Piece::X, game.score(Piece::X), Piece::O, game.score(Piece::O))
+    };
+    let player = |is_ai: bool| -> Box<dyn Player<DotsAndBoxes>> {
+        if is_ai {
+            Box::new(DotsAi::new(options.depth))
+        } else {
+            Box::new(PromptPlayer::new()
+                .with_parser(parse_edge)
+                .with_message(|game: &DotsAndBoxes| format!("Enter the edge to draw (e.g. {}): ", game.valid_moves()[0])))
+        }
+    };
+    let players = [player(options.ai_players[0]), player(options.ai_players[1])];
+    let game = DotsAndBoxes::new(options.size.0, options.size.1);
+    run_game(game, players, TextUi::new().with_status(status).with_announced(options.ai_players));
+}
+
+/// Parses the edge to draw entered by the current player, returning why it is not valid if it
+/// cannot be drawn
+fn parse_edge(game: &DotsAndBoxes, input: &str) -> Result<DotsEdge, String> {
+    match input.parse() {
+        Ok(edge) if game.is_valid_move(edge) => Ok(edge),
+        Ok(edge) if game.has_edge(edge) => Err(format!("Invalid move: `{}` is already drawn.", edge)),
+        Ok(edge) => Err(format!("Invalid move: `{}` is not on the board.", edge)),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        size: (DOTS_DEFAULT_SIZE, DOTS_DEFAULT_SIZE),
+        ai_players: [false, true],
+        depth: DotsAi::default().depth(),
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let value = args.next().ok_or("Missing value for `--size`")?;
+                let err = || format!("Invalid board size: `{}`. Expected something like `3x3` with at most {} boxes each way",
+                    value, DOTS_MAX_SIZE);
+                let (rows, cols) = value.split_once('x').ok_or_else(err)?;
+                let parse_size = |size: &str| size.trim().parse().ok()
+                    .filter(|size| (1..=DOTS_MAX_SIZE).contains(size))
+                    .ok_or_else(err);
+                options.size = (parse_size(rows)?, parse_size(cols)?);
+            },
+
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = match value.split(',').collect::<Vec<_>>()[..] {
+                    [x, o] => [is_ai(x)?, is_ai(o)?],
+                    _ => return Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+                };
+            },
+
+            "--depth" => {
+                let value = args.next().ok_or("Missing value for `--depth`")?;
+                options.depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,132 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    Go,
+    GoMove,
+    GoScoring,
+    GoKoRule,
+    Game,
+    Piece,
+    Player,
+    PromptPlayer,
+    TextUi,
+    run_game,
+    disable_color_if_unsupported,
+    GO_DEFAULT_SIZE,
+    GO_DEFAULT_KOMI,
+    GO_MIN_SIZE,
+    GO_MAX_SIZE,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// The number of intersections on each side of the board (`--size <n>`)
+    size: usize,
+    /// The points added to the second player's score (`--komi <points>`)
+    komi: f64,
+    /// How the score is counted at the end of the game (`--scoring <area|territory>`)
+    scoring: GoScoring,
+    /// Which earlier positions a move cannot repeat (`--ko <simple|superko>`)
+    ko_rule: GoKoRule,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let game = Go::new(options.size)
+        .with_komi(options.komi)
+        .with_scoring(options.scoring)
+        .with_ko_rule(options.ko_rule);
+    let status = |game: &Go| {
+        let mut status = format!("Captures: {} {}, {} {}\n", Piece::X, game.captures(Piece::X), Piece::O, game.captures(Piece::O));
+        if game.is_game_over() {
+            status += &format!("Score ({} scoring, {} komi): {} {}, {} {}", game.scoring(), game.komi(),
+                Piece::X, game.score(Piece::X), Piece::O, game.score(Piece::O));
+        } else if game.history().last() == Some(&GoMove::Pass) {
+            status += "The other player passed. Pass again to end the game.";
+        }
+        status
+    };
+    let player = || -> Box<dyn Player<Go>> {
+        Box::new(PromptPlayer::new()
+            .with_parser(parse_move)
+            .with_message(|_| "Enter your move (e.g. C3 or pass): ".to_string()))
+    };
+    // Both players are human, so they already know every move
+    run_game(game, [player(), player()], TextUi::new().with_status(status).with_announced([false, false]));
+}
+
+/// Parses a move entered by the current player, returning why it is not valid if it cannot be
+/// made
+fn parse_move(game: &Go, input: &str) -> Result<GoMove, String> {
+    match input.parse() {
+        Ok(GoMove::Pass) => Ok(GoMove::Pass),
+        Ok(GoMove::Place(pos)) => match game.check_move(pos) {
+            Ok(()) => Ok(GoMove::Place(pos)),
+            Err(err) => Err(format!("Invalid move: `{}`. {}.", pos, err)),
+        },
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        size: GO_DEFAULT_SIZE,
+        komi: GO_DEFAULT_KOMI,
+        scoring: GoScoring::default(),
+        ko_rule: GoKoRule::default(),
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let value = args.next().ok_or("Missing value for `--size`")?;
+                options.size = value.parse().ok()
+                    .filter(|size| (GO_MIN_SIZE..=GO_MAX_SIZE).contains(size))
+                    .ok_or_else(|| format!("Invalid board size: `{}`. Expected a size from {} to {}",
+                        value, GO_MIN_SIZE, GO_MAX_SIZE))?;
+            },
+
+            "--komi" => {
+                let value = args.next().ok_or("Missing value for `--komi`")?;
+                options.komi = value.parse().ok().filter(|komi: &f64| komi.is_finite())
+                    .ok_or_else(|| format!("Invalid komi: `{}`", value))?;
+            },
+
+            "--scoring" => {
+                let value = args.next().ok_or("Missing value for `--scoring`")?;
+                options.scoring = value.parse().map_err(|err| format!("{}", err))?;
+            },
+
+            "--ko" => {
+                let value = args.next().ok_or("Missing value for `--ko`")?;
+                options.ko_rule = value.parse().map_err(|err| format!("{}", err))?;
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,184 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    Gomoku,
+    GomokuOpening,
+    GomokuRules,
+    GomokuMove,
+    GomokuAi,
+    Game,
+    Piece,
+    TilePos,
+    Player,
+    PromptPlayer,
+    TextUi,
+    run_game,
+    disable_color_if_unsupported,
+    GOMOKU_STANDARD_SIZE,
+    GOMOKU_MIN_SIZE,
+    GOMOKU_MAX_SIZE,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// The number of intersections on each side of the board (`--size <n>`)
+    size: usize,
+    /// The restrictions on the first player's opening stones (`--opening <free|pro|long-pro>`)
+    opening: GomokuOpening,
+    /// The rules the game is played with (`--rules <gomoku|connect6|pente>`)
+    rules: GomokuRules,
+    /// Which players are played by the AI, X first (`--players <human|ai>,<human|ai>`)
+    ai_players: [bool; 2],
+    /// How many moves ahead the AI searches (`--depth <n>`)
+    depth: usize,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let status = |game: &Gomoku| {
+        let mut status = String::new();
+        if game.rules() == GomokuRules::Pente {
+            status += &format!("Captures: {} {}, {} {}\n", Piece::X, game.captures(Piece::X), Piece::O, game.captures(Piece::O));
+        }
+        if game.is_game_over() && Game::winner(game).is_none() {
+            status += "The board is full.";
+        }
+        status
+    };
+    let player = |is_ai: bool| -> Box<dyn Player<Gomoku>> {
+        if is_ai {
+            Box::new(GomokuAi::new(options.depth))
+        } else {
+            Box::new(PromptPlayer::new().with_parser(parse_move).with_message(move_message))
+        }
+    };
+    let players = [player(options.ai_players[0]), player(options.ai_players[1])];
+    let game = Gomoku::new(options.size, options.opening).with_rules(options.rules);
+    run_game(game, players, TextUi::new().with_status(status).with_announced(options.ai_players));
+}
+
+/// Returns an example of the stones the current player places this turn
+fn example_move(game: &Gomoku) -> GomokuMove {
+    let center = game.center();
+    match game.stones_to_place() {
+        1 => GomokuMove::Single(center),
+        _ => GomokuMove::Pair(center, TilePos {col: center.col + 1, ..center}),
+    }
+}
+
+/// Returns the text to prompt for the stones the current player places this turn with
+fn move_message(game: &Gomoku) -> String {
+    match game.stones_to_place() {
+        1 => format!("Enter your move (e.g. {}): ", example_move(game)),
+        _ => format!("Enter your two stones (e.g. {}): ", example_move(game)),
+    }
+}
+
+/// Parses the stones entered by the current player, returning why they are not a valid move if
+/// they cannot be placed
+fn parse_move(game: &Gomoku, input: &str) -> Result<GomokuMove, String> {
+    match input.parse() {
+        Ok(pmove) if game.is_valid_move(pmove) => Ok(pmove),
+        Ok(pmove) => Err(format!("Invalid move: `{}`. {}", pmove, invalid_move_reason(game, pmove))),
+        Err(_) => Err(format!("Invalid input: `{}`. Enter something like '{}'.", input, example_move(game))),
+    }
+}
+
+/// Returns why the given move is not valid
+fn invalid_move_reason(game: &Gomoku, pmove: GomokuMove) -> String {
+    let stones = pmove.stones();
+    if stones.len() != game.stones_to_place() {
+        return format!("Place {} stone(s) this turn.", game.stones_to_place());
+    }
+    if let GomokuMove::Pair(first, second) = pmove {
+        if first == second {
+            return "Choose two different intersections.".to_string();
+        }
+    }
+    if stones.iter().any(|&pos| pos.row >= game.size() || pos.col >= game.size() || game.stone(pos).is_some()) {
+        return "Choose an empty intersection on the board.".to_string();
+    }
+
+    if game.history().is_empty() {
+        format!("The first stone must be placed in the center ({}).", game.center())
+    } else {
+        format!("Your second stone must be further from the center ({} rules).", game.opening())
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        size: GOMOKU_STANDARD_SIZE,
+        opening: GomokuOpening::default(),
+        rules: GomokuRules::default(),
+        ai_players: [false, true],
+        depth: GomokuAi::default().depth(),
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let value = args.next().ok_or("Missing value for `--size`")?;
+                options.size = value.parse().ok()
+                    .filter(|size| (GOMOKU_MIN_SIZE..=GOMOKU_MAX_SIZE).contains(size))
+                    .ok_or_else(|| format!("Invalid board size: `{}`. Expected a size from {} to {}",
+                        value, GOMOKU_MIN_SIZE, GOMOKU_MAX_SIZE))?;
+            },
+
+            "--opening" => {
+                let value = args.next().ok_or("Missing value for `--opening`")?;
+                options.opening = value.parse().map_err(|err| format!("{}", err))?;
+            },
+
+            "--rules" => {
+                let value = args.next().ok_or("Missing value for `--rules`")?;
+                options.rules = value.parse().map_err(|err| format!("{}", err))?;
+            },
+
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = match value.split(',').collect::<Vec<_>>()[..] {
+                    [x, o] => [is_ai(x)?, is_ai(o)?],
+                    _ => return Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+                };
+            },
+
+            "--depth" => {
+                let value = args.next().ok_or("Missing value for `--depth`")?;
+                options.depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,118 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    Kalah,
+    KalahAi,
+    KalahMove,
+    Game,
+    Player,
+    PromptPlayer,
+    TextUi,
+    run_game,
+    disable_color_if_unsupported,
+    KALAH_DEFAULT_SEEDS,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// The number of seeds in each pit at the start of the game (`--seeds <n>`)
+    seeds: usize,
+    /// Which players are played by the AI, X first (`--players <human|ai>,<human|ai>`)
+    ai_players: [bool; 2],
+    /// How many moves ahead the AI searches (`--depth <n>`)
+    depth: usize,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let status = |game: &Kalah| match game.last_move() {
+        Some((last_player, _)) if last_player == game.current_player() && !game.is_game_over() => {
+            format!("{}'s last seed landed in their store, so they move again", last_player)
+        },
+        _ => String::new(),
+    };
+    let player = |is_ai: bool| -> Box<dyn Player<Kalah>> {
+        if is_ai {
+            Box::new(KalahAi::new(options.depth))
+        } else {
+            Box::new(PromptPlayer::new()
+                .with_parser(parse_pit)
+                .with_message(|game: &Kalah| format!("Enter the pit to sow (e.g. {}): ", game.valid_moves()[0])))
+        }
+    };
+    let players = [player(options.ai_players[0]), player(options.ai_players[1])];
+    run_game(Kalah::new(options.seeds), players, TextUi::new().with_status(status).with_announced(options.ai_players));
+}
+
+/// Parses the pit to sow entered by the current player, returning why it is not valid if it
+/// cannot be sown
+fn parse_pit(game: &Kalah, input: &str) -> Result<KalahMove, String> {
+    match input.parse() {
+        Ok(pmove) if game.is_valid_move(pmove) => Ok(pmove),
+        Ok(pmove) => Err(format!("Invalid move: pit {} is empty.", pmove)),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        seeds: KALAH_DEFAULT_SEEDS,
+        ai_players: [false, true],
+        depth: KalahAi::default().depth(),
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seeds" => {
+                let value = args.next().ok_or("Missing value for `--seeds`")?;
+                options.seeds = value.parse().ok().filter(|&seeds: &usize| seeds > 0)
+                    .ok_or_else(|| format!("Invalid number of seeds: `{}`", value))?;
+            },
+
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = match value.split(',').collect::<Vec<_>>()[..] {
+                    [x, o] => [is_ai(x)?, is_ai(o)?],
+                    _ => return Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+                };
+            },
+
+            "--depth" => {
+                let value = args.next().ok_or("Missing value for `--depth`")?;
+                options.depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
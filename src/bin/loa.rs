@@ -0,0 +1,138 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    LinesOfAction,
+    LinesOfActionAi,
+    LinesOfActionMove,
+    DisplayOptions,
+    Theme,
+    Piece,
+    TilePos,
+    Player,
+    PromptPlayer,
+    TextUi,
+    run_game,
+    disable_color_if_unsupported,
+    color_enabled,
+    write_grid,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// Which players are played by the AI, X first (`--players <human|ai>,<human|ai>`)
+    ai_players: [bool; 2],
+    /// How many moves ahead the AI searches (`--depth <n>`)
+    depth: usize,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+    /// The glyphs and colors to draw the board with, if chosen
+    /// (`--theme <classic|minimal|high-contrast|colorblind|shapes>`)
+    theme: Option<Theme>,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    // Both pieces are the same shape in the classic theme, so they can only be told apart by color
+    let theme = options.theme.unwrap_or_else(|| if color_enabled() { Theme::default() } else { Theme::high_contrast() });
+    let board = move |game: &LinesOfAction| {
+        let last_move = game.last_move().map(|pmove| (pmove.to, &[][..]));
+        let mut board = Vec::new();
+        write_grid(&mut board, game.grid(), DisplayOptions {last_move, theme, ..DisplayOptions::default()})
+            .expect("bug: writing to a Vec should not fail");
+        String::from_utf8(board).expect("bug: the board should be valid UTF-8")
+    };
+    let status = |game: &LinesOfAction| {
+        format!("Pieces: {} {}, {} {}", Piece::X, game.count_pieces(Piece::X), Piece::O, game.count_pieces(Piece::O))
+    };
+    let player = |is_ai: bool| -> Box<dyn Player<LinesOfAction>> {
+        if is_ai {
+            Box::new(LinesOfActionAi::new(options.depth))
+        } else {
+            Box::new(PromptPlayer::new().with_parser(parse_move))
+        }
+    };
+    let players = [player(options.ai_players[0]), player(options.ai_players[1])];
+    let ui = TextUi::new().with_board(board).with_status(status).with_announced(options.ai_players);
+    run_game(LinesOfAction::new(), players, ui);
+}
+
+/// Parses a move entered by the current player, returning why it is not valid if it cannot be
+/// made
+///
+/// A single tile lists the moves of the piece on it instead.
+fn parse_move(game: &LinesOfAction, input: &str) -> Result<LinesOfActionMove, String> {
+    if let Ok(from) = input.parse::<TilePos>() {
+        let moves: Vec<_> = game.valid_moves().into_iter().filter(|pmove| pmove.from == from).collect();
+        if moves.is_empty() {
+            return Err(format!("Invalid move: `{}`. Choose one of your pieces that can move.", from));
+        }
+        let moves: Vec<_> = moves.iter().map(|pmove| pmove.to.to_string()).collect();
+        return Err(format!("The piece on {} can move to: {}", from, moves.join(", ")));
+    }
+
+    match input.parse() {
+        Ok(pmove) if game.is_valid_move(pmove) => Ok(pmove),
+        Ok(pmove) => Err(format!("Invalid move: `{}`. A piece moves as many tiles as there are pieces on its line, and cannot jump over the opponent's pieces.", pmove)),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        ai_players: [false, true],
+        depth: LinesOfActionAi::default().depth(),
+        no_color: false,
+        theme: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = match value.split(',').collect::<Vec<_>>()[..] {
+                    [x, o] => [is_ai(x)?, is_ai(o)?],
+                    _ => return Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+                };
+            },
+
+            "--depth" => {
+                let value = args.next().ok_or("Missing value for `--depth`")?;
+                options.depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
+            },
+
+            "--no-color" => options.no_color = true,
+
+            "--theme" => {
+                let value = args.next().ok_or("Missing value for `--theme`")?;
+                options.theme = Some(value.parse().map_err(|err| format!("{}", err))?);
+            },
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,180 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    Morris,
+    MorrisAi,
+    MorrisMove,
+    Game,
+    TilePos,
+    Player,
+    PlayerAction,
+    TextUi,
+    run_game,
+    StdConsole,
+    prompt,
+    disable_color_if_unsupported,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// Which players are played by the AI, X first (`--players <human|ai>,<human|ai>`)
+    ai_players: [bool; 2],
+    /// How many moves ahead the AI searches (`--depth <n>`)
+    depth: usize,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let status = |game: &Morris| {
+        if game.is_game_over() && Game::winner(game).is_none() {
+            "Nobody formed a mill in a long time.".to_string()
+        } else {
+            String::new()
+        }
+    };
+    let player = |is_ai: bool| -> Box<dyn Player<Morris>> {
+        if is_ai {
+            Box::new(MorrisAi::new(options.depth))
+        } else {
+            Box::new(HumanPlayer)
+        }
+    };
+    let players = [player(options.ai_players[0]), player(options.ai_players[1])];
+    run_game(Morris::new(), players, TextUi::new().with_status(status).with_announced(options.ai_players));
+}
+
+/// A player entering their moves at the prompt (see `prompt_move`)
+struct HumanPlayer;
+
+impl Player<Morris> for HumanPlayer {
+    fn choose_move(&mut self, game: &Morris) -> MorrisMove {
+        prompt_move(game).expect("the player quit instead of entering a move")
+    }
+
+    fn choose_action(&mut self, game: &Morris) -> PlayerAction<MorrisMove> {
+        match prompt_move(game) {
+            Some(pmove) => PlayerAction::Move(pmove),
+            None => PlayerAction::Quit,
+        }
+    }
+}
+
+/// Repeatedly prompts for the current player's move until a valid move is entered, returning
+/// `None` at the end of input or if the player quits
+///
+/// If the move forms a mill but does not say which piece to remove, the player is asked for it
+/// separately.
+fn prompt_move(game: &Morris) -> Option<MorrisMove> {
+    let valid_moves = game.valid_moves();
+    let example = valid_moves[0];
+    loop {
+        let input = prompt_line(&format!("Enter your move (e.g. {}): ", example))?;
+        let pmove = match input.parse::<MorrisMove>() {
+            Ok(pmove) => pmove,
+            Err(err) => {
+                println!("{}\n", err);
+                continue;
+            },
+        };
+        if valid_moves.contains(&pmove) {
+            return Some(pmove);
+        }
+
+        let forms_mill = pmove.remove.is_none() && valid_moves.iter()
+            .any(|valid| valid.from == pmove.from && valid.to == pmove.to && valid.remove.is_some());
+        if !forms_mill {
+            println!("Invalid move: `{}`.\n", pmove);
+            continue;
+        }
+
+        println!("You formed a mill!");
+        loop {
+            let input = prompt_line("Enter the opponent's piece to remove: ")?;
+            match input.parse::<TilePos>() {
+                Ok(remove) if valid_moves.contains(&MorrisMove {remove: Some(remove), ..pmove}) => {
+                    return Some(MorrisMove {remove: Some(remove), ..pmove});
+                },
+                Ok(remove) if game.piece(remove) != Some(game.current_player().opposite()) => {
+                    println!("Invalid piece: `{}`. Choose one of your opponent's pieces.\n", remove);
+                },
+                Ok(remove) => println!("Invalid piece: `{}`. Pieces in a mill can only be removed if every piece is in a mill.\n", remove),
+                Err(err) => println!("{}\n", err),
+            }
+        }
+    }
+}
+
+/// Prompts for a line of input, returning it trimmed or `None` at the end of input or if the player
+/// quits
+fn prompt_line(message: &str) -> Option<String> {
+    let line = match prompt(StdConsole, message) {
+        Ok(line) => line,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return None;
+        },
+    };
+    let input = line.trim();
+    if line.is_empty() || input.eq_ignore_ascii_case("quit") {
+        println!();
+        return None;
+    }
+
+    Some(input.to_string())
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        ai_players: [false, true],
+        depth: MorrisAi::default().depth(),
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = match value.split(',').collect::<Vec<_>>()[..] {
+                    [x, o] => [is_ai(x)?, is_ai(o)?],
+                    _ => return Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+                };
+            },
+
+            "--depth" => {
+                let value = args.next().ok_or("Missing value for `--depth`")?;
+                options.depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,121 @@
+use std::env;
+use std::process;
+
+use yansi::Paint;
+
+use board_games::{
+    Nim,
+    NimAi,
+    NimMove,
+    NimVariant,
+    Player,
+    PromptPlayer,
+    TextUi,
+    run_game,
+    disable_color_if_unsupported,
+    NIM_DEFAULT_PILES,
+};
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// The sizes of the piles at the start of the game (`--piles <n>,<n>,...`)
+    piles: Vec<usize>,
+    /// Who wins the game (`--variant <normal|misere>`)
+    variant: NimVariant,
+    /// Which players are played by the AI, X first (`--players <human|ai>,<human|ai>`)
+    ai_players: [bool; 2],
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+fn main() {
+    disable_color_if_unsupported();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.no_color {
+        Paint::disable();
+    }
+
+    let game = Nim::new(options.piles, options.variant);
+    println!("Playing {} Nim: the player who takes the last object {}.", game.variant(),
+        if game.variant() == NimVariant::Normal { "wins" } else { "loses" });
+    let player = |is_ai: bool| -> Box<dyn Player<Nim>> {
+        if is_ai {
+            Box::new(NimAi)
+        } else {
+            Box::new(PromptPlayer::new()
+                .with_parser(parse_move)
+                .with_message(|_| "Enter the pile and how many to take (e.g. 2:3): ".to_string()))
+        }
+    };
+    let players = [player(options.ai_players[0]), player(options.ai_players[1])];
+    run_game(game, players, TextUi::new().with_announced(options.ai_players));
+}
+
+/// Parses a move entered by the current player, returning why it is not valid if it cannot be
+/// made
+fn parse_move(game: &Nim, input: &str) -> Result<NimMove, String> {
+    match input.parse::<NimMove>() {
+        Ok(pmove) if game.is_valid_move(pmove) => Ok(pmove),
+        Ok(pmove) if pmove.pile > game.piles().len() => {
+            Err(format!("Invalid move: there is no pile {}.", pmove.pile))
+        },
+        Ok(pmove) => {
+            Err(format!("Invalid move: pile {} only has {} left.", pmove.pile, game.piles()[pmove.pile - 1]))
+        },
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        piles: NIM_DEFAULT_PILES.to_vec(),
+        variant: NimVariant::default(),
+        ai_players: [false, true],
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--piles" => {
+                let value = args.next().ok_or("Missing value for `--piles`")?;
+                options.piles = value.split(',')
+                    .map(|size| size.trim().parse().ok().filter(|&size: &usize| size > 0))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| format!("Invalid piles: `{}`. Expected pile sizes like `3,4,5`", value))?;
+            },
+
+            "--variant" => {
+                let value = args.next().ok_or("Missing value for `--variant`")?;
+                options.variant = value.parse().map_err(|err| format!("{}", err))?;
+            },
+
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                let is_ai = |kind: &str| match kind.trim() {
+                    "human" => Ok(false),
+                    "ai" => Ok(true),
+                    _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+                };
+                options.ai_players = match value.split(',').collect::<Vec<_>>()[..] {
+                    [x, o] => [is_ai(x)?, is_ai(o)?],
+                    _ => return Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+                };
+            },
+
+            "--no-color" => options.no_color = true,
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
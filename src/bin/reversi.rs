@@ -1,94 +1,2006 @@
+use std::cell::Cell;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use yansi::Paint;
 
 use board_games::{
     Reversi,
+    parse_ascii_board,
+    TerminationReason,
+    Clock,
+    TimeControl,
     Piece,
-    ParseError,
-    prompt,
-    prompt_move,
+    TilePos,
+    Variant,
+    Move,
+    Player,
+    PlayerAction,
+    HumanPrompt,
+    Ui,
+    GameEnd,
+    Seat,
+    PlayerConfig,
+    run_game,
+    MoveInput,
+    parse_input,
+    parse_openings,
     print_game,
-    compute_ai_move,
+    disable_color_if_unsupported,
+    print_game_with,
+    animate_game,
+    DisplayOptions,
+    Theme,
+    Orientation,
+    RenderSize,
+    OpeningBook,
+    load_wthor_games,
+    GameRecord,
+    NotatedMove,
+    GameArchive,
+    PlayerStats,
+    RecordStats,
+    StatsError,
+    StdConsole,
+    prompt,
+    AiDifficulty,
+    AiPlayer,
+    Ponder,
+    WeightedEvaluator,
+    Grid,
+    Tablebase,
+    analyze,
+    suggest_move,
+    explain_move,
+    SvgOptions,
+    render_svg,
+    render_html,
 };
+#[cfg(feature = "cursor")]
+use board_games::{clear_screen, redraw_game};
+
+/// Printed by `--help`, with every option of the game and the subcommands
+const USAGE: &str = "\
+Play reversi against the AI or another human in the terminal
+
+Usage: reversi [options]
+       reversi <subcommand> [options]
+
+Game:
+  --variant <standard|anti>     The rules to play with
+  --players <human|ai>,<human|ai>
+                                Who plays X and who plays O (default: human,ai)
+  --difficulty <beginner|easy|medium|hard|expert>
+                                How strong the AI plays
+  --size <4|6>                  Play on a smaller square board
+  --octagon                     Play on an octagonal board
+  --xot                         Start from a random balanced opening
+  --openings <file>             Start from a random opening in the given file instead
+  --resume <file>               Continue a game saved with `save`
+  --seed <number>               The seed for all random choices, to replay a game exactly
+  --time <seconds>              The main time given to each player
+  --increment <seconds>         The time added after each move
+  --byo-yomi <seconds>          The byo-yomi period once main time runs out
+
+AI:
+  --noise <n>                   The largest random noise added to the AI's score for each move
+  --book <file>                 An opening book to use instead of the builtin book
+  --learn-book <file>           Add the game to the given opening book once it is over
+  --tablebase <file>            Solved positions for the board given by `--size`, saved back once
+                                the game is over
+  --weights <file>              A weights file written by `selfplay tune`
+  --model <file>                A neural network to evaluate positions with (`mlp` feature)
+  --probcut                     Prune the search with Multi-ProbCut
+  --ponder                      Keep thinking while the human is thinking
+  --show-pv                     Print the line of play the AI expects after each of its moves
+  --explain                     Print why the AI chose each of its moves
+  --search-stats                Print statistics about each of the AI's searches
+
+Display:
+  --theme <classic|minimal|high-contrast|colorblind|shapes>
+                                The glyphs and colors to draw the board with
+  --ascii                       Draw the board with plain ASCII characters
+  --no-color                    Never write terminal colors or styles
+  --render-size <compact|normal|large>
+                                How big the tiles of the board are drawn
+  --numbered                    Label the valid moves so they can be chosen by their labels
+  --flip                        Draw the board from O's side
+  --score-bar                   Show how the discs are split under the board
+  --eval-bar                    Show the score of the AI's last search under the board
+  --heatmap                     Color the valid moves by how good the AI thinks they are
+  --summarize-human             Print a summary of the human's moves as well as the AI's
+  --animate                     Show flipped discs turning over after each move
+  --frame-time <seconds>        How long each frame of the animation is shown for
+  --delay-ms <milliseconds>     How long the AI waits after each of its moves
+  --cursor                      Choose moves with the arrow keys (`cursor` feature)
+  --redraw                      Draw the board in the same place every turn (`cursor` feature)
+
+Files:
+  --save-html <file>            Write a web page that steps through the game once it is over
+  --archive <dir>               The directory finished games are added to
+  --no-archive                  Don't add finished games to the archive
+  --stats <file>                The file the results of finished games are recorded in
+  --no-stats                    Don't record the results of finished games
+  --moves-file <file>           Play the moves and commands in the given file (`-` for stdin)
+                                instead of prompting for them
+  --verbose                     Print the board and every move while playing `--moves-file`
+  --no-confirm                  Never ask to confirm resigning, quitting or loading a game
+  -h, --help                    Print this message
+
+Subcommands:
+  analyze       Rank every legal move of a position
+  tablebase     Solve every position of a small board
+  book          Merge, export or import opening books
+  export-svg    Draw a position as an SVG image
+  export-html   Write a web page that steps through a game
+  replay        Replay a saved game
+  history       List or replay the archived games
+  stats         Show the results of the recorded games
+";
+
+/// The longest the AI keeps searching on the opponent's time
+const PONDER_TIME: Duration = Duration::from_secs(30);
+
+/// The number of moves at the start of each game that are added to the book given by
+/// `--learn-book`
+const LEARN_BOOK_DEPTH: usize = 20;
+
+/// How long the AI waits after its move when flips are not animated, to make the game easier to
+/// follow
+const AI_MOVE_DELAY: Duration = Duration::from_millis(200);
+
+/// How many moves ahead each move is searched for `--heatmap`, kept shallow so that the board is
+/// shown without a noticeable delay
+const HEATMAP_DEPTH: usize = 4;
+
+/// How long each frame of the flip animation is shown for unless `--frame-time` is given
+const DEFAULT_FRAME_TIME: Duration = Duration::from_millis(70);
 
 fn main() {
-    let mut game = Reversi::default();
+    disable_color_if_unsupported();
 
-    // Set this variable to control the game type
-    //let ai_controlled = &[]; // Human vs Human
-    let ai_controlled = &[Piece::O]; // Human vs AI
-    //let ai_controlled = &[Piece::X, Piece::O]; // AI vs AI
+    match env::args().nth(1).as_deref() {
+        Some("analyze") => return main_analyze(),
+        Some("tablebase") => return main_tablebase(),
+        Some("book") => return main_book(),
+        Some("export-svg") => return main_export_svg(),
+        Some("export-html") => return main_export_html(),
+        Some("replay") => return main_replay(),
+        Some("history") => return main_history(),
+        Some("stats") => return main_stats(),
+        _ => {},
+    }
 
-    let mut skipped = false;
-    loop {
-        let (x_score, o_score) = game.scores();
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+    if options.help {
+        print!("{}", USAGE);
+        return;
+    }
+    if options.no_color {
+        Paint::disable();
+    }
+
+    // Every random choice comes from this generator so that games can be replayed with `--seed`
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let mut game = match new_game(&mut rng, &options) {
+        Ok(game) => game,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+
+    // A book that is being learned is played from once it exists, so the AI avoids its losses
+    let book_path = options.book.as_ref()
+        .or(options.learn_book.as_ref().filter(|path| path.exists()));
+    let book = match book_path {
+        Some(path) => match OpeningBook::load(path) {
+            Ok(book) => book,
+            Err(err) => {
+                eprintln!("Error: Unable to load opening book `{}`: {}", path.display(), err);
+                process::exit(1);
+            },
+        },
+        None => OpeningBook::builtin().clone(),
+    };
+    let learned_book = options.learn_book.as_ref().map(|path| (path, book.clone()));
+    // A resumed game is played by the same players it was saved with, unless they are given again
+    let saved_players = game.players();
+    let difficulty = options.difficulty.or(saved_players.map(|players| players.difficulty)).unwrap_or_default();
+    let mut ai = AiPlayer::with_rng(difficulty, book, StdRng::seed_from_u64(rng.gen()));
+    if let Some(noise) = options.noise {
+        ai = ai.with_noise(noise);
+    }
+    ai = ai.with_probcut(options.probcut);
+    if let Some(path) = &options.weights {
+        let mut evaluator = WeightedEvaluator::default();
+        if let Err(err) = evaluator.load_weights(path) {
+            eprintln!("Error: Unable to load weights `{}`: {}", path.display(), err);
+            process::exit(1);
+        }
+        ai = ai.with_evaluator(evaluator);
+    }
+    if let Some(size) = options.size {
+        // A tablebase file that doesn't exist yet is created once the game is over
+        let tablebase = match options.tablebase.as_ref().filter(|path| path.exists()) {
+            Some(path) => match Tablebase::load(path) {
+                Ok(tablebase) => tablebase,
+                Err(err) => {
+                    eprintln!("Error: Unable to load tablebase `{}`: {}", path.display(), err);
+                    process::exit(1);
+                },
+            },
+            // Positions are solved as they come up during the game
+            None => Tablebase::new(options.variant, size),
+        };
+        ai = ai.with_tablebase(tablebase);
+    }
+    let learned_tablebase = options.tablebase.as_ref().zip(ai.tablebase().cloned());
+    #[cfg(feature = "mlp")]
+    if let Some(path) = &options.model {
+        match board_games::MlpEvaluator::load(path) {
+            Ok(evaluator) => ai = ai.with_evaluator(evaluator),
+            Err(err) => {
+                eprintln!("Error: Unable to load model `{}`: {}", path.display(), err);
+                process::exit(1);
+            },
+        }
+    }
+
+    if let Some(initial) = options.time {
+        game.set_clock(Clock::new(TimeControl {
+            initial,
+            increment: options.increment,
+            byo_yomi: options.byo_yomi,
+        }));
+    }
+
+    let players = options.players
+        .or_else(|| saved_players.map(|players| players.ai_players.map(|is_ai| if is_ai { PlayerKind::Ai } else { PlayerKind::Human })))
+        .unwrap_or([PlayerKind::Human, PlayerKind::Ai]);
+    game.set_players(PlayerConfig {ai_players: players.map(|kind| kind == PlayerKind::Ai), difficulty});
+    let evaluation = Rc::new(Cell::new(None));
+    let new_ai = || CliAi {
+        ai: ai.clone(),
+        evaluation: evaluation.clone(),
+        show_pv: options.show_pv,
+        explain: options.explain,
+        search_stats: options.search_stats,
+        // Two AIs pondering at the same time would only slow each other down
+        ponder: options.ponder && players.contains(&PlayerKind::Human),
+        pondering: None,
+        delay: match options.delay {
+            Some(delay) => delay,
+            // The animation already slows down the game, and scripts should run as fast as they can
+            None if options.animate || options.moves_file.is_some() => Duration::from_secs(0),
+            None => AI_MOVE_DELAY,
+        },
+    };
+    let orientation = Orientation::new(options.flip);
+    let human = HumanPrompt::new()
+        .with_theme(options.theme)
+        .with_orientation(orientation.clone())
+        .with_render_size(options.render_size)
+        .with_numbered_moves(options.numbered)
+        // Piped input has no one to answer the confirmations
+        .with_confirmations(!options.no_confirm && io::stdin().is_terminal());
+    #[cfg(feature = "cursor")]
+    let human = human.with_cursor(options.cursor);
+    // Clones of the AI share its tablebase, and clones of the human share the board orientation
+    let new_player = |kind: PlayerKind| -> Box<dyn Player> {
+        match kind {
+            PlayerKind::Human => Box::new(human.clone()),
+            PlayerKind::Ai => Box::new(new_ai()),
+        }
+    };
+
+    if let Some(path) = &options.moves_file {
+        let mut ais = players.map(|kind| match kind {
+            PlayerKind::Human => None,
+            PlayerKind::Ai => Some(new_ai()),
+        });
+        let result = read_script(path)
+            .and_then(|script| run_script(&mut game, &script, &mut ais, options.verbose, options.theme));
+        match result {
+            Ok(code) => process::exit(code),
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                process::exit(1);
+            },
+        }
+    }
+
+    let ui = CliUi {
+        options: &options,
+        orientation,
+        evaluation: evaluation.clone(),
+        ai_players: players.map(|kind| kind == PlayerKind::Ai),
+        learned_book,
+        learned_tablebase,
+        undone: Vec::new(),
+        archive: if options.no_archive {
+            None
+        } else {
+            options.archive.clone().or_else(GameArchive::default_dir).map(GameArchive::new)
+        },
+        started: Instant::now(),
+        stats_path: if options.no_stats {
+            None
+        } else {
+            options.stats.clone().or_else(PlayerStats::default_path)
+        },
+    };
+    run_game(game, [new_player(players[0]), new_player(players[1])], ui);
+}
+
+/// Shows the game on the command line as it is played by `run_game`
+struct CliUi<'a> {
+    options: &'a Options,
+    orientation: Orientation,
+    /// The score of the AI's last search from X's perspective, shown by `--eval-bar`
+    evaluation: Rc<Cell<Option<i32>>>,
+    /// Which players are played by the AI, X first
+    ai_players: [bool; 2],
+    /// The path and contents of the book given by `--learn-book`, if any
+    learned_book: Option<(&'a PathBuf, OpeningBook)>,
+    /// The path of the tablebase given by `--tablebase` and the tablebase shared by the AI
+    /// players, which is saved once the game is over
+    learned_tablebase: Option<(&'a PathBuf, Arc<Mutex<Tablebase>>)>,
+    /// The moves taken back by `undo`, with the last one taken back at the end
+    undone: Vec<Move>,
+    /// The archive that the game is added to once it is over, unless archiving is turned off
+    archive: Option<GameArchive>,
+    /// When the game started, to record how long it took in the archive
+    started: Instant,
+    /// The stats file that the result of the game is recorded in once it is over, unless stats
+    /// are turned off
+    stats_path: Option<PathBuf>,
+}
+
+impl CliUi<'_> {
+    /// Prints the board along with the score
+    fn show_board(&self, game: &Reversi) {
+        let options = self.options;
         let valid_moves = game.valid_moves();
+        let last_move = game.last_move_flips();
+        let heatmap = if options.heatmap { analyze(game, HEATMAP_DEPTH) } else { Vec::new() };
+        let display = DisplayOptions {
+            valid_moves,
+            numbered_moves: options.numbered,
+            flip_board: self.orientation.is_flipped(),
+            size: options.render_size,
+            score_bar: options.score_bar,
+            evaluation: if options.eval_bar { self.evaluation.get() } else { None },
+            heatmap: &heatmap,
+            last_move: last_move.as_ref().map(|(pos, flips)| (*pos, &flips[..])),
+            theme: options.theme,
+            ..DisplayOptions::default()
+        };
 
-        // If the grid is full or the turn is skipped twice, the game ends
-        if game.grid().is_full() || (skipped && valid_moves.is_empty()) {
-            // Game has been completed
-            println!();
-            print_game(&game, &valid_moves);
-            println!();
-            println!("Score: {} {} | {} {}", Piece::X, x_score, Piece::O, o_score);
+        println!();
+        draw_board(game, display, options);
+        println!();
+
+        let (x_score, o_score) = game.scores();
+        println!("Score: {} {} | {} {}", Piece::X, x_score, Piece::O, o_score);
+    }
+}
+
+/// Draws the board as configured by the command line options, redrawing it in place or animating
+/// the last move if asked to
+fn draw_board(game: &Reversi, display: DisplayOptions, options: &Options) {
+    let frame_time = options.frame_time.unwrap_or(DEFAULT_FRAME_TIME);
+    #[cfg(feature = "cursor")]
+    if options.redraw {
+        if options.animate && display.last_move.is_some() {
+            clear_screen().expect("failed clearing the terminal");
+            animate_game(game, display, frame_time);
+        } else {
+            redraw_game(game, display).expect("failed printing to stdout");
+        }
+        return;
+    }
+
+    if options.animate && display.last_move.is_some() {
+        animate_game(game, display, frame_time);
+    } else {
+        print_game_with(game, display);
+    }
+}
+
+impl Ui<Reversi> for CliUi<'_> {
+    fn show_turn(&mut self, game: &Reversi, _clock: Option<&Clock>) {
+        self.show_board(game);
+        // Reversi keeps its own clock instead of the one given by the game loop
+        if let Some(clock) = game.clock() {
+            println!("Time: {} {} | {} {}", Piece::X, clock.format_remaining(Piece::X),
+                Piece::O, clock.format_remaining(Piece::O));
+        }
+        println!("The current piece is: {}", game.current_player());
+    }
 
-            use std::cmp::Ordering::*;
-            match x_score.cmp(&o_score) {
-                Greater => println!("The winner is: {}", Piece::X),
-                Less => println!("The winner is: {}", Piece::O),
-                Equal => println!("The game ended with a tie"),
+    fn show_move(&mut self, game: &Reversi, seat: Seat, &pmove: &Move) {
+        // A new move replaces the moves that were taken back
+        self.undone.clear();
+
+        // The AI's moves are summarized since they are easy to miss in the redrawn board
+        let player = seat.piece().expect("bug: Reversi only has two players");
+        let is_ai = self.ai_players[seat.index()];
+        if (is_ai || self.options.summarize_human) && game.history().last() == Some(&pmove) {
+            let flips = game.last_move_flips().map_or(0, |(_, flips)| flips.len());
+            print_move_summary(game, player, pmove, flips);
+        }
+    }
+
+    fn show_end(&mut self, game: &Reversi, end: GameEnd) {
+        let reason = match (end, game.termination()) {
+            (GameEnd::Finished, Some(reason)) => reason,
+            // Nothing is shown when a player quits
+            _ => return,
+        };
+
+        self.show_board(game);
+        match reason {
+            TerminationReason::TimeForfeit(piece) => println!("{} ran out of time", piece),
+            TerminationReason::Resignation(piece) => println!("{} resigned", piece),
+            TerminationReason::FullBoard | TerminationReason::DoublePass => {},
+        }
+
+        match game.winner() {
+            Some(winner) => println!("The winner is: {}", winner),
+            None => println!("The game ended with a tie"),
+        }
+
+        if let Some((path, book)) = &mut self.learned_book {
+            if book.learn(game, LEARN_BOOK_DEPTH) {
+                if let Err(err) = book.save(*path) {
+                    eprintln!("Error: Unable to save opening book `{}`: {}", path.display(), err);
+                    process::exit(1);
+                }
             }
+        }
 
-            break;
+        if let Some((path, tablebase)) = &self.learned_tablebase {
+            let tablebase = tablebase.lock().expect("bug: tablebase lock poisoned");
+            if let Err(err) = tablebase.save(path) {
+                eprintln!("Error: Unable to save tablebase `{}`: {}", path.display(), err);
+                process::exit(1);
+            }
         }
 
-        let player = game.current_player();
-        let is_ai = ai_controlled.contains(&player);
+        if let Some(path) = &self.options.save_html {
+            if let Err(err) = fs::write(path, render_html(game, &SvgOptions::default())) {
+                eprintln!("Error: Unable to write `{}`: {}", path.display(), err);
+                process::exit(1);
+            }
+        }
+
+        // Failing to archive the game is not worth stopping for, since the game is already over
+        if let Some(archive) = &self.archive {
+            match archive.add_game(game, self.started.elapsed()) {
+                Ok(id) => println!("Added to the archive as game #{} (see `reversi history show {}`)", id, id),
+                Err(err) => eprintln!("Error: Unable to add the game to the archive in `{}`: {}", archive.dir().display(), err),
+            }
+        }
+
+        if let Some(path) = &self.stats_path {
+            if let Err(err) = record_stats(path, game) {
+                eprintln!("Error: Unable to update the stats in `{}`: {}", path.display(), err);
+            }
+        }
+    }
+
+    fn run_action(&mut self, game: &mut Reversi, action: PlayerAction) {
+        match run_action(game, &mut self.undone, action) {
+            Ok(message) => println!("{}", message),
+            Err(err) => println!("Error: {}", err),
+        }
+    }
+}
+
+/// Records the result of the given finished game in the stats file at the given path, then shows
+/// the updated record against each opponent of a human player
+fn record_stats(path: &Path, game: &Reversi) -> Result<(), StatsError> {
+    let mut stats = PlayerStats::load(path)?;
+    let mut opponents = stats.record_game(game);
+    if opponents.is_empty() {
+        return Ok(());
+    }
+    stats.save(path)?;
+
+    // Both sides of a game between humans have the same opponent, so it is only shown once
+    opponents.dedup();
+    for opponent in opponents {
+        let record = stats.record(opponent);
+        println!("Record against {}: {} won, {} lost, {} tied", opponent, record.wins, record.losses, record.ties);
+    }
+    if stats.current_streak > 1 {
+        println!("Win streak: {} games", stats.current_streak);
+    }
+    Ok(())
+}
+
+/// The exit code of `--moves-file` when X won the game
+const EXIT_X_WINS: i32 = 10;
+/// The exit code of `--moves-file` when O won the game
+const EXIT_O_WINS: i32 = 11;
+/// The exit code of `--moves-file` when the game ended in a tie
+const EXIT_TIE: i32 = 12;
+/// The exit code of `--moves-file` when the script ended (or entered `quit`) before the game did
+const EXIT_UNFINISHED: i32 = 2;
+
+/// Reads the script given by `--moves-file` or the board given by `--board`, where `-` means stdin
+fn read_script(path: &Path) -> Result<String, String> {
+    if path == Path::new("-") {
+        return io::read_to_string(io::stdin()).map_err(|err| format!("Unable to read stdin: {}", err));
+    }
+
+    fs::read_to_string(path).map_err(|err| format!("Unable to read `{}`: {}", path.display(), err))
+}
+
+/// Parses a script of moves and commands, returning each of them with the line it is on
+///
+/// Each line has either a single command (e.g. `undo` or `save game.txt`) or any number of moves
+/// separated by whitespace (e.g. `D3 C5 F6 pass`), so transcripts written by `save` can be used as
+/// scripts. Everything after a `#` is a comment.
+fn parse_script(script: &str) -> Result<Vec<(usize, MoveInput)>, String> {
+    let mut entries = Vec::new();
+    for (index, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let line_number = index + 1;
+        match parse_input(line) {
+            Ok(input) => entries.push((line_number, input)),
+            Err(_) => for word in line.split_whitespace() {
+                let input = parse_input(word).map_err(|err| format!("Line {}: {}", line_number, err))?;
+                entries.push((line_number, input));
+            },
+        }
+    }
+
+    Ok(entries)
+}
 
+/// Plays the game with the moves and commands of a script (see `parse_script`) instead of
+/// prompting the human players, returning the exit code that tells how the game ended
+///
+/// The AI players, given by `ais`, choose their own moves. Nothing is printed other than the final
+/// score unless `verbose` is true. A player without any valid moves passes automatically, whether
+/// or not the script has a `pass` for them. Commands that only show something to the player (e.g.
+/// `hint`) are skipped. An invalid move is an error.
+fn run_script(
+    game: &mut Reversi,
+    script: &str,
+    ais: &mut [Option<CliAi>; 2],
+    verbose: bool,
+    theme: Theme,
+) -> Result<i32, String> {
+    let mut entries = parse_script(script)?.into_iter().peekable();
+    let mut undone = Vec::new();
+    let show_game = |game: &Reversi| if verbose {
+        let last_move = game.last_move_flips();
         println!();
-        print_game(&game, &valid_moves);
+        print_game_with(game, DisplayOptions {
+            valid_moves: game.valid_moves(),
+            last_move: last_move.as_ref().map(|(pos, flips)| (*pos, &flips[..])),
+            theme,
+            ..DisplayOptions::default()
+        });
         println!();
-        println!("Score: {} {} | {} {}", Piece::X, x_score, Piece::O, o_score);
-        println!("The current piece is: {}", player);
+    };
+
+    while !game.is_game_over() {
+        show_game(game);
+        let player = game.current_player();
+        let ai = match player {
+            Piece::X => &mut ais[0],
+            Piece::O => &mut ais[1],
+        };
+
+        let pmove = if let Some(ai) = ai {
+            ai.choose_move(game)
+        } else if game.valid_moves().is_empty() {
+            if matches!(entries.peek(), Some((_, MoveInput::Pass))) {
+                entries.next();
+            }
+            Move::Pass
+        } else {
+            let (line, input) = match entries.next() {
+                Some(entry) => entry,
+                None => {
+                    eprintln!("The script ended before the game did");
+                    return Ok(EXIT_UNFINISHED);
+                },
+            };
+            let action = match input {
+                MoveInput::Move(pos) if game.valid_moves().contains(&pos) => PlayerAction::Move(Move::Place(pos)),
+                MoveInput::Move(pos) => return Err(format!("Line {}: {} cannot play {}", line, player, pos)),
+                MoveInput::Pass => return Err(format!("Line {}: {} cannot pass with valid moves", line, player)),
+                MoveInput::Resign => PlayerAction::Move(Move::Resign),
+                MoveInput::Best(strength) => PlayerAction::Move(suggest_move(game, strength.unwrap_or_default()).0),
+                MoveInput::Undo => PlayerAction::Undo,
+                MoveInput::Redo => PlayerAction::Redo,
+                MoveInput::Save(path) => PlayerAction::Save(path),
+                MoveInput::Load(path) => PlayerAction::Load(path),
+                MoveInput::Quit => return Ok(EXIT_UNFINISHED),
+                MoveInput::Hint | MoveInput::Flip | MoveInput::Moves | MoveInput::History => continue,
+            };
+
+            match action {
+                PlayerAction::Move(pmove) => pmove,
+                action => {
+                    let message = run_action(game, &mut undone, action)
+                        .map_err(|err| format!("Line {}: {}", line, err))?;
+                    if verbose {
+                        println!("{}", message);
+                    }
+                    continue;
+                },
+            }
+        };
+
+        undone.clear();
+        let flips = match pmove {
+            Move::Place(pos) => game.make_move(pos).len(),
+            Move::Pass => {
+                game.pass();
+                0
+            },
+            Move::Resign => {
+                game.resign(player);
+                0
+            },
+        };
+        if verbose && game.history().last() == Some(&pmove) {
+            print_move_summary(game, player, pmove, flips);
+        }
+    }
+
+    show_game(game);
+    let (x_score, o_score) = game.scores();
+    println!("Score: {} {} | {} {}", Piece::X, x_score, Piece::O, o_score);
+    if verbose {
+        match game.termination() {
+            Some(TerminationReason::TimeForfeit(piece)) => println!("{} ran out of time", piece),
+            Some(TerminationReason::Resignation(piece)) => println!("{} resigned", piece),
+            _ => {},
+        }
+    }
+    Ok(match game.winner() {
+        Some(Piece::X) => EXIT_X_WINS,
+        Some(Piece::O) => EXIT_O_WINS,
+        None => EXIT_TIE,
+    })
+}
+
+/// Runs an action other than a move that the current player asked for, returning a message
+/// describing what was done
+fn run_action(game: &mut Reversi, undone: &mut Vec<Move>, action: PlayerAction) -> Result<String, String> {
+    let player = game.current_player();
+    match action {
+        PlayerAction::Undo | PlayerAction::Load(_) if game.clock().is_some() => {
+            Err("Moves cannot be taken back or replaced in a timed game".to_string())
+        },
+
+        PlayerAction::Undo => {
+            // Goes back to the last position where it was this player's turn, taking back the
+            // opponent's replies along with the player's own move
+            let history = game.history().to_vec();
+            let target = (0..history.len()).rev()
+                .find(|&n| game.at_move(n).current_player() == player)
+                .ok_or("There are no moves to take back")?;
+
+            undone.extend(history[target..].iter().rev());
+            *game = game.at_move(target);
+            Ok(format!("Took back {} move(s)", history.len() - target))
+        },
 
-        if valid_moves.is_empty() {
-            if is_ai {
-                println!("No moves available. Skipping turn. Press enter to continue...");
+        PlayerAction::Redo => {
+            if undone.is_empty() {
+                return Err("There are no moves to play again".to_string());
+            }
+
+            // Plays the moves again until it is this player's turn again, as it was before `undo`
+            let mut count = 0;
+            while let Some(pmove) = undone.pop() {
+                let _ = game.apply(pmove);
+                count += 1;
+                if game.current_player() == player || game.is_game_over() {
+                    break;
+                }
+            }
+            Ok(format!("Played {} move(s) again", count))
+        },
+
+        PlayerAction::Save(path) => {
+            game.save(&path).map_err(|err| format!("Unable to save `{}`: {}", path.display(), err))?;
+            Ok(format!("Saved {} moves to `{}`", game.history().len(), path.display()))
+        },
+
+        PlayerAction::Load(path) => {
+            let text = fs::read_to_string(&path)
+                .map_err(|err| format!("Unable to read `{}`: {}", path.display(), err))?;
+            // Files of moves separated by whitespace (as written by older versions) are replayed
+            // from the start of the current game
+            let mut loaded = if text.trim_start().starts_with('{') {
+                Reversi::from_json(&text).map_err(|err| format!("Unable to load `{}`: {}", path.display(), err))?
             } else {
-                prompt("No moves available. Skipping turn. Press enter to continue...").unwrap();
+                let mut loaded = game.at_move(0);
+                replay_moves(&mut loaded, &text)?;
+                loaded
+            };
+            // The game keeps being played by the same players
+            if let Some(players) = game.players() {
+                loaded.set_players(players);
             }
 
-            skipped = true;
-            game.advance_turn();
-            continue;
+            *game = loaded;
+            undone.clear();
+            Ok(format!("Loaded {} moves from `{}`", game.history().len(), path.display()))
+        },
+
+        PlayerAction::Move(_) | PlayerAction::Quit => unreachable!("bug: moves should be handled by the game loop"),
+    }
+}
+
+/// Prints a line describing the move that was just made, e.g. "O played D3, flipping 4 discs"
+fn print_move_summary(game: &Reversi, player: Piece, pmove: Move, flips: usize) {
+    let (x_score, o_score) = game.scores();
+    match pmove {
+        Move::Place(pos) => {
+            let discs = if flips == 1 { "disc" } else { "discs" };
+            println!("{} played {}, flipping {} {} (score {} {} | {} {})", player, pos, flips, discs,
+                Piece::X, x_score, Piece::O, o_score);
+        },
+        Move::Pass => println!("{} passed", player),
+        // The resignation is reported once the game is over
+        Move::Resign => {},
+    }
+}
+
+/// Runs the `analyze` subcommand, which prints every legal move of a position ranked from best to
+/// worst
+///
+/// ```text
+/// Usage: reversi analyze (--moves <moves> | --transcript <file> | --position <position>
+///                         | --board <file>) [--player <X|O>] [--variant <variant>] [--depth <n>]
+/// ```
+fn main_analyze() {
+    let result = parse_analyze_args().and_then(|options| {
+        let game = analysis_game(&options)?;
+        Ok((game, options.depth))
+    });
+    let (game, depth) = match result {
+        Ok(result) => result,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let last_move = game.last_move_flips();
+    print_game(&game, game.valid_moves(), last_move.as_ref().map(|(pos, flips)| (*pos, &flips[..])));
+    println!();
+    // Can be passed back to `--position`, e.g. to share the position in a bug report
+    println!("Position: {}", game.to_position_str());
+    if game.is_game_over() {
+        println!("The game is over");
+        return;
+    }
+
+    println!("Moves for {} (depth {}):", game.current_player(), depth);
+    for (rank, (pmove, score)) in analyze(&game, depth).into_iter().enumerate() {
+        println!("{:>3}. {:<6} {:>6}", rank + 1, pmove.to_string(), score);
+    }
+}
+
+/// Runs the `tablebase` subcommand, which solves every position of a small board and writes them
+/// to a file that can be passed to `--tablebase`
+///
+/// ```text
+/// Usage: reversi tablebase --size <n> [--variant <variant>] [--output <file>]
+/// ```
+fn main_tablebase() {
+    let (variant, size, output) = match parse_tablebase_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let tablebase = Tablebase::generate(variant, size);
+    eprintln!("Solved {} positions", tablebase.len());
+
+    let result = match &output {
+        Some(path) => fs::File::create(path)
+            .and_then(|file| tablebase.write(io::BufWriter::new(file))),
+        None => tablebase.write(io::stdout().lock()),
+    };
+    if let Err(err) = result {
+        eprintln!("Error: Unable to write tablebase: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Runs the `book` subcommand, which combines opening book files, writes out a book or builds a
+/// book from the games in WTHOR database files
+///
+/// ```text
+/// Usage: reversi book merge <file>... [--output <file>]
+///        reversi book export [--book <file>] [--output <file>]
+///        reversi book import-wthor <file>... [--depth <n>] [--output <file>]
+/// ```
+fn main_book() {
+    let result = parse_book_args().and_then(|(book, output)| {
+        let result = match &output {
+            Some(path) => book.save(path),
+            None => book.write(io::stdout().lock()),
+        };
+        result.map_err(|err| format!("Unable to write opening book: {}", err))
+    });
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Runs the `export-svg` subcommand, which draws a position as an SVG image
+///
+/// ```text
+/// Usage: reversi export-svg (--moves <moves> | --transcript <file> | --position <position>
+///                            | --board <file>) [--player <X|O>] [--variant <variant>] [--tile-size <pixels>]
+///                           [--no-coordinates] [--no-last-move] [--valid-moves] [--flip]
+///                           [--output <file>]
+/// ```
+fn main_export_svg() {
+    main_export(render_svg)
+}
+
+/// Runs the `export-html` subcommand, which writes a web page that steps through every position
+/// of a game
+///
+/// ```text
+/// Usage: reversi export-html (--moves <moves> | --transcript <file>) [--variant <variant>]
+///                            [--tile-size <pixels>] [--no-coordinates] [--no-last-move]
+///                            [--valid-moves] [--flip] [--output <file>]
+/// ```
+fn main_export_html() {
+    main_export(render_html)
+}
+
+/// Runs an export subcommand, rendering the game given by its arguments with the given function
+fn main_export(render: fn(&Reversi, &SvgOptions) -> String) {
+    let result = parse_export_args().and_then(|(position, options, output)| {
+        let text = render(&analysis_game(&position)?, &options);
+        match &output {
+            Some(path) => fs::write(path, text)
+                .map_err(|err| format!("Unable to write `{}`: {}", path.display(), err)),
+            None => {
+                print!("{}", text);
+                Ok(())
+            },
         }
-        // If the previous turn was skipped, we can reset that now
-        skipped = false;
+    });
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
 
-        if is_ai {
-            let pmove = compute_ai_move(&game, &valid_moves);
-            game.make_move(pmove);
-            // Slow down the game a bit so it's easier to follow
-            thread::sleep(Duration::from_millis(200));
-            continue;
+/// Runs the `replay` subcommand, which steps through a saved game or game record move by move
+///
+/// ```text
+/// Usage: reversi replay <file> [--delay <ms>] [--flip] [--no-color]
+/// ```
+fn main_replay() {
+    let result = parse_replay_args(env::args().skip(2), None).and_then(|options| replay(&options));
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Runs the `history` subcommand, which lists the games in the archive that finished games are
+/// added to or replays one of them
+///
+/// ```text
+/// Usage: reversi history list [--archive <dir>]
+///        reversi history show <id> [--archive <dir>] [--delay <ms>] [--flip] [--no-color]
+/// ```
+fn main_history() {
+    let result = parse_history_args().and_then(|(archive, command)| match command {
+        HistoryCommand::List => print_archive(&archive),
+        HistoryCommand::Show(id, args) => {
+            let game = archive.get(id).map_err(|err| err.to_string())?;
+            replay(&parse_replay_args(args.into_iter(), Some(game.path))?)
+        },
+    });
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Runs the `stats` subcommand, which shows the results of the games played so far
+///
+/// ```text
+/// Usage: reversi stats [--stats <file>]
+/// ```
+fn main_stats() {
+    let result = parse_stats_args().and_then(|path| {
+        let stats = PlayerStats::load(&path).map_err(|err| format!("Unable to load `{}`: {}", path.display(), err))?;
+        print_stats(&stats);
+        Ok(())
+    });
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Prints a table of the results against each opponent, followed by the win streaks
+fn print_stats(stats: &PlayerStats) {
+    let records = stats.records();
+    if records.is_empty() {
+        println!("No games have been recorded yet. Finish a game against the AI to start your record!");
+        return;
+    }
+
+    let row = |name: &str, record: RecordStats| {
+        let win_percentage = record.win_percentage().unwrap_or(0.0);
+        let differential = record.average_disc_differential().unwrap_or(0.0);
+        println!("{:<14}  {:>5}  {:>5}  {:>6}  {:>4}  {:>5.1}%  {:>+8.1}", name, record.games(), record.wins,
+            record.losses, record.ties, win_percentage, differential);
+    };
+    println!("{:<14}  {:>5}  {:>5}  {:>6}  {:>4}  {:>6}  Avg discs", "Opponent", "Games", "Wins", "Losses", "Ties", "Win %");
+    for (opponent, record) in records {
+        row(&opponent.to_string(), record);
+    }
+    row("Total", stats.total());
+
+    println!();
+    println!("Current win streak against the AI: {}", stats.current_streak);
+    println!("Longest win streak against the AI: {}", stats.longest_streak);
+}
+
+/// Prints a table of every game in the given archive
+fn print_archive(archive: &GameArchive) -> Result<(), String> {
+    let games = archive.list().map_err(|err| err.to_string())?;
+    if games.is_empty() {
+        println!("There are no games in the archive in `{}`", archive.dir().display());
+        return Ok(());
+    }
+
+    println!("{:>5}  {:<10}  {:<16}  {:<16}  {:<7}  {:>5}  Duration", "#", "Date", "X", "O", "Result", "Moves");
+    for game in &games {
+        let tag = |name: &str| game.record.tag(name).unwrap_or("?").to_string();
+        println!("{:>5}  {:<10}  {:<16}  {:<16}  {:<7}  {:>5}  {}", game.id, tag("Date"), tag("X"), tag("O"),
+            game.record.result.to_string(), game.record.moves.len(), tag("Duration"));
+    }
+    println!("\nReplay a game with `reversi history show <#>`");
+    Ok(())
+}
+
+/// Replays the game in the file given by the options of the `replay` subcommand
+fn replay(options: &ReplayArgs) -> Result<(), String> {
+    if options.no_color {
+        Paint::disable();
+    }
+    let (game, moves) = load_replay(&options.file)?;
+    run_replay(&game, &moves, options).map_err(|err| err.to_string())
+}
+
+/// Loads the game to replay from a file written by `save` or a game record (see `GameRecord`),
+/// which may also be a list of moves like "E3 F5 pass", returning the game along with the
+/// comments and clock times of its moves
+fn load_replay(path: &Path) -> Result<(Reversi, Vec<NotatedMove>), String> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read `{}`: {}", path.display(), err))?;
+    let invalid = |err: &dyn std::fmt::Display| format!("Unable to load `{}`: {}", path.display(), err);
+
+    if text.trim_start().starts_with('{') {
+        let game = Reversi::from_json(&text).map_err(|err| invalid(&err))?;
+        let moves = game.history().iter().map(|&pmove| NotatedMove::new(pmove)).collect();
+        return Ok((game, moves));
+    }
+
+    let record: GameRecord = text.parse().map_err(|err| invalid(&err))?;
+    let game = record.to_game().map_err(|err| invalid(&err))?;
+    Ok((game, record.moves))
+}
+
+/// Shows the position after the given number of moves, the move that led to it and the score
+fn show_replay_position(game: &Reversi, moves: &[NotatedMove], ply: usize, options: &ReplayArgs) {
+    let position = game.at_move(ply);
+    let last_move = position.last_move_flips();
+    println!();
+    print_game_with(&position, DisplayOptions {
+        last_move: last_move.as_ref().map(|(pos, flips)| (*pos, &flips[..])),
+        flip_board: options.flip,
+        score_bar: true,
+        ..DisplayOptions::default()
+    });
+
+    let total = game.history().len();
+    match ply.checked_sub(1) {
+        Some(index) => {
+            let player = game.at_move(index).current_player();
+            println!("Move {}/{}: {} played {}", ply, total, player, game.history()[index]);
+        },
+        None => println!("Start of the game ({} moves)", total),
+    }
+    if let Some(notated) = moves.get(ply.wrapping_sub(1)) {
+        if let Some(clock) = notated.clock {
+            let secs = clock.as_secs();
+            println!("Time left: {}:{:02}", secs / 60, secs % 60);
+        }
+        if let Some(comment) = &notated.comment {
+            println!("Comment: {}", comment);
+        }
+    }
+
+    let (x_score, o_score) = position.scores();
+    println!("Score: {} {} | {} {}", Piece::X, x_score, Piece::O, o_score);
+    if ply < total {
+        return;
+    }
+    match game.termination() {
+        Some(TerminationReason::TimeForfeit(piece)) => println!("{} ran out of time", piece),
+        Some(TerminationReason::Resignation(piece)) => println!("{} resigned", piece),
+        Some(TerminationReason::FullBoard) | Some(TerminationReason::DoublePass) => {},
+        None => {
+            println!("The game was not finished");
+            return;
+        },
+    }
+    match game.winner() {
+        Some(winner) => println!("The winner is: {}", winner),
+        None => println!("The game ended with a tie"),
+    }
+}
+
+/// A command entered while replaying a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReplayCommand {
+    /// Go forward one move (`next`, `n` or just Enter)
+    Next,
+    /// Go back one move (`prev` or `p`)
+    Prev,
+    /// Go to the position after the given number of moves (`jump <n>`, `j <n>` or just `<n>`)
+    Jump(usize),
+    /// Play the rest of the game, waiting the given time (or `--delay`) after each move
+    /// (`auto [ms]` or `a [ms]`)
+    Auto(Option<Duration>),
+    /// Stop replaying (`quit` or `q`)
+    Quit,
+}
+
+/// Parses a command entered while replaying a game with the given number of moves
+fn parse_replay_command(input: &str, total: usize) -> Result<ReplayCommand, String> {
+    let (command, argument) = match input.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (input, ""),
+    };
+    let number = |text: &str| text.parse::<usize>().ok().filter(|&n| n <= total)
+        .ok_or_else(|| format!("Invalid move number: `{}`. Enter a number from 0 to {}.", text, total));
+
+    match command.to_ascii_lowercase().as_str() {
+        "" | "n" | "next" => Ok(ReplayCommand::Next),
+        "p" | "prev" => Ok(ReplayCommand::Prev),
+        "first" => Ok(ReplayCommand::Jump(0)),
+        "last" => Ok(ReplayCommand::Jump(total)),
+        "j" | "jump" => Ok(ReplayCommand::Jump(number(argument)?)),
+        "a" | "auto" if argument.is_empty() => Ok(ReplayCommand::Auto(None)),
+        "a" | "auto" => {
+            let millis = argument.parse().map_err(|_| format!("Invalid delay: `{}`", argument))?;
+            Ok(ReplayCommand::Auto(Some(Duration::from_millis(millis))))
+        },
+        "q" | "quit" | "exit" => Ok(ReplayCommand::Quit),
+        _ if command.starts_with(|ch: char| ch.is_ascii_digit()) => Ok(ReplayCommand::Jump(number(command)?)),
+        _ => Err(format!("Unknown command: `{}`", input)),
+    }
+}
+
+/// Steps through the given game, showing each position as it is reached, until the player quits
+fn run_replay(game: &Reversi, moves: &[NotatedMove], options: &ReplayArgs) -> io::Result<()> {
+    let total = game.history().len();
+    let mut ply = 0;
+    show_replay_position(game, moves, ply, options);
+    loop {
+        let line = prompt(StdConsole, "[n]ext, [p]rev, jump <n>, first, last, [a]uto [ms], [q]uit: ")?;
+        // Nothing is read at the end of input, not even a newline
+        if line.is_empty() {
+            println!();
+            return Ok(());
+        }
+
+        match parse_replay_command(line.trim(), total) {
+            Ok(ReplayCommand::Next) if ply == total => println!("This is the end of the game"),
+            Ok(ReplayCommand::Next) => ply += 1,
+            Ok(ReplayCommand::Prev) if ply == 0 => println!("This is the start of the game"),
+            Ok(ReplayCommand::Prev) => ply -= 1,
+            Ok(ReplayCommand::Jump(target)) => ply = target,
+            Ok(ReplayCommand::Auto(delay)) => {
+                let delay = delay.unwrap_or(options.delay);
+                while ply < total {
+                    ply += 1;
+                    show_replay_position(game, moves, ply, options);
+                    thread::sleep(delay);
+                }
+                continue;
+            },
+            Ok(ReplayCommand::Quit) => return Ok(()),
+            Err(err) => {
+                println!("{}", err);
+                continue;
+            },
+        }
+        show_replay_position(game, moves, ply, options);
+    }
+}
+
+/// The AI as it is played from the command line
+struct CliAi {
+    ai: AiPlayer<StdRng>,
+    /// The score of the AI's last search from X's perspective, shown by `--eval-bar`
+    evaluation: Rc<Cell<Option<i32>>>,
+    /// If true, print the line of play the AI expects after each of its moves
+    show_pv: bool,
+    /// If true, print the reasons for each of the AI's moves
+    explain: bool,
+    /// If true, print statistics about the search after each of the AI's moves
+    search_stats: bool,
+    /// If true, keep searching on the opponent's time
+    ponder: bool,
+    /// The search running on the opponent's time (if any)
+    pondering: Option<Ponder>,
+    /// How long to wait after each move so it's easier to follow
+    delay: Duration,
+}
+
+impl Player for CliAi {
+    fn choose_move(&mut self, game: &Reversi) -> Move {
+        let budget = self.ai.move_budget(game);
+        let pondered = self.pondering.take().and_then(|ponder| match game.history() {
+            // The pondered position is only reached if the AI did not pass since its last move
+            &[.., Move::Place(_), last_move] => ponder.finish(last_move, budget),
+            _ => None,
+        });
+
+        if game.valid_moves().is_empty() {
+            return self.ai.choose_move(game);
+        }
+
+        let result = match pondered {
+            Some(result) => result,
+            None => self.ai.compute_move(game, budget),
+        };
+        self.evaluation.set(Some(match game.current_player() {
+            Piece::X => result.score,
+            Piece::O => -result.score,
+        }));
+        if self.show_pv {
+            let pv: Vec<_> = result.pv.iter().map(|pmove| pmove.to_string()).collect();
+            println!("AI played {} (score: {}, depth: {}, {} nodes in {:.2?})", result.best_move,
+                result.score, result.depth, result.nodes, result.elapsed);
+            println!("Expected line: {}", pv.join(" "));
+        }
+        if self.explain {
+            println!("AI played {}: {}", result.best_move, explain_move(game, result.best_move).join(", "));
+        }
+        if self.search_stats {
+            let stats = &result.stats;
+            let secs = result.elapsed.as_secs_f64();
+            let nodes_per_sec = if secs > 0.0 { result.nodes as f64 / secs } else { 0.0 };
+            println!("Search: {} nodes ({:.0}/s), {} cutoffs, {} probcuts, TT hit rate {:.1}% ({}/{})",
+                result.nodes, nodes_per_sec, stats.cutoffs, stats.probcuts, stats.tt_hit_rate() * 100.0,
+                stats.tt_hits, stats.tt_probes);
+            match stats.effective_branching_factor() {
+                Some(ebf) => println!("Effective branching factor: {:.2}", ebf),
+                None => println!("Effective branching factor: n/a"),
+            }
+        }
+
+        if self.ponder {
+            // The second move of the expected line is the opponent's most likely reply
+            if let Some(&Move::Place(predicted)) = result.pv.get(1) {
+                let mut game = game.clone();
+                let _ = game.apply(Move::Place(result.best_move));
+                self.pondering = Ponder::start(self.ai.clone(), &game, predicted, PONDER_TIME);
+            }
+        }
+
+        // Slow down the game a bit so it's easier to follow
+        thread::sleep(self.delay);
+        Move::Place(result.best_move)
+    }
+}
+
+/// Who chooses the moves of one of the players
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayerKind {
+    /// A person entering moves at the prompt
+    Human,
+    /// The AI
+    Ai,
+}
+
+/// The options that can be configured from the command line
+#[derive(Debug, Default)]
+struct Options {
+    /// The rules to play with (`--variant <standard|anti>`)
+    variant: Variant,
+    /// Who plays X and who plays O, or `None` for a human playing X against the AI
+    /// (`--players <human|ai>,<human|ai>`)
+    players: Option<[PlayerKind; 2]>,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+    /// How long the AI waits after each of its moves, instead of a short delay when flips are not
+    /// animated (`--delay-ms <milliseconds>`)
+    delay: Option<Duration>,
+    /// The glyphs and colors to draw the board with
+    /// (`--theme <classic|minimal|high-contrast|colorblind|shapes>`)
+    theme: Theme,
+    /// If true, label the valid moves on the board so they can be chosen by their labels
+    /// (`--numbered`)
+    numbered: bool,
+    /// If true, moves are chosen with a cursor moved by the arrow keys (`--cursor`)
+    #[cfg(feature = "cursor")]
+    cursor: bool,
+    /// If true, the terminal is cleared and the board is drawn in the same place every turn
+    /// instead of scrolling (`--redraw`)
+    #[cfg(feature = "cursor")]
+    redraw: bool,
+    /// If true, draw the board from O's side, with the rows reversed and the columns mirrored. The
+    /// human can also flip the board during the game by entering `flip`. (`--flip`)
+    flip: bool,
+    /// If true, show how the discs are split between the players under the board (`--score-bar`)
+    score_bar: bool,
+    /// If true, show the score of the AI's last search under the board (`--eval-bar`)
+    eval_bar: bool,
+    /// If true, color the valid moves from red to green by how good the AI thinks they are
+    /// (`--heatmap`)
+    heatmap: bool,
+    /// If true, print a summary of the human's moves as well as the AI's (`--summarize-human`)
+    summarize_human: bool,
+    /// How big the tiles of the board are drawn (`--render-size <compact|normal|large>`)
+    render_size: RenderSize,
+    /// If true, play on an octagonal board (`--octagon`)
+    octagon: bool,
+    /// The number of tiles on each side of a smaller square board to play on, or `None` to play
+    /// on the full board (`--size <4|6>`)
+    size: Option<usize>,
+    /// A file of solved positions for the board given by `size`, written by the `tablebase`
+    /// subcommand. The positions solved during the game are saved back to it, creating it if it
+    /// doesn't exist. (`--tablebase <file>`)
+    tablebase: Option<PathBuf>,
+    /// The main time given to each player, or `None` for an untimed game (`--time <seconds>`)
+    time: Option<Duration>,
+    /// The time added after each move (`--increment <seconds>`)
+    increment: Duration,
+    /// The byo-yomi period once main time runs out (`--byo-yomi <seconds>`)
+    byo_yomi: Option<Duration>,
+    /// A game saved with `save` to continue playing, with the same players and AI difficulty
+    /// unless they are given as well (`--resume <file>`)
+    resume: Option<PathBuf>,
+    /// If true, start the game from a random balanced opening (`--xot`)
+    xot: bool,
+    /// A file of openings to randomly start the game from instead of the builtin openings
+    /// (`--openings <file>`)
+    openings: Option<PathBuf>,
+    /// An opening book file for the AI to use instead of the builtin book (`--book <file>`)
+    book: Option<PathBuf>,
+    /// An opening book file that the moves and outcome of the game are added to once it is over,
+    /// created from the book the AI uses if it does not exist yet (`--learn-book <file>`)
+    learn_book: Option<PathBuf>,
+    /// A file to write a web page that steps through the game to once it is over (`--save-html
+    /// <file>`)
+    save_html: Option<PathBuf>,
+    /// The directory finished games are added to instead of the default archive (see
+    /// `GameArchive::default_dir`), which can be browsed with the `history` subcommand
+    /// (`--archive <dir>`)
+    archive: Option<PathBuf>,
+    /// If true, finished games are not added to the archive (`--no-archive`)
+    no_archive: bool,
+    /// The file that the results of finished games are recorded in instead of the default one (see
+    /// `PlayerStats::default_path`), which can be shown with the `stats` subcommand (`--stats
+    /// <file>`)
+    stats: Option<PathBuf>,
+    /// If true, the results of finished games are not recorded (`--no-stats`)
+    no_stats: bool,
+    /// A weights file for the AI's evaluation, written by `selfplay tune` (`--weights <file>`)
+    weights: Option<PathBuf>,
+    /// A neural network for the AI to evaluate positions with, instead of its usual evaluation
+    /// (`--model <file>`)
+    #[cfg(feature = "mlp")]
+    model: Option<PathBuf>,
+    /// How strong the AI plays, or `None` for the default difficulty or the one saved in the
+    /// game given by `resume` (`--difficulty <beginner|easy|medium|hard|expert>`)
+    difficulty: Option<AiDifficulty>,
+    /// The largest amount of random noise added to the AI's score for each move, instead of the
+    /// amount used by its difficulty (`--noise <n>`)
+    noise: Option<i32>,
+    /// The seed for all random choices, used to replay a game exactly (`--seed <number>`)
+    seed: Option<u64>,
+    /// If true, print the line of play the AI expects after each of its moves (`--show-pv`)
+    show_pv: bool,
+    /// If true, print why the AI chose each of its moves (`--explain`)
+    explain: bool,
+    /// If true, print statistics about each of the AI's searches (`--search-stats`)
+    search_stats: bool,
+    /// If true, the AI keeps thinking while the human is thinking (`--ponder`)
+    ponder: bool,
+    /// If true, the AI prunes its search with Multi-ProbCut (`--probcut`)
+    probcut: bool,
+    /// If true, flipped discs are shown turning over after each move (`--animate`)
+    animate: bool,
+    /// How long each frame of the flip animation is shown for (`--frame-time <seconds>`)
+    frame_time: Option<Duration>,
+    /// A file of moves and commands to play for the human players instead of prompting for them,
+    /// or `-` to read them from stdin (`--moves-file <file>`). See `run_script`.
+    moves_file: Option<PathBuf>,
+    /// If true, print the board and every move while playing `moves_file` (`--verbose`)
+    verbose: bool,
+    /// If true, never ask the human to confirm resigning, quitting or loading a game
+    /// (`--no-confirm`). They are also not asked when stdin is not a terminal.
+    no_confirm: bool,
+    /// If true, print `USAGE` instead of playing (`--help` or `-h`)
+    help: bool,
+}
+
+/// Parses the value of `--players`, which gives who plays X and who plays O separated by a comma
+/// (e.g. "human,ai")
+fn parse_players(value: &str) -> Result<[PlayerKind; 2], String> {
+    let parse_kind = |kind: &str| match kind.trim() {
+        "human" => Ok(PlayerKind::Human),
+        "ai" => Ok(PlayerKind::Ai),
+        _ => Err(format!("Invalid player: `{}`. Expected `human` or `ai`", kind)),
+    };
+
+    match value.split(',').collect::<Vec<_>>()[..] {
+        [x, o] => Ok([parse_kind(x)?, parse_kind(o)?]),
+        _ => Err(format!("Invalid players: `{}`. Expected two players like `human,ai`", value)),
+    }
+}
+
+/// The options of the `analyze` subcommand, which `export-svg` also uses to choose its position
+#[derive(Debug)]
+struct AnalyzeArgs {
+    /// The moves played so far, separated by whitespace (e.g. "D3 C5 F6 pass") (`--moves <moves>`
+    /// or `--transcript <file>`)
+    moves: Option<String>,
+    /// The position to analyze, written as 64 tiles row by row with `X`, `O`, `-` for empty
+    /// tiles and `#` for blocked tiles, optionally followed by the player to move like the
+    /// positions written by `Reversi::to_position_str` (`--position <position>`)
+    position: Option<String>,
+    /// The position to analyze drawn as a grid (see `parse_ascii_board`), read from a file where
+    /// `-` means stdin (`--board <file>`)
+    board: Option<String>,
+    /// The player to move in `position` or `board` if it does not say (`--player <X|O>`)
+    player: Piece,
+    /// The rules to play with (`--variant <standard|anti>`)
+    variant: Variant,
+    /// How many moves ahead to search for each move (`--depth <n>`)
+    depth: usize,
+}
+
+/// What the `history` subcommand does with the archive
+#[derive(Debug)]
+enum HistoryCommand {
+    /// List every game (`list`)
+    List,
+    /// Replay the game with the given number, with the given arguments of the `replay`
+    /// subcommand (`show <id>`)
+    Show(usize, Vec<String>),
+}
+
+/// The options of the `replay` subcommand
+#[derive(Debug)]
+struct ReplayArgs {
+    /// The saved game or game record to replay (`<file>`)
+    file: PathBuf,
+    /// How long each move is shown for when the game is played automatically (`--delay <ms>`)
+    delay: Duration,
+    /// If true, draw the board from O's side of the table (`--flip`)
+    flip: bool,
+    /// If true, never write terminal colors or styles (`--no-color`)
+    no_color: bool,
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--variant" => {
+                let value = args.next().ok_or("Missing value for `--variant`")?;
+                options.variant = value.parse().map_err(|err| format!("{}", err))?;
+            },
+
+            "--players" => {
+                let value = args.next().ok_or("Missing value for `--players`")?;
+                options.players = Some(parse_players(&value)?);
+            },
+
+            "--delay-ms" => {
+                let value = args.next().ok_or("Missing value for `--delay-ms`")?;
+                let millis = value.parse().map_err(|_| format!("Invalid delay: `{}`", value))?;
+                options.delay = Some(Duration::from_millis(millis));
+            },
+
+            "--no-color" => options.no_color = true,
+            // Plain ASCII characters are only used by the minimal theme
+            "--ascii" => options.theme = Theme::minimal(),
+
+            "--difficulty" => {
+                let value = args.next().ok_or("Missing value for `--difficulty`")?;
+                options.difficulty = Some(value.parse().map_err(|err| format!("{}", err))?);
+            },
+
+            "--theme" => {
+                let value = args.next().ok_or("Missing value for `--theme`")?;
+                options.theme = value.parse().map_err(|err| format!("{}", err))?;
+            },
+
+            "--render-size" => {
+                let value = args.next().ok_or("Missing value for `--render-size`")?;
+                options.render_size = value.parse().map_err(|err| format!("{}", err))?;
+            },
+
+            "--noise" => {
+                let value = args.next().ok_or("Missing value for `--noise`")?;
+                let noise = value.parse().ok().filter(|&noise: &i32| noise >= 0)
+                    .ok_or_else(|| format!("Invalid amount of noise: `{}`", value))?;
+                options.noise = Some(noise);
+            },
+
+            "--seed" => {
+                let value = args.next().ok_or("Missing value for `--seed`")?;
+                let seed = value.parse().map_err(|_| format!("Invalid seed: `{}`", value))?;
+                options.seed = Some(seed);
+            },
+
+            "--octagon" => options.octagon = true,
+            "--numbered" => options.numbered = true,
+            "--score-bar" => options.score_bar = true,
+            "--eval-bar" => options.eval_bar = true,
+            "--heatmap" => options.heatmap = true,
+            "--summarize-human" => options.summarize_human = true,
+            "--flip" => options.flip = true,
+            #[cfg(feature = "cursor")]
+            "--cursor" => options.cursor = true,
+            #[cfg(feature = "cursor")]
+            "--redraw" => options.redraw = true,
+            "--size" => options.size = parse_board_size(args.next())?,
+
+            "--tablebase" => {
+                let value = args.next().ok_or("Missing value for `--tablebase`")?;
+                options.tablebase = Some(PathBuf::from(value));
+            },
+
+            "--show-pv" => options.show_pv = true,
+            "--explain" => options.explain = true,
+            "--search-stats" => options.search_stats = true,
+            "--ponder" => options.ponder = true,
+            "--probcut" => options.probcut = true,
+            "--animate" => options.animate = true,
+            "--frame-time" => options.frame_time = Some(parse_seconds(&arg, args.next())?),
+
+            "--resume" => {
+                let value = args.next().ok_or("Missing value for `--resume`")?;
+                options.resume = Some(PathBuf::from(value));
+            },
+
+            "--xot" => options.xot = true,
+            "--openings" => {
+                let value = args.next().ok_or("Missing value for `--openings`")?;
+                options.openings = Some(PathBuf::from(value));
+            },
+
+            "--book" => {
+                let value = args.next().ok_or("Missing value for `--book`")?;
+                options.book = Some(PathBuf::from(value));
+            },
+
+            "--learn-book" => {
+                let value = args.next().ok_or("Missing value for `--learn-book`")?;
+                options.learn_book = Some(PathBuf::from(value));
+            },
+
+            "--save-html" => {
+                let value = args.next().ok_or("Missing value for `--save-html`")?;
+                options.save_html = Some(PathBuf::from(value));
+            },
+
+            "--archive" => {
+                let value = args.next().ok_or("Missing value for `--archive`")?;
+                options.archive = Some(PathBuf::from(value));
+            },
+            "--no-archive" => options.no_archive = true,
+
+            "--stats" => {
+                let value = args.next().ok_or("Missing value for `--stats`")?;
+                options.stats = Some(PathBuf::from(value));
+            },
+            "--no-stats" => options.no_stats = true,
+
+            "--moves-file" => {
+                let value = args.next().ok_or("Missing value for `--moves-file`")?;
+                options.moves_file = Some(PathBuf::from(value));
+            },
+            "--verbose" => options.verbose = true,
+            "--no-confirm" => options.no_confirm = true,
+
+            "--weights" => {
+                let value = args.next().ok_or("Missing value for `--weights`")?;
+                options.weights = Some(PathBuf::from(value));
+            },
+
+            #[cfg(feature = "mlp")]
+            "--model" => {
+                let value = args.next().ok_or("Missing value for `--model`")?;
+                options.model = Some(PathBuf::from(value));
+            },
+
+            "--time" => options.time = Some(parse_seconds(&arg, args.next())?),
+            "--increment" => options.increment = parse_seconds(&arg, args.next())?,
+            "--byo-yomi" => options.byo_yomi = Some(parse_seconds(&arg, args.next())?),
+
+            "--help" | "-h" => options.help = true,
+
+            _ => return Err(format!("Unknown argument: `{}`. Run with `--help` to see every option.", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Parses the command line arguments of the `analyze` subcommand
+fn parse_analyze_args() -> Result<AnalyzeArgs, String> {
+    let mut args = env::args().skip(2);
+    let mut options = AnalyzeArgs {
+        moves: None,
+        position: None,
+        board: None,
+        player: Piece::X,
+        variant: Variant::default(),
+        depth: 6,
+    };
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("Missing value for `{}`", arg))?;
+        match arg.as_str() {
+            "--depth" => {
+                options.depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
+            },
+
+            _ => parse_position_arg(&mut options, &arg, value)?,
+        }
+    }
+
+    Ok(options)
+}
+
+/// Parses the command line arguments of the `replay` subcommand, or the arguments of `history
+/// show` that choose how to replay the given file
+fn parse_replay_args(mut args: impl Iterator<Item = String>, mut file: Option<PathBuf>) -> Result<ReplayArgs, String> {
+    let mut options = ReplayArgs {
+        file: PathBuf::new(),
+        delay: Duration::from_secs(1),
+        flip: false,
+        no_color: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--delay" => {
+                let value = args.next().ok_or("Missing value for `--delay`")?;
+                let millis = value.parse().map_err(|_| format!("Invalid delay: `{}`", value))?;
+                options.delay = Duration::from_millis(millis);
+            },
+
+            "--flip" => options.flip = true,
+            "--no-color" => options.no_color = true,
+
+            _ if !arg.starts_with("--") && file.is_none() => file = Some(PathBuf::from(arg)),
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    options.file = file.ok_or("Missing the saved game or game record to replay")?;
+    Ok(options)
+}
+
+/// Parses the command line arguments of the `history` subcommand, returning the archive to use
+/// and what to do with it
+fn parse_history_args() -> Result<(GameArchive, HistoryCommand), String> {
+    let mut args = env::args().skip(2);
+    let command = args.next().ok_or("Missing history command. Expected `list` or `show`")?;
+    let id = match command.as_str() {
+        "list" => None,
+        "show" => {
+            let value = args.next().ok_or("Missing the number of the game to show")?;
+            Some(value.parse().map_err(|_| format!("Invalid game number: `{}`", value))?)
+        },
+        _ => return Err(format!("Unknown history command: `{}`. Expected `list` or `show`", command)),
+    };
+
+    let mut dir = None;
+    let mut replay_args = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--archive" => {
+                let value = args.next().ok_or("Missing value for `--archive`")?;
+                dir = Some(PathBuf::from(value));
+            },
+
+            _ if id.is_some() => replay_args.push(arg),
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    let dir = dir.or_else(GameArchive::default_dir)
+        .ok_or("Unable to find the archive without a home directory. Use `--archive <dir>`.")?;
+    let command = match id {
+        Some(id) => HistoryCommand::Show(id, replay_args),
+        None => HistoryCommand::List,
+    };
+    Ok((GameArchive::new(dir), command))
+}
+
+/// Parses the command line arguments of the `stats` subcommand, returning the stats file to show
+fn parse_stats_args() -> Result<PathBuf, String> {
+    let mut args = env::args().skip(2);
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stats" => {
+                let value = args.next().ok_or("Missing value for `--stats`")?;
+                path = Some(PathBuf::from(value));
+            },
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    path.or_else(PlayerStats::default_path)
+        .ok_or_else(|| "Unable to find the stats file without a home directory. Use `--stats <file>`.".to_string())
+}
+
+/// Parses one of the arguments that choose the position of the `analyze`, `export-svg` and
+/// `export-html` subcommands
+fn parse_position_arg(options: &mut AnalyzeArgs, arg: &str, value: String) -> Result<(), String> {
+    match arg {
+        "--moves" => options.moves = Some(value),
+        "--position" => options.position = Some(value),
+        "--variant" => options.variant = value.parse().map_err(|err| format!("{}", err))?,
+
+        "--transcript" => {
+            let text = fs::read_to_string(&value)
+                .map_err(|err| format!("Unable to read `{}`: {}", value, err))?;
+            options.moves = Some(text);
+        },
+
+        "--board" => options.board = Some(read_script(Path::new(&value))?),
+
+        "--player" => {
+            options.player = match value.as_str() {
+                "X" | "x" => Piece::X,
+                "O" | "o" => Piece::O,
+                _ => return Err(format!("Invalid player: `{}`. Expected `X` or `O`", value)),
+            };
+        },
+
+        _ => return Err(format!("Unknown argument: `{}`", arg)),
+    }
+
+    Ok(())
+}
+
+/// Parses the command line arguments of the `export-svg` and `export-html` subcommands, returning
+/// the game to draw, how to draw it and the file to write to
+fn parse_export_args() -> Result<(AnalyzeArgs, SvgOptions, Option<PathBuf>), String> {
+    let mut args = env::args().skip(2);
+    let mut position = AnalyzeArgs {
+        moves: None,
+        position: None,
+        board: None,
+        player: Piece::X,
+        variant: Variant::default(),
+        depth: 0,
+    };
+    let mut options = SvgOptions::default();
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-coordinates" => options.coordinates = false,
+            "--no-last-move" => options.last_move = false,
+            "--valid-moves" => options.valid_moves = true,
+            "--flip" => options.flip_board = true,
+
+            _ => {
+                let value = args.next().ok_or_else(|| format!("Missing value for `{}`", arg))?;
+                match arg.as_str() {
+                    "--output" => output = Some(PathBuf::from(value)),
+
+                    "--tile-size" => {
+                        options.tile_size = value.parse().ok().filter(|&size: &usize| size > 0)
+                            .ok_or_else(|| format!("Invalid tile size: `{}`", value))?;
+                    },
+
+                    _ => parse_position_arg(&mut position, &arg, value)?,
+                }
+            },
+        }
+    }
+
+    Ok((position, options, output))
+}
+
+/// Creates the game to analyze by replaying the given moves or setting up the given position
+fn analysis_game(options: &AnalyzeArgs) -> Result<Reversi, String> {
+    let given = [options.moves.is_some(), options.position.is_some(), options.board.is_some()];
+    if given.iter().filter(|&&given| given).count() > 1 {
+        return Err("Only one of a position, a board or a list of moves can be given".to_string());
+    }
+
+    if let Some(board) = &options.board {
+        let board = parse_ascii_board(board).map_err(|err| format!("{}", err))?;
+        return Ok(Reversi::from_grid(options.variant, board.grid, board.player.unwrap_or(options.player)));
+    }
+
+    if let Some(position) = &options.position {
+        let position = position.trim();
+        if position.chars().count() == 64 {
+            let grid: Grid = position.parse().map_err(|err| format!("{}", err))?;
+            return Ok(Reversi::from_grid(options.variant, grid, options.player));
+        }
+        return Reversi::from_position_str(options.variant, position).map_err(|err| format!("{}", err));
+    }
+
+    let mut game = Reversi::new(options.variant);
+    replay_moves(&mut game, options.moves.as_deref().unwrap_or(""))?;
+    Ok(game)
+}
+
+/// Plays the given moves, separated by whitespace (e.g. "D3 C5 F6 pass"), checking that each of
+/// them is valid
+fn replay_moves(game: &mut Reversi, moves: &str) -> Result<(), String> {
+    for (index, text) in moves.split_whitespace().enumerate() {
+        let pmove = if text.eq_ignore_ascii_case("pass") {
+            Move::Pass
+        } else {
+            Move::Place(text.parse().map_err(|err| format!("{}", err))?)
+        };
+
+        let legal = match pmove {
+            Move::Place(pos) => game.valid_moves().contains(&pos),
+            Move::Pass => game.valid_moves().is_empty() && !game.is_game_over(),
+            Move::Resign => false,
+        };
+        if !legal {
+            return Err(format!("Move {} ({}) is not a valid move", index + 1, text));
         }
 
-        let pmove = prompt_move(&valid_moves);
         match pmove {
-            Ok(pmove) => game.make_move(pmove),
+            Move::Place(pos) => {
+                game.make_move(pos);
+            },
+            _ => game.pass(),
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses the command line arguments of the `tablebase` subcommand, returning the variant, the
+/// board size and the file to write to
+fn parse_tablebase_args() -> Result<(Variant, usize, Option<PathBuf>), String> {
+    let mut args = env::args().skip(2);
+    let mut variant = Variant::default();
+    let mut size = None;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => size = parse_board_size(args.next())?,
 
-            Err(ParseError::EndOfInput) => {
-                // Print a final newline
-                println!();
-                break;
+            "--variant" => {
+                let value = args.next().ok_or("Missing value for `--variant`")?;
+                variant = value.parse().map_err(|err| format!("{}", err))?;
             },
 
-            Err(ParseError::InvalidInput(_)) => unreachable!(),
+            "--output" => {
+                let value = args.next().ok_or("Missing value for `--output`")?;
+                output = Some(PathBuf::from(value));
+            },
 
-            Err(ParseError::IOError(err)) => {
-                eprintln!("Error: {}", err);
-                break;
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    let size = size.ok_or("Missing `--size <4|6>` for the board to solve")?;
+    Ok((variant, size, output))
+}
+
+/// Parses the command line arguments of the `book` subcommand, returning the book to write and
+/// the file to write it to
+fn parse_book_args() -> Result<(OpeningBook, Option<PathBuf>), String> {
+    let mut args = env::args().skip(2);
+    let command = args.next().ok_or("Missing book command. Expected `merge`, `export` or `import-wthor`")?;
+
+    let mut files = Vec::new();
+    let mut output = None;
+    let mut depth = LEARN_BOOK_DEPTH;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => {
+                let value = args.next().ok_or("Missing value for `--output`")?;
+                output = Some(PathBuf::from(value));
+            },
+
+            "--book" if command == "export" => {
+                let value = args.next().ok_or("Missing value for `--book`")?;
+                files.push(PathBuf::from(value));
+            },
+
+            "--depth" if command == "import-wthor" => {
+                let value = args.next().ok_or("Missing value for `--depth`")?;
+                depth = value.parse().ok().filter(|&depth: &usize| depth > 0)
+                    .ok_or_else(|| format!("Invalid depth: `{}`", value))?;
             },
+
+            _ if (command == "merge" || command == "import-wthor") && !arg.starts_with("--") => files.push(PathBuf::from(arg)),
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
         }
     }
+
+    let load = |path: &PathBuf| OpeningBook::load(path)
+        .map_err(|err| format!("Unable to load opening book `{}`: {}", path.display(), err));
+    let book = match command.as_str() {
+        "merge" => {
+            if files.is_empty() {
+                return Err("Missing the opening book files to merge".to_string());
+            }
+
+            let mut book = OpeningBook::default();
+            for path in &files {
+                book.merge(&load(path)?);
+            }
+            book
+        },
+
+        "export" => match files.first() {
+            Some(path) => load(path)?,
+            None => OpeningBook::builtin().clone(),
+        },
+
+        // The first moves of every game are added along with its result
+        "import-wthor" => {
+            if files.is_empty() {
+                return Err("Missing the WTHOR files to import".to_string());
+            }
+
+            let mut book = OpeningBook::default();
+            for path in &files {
+                let games = load_wthor_games(path)
+                    .map_err(|err| format!("Unable to read WTHOR file `{}`: {}", path.display(), err))?;
+                for game in games {
+                    let mut line = game.line();
+                    line.truncate(depth);
+                    if !line.is_empty() {
+                        book.add_line_with_outcome(&line, Some(game.outcome()))
+                            .expect("bug: moves from WTHOR files are checked when they are read");
+                    }
+                }
+            }
+            book
+        },
+
+        _ => return Err(format!("Unknown book command: `{}`. Expected `merge`, `export` or `import-wthor`", command)),
+    };
+
+    Ok((book, output))
+}
+
+/// Parses the value of `--size`, returning `None` for the full board
+fn parse_board_size(value: Option<String>) -> Result<Option<usize>, String> {
+    let value = value.ok_or("Missing value for `--size`")?;
+    match value.as_str() {
+        "4" => Ok(Some(4)),
+        "6" => Ok(Some(6)),
+        "8" => Ok(None),
+        _ => Err(format!("Invalid board size: `{}`. Expected `4`, `6` or `8`", value)),
+    }
+}
+
+/// Creates the game to play based on the given options
+fn new_game(rng: &mut StdRng, options: &Options) -> Result<Reversi, String> {
+    if let Some(path) = &options.resume {
+        if options.size.is_some() || options.octagon || options.xot || options.openings.is_some() || options.time.is_some() {
+            return Err("A resumed game keeps its own board and clock, so `--resume` cannot be combined with `--size`, `--octagon`, `--xot`, `--openings` or `--time`".to_string());
+        }
+        return Reversi::load(path).map_err(|err| format!("Unable to resume `{}`: {}", path.display(), err));
+    }
+
+    if options.size.is_some() && (options.octagon || options.xot || options.openings.is_some()) {
+        return Err("Smaller boards cannot be combined with `--octagon`, `--xot` or `--openings`".to_string());
+    }
+    if let Some(size) = options.size {
+        return Ok(Reversi::with_board_size(options.variant, size));
+    }
+
+    if options.xot || options.openings.is_some() {
+        if options.octagon {
+            return Err("XOT openings can only be played on the standard board".to_string());
+        }
+
+        let openings = match &options.openings {
+            Some(path) => {
+                let text = fs::read_to_string(path)
+                    .map_err(|err| format!("Unable to read `{}`: {}", path.display(), err))?;
+                Some(parse_openings(&text).map_err(|err| format!("{}", err))?)
+            },
+            None => None,
+        };
+        return Reversi::new_xot(rng, options.variant, openings.as_deref())
+            .map_err(|err| format!("{}", err));
+    }
+
+    Ok(if options.octagon {
+        Reversi::with_blocked_tiles(options.variant, &octagon_blocked_tiles())
+    } else {
+        Reversi::new(options.variant)
+    })
+}
+
+/// Parses the value of an argument given in seconds
+fn parse_seconds(arg: &str, value: Option<String>) -> Result<Duration, String> {
+    let value = value.ok_or_else(|| format!("Missing value for `{}`", arg))?;
+    let secs: f64 = value.parse()
+        .map_err(|_| format!("Invalid number of seconds for `{}`: `{}`", arg, value))?;
+    if !secs.is_finite() || secs < 0.0 {
+        return Err(format!("Invalid number of seconds for `{}`: `{}`", arg, value));
+    }
+
+    Ok(Duration::from_secs_f64(secs))
+}
+
+/// Returns the tiles to block in order to turn the 8x8 board into an octagon by cutting off a
+/// small triangle from each corner
+fn octagon_blocked_tiles() -> Vec<TilePos> {
+    let mut blocked = Vec::new();
+    for &(row, col) in &[(0, 0), (0, 7), (7, 0), (7, 7)] {
+        // The neighbours of the corner that are along the edges of the board
+        let drow = if row == 0 { 1 } else { -1 };
+        let dcol = if col == 0 { 1 } else { -1 };
+
+        let corner = TilePos {row, col};
+        blocked.push(corner);
+        blocked.extend(corner.offset(drow, 0, (8, 8)));
+        blocked.extend(corner.offset(0, dcol, (8, 8)));
+    }
+
+    blocked
 }
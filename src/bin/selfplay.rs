@@ -0,0 +1,434 @@
+//! Plays games between two AIs and records every position, the move chosen in it and the final
+//! outcome of the game, for tuning the evaluation or training models
+//!
+//! ```text
+//! Usage: selfplay [--games <n>] [--difficulty <level>] [--time <ms>] [--variant <variant>]
+//!                 [--seed <number>] [--format <jsonl|csv>] [--weights <file>] [--output <file>]
+//! ```
+//!
+//! The `tune` subcommand reads the recorded games back and tunes the weights of the evaluation
+//! to better predict their outcomes, writing a weights file that can be passed to `--weights`.
+//! The games can also be tournament games from a WTHOR database file (`.wtb`):
+//!
+//! ```text
+//! Usage: selfplay tune --input <file> [--variant <variant>] [--iterations <n>]
+//!                      [--learning-rate <rate>] [--scale <score>] [--weights <file>]
+//!                      [--output <file>]
+//! ```
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write, BufWriter};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use board_games::{
+    Reversi,
+    Grid,
+    Move,
+    Piece,
+    Variant,
+    AiDifficulty,
+    AiPlayer,
+    OpeningBook,
+    Evaluator,
+    WeightedEvaluator,
+    TrainingPosition,
+    TuneOptions,
+    WthorGame,
+    load_wthor_games,
+    tune,
+    prediction_error,
+    disable_color_if_unsupported,
+};
+
+fn main() {
+    disable_color_if_unsupported();
+
+    if env::args().nth(1).as_deref() == Some("tune") {
+        return main_tune();
+    }
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let evaluator = match load_evaluator(options.weights.as_deref()) {
+        Ok(evaluator) => evaluator,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let output = create_output(options.output.as_deref());
+    if let Err(err) = run(&options, &Arc::new(evaluator), BufWriter::new(output)) {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Runs the `tune` subcommand
+fn main_tune() {
+    let options = match parse_tune_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let mut evaluator = match load_evaluator(options.weights.as_deref()) {
+        Ok(evaluator) => evaluator,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let positions = match load_positions(&options.input, options.variant) {
+        Ok(positions) => positions,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let initial_error = prediction_error(&evaluator, &positions, options.tune.scale);
+    let final_error = tune(&mut evaluator, &positions, &options.tune);
+    eprintln!("Tuned on {} positions: error {:.6} -> {:.6}", positions.len(), initial_error, final_error);
+
+    let mut out = BufWriter::new(create_output(options.output.as_deref()));
+    if let Err(err) = evaluator.write_weights(&mut out).and_then(|_| out.flush()) {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Opens the given file for writing, or stdout if no file is given
+fn create_output(path: Option<&Path>) -> Box<dyn Write> {
+    match path {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(err) => {
+                eprintln!("Error: Unable to create `{}`: {}", path.display(), err);
+                process::exit(1);
+            },
+        },
+        None => Box::new(io::stdout()),
+    }
+}
+
+/// Returns the default evaluator, with its weights loaded from the given file (if any)
+fn load_evaluator(weights: Option<&Path>) -> Result<WeightedEvaluator, String> {
+    let mut evaluator = WeightedEvaluator::default();
+    if let Some(path) = weights {
+        evaluator.load_weights(path)
+            .map_err(|err| format!("Unable to load weights `{}`: {}", path.display(), err))?;
+    }
+
+    Ok(evaluator)
+}
+
+fn run<W: Write>(options: &Options, evaluator: &Arc<WeightedEvaluator>, mut out: W) -> io::Result<()> {
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    if options.format == Format::Csv {
+        writeln!(out, "game,ply,position,player,move,winner,disc_diff")?;
+    }
+
+    for game_index in 0..options.games {
+        let mut players = [
+            new_ai(&mut rng, options.difficulty, evaluator),
+            new_ai(&mut rng, options.difficulty, evaluator),
+        ];
+
+        // Moves can only be written once the outcome of the game is known
+        let mut records = Vec::new();
+        let mut game = Reversi::new(options.variant);
+        while !game.is_game_over() {
+            let player = game.current_player();
+            let ai = &mut players[piece_index(player)];
+            let pmove = if game.valid_moves().is_empty() {
+                Move::Pass
+            } else {
+                Move::Place(ai.compute_move(&game, options.time).best_move)
+            };
+
+            records.push((game.grid().to_position_string(), player, pmove));
+            match pmove {
+                Move::Place(pos) => {
+                    game.make_move(pos);
+                },
+                Move::Pass => game.pass(),
+                Move::Resign => game.resign(player),
+            }
+        }
+
+        let (x_score, o_score) = game.scores();
+        let disc_diff = x_score as i32 - o_score as i32;
+        let winner = game.winner().map_or("draw", piece_name);
+        for (ply, (position, player, pmove)) in records.into_iter().enumerate() {
+            match options.format {
+                Format::Jsonl => writeln!(out, "{{\"game\":{},\"ply\":{},\"position\":\"{}\",\"player\":\"{}\",\"move\":\"{}\",\"winner\":\"{}\",\"disc_diff\":{}}}",
+                    game_index, ply, position, piece_name(player), pmove, winner, disc_diff)?,
+                Format::Csv => writeln!(out, "{},{},{},{},{},{},{}",
+                    game_index, ply, position, piece_name(player), pmove, winner, disc_diff)?,
+            }
+        }
+
+        eprintln!("Game {}/{}: {} {} | {} {}", game_index + 1, options.games,
+            piece_name(Piece::X), x_score, piece_name(Piece::O), o_score);
+    }
+
+    out.flush()
+}
+
+/// Creates an AI with its own random number generator seeded from the given one
+fn new_ai(rng: &mut StdRng, difficulty: AiDifficulty, evaluator: &Arc<WeightedEvaluator>) -> AiPlayer<StdRng> {
+    let ai_rng = StdRng::seed_from_u64(rng.gen());
+    let evaluator = evaluator.clone();
+    AiPlayer::with_rng(difficulty, OpeningBook::builtin().clone(), ai_rng)
+        .with_evaluator(move |game: &Reversi, player: Piece| evaluator.evaluate(game, player))
+}
+
+/// Reads the positions recorded in a file written by this program, in either format, or every
+/// position from the games in a WTHOR database file (ending in `.wtb`)
+fn load_positions(path: &Path, variant: Variant) -> Result<Vec<TrainingPosition>, String> {
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wtb")) {
+        if variant != Variant::Standard {
+            return Err("WTHOR games are always played with the standard rules".to_string());
+        }
+        let games = load_wthor_games(path)
+            .map_err(|err| format!("Unable to read `{}`: {}", path.display(), err))?;
+        return Ok(games.iter().flat_map(WthorGame::training_positions).collect());
+    }
+
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read `{}`: {}", path.display(), err))?;
+
+    let mut positions = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        // Skip the header of CSV files
+        if line.is_empty() || line.starts_with("game,") {
+            continue;
+        }
+
+        let (position, player, winner) = if line.starts_with('{') {
+            (json_field(line, "position"), json_field(line, "player"), json_field(line, "winner"))
+        } else {
+            let fields: Vec<_> = line.split(',').collect();
+            (fields.get(2).copied(), fields.get(3).copied(), fields.get(5).copied())
+        };
+
+        let invalid = || format!("Line {}: invalid record `{}`", i + 1, line);
+        let grid: Grid = position.ok_or_else(invalid)?.parse()
+            .map_err(|err| format!("Line {}: {}", i + 1, err))?;
+        let player = match player.ok_or_else(invalid)? {
+            "X" => Piece::X,
+            "O" => Piece::O,
+            _ => return Err(invalid()),
+        };
+        let result = match winner.ok_or_else(invalid)? {
+            "draw" => 0.5,
+            "X" | "O" if winner == Some(piece_name(player)) => 1.0,
+            "X" | "O" => 0.0,
+            _ => return Err(invalid()),
+        };
+
+        let game = Reversi::from_grid(variant, grid, player);
+        positions.push(TrainingPosition {game, result});
+    }
+
+    Ok(positions)
+}
+
+/// Returns the value of a field in one of the JSON objects written by this program, without the
+/// quotes around it if it is a string
+fn json_field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let key = format!("\"{}\":", name);
+    let start = line.find(&key)? + key.len();
+    let rest = &line[start..];
+    match rest.strip_prefix('"') {
+        Some(rest) => rest.find('"').map(|end| &rest[..end]),
+        None => rest.find([',', '}']).map(|end| &rest[..end]),
+    }
+}
+
+/// Returns the name of a piece without any color codes
+fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "X",
+        Piece::O => "O",
+    }
+}
+
+fn piece_index(piece: Piece) -> usize {
+    match piece {
+        Piece::X => 0,
+        Piece::O => 1,
+    }
+}
+
+/// The file formats the records can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// One JSON object per line
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+/// The options that can be configured from the command line
+#[derive(Debug)]
+struct Options {
+    /// The number of games to play (`--games <n>`)
+    games: usize,
+    /// How strong both AIs play (`--difficulty <level>`)
+    difficulty: AiDifficulty,
+    /// The time each AI is given to think about each move (`--time <ms>`)
+    time: Duration,
+    /// The rules to play with (`--variant <standard|anti>`)
+    variant: Variant,
+    /// The seed for all random choices (`--seed <number>`)
+    seed: Option<u64>,
+    /// The format to write the records in (`--format <jsonl|csv>`)
+    format: Format,
+    /// A weights file for the AIs' evaluation, written by `selfplay tune` (`--weights <file>`)
+    weights: Option<PathBuf>,
+    /// The file to write the records to, or stdout if not set (`--output <file>`)
+    output: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            games: 10,
+            difficulty: AiDifficulty::Easy,
+            time: Duration::from_millis(100),
+            variant: Variant::default(),
+            seed: None,
+            format: Format::Jsonl,
+            weights: None,
+            output: None,
+        }
+    }
+}
+
+/// The options of the `tune` subcommand
+#[derive(Debug)]
+struct TuneArgs {
+    /// The games to tune with, in either format written by this program or as a WTHOR database
+    /// file (`--input <file>`)
+    input: PathBuf,
+    /// The rules the games were played with (`--variant <standard|anti>`)
+    variant: Variant,
+    /// The settings of the tuner (`--iterations <n>`, `--learning-rate <rate>` and
+    /// `--scale <score>`)
+    tune: TuneOptions,
+    /// The weights to start tuning from, instead of the default weights (`--weights <file>`)
+    weights: Option<PathBuf>,
+    /// The file to write the tuned weights to, or stdout if not set (`--output <file>`)
+    output: Option<PathBuf>,
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Missing value for `{}`", arg));
+        match arg.as_str() {
+            "--games" => {
+                let value = value()?;
+                options.games = value.parse()
+                    .map_err(|_| format!("Invalid number of games: `{}`", value))?;
+            },
+
+            "--difficulty" => options.difficulty = value()?.parse().map_err(|err| format!("{}", err))?,
+            "--variant" => options.variant = value()?.parse().map_err(|err| format!("{}", err))?,
+
+            "--time" => {
+                let value = value()?;
+                let millis = value.parse()
+                    .map_err(|_| format!("Invalid number of milliseconds: `{}`", value))?;
+                options.time = Duration::from_millis(millis);
+            },
+
+            "--seed" => {
+                let value = value()?;
+                options.seed = Some(value.parse().map_err(|_| format!("Invalid seed: `{}`", value))?);
+            },
+
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "jsonl" => Format::Jsonl,
+                    "csv" => Format::Csv,
+                    format => return Err(format!("Unknown format: `{}`. Expected `jsonl` or `csv`", format)),
+                };
+            },
+
+            "--weights" => options.weights = Some(PathBuf::from(value()?)),
+            "--output" => options.output = Some(PathBuf::from(value()?)),
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Parses the command line arguments of the `tune` subcommand
+fn parse_tune_args() -> Result<TuneArgs, String> {
+    let mut args = env::args().skip(2);
+    let mut input = None;
+    let mut variant = Variant::default();
+    let mut tune = TuneOptions::default();
+    let mut weights = None;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Missing value for `{}`", arg));
+        match arg.as_str() {
+            "--input" => input = Some(PathBuf::from(value()?)),
+            "--variant" => variant = value()?.parse().map_err(|err| format!("{}", err))?,
+
+            "--iterations" => {
+                let value = value()?;
+                tune.iterations = value.parse()
+                    .map_err(|_| format!("Invalid number of iterations: `{}`", value))?;
+            },
+
+            "--learning-rate" => {
+                let value = value()?;
+                tune.learning_rate = value.parse()
+                    .map_err(|_| format!("Invalid learning rate: `{}`", value))?;
+            },
+
+            "--scale" => {
+                let value = value()?;
+                tune.scale = value.parse().ok().filter(|&scale: &f64| scale > 0.0)
+                    .ok_or_else(|| format!("Invalid scale: `{}`", value))?;
+            },
+
+            "--weights" => weights = Some(PathBuf::from(value()?)),
+            "--output" => output = Some(PathBuf::from(value()?)),
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    let input = input.ok_or("Missing `--input <file>` with the games to tune with")?;
+    Ok(TuneArgs {input, variant, tune, weights, output})
+}
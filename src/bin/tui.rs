@@ -0,0 +1,423 @@
+//! Plays reversi against the AI in a full-screen terminal interface, with the board, the score,
+//! the clock, an evaluation bar and the moves played so far all on screen at once
+//!
+//! ```text
+//! Usage: tui [--difficulty <level>] [--variant <variant>] [--time <seconds>]
+//! ```
+//!
+//! Move the cursor with the arrow keys (or `h`, `j`, `k`, `l`) and press Enter or Space to play
+//! on the highlighted tile, or click on a tile to play there. `n` starts a new game and `q` or Esc quits.
+
+use std::cell::Cell;
+use std::env;
+use std::io::{self, Stdout};
+use std::process;
+use std::time::Duration;
+
+use crossterm::event::{
+    self,
+    Event,
+    KeyCode,
+    KeyEventKind,
+    MouseButton,
+    MouseEventKind,
+    EnableMouseCapture,
+    DisableMouseCapture,
+};
+use crossterm::execute;
+use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode};
+use rand::{SeedableRng, rngs::StdRng};
+use ratatui::Frame;
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph};
+
+use board_games::{
+    Reversi,
+    TerminationReason,
+    Clock,
+    TimeControl,
+    Piece,
+    TilePos,
+    Variant,
+    OpeningBook,
+    AiDifficulty,
+    AiPlayer,
+    Evaluator,
+    WeightedEvaluator,
+};
+
+/// How often the screen is redrawn while waiting for a key, so that the clock keeps ticking
+const REDRAW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The evaluation that fills 73% of the evaluation bar. Larger values make the bar move less.
+const EVAL_SCALE: f64 = 200.0;
+
+/// The player controlled from the keyboard. The AI plays the other pieces.
+const HUMAN: Piece = Piece::X;
+
+/// The number of characters taken up by each tile of the board
+const CELL_WIDTH: u16 = 3;
+
+/// The number of characters taken up by the row labels on the left of the board
+const LABEL_WIDTH: u16 = 2;
+
+type Term = Terminal<CrosstermBackend<Stdout>>;
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let result = setup_terminal().and_then(|mut terminal| {
+        let result = App::new(options).run(&mut terminal);
+        // The terminal is restored even if the game failed so the error can be read
+        restore_terminal(&mut terminal)?;
+        result
+    });
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn setup_terminal() -> io::Result<Term> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    Terminal::new(CrosstermBackend::new(stdout))
+}
+
+fn restore_terminal(terminal: &mut Term) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+    terminal.show_cursor()
+}
+
+/// The options that can be configured from the command line
+#[derive(Debug, Default)]
+struct Options {
+    /// How strong the AI plays (`--difficulty <beginner|easy|medium|hard|expert>`)
+    difficulty: AiDifficulty,
+    /// The rules to play with (`--variant <standard|anti>`)
+    variant: Variant,
+    /// The main time given to each player, or `None` for an untimed game (`--time <seconds>`)
+    time: Option<Duration>,
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("Missing value for `{}`", arg))?;
+        match arg.as_str() {
+            "--difficulty" => options.difficulty = value.parse().map_err(|err| format!("{}", err))?,
+            "--variant" => options.variant = value.parse().map_err(|err| format!("{}", err))?,
+
+            "--time" => {
+                let secs: f64 = value.parse().ok().filter(|secs: &f64| secs.is_finite() && *secs > 0.0)
+                    .ok_or_else(|| format!("Invalid number of seconds for `--time`: `{}`", value))?;
+                options.time = Some(Duration::from_secs_f64(secs));
+            },
+
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+/// The state of the interface
+struct App {
+    options: Options,
+    game: Reversi,
+    ai: AiPlayer<StdRng>,
+    /// The tile the human will play on when they press Enter
+    cursor: TilePos,
+    /// A message shown under the board (e.g. why a move was rejected)
+    message: String,
+    /// Where the board was last drawn on the screen, used to find the tile that was clicked
+    board_area: Cell<Rect>,
+}
+
+impl App {
+    fn new(options: Options) -> Self {
+        let ai = AiPlayer::with_rng(options.difficulty, OpeningBook::builtin().clone(), StdRng::from_entropy());
+        let mut app = Self {
+            options,
+            game: Reversi::default(),
+            ai,
+            cursor: TilePos {row: 3, col: 3},
+            message: String::new(),
+            board_area: Cell::new(Rect::default()),
+        };
+        app.new_game();
+        app
+    }
+
+    fn new_game(&mut self) {
+        self.game = Reversi::new(self.options.variant);
+        if let Some(initial) = self.options.time {
+            self.game.set_clock(Clock::new(TimeControl {initial, increment: Duration::from_secs(0), byo_yomi: None}));
+        }
+        self.message = "Your move".to_string();
+    }
+
+    /// Runs the interface until the player quits
+    fn run(&mut self, terminal: &mut Term) -> io::Result<()> {
+        loop {
+            if !self.game.is_game_over() && self.game.current_player() != HUMAN {
+                self.message = "The AI is thinking...".to_string();
+                terminal.draw(|frame| self.draw(frame))?;
+                self.play_ai_move();
+                continue;
+            }
+
+            terminal.draw(|frame| self.draw(frame))?;
+            if !event::poll(REDRAW_INTERVAL)? {
+                continue;
+            }
+
+            let key = match event::read()? {
+                // Only presses are handled so that keys are not handled twice on some platforms
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    if let Some(pos) = self.tile_at(mouse.column, mouse.row) {
+                        self.cursor = pos;
+                        self.play_human_move();
+                    }
+                    continue;
+                },
+
+                _ => continue,
+            };
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('n') => self.new_game(),
+
+                KeyCode::Left | KeyCode::Char('h') => self.move_cursor(0, -1),
+                KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1, 0),
+                KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1, 0),
+                KeyCode::Right | KeyCode::Char('l') => self.move_cursor(0, 1),
+
+                KeyCode::Enter | KeyCode::Char(' ') => self.play_human_move(),
+
+                _ => {},
+            }
+        }
+    }
+
+    fn move_cursor(&mut self, drow: isize, dcol: isize) {
+        let grid = self.game.grid();
+        if let Some(pos) = self.cursor.offset(drow, dcol, (grid.rows().len(), grid.row_len())) {
+            self.cursor = pos;
+        }
+    }
+
+    /// Returns the tile drawn at the given position on the screen, if any
+    fn tile_at(&self, x: u16, y: u16) -> Option<TilePos> {
+        let area = self.board_area.get();
+        // Inside the border, past the row labels and the column labels
+        let left = area.x + 1 + LABEL_WIDTH;
+        let top = area.y + 2;
+        if x < left || y < top {
+            return None;
+        }
+
+        let pos = TilePos {row: (y - top) as usize, col: ((x - left) / CELL_WIDTH) as usize};
+        let grid = self.game.grid();
+        if pos.row < grid.rows().len() && pos.col < grid.row_len() {
+            Some(pos)
+        } else {
+            None
+        }
+    }
+
+    fn play_human_move(&mut self) {
+        if self.game.is_game_over() {
+            return;
+        }
+
+        if self.game.valid_moves().is_empty() {
+            self.game.pass();
+            self.message = "You passed".to_string();
+        } else if self.game.valid_moves().contains(&self.cursor) {
+            self.game.make_move(self.cursor);
+            self.message.clear();
+        } else {
+            self.message = format!("{} is not a valid move", self.cursor);
+        }
+    }
+
+    fn play_ai_move(&mut self) {
+        if self.game.valid_moves().is_empty() {
+            self.game.pass();
+            self.message = "The AI passed. Your move".to_string();
+            return;
+        }
+
+        let budget = self.ai.move_budget(&self.game);
+        let result = self.ai.compute_move(&self.game, budget);
+        self.game.make_move(result.best_move);
+        self.message = format!("The AI played {}. Your move", result.best_move);
+        if self.game.valid_moves().is_empty() && !self.game.is_game_over() {
+            self.message = format!("The AI played {}. You have no moves, press Enter to pass", result.best_move);
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(29), Constraint::Min(24)])
+            .split(frame.size());
+        let panel = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Length(4),
+                Constraint::Length(3),
+                Constraint::Min(3),
+            ])
+            .split(columns[1]);
+
+        self.draw_board(frame, columns[0]);
+        self.draw_score(frame, panel[0]);
+        self.draw_clock(frame, panel[1]);
+        self.draw_evaluation(frame, panel[2]);
+        self.draw_history(frame, panel[3]);
+    }
+
+    fn draw_board(&self, frame: &mut Frame, area: Rect) {
+        self.board_area.set(area);
+        let grid = self.game.grid();
+        let valid_moves = if self.game.current_player() == HUMAN { self.game.valid_moves() } else { &[] };
+        let last_move = self.game.last_move_flips();
+
+        let mut lines = Vec::new();
+        let header: String = (0..grid.row_len()).map(|col| format!(" {} ", (b'A' + col as u8) as char)).collect();
+        lines.push(Line::from(format!("  {}", header)));
+        for (row, row_tiles) in grid.rows().iter().enumerate() {
+            let mut spans = vec![Span::raw(format!("{} ", row + 1))];
+            for (col, &tile) in row_tiles.iter().enumerate() {
+                let pos = TilePos {row, col};
+                let (glyph, mut style) = match tile {
+                    Some(Piece::X) => ("\u{25CF}", Style::default().fg(Color::Red)),
+                    Some(Piece::O) => ("\u{25CF}", Style::default().fg(Color::Blue)),
+                    None if grid.is_blocked(pos) => ("\u{2573}", Style::default().fg(Color::DarkGray)),
+                    None if valid_moves.contains(&pos) => ("\u{00B7}", Style::default().fg(Color::Yellow)),
+                    None => (" ", Style::default()),
+                };
+                style = style.bg(Color::Green);
+
+                if last_move.as_ref().is_some_and(|&(placed, _)| placed == pos) {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                if pos == self.cursor && !self.game.is_game_over() {
+                    style = style.bg(if valid_moves.contains(&pos) { Color::LightGreen } else { Color::LightRed });
+                }
+                spans.push(Span::styled(format!(" {} ", glyph), style));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(self.status()));
+
+        let board = Paragraph::new(lines).block(Block::default().title("Reversi").borders(Borders::ALL));
+        frame.render_widget(board, area);
+    }
+
+    /// Returns the message shown under the board
+    fn status(&self) -> String {
+        let reason = match self.game.termination() {
+            Some(reason) => reason,
+            None => return self.message.clone(),
+        };
+
+        let ending = match reason {
+            TerminationReason::TimeForfeit(piece) => format!("{} ran out of time. ", piece_name(piece)),
+            TerminationReason::Resignation(piece) => format!("{} resigned. ", piece_name(piece)),
+            TerminationReason::FullBoard | TerminationReason::DoublePass => String::new(),
+        };
+        let result = match self.game.winner() {
+            Some(winner) if winner == HUMAN => "You win!",
+            Some(_) => "The AI wins",
+            None => "It's a tie",
+        };
+        format!("{}{} (n: new game, q: quit)", ending, result)
+    }
+
+    fn draw_score(&self, frame: &mut Frame, area: Rect) {
+        let (x_score, o_score) = self.game.scores();
+        let turn = |piece| if !self.game.is_game_over() && self.game.current_player() == piece { " <" } else { "" };
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("\u{25CF} ", Style::default().fg(Color::Red)),
+                Span::raw(format!("You    {:>2}{}", x_score, turn(Piece::X))),
+            ]),
+            Line::from(vec![
+                Span::styled("\u{25CF} ", Style::default().fg(Color::Blue)),
+                Span::raw(format!("AI     {:>2}{}", o_score, turn(Piece::O))),
+            ]),
+        ];
+        let title = format!("Score ({})", self.options.difficulty);
+        frame.render_widget(Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL)), area);
+    }
+
+    fn draw_clock(&self, frame: &mut Frame, area: Rect) {
+        let lines = match self.game.clock() {
+            Some(clock) => vec![
+                Line::from(format!("You    {}", format_time(clock, Piece::X))),
+                Line::from(format!("AI     {}", format_time(clock, Piece::O))),
+            ],
+            None => vec![Line::from("Untimed")],
+        };
+        frame.render_widget(Paragraph::new(lines).block(Block::default().title("Clock").borders(Borders::ALL)), area);
+    }
+
+    fn draw_evaluation(&self, frame: &mut Frame, area: Rect) {
+        let score = WeightedEvaluator::default().evaluate(&self.game, HUMAN);
+        // Maps the score to the chance of winning, the same way the evaluation is tuned
+        let ratio = 1.0 / (1.0 + (-score as f64 / EVAL_SCALE).exp());
+        let gauge = Gauge::default()
+            .block(Block::default().title("Evaluation").borders(Borders::ALL))
+            .gauge_style(Style::default().fg(Color::Red).bg(Color::Blue))
+            .ratio(ratio.clamp(0.0, 1.0))
+            .label(format!("{:+}", score));
+        frame.render_widget(gauge, area);
+    }
+
+    fn draw_history(&self, frame: &mut Frame, area: Rect) {
+        let history = self.game.history();
+        let items: Vec<_> = history.chunks(2).enumerate().map(|(i, pair)| {
+            let moves: Vec<_> = pair.iter().map(|pmove| format!("{:<6}", pmove.to_string())).collect();
+            ListItem::new(format!("{:>2}. {}", i + 1, moves.concat().trim_end()))
+        }).collect();
+
+        // Only the most recent moves that fit are shown
+        let visible = area.height.saturating_sub(2) as usize;
+        let skip = items.len().saturating_sub(visible);
+        let list = List::new(items.into_iter().skip(skip).collect::<Vec<_>>())
+            .block(Block::default().title("Moves").borders(Borders::ALL));
+        frame.render_widget(list, area);
+    }
+}
+
+fn piece_name(piece: Piece) -> &'static str {
+    if piece == HUMAN { "You" } else { "The AI" }
+}
+
+
+/// Formats the time remaining for the given player as minutes and seconds
+fn format_time(clock: &Clock, piece: Piece) -> String {
+    let secs = clock.remaining(piece).as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
@@ -0,0 +1,577 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use rand::Rng;
+use thiserror::Error;
+
+use crate::{Reversi, Grid, Piece, TilePos, Move, Variant, OpeningError, from_standard_notation};
+use crate::xot::parse_opening_line;
+
+/// A small book of standard openings, in the same format as the XOT opening lists
+///
+/// Every line is played from the start of the game and each position along the way is added to
+/// the book, so it is enough to list only the longest line of each opening.
+const BUILTIN_BOOK: &str = "\
+# Perpendicular openings
+f5d6c3d3c4f4f6f3e6e7
+f5d6c3d3c4f4f6g5
+f5d6c3d3c4f4c5b3c2
+f5d6c4d3c5
+f5d6c5f4e3
+# Diagonal openings
+f5f6e6f4c3
+f5f6e6f4g5
+f5f6e6f4e3c5c4
+# Parallel opening
+f5f4e3f6d3c5
+";
+
+#[derive(Debug, Error)]
+pub enum BookError {
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+    #[error(transparent)]
+    InvalidOpening(#[from] OpeningError),
+}
+
+/// A book of known good moves for positions near the start of the game
+///
+/// Positions are stored in their canonical (symmetry-reduced) form, so a single entry covers all
+/// 8 rotations and reflections of a position.
+#[derive(Debug, Default, Clone)]
+pub struct OpeningBook {
+    /// The moves for each canonical grid and player to move, stored relative to the canonical
+    /// grid
+    entries: HashMap<(Grid, Piece), Vec<BookMove>>,
+    /// Every distinct line that was added to the book, in order, along with the outcomes of the
+    /// games that followed it, so that the book can be written back out
+    lines: Vec<(Vec<TilePos>, LineResults)>,
+    /// The index in `lines` of each line
+    line_indexes: HashMap<Vec<TilePos>, usize>,
+}
+
+/// How a game that followed a line of the book ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineOutcome {
+    /// The given player won the game
+    Won(Piece),
+    /// The game ended in a draw
+    Drawn,
+}
+
+impl LineOutcome {
+    /// Returns the outcome of the given game, or `None` if the game is not over yet
+    pub fn of_game(game: &Reversi) -> Option<Self> {
+        if !game.is_game_over() {
+            return None;
+        }
+
+        Some(match game.winner() {
+            Some(winner) => LineOutcome::Won(winner),
+            None => LineOutcome::Drawn,
+        })
+    }
+
+    fn to_token(self) -> &'static str {
+        match self {
+            LineOutcome::Won(Piece::X) => "X",
+            LineOutcome::Won(Piece::O) => "O",
+            LineOutcome::Drawn => "=",
+        }
+    }
+
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "X" => Some(LineOutcome::Won(Piece::X)),
+            "O" => Some(LineOutcome::Won(Piece::O)),
+            "=" => Some(LineOutcome::Drawn),
+            _ => None,
+        }
+    }
+}
+
+/// The number of games that followed a line of the book that were won by each player or drawn
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct LineResults {
+    x_wins: u32,
+    o_wins: u32,
+    draws: u32,
+}
+
+impl LineResults {
+    fn of_outcome(outcome: Option<LineOutcome>) -> Self {
+        let mut results = Self::default();
+        if let Some(outcome) = outcome {
+            results.add_games(outcome, 1);
+        }
+        results
+    }
+
+    fn add_games(&mut self, outcome: LineOutcome, games: u32) {
+        match outcome {
+            LineOutcome::Won(Piece::X) => self.x_wins += games,
+            LineOutcome::Won(Piece::O) => self.o_wins += games,
+            LineOutcome::Drawn => self.draws += games,
+        }
+    }
+
+    fn add(&mut self, other: LineResults) {
+        self.x_wins += other.x_wins;
+        self.o_wins += other.o_wins;
+        self.draws += other.draws;
+    }
+
+    /// Returns every outcome along with the number of games that ended that way
+    fn games(self) -> [(LineOutcome, u32); 3] {
+        [
+            (LineOutcome::Won(Piece::X), self.x_wins),
+            (LineOutcome::Won(Piece::O), self.o_wins),
+            (LineOutcome::Drawn, self.draws),
+        ]
+    }
+}
+
+/// A move in the book, relative to the canonical grid of its position
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BookMove {
+    pos: TilePos,
+    /// The number of lines in the book that played this move
+    count: u32,
+    /// The number of games through this move that the player making it won, drew and lost
+    wins: u32,
+    draws: u32,
+    losses: u32,
+}
+
+impl BookMove {
+    /// Returns how likely this move is to be chosen relative to the other moves in its position
+    ///
+    /// Moves played by more lines are more likely, and moves that have been lost with more often
+    /// than they have been won with are less likely. Moves with no recorded games count as half
+    /// won, so books without any outcomes are played exactly by their counts.
+    fn weight(&self) -> f64 {
+        let games = (self.wins + self.draws + self.losses) as f64;
+        let score = (self.wins as f64 + self.draws as f64 / 2.0 + 1.0) / (games + 2.0);
+        self.count as f64 * score
+    }
+}
+
+impl OpeningBook {
+    /// Returns the book of standard openings that is compiled into the crate
+    pub fn builtin() -> &'static OpeningBook {
+        static BOOK: OnceLock<OpeningBook> = OnceLock::new();
+        BOOK.get_or_init(|| {
+            OpeningBook::parse(BUILTIN_BOOK).expect("bug: builtin opening book is invalid")
+        })
+    }
+
+    /// Loads a book from a file with one line of moves per line (e.g. "f5d6c3d3c4"), written in
+    /// standard Othello notation
+    ///
+    /// A line may end with the outcomes of the games that were played with it, separated from the
+    /// moves by whitespace: `X` or `O` for the winner, or `=` for a draw, followed by the number of
+    /// games if there was more than one (e.g. "f5d6c3d3c4 O3 ="). Empty lines and lines starting
+    /// with `#` are ignored. A line that is listed more than once is only added once, with the
+    /// outcomes of every listing.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BookError> {
+        let text = fs::read_to_string(path)?;
+        Ok(Self::parse(&text)?)
+    }
+
+    /// Parses a book in the format described in `load`
+    pub fn parse(text: &str) -> Result<Self, OpeningError> {
+        let mut book = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // The outcomes are taken off the end of the line until only the moves are left
+            let mut moves = line;
+            let mut results = LineResults::default();
+            while let Some((rest, token)) = moves.rsplit_once(char::is_whitespace) {
+                match parse_outcome_token(token) {
+                    Some((outcome, games)) => results.add_games(outcome, games),
+                    None => break,
+                }
+                moves = rest.trim_end();
+            }
+            let moves = parse_opening_line(moves, i + 1)?;
+            book.add_line_with_results(&moves, results)?;
+        }
+
+        Ok(book)
+    }
+
+    /// Writes every line of the book in the format read by `load`
+    pub fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        for (line, results) in &self.lines {
+            let moves: String = line.iter()
+                // Mirroring is its own inverse, so this converts back to standard notation
+                .map(|&pmove| from_standard_notation(pmove).to_string().to_lowercase())
+                .collect();
+            write!(out, "{}", moves)?;
+            for (outcome, games) in results.games() {
+                match games {
+                    0 => {},
+                    1 => write!(out, " {}", outcome.to_token())?,
+                    _ => write!(out, " {}{}", outcome.to_token(), games)?,
+                }
+            }
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the book to a file in the format read by `load`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        self.write(&mut out)?;
+        out.flush()
+    }
+
+    /// Adds every position along the given line of moves (played from the start of a standard
+    /// game) to the book
+    ///
+    /// Adding a line that is already in the book does not change it.
+    pub fn add_line(&mut self, line: &[TilePos]) -> Result<(), OpeningError> {
+        self.add_line_with_outcome(line, None)
+    }
+
+    /// Same as `add_line`, but also records how a game that followed the line ended so that moves
+    /// that keep losing are played less often
+    ///
+    /// The outcome of a line that is already in the book is added to the outcomes it already has.
+    pub fn add_line_with_outcome(
+        &mut self,
+        line: &[TilePos],
+        outcome: Option<LineOutcome>,
+    ) -> Result<(), OpeningError> {
+        self.add_line_with_results(line, LineResults::of_outcome(outcome))
+    }
+
+    /// Adds the given line along with the outcomes of the games that followed it
+    fn add_line_with_results(&mut self, line: &[TilePos], results: LineResults) -> Result<(), OpeningError> {
+        // Checked before anything is added so that an illegal line leaves the book unchanged
+        let mut game = Reversi::new(Variant::Standard);
+        for (index, &pmove) in line.iter().enumerate() {
+            if !game.valid_moves().contains(&pmove) {
+                return Err(OpeningError::IllegalMove {index: index + 1, pmove});
+            }
+            let _ = game.apply(Move::Place(pmove));
+        }
+
+        // Each line is only counted once, no matter how many games followed it
+        let index = self.line_indexes.get(line).copied();
+        let mut game = Reversi::new(Variant::Standard);
+        for &pmove in line {
+            self.add_move(&game, pmove, index.is_none(), results);
+            let _ = game.apply(Move::Place(pmove));
+        }
+        match index {
+            Some(index) => self.lines[index].1.add(results),
+            None => {
+                self.line_indexes.insert(line.to_vec(), self.lines.len());
+                self.lines.push((line.to_vec(), results));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Adds the first `depth` moves of the given finished game to the book along with its
+    /// outcome, so that the book learns from the games that are played with it
+    ///
+    /// Only standard games played from the usual starting position can be learned from. The line
+    /// stops at the first pass. Returns true if the game was added to the book.
+    pub fn learn(&mut self, game: &Reversi, depth: usize) -> bool {
+        let outcome = match LineOutcome::of_game(game) {
+            Some(outcome) => outcome,
+            None => return false,
+        };
+        if game.variant() != Variant::Standard || game.at_move(0).grid() != Reversi::new(Variant::Standard).grid() {
+            return false;
+        }
+
+        let line: Vec<_> = game.history().iter()
+            .take(depth)
+            .map_while(|&pmove| match pmove {
+                Move::Place(pos) => Some(pos),
+                Move::Pass | Move::Resign => None,
+            })
+            .collect();
+        !line.is_empty() && self.add_line_with_outcome(&line, Some(outcome)).is_ok()
+    }
+
+    /// Adds every line of the other book to this book
+    pub fn merge(&mut self, other: &OpeningBook) {
+        for (line, results) in &other.lines {
+            self.add_line_with_results(line, *results)
+                .expect("bug: lines in a book should always be valid");
+        }
+    }
+
+    /// Returns the number of distinct positions in the book
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the book has no positions
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns every book move for the current position of the given game, along with the number
+    /// of lines in the book that played it
+    ///
+    /// The book only applies to standard games, so no moves are returned for other variants.
+    pub fn moves(&self, game: &Reversi) -> Vec<(TilePos, u32)> {
+        self.book_moves(game).into_iter()
+            .map(|book_move| (book_move.pos, book_move.count))
+            .collect()
+    }
+
+    /// Chooses one of the book moves for the current position of the given game at random, with
+    /// moves played by more lines in the book being more likely and moves that keep losing being
+    /// less likely
+    pub fn choose_move<R: Rng + ?Sized>(&self, rng: &mut R, game: &Reversi) -> Option<TilePos> {
+        let moves = self.book_moves(game);
+        let total: f64 = moves.iter().map(BookMove::weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut choice = rng.gen::<f64>() * total;
+        for book_move in &moves {
+            if choice < book_move.weight() {
+                return Some(book_move.pos);
+            }
+            choice -= book_move.weight();
+        }
+
+        // Rounding errors can leave a tiny bit of the total over
+        moves.last().map(|book_move| book_move.pos)
+    }
+
+    /// Returns every book move for the current position of the given game, mapped to the
+    /// positions of the game's grid
+    fn book_moves(&self, game: &Reversi) -> Vec<BookMove> {
+        if game.variant() != Variant::Standard {
+            return Vec::new();
+        }
+
+        let grid = game.grid();
+        let symmetry = grid.canonical_symmetry();
+        let key = (grid.symmetries()[symmetry].clone(), game.current_player());
+        let moves = match self.entries.get(&key) {
+            Some(moves) => moves,
+            None => return Vec::new(),
+        };
+
+        moves.iter()
+            .map(|&book_move| BookMove {pos: grid.map_from_symmetry(book_move.pos, symmetry), ..book_move})
+            // Guards against the book containing moves that are not valid in this game
+            .filter(|book_move| game.valid_moves().contains(&book_move.pos))
+            .collect()
+    }
+
+    /// Adds a single move for the current position of the given game, counting it as played by
+    /// another line if `new_line` is true
+    fn add_move(&mut self, game: &Reversi, pmove: TilePos, new_line: bool, results: LineResults) {
+        let grid = game.grid();
+        let symmetry = grid.canonical_symmetry();
+        let key = (grid.symmetries()[symmetry].clone(), game.current_player());
+        let pmove = grid.map_to_symmetry(pmove, symmetry);
+
+        let moves = self.entries.entry(key).or_default();
+        let index = match moves.iter().position(|book_move| book_move.pos == pmove) {
+            Some(index) => index,
+            None => {
+                moves.push(BookMove {pos: pmove, count: 0, wins: 0, draws: 0, losses: 0});
+                moves.len() - 1
+            },
+        };
+
+        let book_move = &mut moves[index];
+        if new_line {
+            book_move.count += 1;
+        }
+        for (outcome, games) in results.games() {
+            match outcome {
+                LineOutcome::Won(winner) if winner == game.current_player() => book_move.wins += games,
+                LineOutcome::Won(_) => book_move.losses += games,
+                LineOutcome::Drawn => book_move.draws += games,
+            }
+        }
+    }
+}
+
+/// Parses an outcome written by `OpeningBook::write`, returning the outcome and the number of
+/// games that ended that way
+fn parse_outcome_token(token: &str) -> Option<(LineOutcome, u32)> {
+    let outcome = LineOutcome::from_token(token.get(..1)?)?;
+    let games = match &token[1..] {
+        "" => 1,
+        games => games.parse().ok().filter(|&games| games > 0)?,
+    };
+    Some((outcome, games))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    /// Parses a line of moves in standard notation
+    fn line(moves: &str) -> Vec<TilePos> {
+        parse_opening_line(moves, 1).unwrap()
+    }
+
+    fn written(book: &OpeningBook) -> String {
+        let mut out = Vec::new();
+        book.write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn builtin_book_parses() {
+        let book = OpeningBook::builtin();
+        assert!(!book.is_empty());
+
+        let game = Reversi::new(Variant::Standard);
+        let moves = book.moves(&game);
+        assert!(!moves.is_empty());
+        for (pos, count) in moves {
+            assert!(game.valid_moves().contains(&pos));
+            assert!(count > 0);
+        }
+
+        // The book only applies to standard games
+        assert!(book.moves(&Reversi::new(Variant::Anti)).is_empty());
+    }
+
+    #[test]
+    fn symmetric_positions_share_book_moves() {
+        let opening = line("f5d6c3d3c4");
+        let mut book = OpeningBook::default();
+        book.add_line(&opening).unwrap();
+
+        // Only the symmetries that leave the starting position unchanged turn the opening into
+        // another legal line of moves
+        let start = Reversi::new(Variant::Standard);
+        let grid = start.grid();
+        let symmetries: Vec<_> = (0..8).filter(|&i| grid.symmetries()[i] == *grid).collect();
+        assert!(symmetries.len() > 1);
+
+        for symmetry in symmetries {
+            let mut game = start.clone();
+            for &pmove in &opening {
+                let pmove = grid.map_to_symmetry(pmove, symmetry);
+                let moves = book.moves(&game);
+                assert_eq!(moves.len(), 1, "symmetry {}", symmetry);
+
+                // Symmetric positions (e.g. the start) have several moves that are all the same
+                // move as far as the book is concerned
+                let (book_move, count) = moves[0];
+                assert_eq!(count, 1);
+                let mut expected = game.clone();
+                expected.make_move(pmove);
+                game.make_move(book_move);
+                assert_eq!(game.grid().canonical(), expected.grid().canonical(), "symmetry {}", symmetry);
+                game = expected;
+            }
+            assert!(book.moves(&game).is_empty());
+        }
+    }
+
+    #[test]
+    fn write_and_parse_round_trip() {
+        let text = "f5d6c3d3c4 X\nf5f6e6f4 =\nf5d6c5f4e3 O\nf5f4e3\n";
+        let book = OpeningBook::parse(text).unwrap();
+        assert_eq!(written(&book), text);
+
+        let reparsed = OpeningBook::parse(&written(&book)).unwrap();
+        assert_eq!(written(&reparsed), text);
+        assert_eq!(reparsed.len(), book.len());
+
+        let builtin = OpeningBook::builtin();
+        let reparsed = OpeningBook::parse(&written(builtin)).unwrap();
+        assert_eq!(written(&reparsed), written(builtin));
+        assert_eq!(reparsed.len(), builtin.len());
+    }
+
+    #[test]
+    fn illegal_line_leaves_book_unchanged() {
+        let mut book = OpeningBook::builtin().clone();
+        let before = (book.len(), written(&book));
+        // The last move is not legal
+        assert_eq!(
+            book.add_line(&line("f5d6c3d3a1")),
+            Err(OpeningError::IllegalMove {index: 5, pmove: from_standard_notation("A1".parse().unwrap())}),
+        );
+        assert_eq!((book.len(), written(&book)), before);
+        assert!(OpeningBook::parse("f5d6\nf5f5\n").is_err());
+    }
+
+    #[test]
+    fn identical_lines_are_combined() {
+        let book = OpeningBook::parse("f5d6 X\nf5d6c3\nf5d6 O2\nf5d6 =\nf5d6\n").unwrap();
+        assert_eq!(written(&book), "f5d6 X O2 =\nf5d6c3\n");
+
+        // Each distinct line counts once, however many games followed it
+        let start = Reversi::new(Variant::Standard);
+        assert_eq!(book.moves(&start), [(from_standard_notation("F5".parse().unwrap()), 2)]);
+        let key = (start.grid().canonical(), Piece::X);
+        let book_move = book.entries[&key][0];
+        assert_eq!((book_move.wins, book_move.draws, book_move.losses), (1, 1, 2));
+
+        assert!(OpeningBook::parse("f5d6 X0").is_err());
+    }
+
+    #[test]
+    fn learn_and_merge_record_outcomes() {
+        let mut rng = StdRng::seed_from_u64(1570);
+        let mut game = Reversi::new(Variant::Standard);
+        while !game.is_game_over() {
+            let pmove = match game.valid_moves().choose(&mut rng) {
+                Some(&pos) => Move::Place(pos),
+                None => Move::Pass,
+            };
+            let _ = game.apply(pmove);
+        }
+        let moves: String = game.history()[..4].iter()
+            .map(|&pmove| match pmove {
+                Move::Place(pos) => from_standard_notation(pos).to_string().to_lowercase(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(moves, "d3e3f2e2");
+        assert_eq!(LineOutcome::of_game(&game), Some(LineOutcome::Won(Piece::O)));
+
+        let mut book = OpeningBook::default();
+        assert!(book.learn(&game, 4));
+        assert!(book.learn(&game, 4));
+        assert_eq!(written(&book), "d3e3f2e2 O2\n");
+
+        // Only finished standard games can be learned from
+        assert!(!book.learn(&Reversi::new(Variant::Standard), 4));
+        let mut anti = Reversi::new(Variant::Anti);
+        for &pmove in game.history() {
+            if anti.is_game_over() {
+                break;
+            }
+            let _ = anti.apply(pmove);
+        }
+        assert!(!book.learn(&anti, 4));
+
+        let mut merged = OpeningBook::parse("f5f6\nd3e3f2e2 X\nd3e3f2e2 O\n").unwrap();
+        merged.merge(&book);
+        assert_eq!(written(&merged), "f5f6\nd3e3f2e2 X O3\n");
+    }
+}
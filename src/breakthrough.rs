@@ -0,0 +1,344 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Piece, TilePos, Grid, Game, Heuristic, negamax, Player};
+
+/// The number of rows and columns on the board
+const BOARD_SIZE: usize = 8;
+
+/// A move in Breakthrough: a piece moving one row forward, either straight or diagonally
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreakthroughMove {
+    /// The tile the piece moves from
+    pub from: TilePos,
+    /// The tile the piece moves to
+    pub to: TilePos,
+}
+
+/// Writes the move as its two tiles, e.g. "A7-B6"
+impl fmt::Display for BreakthroughMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.from, self.to)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid move: `{0}`. Expected two tiles like `A7-A6`")]
+pub struct ParseBreakthroughMoveError(pub String);
+
+impl FromStr for BreakthroughMove {
+    type Err = ParseBreakthroughMoveError;
+
+    /// Parses the tile moved from and the tile moved to, separated by `-` or whitespace
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseBreakthroughMoveError(s.to_string());
+
+        let tiles = s.split(|c: char| c == '-' || c.is_whitespace())
+            .filter(|tile| !tile.is_empty())
+            .map(|tile| tile.parse::<TilePos>().map_err(|_| err()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match tiles[..] {
+            [from, to] => Ok(BreakthroughMove {from, to}),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// A game of Breakthrough
+///
+/// Each player starts with two rows of pieces on their side of an 8x8 board. On their turn, a
+/// player moves one of their pieces one row forward, either straight ahead onto an empty tile or
+/// diagonally onto an empty tile or a tile with an opponent's piece, which captures it. The first
+/// player to reach the row furthest from them wins, as does a player whose opponent cannot move
+/// (e.g. because they have no pieces left). `Piece::X` starts at the bottom and moves first.
+#[derive(Debug, Clone)]
+pub struct Breakthrough {
+    /// The pieces on the board
+    grid: Grid,
+    /// The player whose turn it is currently
+    current_player: Piece,
+    /// The last move made, if any
+    last_move: Option<BreakthroughMove>,
+    /// The player who reached the opposite row, if any
+    winner: Option<Piece>,
+}
+
+impl Default for Breakthrough {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Breakthrough {
+    /// Creates a new game with the pieces in their starting positions
+    pub fn new() -> Self {
+        let mut grid = Grid::default();
+        for col in 0..BOARD_SIZE {
+            for row in 0..2 {
+                grid.place(TilePos {row, col}, Piece::O);
+                grid.place(TilePos {row: BOARD_SIZE - 1 - row, col}, Piece::X);
+            }
+        }
+
+        Self {
+            grid,
+            current_player: Piece::X,
+            last_move: None,
+            winner: None,
+        }
+    }
+
+    /// Returns the pieces on the board
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Returns the last move made, if any
+    pub fn last_move(&self) -> Option<BreakthroughMove> {
+        self.last_move
+    }
+
+    /// Returns true if the current player can make the given move
+    pub fn is_valid_move(&self, pmove: BreakthroughMove) -> bool {
+        self.winner.is_none() && self.piece_moves(pmove.from).contains(&pmove)
+    }
+
+    /// Returns every move the current player can make, or no moves once the game is over
+    pub fn valid_moves(&self) -> Vec<BreakthroughMove> {
+        if self.winner.is_some() {
+            return Vec::new();
+        }
+
+        (0..BOARD_SIZE * BOARD_SIZE)
+            .flat_map(|index| self.piece_moves(TilePos {row: index / BOARD_SIZE, col: index % BOARD_SIZE}))
+            .collect()
+    }
+
+    /// Returns the moves of the current player's piece on the given tile, if there is one
+    fn piece_moves(&self, from: TilePos) -> Vec<BreakthroughMove> {
+        let player = self.current_player;
+        if from.row >= BOARD_SIZE || from.col >= BOARD_SIZE || self.grid.tile(from) != Some(player) {
+            return Vec::new();
+        }
+
+        let forward = forward(player);
+        let bounds = (BOARD_SIZE, BOARD_SIZE);
+        [-1, 0, 1].iter()
+            .filter_map(|&dcol| from.offset(forward, dcol, bounds).map(|to| (dcol, to)))
+            .filter(|&(dcol, to)| match self.grid.tile(to) {
+                None => true,
+                // Pieces only capture diagonally
+                Some(piece) => dcol != 0 && piece != player,
+            })
+            .map(|(_, to)| BreakthroughMove {from, to})
+            .collect()
+    }
+
+    /// Makes the given move for the current player and passes the turn to the other player
+    ///
+    /// # Panics
+    ///
+    /// Panics if the move is not valid.
+    pub fn make_move(&mut self, pmove: BreakthroughMove) {
+        assert!(self.is_valid_move(pmove), "bug: attempt to make the move `{}`, which is not valid", pmove);
+
+        let player = self.current_player;
+        self.grid.clear(pmove.from);
+        self.grid.place(pmove.to, player);
+        if pmove.to.row == goal_row(player) {
+            self.winner = Some(player);
+        }
+
+        self.last_move = Some(pmove);
+        self.current_player = player.opposite();
+    }
+
+    /// Returns the number of pieces the given player has left
+    pub fn count_pieces(&self, player: Piece) -> usize {
+        self.grid.rows().iter().flatten().filter(|&&tile| tile == Some(player)).count()
+    }
+}
+
+/// Returns the direction (as a row offset) that the given player's pieces move in
+fn forward(player: Piece) -> isize {
+    match player {
+        Piece::X => -1,
+        Piece::O => 1,
+    }
+}
+
+/// Returns the row the given player has to reach to win
+fn goal_row(player: Piece) -> usize {
+    match player {
+        Piece::X => 0,
+        Piece::O => BOARD_SIZE - 1,
+    }
+}
+
+impl Game for Breakthrough {
+    type Move = BreakthroughMove;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<BreakthroughMove> {
+        self.valid_moves()
+    }
+
+    fn play_move(&mut self, pmove: BreakthroughMove) {
+        self.make_move(pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.valid_moves().is_empty()
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        match self.winner {
+            Some(winner) => Some(winner),
+            // The player who cannot move loses
+            None if self.is_game_over() => Some(self.current_player.opposite()),
+            None => None,
+        }
+    }
+}
+
+/// Formats the board the same way as a Reversi board, without colors. The alternate flag (`{:#}`)
+/// formats the board with plain ASCII characters.
+impl fmt::Display for Breakthrough {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.grid, f)
+    }
+}
+
+/// An AI that plays Breakthrough by searching a number of moves ahead (see `negamax`)
+///
+/// Positions are evaluated by the number of pieces each player has, with pieces that are closer
+/// to the opposite row being worth more, and a bonus for pieces still guarding the home row.
+#[derive(Debug, Clone, Copy)]
+pub struct BreakthroughAi {
+    /// How many moves ahead to search
+    depth: usize,
+}
+
+impl Default for BreakthroughAi {
+    fn default() -> Self {
+        Self {depth: 4}
+    }
+}
+
+impl BreakthroughAi {
+    /// Creates an AI that searches the given number of moves ahead
+    pub fn new(depth: usize) -> Self {
+        Self {depth}
+    }
+
+    /// Returns how many moves ahead the AI searches
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the move the AI would play for the current player, or `None` if the game is over
+    pub fn choose_move(&self, game: &Breakthrough) -> Option<BreakthroughMove> {
+        negamax(game, self.depth, self).map(|(pmove, _)| pmove)
+    }
+}
+
+impl Heuristic<Breakthrough> for BreakthroughAi {
+    fn evaluate(&self, game: &Breakthrough, player: Piece) -> i32 {
+        let mut score = 0;
+        for (row, tiles) in game.grid.rows().iter().enumerate() {
+            for &piece in tiles.iter().flatten() {
+                // The number of rows the piece has moved towards the opposite row
+                let advanced = (BOARD_SIZE - 1 - goal_row(piece).abs_diff(row)) as i32;
+                let guards_home_row = advanced == 0;
+                let value = 100 + 2 * advanced * advanced + if guards_home_row { 10 } else { 0 };
+
+                if piece == player {
+                    score += value;
+                } else {
+                    score -= value;
+                }
+            }
+        }
+        score
+    }
+
+    fn order_moves(&self, game: &Breakthrough, moves: &mut Vec<BreakthroughMove>) {
+        // Winning moves first, then captures
+        let player = game.current_player;
+        moves.sort_by_key(|pmove| {
+            if pmove.to.row == goal_row(player) {
+                0
+            } else if game.grid.tile(pmove.to).is_some() {
+                1
+            } else {
+                2
+            }
+        });
+    }
+}
+
+/// Plays the move chosen by `BreakthroughAi::choose_move`
+impl Player<Breakthrough> for BreakthroughAi {
+    fn choose_move(&mut self, game: &Breakthrough) -> BreakthroughMove {
+        BreakthroughAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pmove(s: &str) -> BreakthroughMove {
+        s.parse().unwrap()
+    }
+
+    /// Creates a game with only the given pieces on the board, with X to move
+    fn setup(x: &[&str], o: &[&str]) -> Breakthrough {
+        let mut game = Breakthrough::new();
+        game.grid = Grid::default();
+        for (tiles, piece) in [(x, Piece::X), (o, Piece::O)] {
+            for tile in tiles {
+                game.grid.place(tile.parse().unwrap(), piece);
+            }
+        }
+        game
+    }
+
+    #[test]
+    fn opening_moves() {
+        let game = Breakthrough::new();
+        // Only the front row can move: 3 moves each, except for the 2 pieces on the edges
+        assert_eq!(game.valid_moves().len(), 3 * 8 - 2);
+        assert!(game.is_valid_move(pmove("A7-B6")));
+        assert!(!game.is_valid_move(pmove("A8-A7")));
+    }
+
+    #[test]
+    fn captures_only_diagonally() {
+        let game = setup(&["D5"], &["D4", "E4"]);
+        assert!(!game.is_valid_move(pmove("D5-D4")));
+        assert!(game.is_valid_move(pmove("D5-E4")));
+        assert!(game.is_valid_move(pmove("D5-C4")));
+    }
+
+    #[test]
+    fn reaching_opposite_row_wins() {
+        let mut game = setup(&["B2"], &["G7"]);
+        game.make_move(pmove("B2-A1"));
+        assert!(game.is_game_over());
+        assert_eq!(Game::winner(&game), Some(Piece::X));
+    }
+
+    #[test]
+    fn ai_finds_win() {
+        let game = setup(&["B3", "H8"], &["A1", "C1", "G6"]);
+        // B2 would be captured, but the pieces on A1 and C1 cannot capture straight ahead
+        let choice = BreakthroughAi::new(3).choose_move(&game).unwrap();
+        assert!(choice == pmove("B3-A2") || choice == pmove("B3-C2"), "{}", choice);
+    }
+}
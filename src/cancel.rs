@@ -0,0 +1,47 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// A handle for stopping a search early, either from another thread or once a deadline is reached
+///
+/// Clones of a token share the same flag, so canceling any clone cancels all of them. Searches
+/// check the token periodically and return the best result found so far once it is canceled.
+#[derive(Debug, Default, Clone)]
+pub struct CancelToken {
+    canceled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// Creates a token that is only canceled by calling `cancel`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a token that shares the flag of this token, but is also canceled once the given
+    /// time is reached
+    pub fn with_deadline(&self, deadline: Instant) -> Self {
+        let deadline = match self.deadline {
+            Some(current) => current.min(deadline),
+            None => deadline,
+        };
+
+        Self {canceled: self.canceled.clone(), deadline: Some(deadline)}
+    }
+
+    /// Returns the time at which this token is canceled automatically (if any)
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Cancels this token and every clone of it
+    pub fn cancel(&self) {
+        self.canceled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if this token has been canceled or its deadline has been reached
+    pub fn is_canceled(&self) -> bool {
+        self.canceled.load(Ordering::Relaxed)
+            || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
@@ -0,0 +1,592 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Piece, TilePos, Game, Heuristic, negamax, Player};
+
+/// The number of rows and columns on the board
+const BOARD_SIZE: usize = 8;
+
+/// The number of moves in a row (counting both players) without a capture or a man moving after
+/// which the game is a draw, like the 40-move rule of tournament play
+pub const CHECKERS_DRAW_PLIES: usize = 80;
+
+/// A piece on a checkers board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checker {
+    /// The player that owns the piece
+    pub owner: Piece,
+    /// True if the piece has been crowned and can move backwards
+    pub king: bool,
+}
+
+impl Checker {
+    /// Returns the directions (as row and column offsets) that this piece can move and jump in
+    fn directions(self) -> &'static [(isize, isize)] {
+        match (self.king, self.owner) {
+            (true, _) => &[(-1, -1), (-1, 1), (1, -1), (1, 1)],
+            // X starts at the bottom of the board and moves up
+            (false, Piece::X) => &[(-1, -1), (-1, 1)],
+            (false, Piece::O) => &[(1, -1), (1, 1)],
+        }
+    }
+}
+
+/// Draws men as lowercase letters and kings as uppercase letters
+impl fmt::Display for Checker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letter = match self.owner {
+            Piece::X => 'x',
+            Piece::O => 'o',
+        };
+        if self.king {
+            write!(f, "{}", letter.to_ascii_uppercase())
+        } else {
+            write!(f, "{}", letter)
+        }
+    }
+}
+
+/// A move in checkers: the squares a piece stops on, starting with the square it moves from
+///
+/// A simple move has two squares next to each other. A capture has a square for every jump in the
+/// sequence, each two squares away from the last, with the captured pieces in between.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckersMove {
+    path: Vec<TilePos>,
+}
+
+impl CheckersMove {
+    /// Returns the squares the piece stops on, starting with the square it moves from
+    pub fn path(&self) -> &[TilePos] {
+        &self.path
+    }
+
+    /// Returns the square the piece moves from
+    pub fn from(&self) -> TilePos {
+        self.path[0]
+    }
+
+    /// Returns the square the piece ends up on
+    pub fn to(&self) -> TilePos {
+        self.path[self.path.len() - 1]
+    }
+
+    /// Returns true if the move jumps over (and captures) at least one piece
+    pub fn is_capture(&self) -> bool {
+        self.path[0].row.abs_diff(self.path[1].row) == 2
+    }
+
+    /// Returns the squares of the pieces captured by the move, in the order they are jumped
+    pub fn captures(&self) -> Vec<TilePos> {
+        if !self.is_capture() {
+            return Vec::new();
+        }
+
+        self.path.windows(2)
+            .map(|step| TilePos {row: (step[0].row + step[1].row) / 2, col: (step[0].col + step[1].col) / 2})
+            .collect()
+    }
+}
+
+/// Writes the move in the usual notation, e.g. "C3-D4" for a simple move or "C3xE5xC7" for a
+/// capture
+impl fmt::Display for CheckersMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = if self.is_capture() { "x" } else { "-" };
+        let squares: Vec<_> = self.path.iter().map(TilePos::to_string).collect();
+        write!(f, "{}", squares.join(separator))
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid checkers move: `{0}`. Expected something like `C3-D4` or `C3xE5xC7`")]
+pub struct ParseCheckersMoveError(pub String);
+
+impl FromStr for CheckersMove {
+    type Err = ParseCheckersMoveError;
+
+    /// Parses the squares of a move separated by `-`, `x` or whitespace (e.g. "C3-D4", "c3xe5xc7"
+    /// or "C3 E5 C7"), checking only that each square is where the last one could move to
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseCheckersMoveError(s.to_string());
+
+        let path = s.split(|c: char| c == '-' || c == 'x' || c == 'X' || c.is_whitespace())
+            .filter(|square| !square.is_empty())
+            .map(|square| square.parse::<TilePos>().map_err(|_| err()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if path.len() < 2 {
+            return Err(err());
+        }
+
+        let distance = |a: TilePos, b: TilePos| (a.row.abs_diff(b.row), a.col.abs_diff(b.col));
+        let first = distance(path[0], path[1]);
+        let valid = match first {
+            (1, 1) => path.len() == 2,
+            (2, 2) => path.windows(2).all(|step| distance(step[0], step[1]) == (2, 2)),
+            _ => false,
+        };
+        if !valid {
+            return Err(err());
+        }
+
+        Ok(CheckersMove {path})
+    }
+}
+
+/// A game of checkers (English draughts) on an 8x8 board
+///
+/// Pieces only stand on the dark squares and move diagonally. `Piece::X` starts at the bottom of
+/// the board and moves first. Men move forward one square and kings move in any diagonal
+/// direction. Capturing is forced: if a player can jump over an opponent's piece, they must, and
+/// they must keep jumping with the same piece while they can. A man that reaches the far row is
+/// crowned king, which ends its move. A player without any legal moves (e.g. because they have
+/// no pieces left) loses.
+#[derive(Debug, Clone)]
+pub struct Checkers {
+    /// The pieces on the board, indexed by row and then column
+    board: [[Option<Checker>; BOARD_SIZE]; BOARD_SIZE],
+    /// The player whose turn it is currently
+    current_player: Piece,
+    /// The legal moves of the current player, cached after every move
+    moves: Vec<CheckersMove>,
+    /// The last move made, if any
+    last_move: Option<CheckersMove>,
+    /// The number of moves made since the last capture or move by a man
+    quiet_plies: usize,
+}
+
+impl Default for Checkers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checkers {
+    /// Creates a new game with the pieces in their starting positions
+    pub fn new() -> Self {
+        let mut board = [[None; BOARD_SIZE]; BOARD_SIZE];
+        for (row, row_squares) in board.iter_mut().enumerate() {
+            let owner = match row {
+                0..=2 => Piece::O,
+                5..=7 => Piece::X,
+                _ => continue,
+            };
+            for (col, square) in row_squares.iter_mut().enumerate() {
+                if is_dark(TilePos {row, col}) {
+                    *square = Some(Checker {owner, king: false});
+                }
+            }
+        }
+
+        let mut game = Self {
+            board,
+            current_player: Piece::X,
+            moves: Vec::new(),
+            last_move: None,
+            quiet_plies: 0,
+        };
+        game.update_moves();
+        game
+    }
+
+    /// Returns the piece on the given square, or `None` if it is empty or off the board
+    pub fn piece(&self, pos: TilePos) -> Option<Checker> {
+        self.board.get(pos.row)?.get(pos.col).copied().flatten()
+    }
+
+    /// Returns the last move made, if any
+    pub fn last_move(&self) -> Option<&CheckersMove> {
+        self.last_move.as_ref()
+    }
+
+    /// Returns the legal moves of the current player
+    ///
+    /// If any capture is possible, only captures are returned, since capturing is forced.
+    pub fn valid_moves(&self) -> &[CheckersMove] {
+        &self.moves
+    }
+
+    /// Returns the number of men and kings the given player has on the board
+    pub fn count_pieces(&self, player: Piece) -> (usize, usize) {
+        let mut men = 0;
+        let mut kings = 0;
+        for checker in self.board.iter().flatten().flatten() {
+            match checker {
+                Checker {owner, king: false} if *owner == player => men += 1,
+                Checker {owner, king: true} if *owner == player => kings += 1,
+                _ => {},
+            }
+        }
+        (men, kings)
+    }
+
+    /// Returns true if the game ended in a draw because neither player has captured or moved a man
+    /// in a long time (see `CHECKERS_DRAW_PLIES`)
+    pub fn is_draw(&self) -> bool {
+        self.quiet_plies >= CHECKERS_DRAW_PLIES
+    }
+
+    /// Makes the given move for the current player and passes the turn to the other player
+    ///
+    /// # Panics
+    ///
+    /// Panics if the move is not one of `valid_moves`.
+    pub fn make_move(&mut self, pmove: &CheckersMove) {
+        assert!(self.moves.contains(pmove), "bug: attempt to make the move `{}`, which is not valid", pmove);
+
+        let from = pmove.from();
+        let to = pmove.to();
+        let mut checker = self.board[from.row][from.col].take()
+            .expect("bug: a valid move should start at a piece");
+        for captured in pmove.captures() {
+            self.board[captured.row][captured.col] = None;
+        }
+
+        let progress = !checker.king || pmove.is_capture();
+        if to.row == crowning_row(checker.owner) {
+            checker.king = true;
+        }
+        self.board[to.row][to.col] = Some(checker);
+
+        self.quiet_plies = if progress { 0 } else { self.quiet_plies + 1 };
+        self.last_move = Some(pmove.clone());
+        self.current_player = self.current_player.opposite();
+        self.update_moves();
+    }
+
+    /// Recomputes the cached legal moves of the current player
+    fn update_moves(&mut self) {
+        self.moves.clear();
+        if self.is_draw() {
+            return;
+        }
+
+        for row in 0..BOARD_SIZE {
+            for col in 0..BOARD_SIZE {
+                let pos = TilePos {row, col};
+                match self.piece(pos) {
+                    Some(checker) if checker.owner == self.current_player => {
+                        let mut path = vec![pos];
+                        self.find_jumps(checker, &mut path, &mut Vec::new());
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        // Simple moves are only allowed if there are no captures
+        if !self.moves.is_empty() {
+            return;
+        }
+        let bounds = (BOARD_SIZE, BOARD_SIZE);
+        for row in 0..BOARD_SIZE {
+            for col in 0..BOARD_SIZE {
+                let from = TilePos {row, col};
+                let checker = match self.piece(from) {
+                    Some(checker) if checker.owner == self.current_player => checker,
+                    _ => continue,
+                };
+
+                for &(drow, dcol) in checker.directions() {
+                    if let Some(to) = from.offset(drow, dcol, bounds) {
+                        if self.piece(to).is_none() {
+                            self.moves.push(CheckersMove {path: vec![from, to]});
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds every complete sequence of jumps that continues the given path to the cached moves
+    ///
+    /// The captured pieces stay on the board until the move is made, so they are tracked
+    /// separately to make sure that no piece is jumped twice. The moving piece is treated as if
+    /// it had already left its starting square.
+    fn find_jumps(&mut self, checker: Checker, path: &mut Vec<TilePos>, captured: &mut Vec<TilePos>) {
+        let bounds = (BOARD_SIZE, BOARD_SIZE);
+        let from = path[path.len() - 1];
+        let start = path[0];
+        // A man that is crowned during a capture stops there
+        let crowned = !checker.king && path.len() > 1 && from.row == crowning_row(checker.owner);
+
+        let mut extended = false;
+        if !crowned {
+            for &(drow, dcol) in checker.directions() {
+                let (over, to) = match (from.offset(drow, dcol, bounds), from.offset(drow * 2, dcol * 2, bounds)) {
+                    (Some(over), Some(to)) => (over, to),
+                    _ => continue,
+                };
+                let jumps_opponent = matches!(self.piece(over), Some(piece) if piece.owner != checker.owner);
+                let lands_on_empty = self.piece(to).is_none() || to == start;
+                if !jumps_opponent || !lands_on_empty || captured.contains(&over) {
+                    continue;
+                }
+
+                extended = true;
+                path.push(to);
+                captured.push(over);
+                self.find_jumps(checker, path, captured);
+                captured.pop();
+                path.pop();
+            }
+        }
+
+        if !extended && path.len() > 1 {
+            self.moves.push(CheckersMove {path: path.clone()});
+        }
+    }
+}
+
+/// Returns true if the given square is one of the dark squares that pieces stand on
+fn is_dark(pos: TilePos) -> bool {
+    (pos.row + pos.col) % 2 == 1
+}
+
+/// Returns the row on which the men of the given player are crowned
+fn crowning_row(player: Piece) -> usize {
+    match player {
+        Piece::X => 0,
+        Piece::O => BOARD_SIZE - 1,
+    }
+}
+
+impl Game for Checkers {
+    type Move = CheckersMove;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<CheckersMove> {
+        self.moves.clone()
+    }
+
+    fn play_move(&mut self, pmove: CheckersMove) {
+        self.make_move(&pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        if !self.moves.is_empty() || self.is_draw() {
+            return None;
+        }
+
+        // The player who cannot move loses
+        Some(self.current_player.opposite())
+    }
+}
+
+/// Draws the board with the columns labeled with letters and the rows with numbers, marking the
+/// squares of the last move with brackets
+impl fmt::Display for Checkers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let marked: Vec<_> = self.last_move.iter()
+            .flat_map(|pmove| pmove.path.iter().copied())
+            .collect();
+
+        write!(f, "  ")?;
+        for col in 0..BOARD_SIZE {
+            write!(f, "  {}", (b'A' + col as u8) as char)?;
+        }
+        writeln!(f)?;
+
+        for row in 0..BOARD_SIZE {
+            write!(f, "{:>2}", row + 1)?;
+            for col in 0..BOARD_SIZE {
+                let pos = TilePos {row, col};
+                let (open, close) = if marked.contains(&pos) { ('[', ']') } else { (' ', ' ') };
+                match self.piece(pos) {
+                    Some(checker) => write!(f, "{}{}{}", open, checker, close)?,
+                    None if is_dark(pos) => write!(f, "{}.{}", open, close)?,
+                    None => write!(f, "   ")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An AI that plays checkers by searching a number of moves ahead (see `negamax`)
+///
+/// Positions are evaluated by their material, with kings worth more than men, along with how far
+/// the men have advanced and whether the back row is still guarding against the opponent's men
+/// being crowned.
+#[derive(Debug, Clone, Copy)]
+pub struct CheckersAi {
+    /// How many moves ahead to search
+    depth: usize,
+}
+
+impl Default for CheckersAi {
+    fn default() -> Self {
+        Self {depth: 6}
+    }
+}
+
+impl CheckersAi {
+    /// Creates an AI that searches the given number of moves ahead
+    pub fn new(depth: usize) -> Self {
+        Self {depth}
+    }
+
+    /// Returns how many moves ahead the AI searches
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the move the AI would play for the current player, or `None` if the game is over
+    pub fn choose_move(&self, game: &Checkers) -> Option<CheckersMove> {
+        negamax(game, self.depth, self).map(|(pmove, _)| pmove)
+    }
+}
+
+impl Heuristic<Checkers> for CheckersAi {
+    fn evaluate(&self, game: &Checkers, player: Piece) -> i32 {
+        let mut score = 0;
+        for (row, row_squares) in game.board.iter().enumerate() {
+            for checker in row_squares.iter().flatten() {
+                let value = if checker.king {
+                    150
+                } else {
+                    // The number of rows the man has moved towards being crowned
+                    let advanced = crowning_row(checker.owner).abs_diff(row);
+                    let advanced = BOARD_SIZE - 1 - advanced;
+                    let guards_back_row = advanced == 0;
+                    100 + 3 * advanced as i32 + if guards_back_row { 5 } else { 0 }
+                };
+
+                if checker.owner == player {
+                    score += value;
+                } else {
+                    score -= value;
+                }
+            }
+        }
+        score
+    }
+
+    fn order_moves(&self, _game: &Checkers, moves: &mut Vec<CheckersMove>) {
+        // Longer captures are usually better
+        moves.sort_by_key(|pmove| std::cmp::Reverse(pmove.path.len()));
+    }
+}
+
+/// Plays the move chosen by `CheckersAi::choose_move`
+impl Player<Checkers> for CheckersAi {
+    fn choose_move(&mut self, game: &Checkers) -> CheckersMove {
+        CheckersAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(s: &str) -> TilePos {
+        s.parse().unwrap()
+    }
+
+    /// Creates a game with only the given pieces on the board, with the given player to move
+    fn setup(pieces: &[(&str, Piece, bool)], current_player: Piece) -> Checkers {
+        let mut game = Checkers::new();
+        game.board = [[None; BOARD_SIZE]; BOARD_SIZE];
+        for &(square, owner, king) in pieces {
+            let square = pos(square);
+            game.board[square.row][square.col] = Some(Checker {owner, king});
+        }
+        game.current_player = current_player;
+        game.update_moves();
+        game
+    }
+
+    fn moves(game: &Checkers) -> Vec<String> {
+        let mut moves: Vec<_> = game.valid_moves().iter().map(CheckersMove::to_string).collect();
+        moves.sort();
+        moves
+    }
+
+    #[test]
+    fn opening_moves() {
+        let game = Checkers::new();
+        assert_eq!(game.count_pieces(Piece::X), (12, 0));
+        assert_eq!(game.count_pieces(Piece::O), (12, 0));
+        assert_eq!(moves(&game), ["A6-B5", "C6-B5", "C6-D5", "E6-D5", "E6-F5", "G6-F5", "G6-H5"]);
+    }
+
+    #[test]
+    fn captures_are_forced() {
+        let game = setup(&[("C6", Piece::X, false), ("D5", Piece::O, false), ("H6", Piece::X, false)], Piece::X);
+        assert_eq!(moves(&game), ["C6xE4"]);
+    }
+
+    #[test]
+    fn multi_jumps_must_be_completed() {
+        let mut game = setup(&[
+            ("A8", Piece::X, false),
+            ("B7", Piece::O, false),
+            ("D5", Piece::O, false),
+            ("F5", Piece::O, false),
+        ], Piece::X);
+        assert_eq!(moves(&game), ["A8xC6xE4"]);
+
+        game.make_move(&"A8xC6xE4".parse().unwrap());
+        assert_eq!(game.count_pieces(Piece::O), (1, 0));
+        assert_eq!(game.piece(pos("E4")), Some(Checker {owner: Piece::X, king: false}));
+    }
+
+    #[test]
+    fn men_only_move_forward_and_kings_move_both_ways() {
+        let game = setup(&[("D5", Piece::X, false)], Piece::X);
+        assert_eq!(moves(&game), ["D5-C4", "D5-E4"]);
+
+        let game = setup(&[("D5", Piece::X, true)], Piece::X);
+        assert_eq!(moves(&game), ["D5-C4", "D5-C6", "D5-E4", "D5-E6"]);
+    }
+
+    #[test]
+    fn crowning_ends_the_move() {
+        let mut game = setup(&[
+            ("C3", Piece::X, false),
+            ("D2", Piece::O, false),
+            ("F2", Piece::O, false),
+        ], Piece::X);
+        // As a king it could keep jumping over F2, but being crowned ends the move
+        assert_eq!(moves(&game), ["C3xE1"]);
+
+        game.make_move(&"C3xE1".parse().unwrap());
+        assert_eq!(game.piece(pos("E1")), Some(Checker {owner: Piece::X, king: true}));
+    }
+
+    #[test]
+    fn no_moves_loses() {
+        let mut game = setup(&[("C3", Piece::X, false), ("D2", Piece::O, false)], Piece::X);
+        game.make_move(&"C3xE1".parse().unwrap());
+        assert!(game.is_game_over());
+        assert_eq!(game.winner(), Some(Piece::X));
+    }
+
+    #[test]
+    fn parse_moves() {
+        let pmove: CheckersMove = "c3xe5xc7".parse().unwrap();
+        assert_eq!(pmove.to_string(), "C3xE5xC7");
+        assert_eq!(pmove.captures(), [pos("D4"), pos("D6")]);
+        assert_eq!("C3 D4".parse::<CheckersMove>().unwrap().to_string(), "C3-D4");
+        assert!("C3-D5".parse::<CheckersMove>().is_err());
+        assert!("C3-D4-E5".parse::<CheckersMove>().is_err());
+        assert!("C3".parse::<CheckersMove>().is_err());
+    }
+
+    #[test]
+    fn ai_takes_free_piece() {
+        let game = setup(&[("C6", Piece::X, false), ("D5", Piece::O, false), ("A2", Piece::O, false)], Piece::X);
+        assert_eq!(CheckersAi::new(4).choose_move(&game).map(|pmove| pmove.to_string()).as_deref(), Some("C6xE4"));
+    }
+}
@@ -0,0 +1,681 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+use yansi::{Color, Paint, Style};
+
+use crate::{Seat, HexPos, HEX_DIRECTIONS, Game, Player};
+
+/// The number of pieces each player starts with, which fill one arm of the star
+pub const CHINESE_CHECKERS_PIECES: usize = 10;
+
+/// The number of holes from the center of the star to the edge of its central hexagon
+const STAR_RADIUS: isize = 4;
+/// The number of holes from the center of the star to the tip of an arm
+const ARM_TIP: isize = 2 * STAR_RADIUS;
+/// The number of rows (and the most holes along any line) of the star
+const BOARD_WIDTH: usize = 2 * ARM_TIP as usize + 1;
+
+/// The arm that each player starts in (see `arm_of`), in the order of their seats, for each number
+/// of players from 2 to 6
+///
+/// The seats go clockwise around the board starting from the bottom, and every player's goal is
+/// the arm opposite to the one they start in.
+const HOME_ARMS: [&[usize]; 5] = [
+    &[3, 0],
+    &[3, 5, 1],
+    &[3, 4, 0, 1],
+    &[3, 4, 5, 0, 1],
+    &[3, 4, 5, 0, 1, 2],
+];
+
+/// The colors and letters of the players in the seats after the first two, whose pieces are drawn
+/// the same way as `Piece::X` and `Piece::O`
+const SEAT_GLYPHS: [(Color, char); 4] = [(Color::Green, 'A'), (Color::Yellow, 'B'), (Color::Magenta, 'C'), (Color::Cyan, 'D')];
+
+/// A move in Chinese Checkers: a piece stepping to a neighboring hole or making a chain of hops,
+/// written as every hole the piece lands on (e.g. "M1-L1" or "M3-K3-I3"), or a pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChineseCheckersMove {
+    /// The hole the piece moves from followed by every hole it lands on, or no holes if the
+    /// player passes because they cannot move
+    pub path: Vec<HexPos>,
+}
+
+impl ChineseCheckersMove {
+    /// Returns the hole the piece moves from, or `None` for a pass
+    pub fn from(&self) -> Option<HexPos> {
+        self.path.first().copied()
+    }
+
+    /// Returns the hole the piece ends up in, or `None` for a pass
+    pub fn to(&self) -> Option<HexPos> {
+        self.path.last().copied()
+    }
+}
+
+/// Writes the move as the names of its holes (see `ChineseCheckers::hole_name`), e.g. "M3-K3-I3",
+/// or "pass"
+impl fmt::Display for ChineseCheckersMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "pass");
+        }
+
+        let holes: Vec<_> = self.path.iter().map(|&hole| hole_name(hole)).collect();
+        write!(f, "{}", holes.join("-"))
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid move: `{0}`. Expected at least two holes like `M1-L1`, or `pass`")]
+pub struct ParseChineseCheckersMoveError(pub String);
+
+impl FromStr for ChineseCheckersMove {
+    type Err = ParseChineseCheckersMoveError;
+
+    /// Parses the holes the piece lands on, separated by `-` or whitespace, or "pass"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseChineseCheckersMoveError(s.to_string());
+
+        if s.trim().eq_ignore_ascii_case("pass") {
+            return Ok(ChineseCheckersMove {path: Vec::new()});
+        }
+        let path = s.split(|c: char| c == '-' || c.is_whitespace())
+            .filter(|hole| !hole.is_empty())
+            .map(|hole| parse_hole(hole).ok_or_else(err))
+            .collect::<Result<Vec<_>, _>>()?;
+        if path.len() < 2 {
+            return Err(err());
+        }
+        Ok(ChineseCheckersMove {path})
+    }
+}
+
+/// A game of Chinese Checkers for 2 to 6 players
+///
+/// The board is a six-pointed star of 121 holes. Each player starts with their pieces filling one
+/// of the arms of the star, and races to move all of them into the opposite arm. On their turn, a
+/// player either steps one of their pieces into a neighboring empty hole, or hops it over a
+/// neighboring piece (of any player) into the empty hole right behind it. Hops can be chained, as
+/// long as every hop lands in an empty hole and the piece never lands in the same hole twice.
+/// Pieces are never captured.
+///
+/// The first player whose goal arm is full, with at least one of their own pieces in it, wins.
+/// Pieces of other players that never leave the goal arm therefore cannot keep a player from
+/// winning. A player who cannot move passes.
+///
+/// Holes are named by their row, from A at the top to Q at the bottom, and their number in the
+/// row, starting at 1 on the left (see `hole_name`). The players sit clockwise around the board,
+/// with the first player starting at the bottom.
+#[derive(Debug, Clone)]
+pub struct ChineseCheckers {
+    /// The seat of the player whose piece is in each hole, indexed by row (`r`) and then by `q`
+    /// (each offset by `ARM_TIP`)
+    holes: [[Option<Seat>; BOARD_WIDTH]; BOARD_WIDTH],
+    /// The arm that each player starts in, in the order of their seats
+    home_arms: &'static [usize],
+    /// The player whose turn it is currently
+    current_seat: Seat,
+    /// The last move made, if any
+    last_move: Option<ChineseCheckersMove>,
+    /// The player who filled their goal arm, if any
+    winner: Option<Seat>,
+}
+
+impl Default for ChineseCheckers {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+impl ChineseCheckers {
+    /// Creates a new game for the given number of players with their pieces in their starting
+    /// arms
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than 2 or more than 6 players.
+    pub fn new(players: usize) -> Self {
+        assert!((2..=6).contains(&players), "bug: Chinese Checkers is played by 2 to 6 players, not {}", players);
+
+        let mut game = Self {
+            holes: [[None; BOARD_WIDTH]; BOARD_WIDTH],
+            home_arms: HOME_ARMS[players - 2],
+            current_seat: Seat(0),
+            last_move: None,
+            winner: None,
+        };
+        for (index, &arm) in game.home_arms.iter().enumerate() {
+            for hole in arm_holes(arm) {
+                game.place(hole, Some(Seat(index)));
+            }
+        }
+        game
+    }
+
+    /// Returns every hole of the board, row by row from the top and from left to right in each row
+    pub fn holes() -> Vec<HexPos> {
+        (-ARM_TIP..=ARM_TIP)
+            .flat_map(|r| (-ARM_TIP..=ARM_TIP).map(move |q| HexPos {q, r}))
+            .filter(|&hole| is_hole(hole))
+            .collect()
+    }
+
+    /// Returns the name of the given hole: the letter of its row (from A at the top to Q at the
+    /// bottom) followed by its number in the row (starting at 1 on the left), e.g. "E13"
+    pub fn hole_name(hole: HexPos) -> String {
+        hole_name(hole)
+    }
+
+    /// Returns the seat of the player whose piece is in the given hole, or `None` if the hole is
+    /// empty or not on the board
+    pub fn piece(&self, hole: HexPos) -> Option<Seat> {
+        if !is_hole(hole) {
+            return None;
+        }
+        self.holes[(hole.r + ARM_TIP) as usize][(hole.q + ARM_TIP) as usize]
+    }
+
+    /// Puts the given piece (or no piece) in the given hole
+    fn place(&mut self, hole: HexPos, piece: Option<Seat>) {
+        self.holes[(hole.r + ARM_TIP) as usize][(hole.q + ARM_TIP) as usize] = piece;
+    }
+
+    /// Returns the holes of the arm that the player in the given seat starts in
+    pub fn home_holes(&self, seat: Seat) -> Vec<HexPos> {
+        arm_holes(self.home_arms[seat.index()])
+    }
+
+    /// Returns the holes of the arm that the player in the given seat has to fill to win
+    pub fn goal_holes(&self, seat: Seat) -> Vec<HexPos> {
+        arm_holes(goal_arm(self.home_arms[seat.index()]))
+    }
+
+    /// Returns the number of pieces the player in the given seat has in their goal arm
+    pub fn pieces_in_goal(&self, seat: Seat) -> usize {
+        self.goal_holes(seat).into_iter().filter(|&hole| self.piece(hole) == Some(seat)).count()
+    }
+
+    /// Returns the last move made, if any
+    pub fn last_move(&self) -> Option<&ChineseCheckersMove> {
+        self.last_move.as_ref()
+    }
+
+    /// Returns true if the current player can make the given move
+    ///
+    /// Moves are matched by the holes they start and end in, so the holes that a chain of hops
+    /// lands on along the way can be left out.
+    pub fn is_valid_move(&self, pmove: &ChineseCheckersMove) -> bool {
+        self.find_move(pmove).is_some()
+    }
+
+    /// Returns the valid move that starts and ends in the same holes as the given move, if any
+    fn find_move(&self, pmove: &ChineseCheckersMove) -> Option<ChineseCheckersMove> {
+        self.valid_moves().into_iter()
+            .find(|valid| valid.from() == pmove.from() && valid.to() == pmove.to())
+    }
+
+    /// Returns every move the current player can make, with one move for each hole that each
+    /// piece can end up in, or no moves once the game is over
+    ///
+    /// If the current player cannot move any of their pieces, their only move is to pass.
+    pub fn valid_moves(&self) -> Vec<ChineseCheckersMove> {
+        if self.winner.is_some() {
+            return Vec::new();
+        }
+
+        let moves: Vec<_> = Self::holes().into_iter()
+            .filter(|&hole| self.piece(hole) == Some(self.current_seat))
+            .flat_map(|from| self.piece_moves(from))
+            .collect();
+        if moves.is_empty() {
+            return vec![ChineseCheckersMove {path: Vec::new()}];
+        }
+        moves
+    }
+
+    /// Returns the moves of the piece in the given hole: its steps followed by its chains of hops
+    fn piece_moves(&self, from: HexPos) -> Vec<ChineseCheckersMove> {
+        let mut moves: Vec<_> = from.neighbors().iter()
+            .filter(|&&to| is_hole(to) && self.piece(to).is_none())
+            .map(|&to| ChineseCheckersMove {path: vec![from, to]})
+            .collect();
+
+        // Search every chain of hops, keeping the first (and shortest) chain to each hole. The
+        // hole the piece started in counts as visited, since the piece would just be back where
+        // it started.
+        let mut chains = vec![vec![from]];
+        let mut visited = vec![from];
+        while !chains.is_empty() {
+            let mut next_chains = Vec::new();
+            for chain in chains {
+                let &hole = chain.last().expect("bug: a chain of hops always has a start");
+                for &direction in HEX_DIRECTIONS.iter() {
+                    let over = hole.offset(direction, 1);
+                    let to = hole.offset(direction, 2);
+                    if over == from || self.piece(over).is_none() || !is_hole(to) || self.piece(to).is_some() || visited.contains(&to) {
+                        continue;
+                    }
+
+                    visited.push(to);
+                    let mut path = chain.clone();
+                    path.push(to);
+                    moves.push(ChineseCheckersMove {path: path.clone()});
+                    next_chains.push(path);
+                }
+            }
+            chains = next_chains;
+        }
+
+        moves
+    }
+
+    /// Makes the given move for the current player and passes the turn to the next player
+    ///
+    /// The move is played along the holes of the valid move that starts and ends in the same holes
+    /// (see `is_valid_move`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the move is not valid.
+    pub fn make_move(&mut self, pmove: ChineseCheckersMove) {
+        let pmove = match self.find_move(&pmove) {
+            Some(pmove) => pmove,
+            None => panic!("bug: attempt to make the move `{}`, which is not valid", pmove),
+        };
+
+        let seat = self.current_seat;
+        if let (Some(from), Some(to)) = (pmove.from(), pmove.to()) {
+            self.place(from, None);
+            self.place(to, Some(seat));
+
+            // Filling another player's goal arm (e.g. by leaving a piece in it) can win the game
+            // for that player instead
+            let players = self.player_count();
+            self.winner = (0..players).map(|offset| Seat((seat.index() + offset) % players))
+                .find(|&player| self.has_filled_goal(player));
+        }
+
+        self.last_move = Some(pmove);
+        self.current_seat = seat.next(self.player_count());
+    }
+
+    /// Returns true if the goal arm of the player in the given seat is full, with at least one of
+    /// their own pieces in it
+    fn has_filled_goal(&self, seat: Seat) -> bool {
+        let goal = self.goal_holes(seat);
+        goal.iter().all(|&hole| self.piece(hole).is_some()) && goal.iter().any(|&hole| self.piece(hole) == Some(seat))
+    }
+
+    /// Returns the total number of steps that the pieces of the player in the given seat are from
+    /// the tip of their goal arm, which is smallest once the goal arm is full of their pieces
+    pub fn distance_left(&self, seat: Seat) -> usize {
+        let tip = arm_tip(goal_arm(self.home_arms[seat.index()]));
+        Self::holes().into_iter()
+            .filter(|&hole| self.piece(hole) == Some(seat))
+            .map(|hole| hole.distance(tip))
+            .sum()
+    }
+}
+
+/// Returns true if the given position is one of the holes of the star
+///
+/// The star is made of two overlapping triangles: one pointing up, with no coordinate above
+/// `STAR_RADIUS`, and one pointing down, with no coordinate below `-STAR_RADIUS`.
+fn is_hole(pos: HexPos) -> bool {
+    let (q, r, s) = (pos.q, pos.r, pos.s());
+    (q <= STAR_RADIUS && r <= STAR_RADIUS && s <= STAR_RADIUS) || (q >= -STAR_RADIUS && r >= -STAR_RADIUS && s >= -STAR_RADIUS)
+}
+
+/// Returns the arm of the star that the given hole is in, numbered clockwise from 0 at the top,
+/// or `None` if the hole is in the central hexagon
+fn arm_of(hole: HexPos) -> Option<usize> {
+    let (q, r, s) = (hole.q, hole.r, hole.s());
+    if r < -STAR_RADIUS {
+        Some(0)
+    } else if q > STAR_RADIUS {
+        Some(1)
+    } else if s < -STAR_RADIUS {
+        Some(2)
+    } else if r > STAR_RADIUS {
+        Some(3)
+    } else if q < -STAR_RADIUS {
+        Some(4)
+    } else if s > STAR_RADIUS {
+        Some(5)
+    } else {
+        None
+    }
+}
+
+/// Returns the holes of the given arm
+fn arm_holes(arm: usize) -> Vec<HexPos> {
+    ChineseCheckers::holes().into_iter().filter(|&hole| arm_of(hole) == Some(arm)).collect()
+}
+
+/// Returns the hole at the tip of the given arm, which is the furthest from the center
+fn arm_tip(arm: usize) -> HexPos {
+    let center = HexPos {q: 0, r: 0};
+    arm_holes(arm).into_iter().max_by_key(|hole| hole.distance(center)).expect("bug: every arm has holes")
+}
+
+/// Returns the arm opposite to the given arm
+fn goal_arm(arm: usize) -> usize {
+    (arm + 3) % 6
+}
+
+/// Returns the holes in the given row, from left to right
+fn row_holes(r: isize) -> impl Iterator<Item=HexPos> {
+    (-ARM_TIP..=ARM_TIP).map(move |q| HexPos {q, r}).filter(|&hole| is_hole(hole))
+}
+
+/// Returns the name of the given hole (see `ChineseCheckers::hole_name`)
+fn hole_name(hole: HexPos) -> String {
+    let row = (b'A' + (hole.r + ARM_TIP) as u8) as char;
+    let number = row_holes(hole.r).take_while(|other| other.q < hole.q).count() + 1;
+    format!("{}{}", row, number)
+}
+
+/// Parses the name of a hole (see `ChineseCheckers::hole_name`), ignoring the case of the row
+fn parse_hole(name: &str) -> Option<HexPos> {
+    let row = name.bytes().next().filter(u8::is_ascii_alphabetic)?.to_ascii_uppercase();
+    let r = (row - b'A') as isize - ARM_TIP;
+    if r > ARM_TIP {
+        return None;
+    }
+    let number: usize = name[1..].parse().ok()?;
+    row_holes(r).nth(number.checked_sub(1)?)
+}
+
+/// Returns how the pieces of the player in the given seat are drawn: the first two players are
+/// drawn as their `Piece`, and the others as a circle of their own color, or as a letter when
+/// colors are disabled
+fn seat_glyph(seat: Seat) -> String {
+    if let Some(piece) = seat.piece() {
+        return piece.to_string();
+    }
+
+    let (color, letter) = SEAT_GLYPHS[seat.index() - 2];
+    if !Paint::is_enabled() {
+        return letter.to_string();
+    }
+    Style::new(color).paint("\u{25CF}").to_string()
+}
+
+impl Game for ChineseCheckers {
+    type Move = ChineseCheckersMove;
+
+    fn player_count(&self) -> usize {
+        self.home_arms.len()
+    }
+
+    fn current_seat(&self) -> Seat {
+        self.current_seat
+    }
+
+    fn legal_moves(&self) -> Vec<ChineseCheckersMove> {
+        self.valid_moves()
+    }
+
+    fn play_move(&mut self, pmove: ChineseCheckersMove) {
+        self.make_move(pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.winner.is_some()
+    }
+
+    fn winning_seat(&self) -> Option<Seat> {
+        self.winner
+    }
+
+    fn seat_name(&self, seat: Seat) -> String {
+        seat_glyph(seat)
+    }
+
+    fn is_pass(&self, pmove: &ChineseCheckersMove) -> bool {
+        pmove.path.is_empty()
+    }
+}
+
+/// Draws the star with the letter of each row on both sides, and a dot for each empty hole
+impl fmt::Display for ChineseCheckers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for r in -ARM_TIP..=ARM_TIP {
+            let row = (b'A' + (r + ARM_TIP) as u8) as char;
+            write!(f, "{} ", row)?;
+
+            // Each hole is drawn one column further right than the hole to its lower left, which
+            // lines up the rows like the holes of the star
+            let mut column = -3 * STAR_RADIUS;
+            for hole in row_holes(r) {
+                let hole_column = 2 * hole.q + r;
+                while column < hole_column {
+                    write!(f, " ")?;
+                    column += 1;
+                }
+                match self.piece(hole) {
+                    Some(seat) => write!(f, "{}", seat_glyph(seat))?,
+                    None => write!(f, "\u{00B7}")?,
+                }
+                column += 1;
+            }
+            while column <= 3 * STAR_RADIUS {
+                write!(f, " ")?;
+                column += 1;
+            }
+
+            writeln!(f, " {}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// An AI that plays Chinese Checkers by looking a number of its own moves ahead
+///
+/// Other players' moves are not searched, since `negamax` only works with two players and the
+/// game is mostly a race anyway. Positions are scored by how far the AI's pieces still have to
+/// go (see `ChineseCheckers::distance_left`), with ties broken in favor of moving the pieces that
+/// are furthest behind.
+#[derive(Debug, Clone, Copy)]
+pub struct ChineseCheckersAi {
+    /// How many of its own moves in a row to look ahead
+    depth: usize,
+}
+
+impl Default for ChineseCheckersAi {
+    fn default() -> Self {
+        Self {depth: 1}
+    }
+}
+
+impl ChineseCheckersAi {
+    /// Creates an AI that looks the given number of its own moves ahead
+    pub fn new(depth: usize) -> Self {
+        Self {depth}
+    }
+
+    /// Returns how many of its own moves in a row the AI looks ahead
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the move the AI would play for the current player, or `None` if the game is over
+    pub fn choose_move(&self, game: &ChineseCheckers) -> Option<ChineseCheckersMove> {
+        let seat = game.current_seat;
+        game.valid_moves().into_iter()
+            .map(|pmove| {
+                let mut child = game.clone();
+                child.make_move(pmove.clone());
+                // The piece that moved was this far from the tip of the goal arm before moving
+                let behind = pmove.from().map_or(0, |from| from.distance(arm_tip(goal_arm(game.home_arms[seat.index()]))));
+                let score = self.best_distance(&mut child, seat, self.depth.max(1) - 1);
+                (pmove, score, behind)
+            })
+            // The smallest distance left wins, then the piece furthest behind, then the first move
+            .min_by(|(_, score1, behind1), (_, score2, behind2)| score1.cmp(score2).then(behind2.cmp(behind1)))
+            .map(|(pmove, _, _)| pmove)
+    }
+
+    /// Returns the smallest distance left for the player in the given seat after the given number
+    /// of their own moves in a row
+    fn best_distance(&self, game: &mut ChineseCheckers, seat: Seat, depth: usize) -> usize {
+        if game.winner == Some(seat) {
+            return 0;
+        }
+        let distance = game.distance_left(seat);
+        if depth == 0 || game.winner.is_some() {
+            return distance;
+        }
+
+        // The other players sit out so that the AI can move again
+        game.current_seat = seat;
+        game.valid_moves().into_iter()
+            .map(|pmove| {
+                let mut child = game.clone();
+                child.make_move(pmove);
+                self.best_distance(&mut child, seat, depth - 1)
+            })
+            .min()
+            .unwrap_or(distance)
+    }
+}
+
+/// Plays the move chosen by `ChineseCheckersAi::choose_move`
+impl Player<ChineseCheckers> for ChineseCheckersAi {
+    fn choose_move(&mut self, game: &ChineseCheckers) -> ChineseCheckersMove {
+        ChineseCheckersAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Piece;
+
+    fn pmove(s: &str) -> ChineseCheckersMove {
+        s.parse().unwrap()
+    }
+
+    fn hole(name: &str) -> HexPos {
+        parse_hole(name).unwrap()
+    }
+
+    /// Creates a game for the given number of players with only the given pieces on the board, in
+    /// the order of their seats
+    fn setup(players: usize, pieces: &[&[&str]]) -> ChineseCheckers {
+        let mut game = ChineseCheckers::new(players);
+        game.holes = [[None; BOARD_WIDTH]; BOARD_WIDTH];
+        for (index, holes) in pieces.iter().enumerate() {
+            for name in holes.iter() {
+                game.place(hole(name), Some(Seat(index)));
+            }
+        }
+        game
+    }
+
+    #[test]
+    fn star_and_arms() {
+        assert_eq!(ChineseCheckers::holes().len(), 121);
+        for arm in 0..6 {
+            assert_eq!(arm_holes(arm).len(), CHINESE_CHECKERS_PIECES);
+        }
+        assert_eq!(arm_tip(0), hole("A1"));
+        assert_eq!(arm_tip(3), hole("Q1"));
+
+        for &name in &["A1", "E1", "E13", "I5", "M13", "Q1"] {
+            assert_eq!(hole_name(hole(name)), name);
+        }
+        assert_eq!(parse_hole("B3"), None);
+        assert_eq!(parse_hole("R1"), None);
+        assert_eq!(parse_hole("E0"), None);
+    }
+
+    #[test]
+    fn players_start_in_opposite_arms() {
+        for players in 2..=6 {
+            let game = ChineseCheckers::new(players);
+            assert_eq!(game.player_count(), players);
+            for index in 0..players {
+                let seat = Seat(index);
+                assert!(game.home_holes(seat).iter().all(|&hole| game.piece(hole) == Some(seat)));
+                assert_eq!(game.pieces_in_goal(seat), 0);
+            }
+        }
+
+        // With three players, every goal arm starts empty
+        let game = ChineseCheckers::new(3);
+        assert!((0..3).all(|index| game.goal_holes(Seat(index)).iter().all(|&hole| game.piece(hole).is_none())));
+    }
+
+    #[test]
+    fn opening_moves() {
+        let game = ChineseCheckers::new(2);
+        // Each piece in the front row of the arm can step into two holes, and the pieces behind
+        // them can hop into the same holes
+        assert!(game.is_valid_move(&pmove("N1-M5")));
+        assert!(game.is_valid_move(&pmove("O1-M5")));
+        assert!(!game.is_valid_move(&pmove("Q1-P1")));
+        assert!(!game.is_valid_move(&pmove("A1-B1")));
+        assert_eq!(game.valid_moves().len(), 8 + 6);
+    }
+
+    #[test]
+    fn hops_chain() {
+        let mut game = setup(2, &[&["I5"], &["H5", "F5", "F6"]]);
+        let moves: Vec<_> = game.valid_moves().iter().map(ChineseCheckersMove::to_string).collect();
+        for hops in &["I5-G5", "I5-G5-E5", "I5-G5-E7"] {
+            assert!(moves.contains(&hops.to_string()), "{:?}", moves);
+        }
+        // Hopping back over H5 would land where the piece started
+        assert!(!moves.iter().any(|pmove| pmove.ends_with("-I5")));
+
+        // The holes along the way can be left out
+        game.make_move(pmove("I5-E7"));
+        assert_eq!(game.last_move().unwrap().to_string(), "I5-G5-E7");
+        assert_eq!(game.piece(hole("E7")), Some(Seat(0)));
+        assert_eq!(game.piece(hole("I5")), None);
+        assert_eq!(game.current_seat(), Seat(1));
+    }
+
+    #[test]
+    fn filling_goal_arm_wins() {
+        let mut game = setup(2, &[&["A1", "B1", "B2", "C1", "C2", "C3", "D1", "D2", "D3", "E8"], &["Q1"]]);
+        game.make_move(pmove("E8-D4"));
+        assert!(game.is_game_over());
+        assert_eq!(game.winning_seat(), Some(Seat(0)));
+        assert_eq!(Game::winner(&game), Some(Piece::X));
+        assert_eq!(game.pieces_in_goal(Seat(0)), CHINESE_CHECKERS_PIECES);
+        assert!(game.legal_moves().is_empty());
+
+        // A piece of another player left in the goal arm does not stop the arm from being filled
+        let mut game = setup(2, &[&["A1", "B1", "B2", "C1", "C2", "C3", "D1", "D2", "E8"], &["D3", "Q1"]]);
+        game.make_move(pmove("E8-D4"));
+        assert_eq!(game.winning_seat(), Some(Seat(0)));
+    }
+
+    #[test]
+    fn moves_rotate_among_players() {
+        let mut game = ChineseCheckers::new(3);
+        for index in 0..4 {
+            assert_eq!(game.current_seat(), Seat(index % 3));
+            let pmove = game.valid_moves()[0].clone();
+            game.make_move(pmove);
+        }
+    }
+
+    #[test]
+    fn ai_finishes_game() {
+        let mut game = ChineseCheckers::new(2);
+        let ai = ChineseCheckersAi::new(1);
+        for _ in 0..300 {
+            if game.is_game_over() {
+                break;
+            }
+            let pmove = ai.choose_move(&game).unwrap();
+            game.make_move(pmove);
+        }
+        assert!(game.is_game_over());
+    }
+}
@@ -0,0 +1,256 @@
+use std::time::{Duration, Instant};
+
+use crate::Piece;
+
+/// The amount of time each player is given to make their moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    /// The main time each player starts with
+    pub initial: Duration,
+    /// The time added to a player's main time after each of their moves
+    pub increment: Duration,
+    /// Once the main time has run out, each move must be made within this period of time. If
+    /// this is `None`, running out of main time immediately loses the game.
+    pub byo_yomi: Option<Duration>,
+}
+
+/// Tracks the time remaining for each player
+#[derive(Debug, Clone)]
+pub struct Clock {
+    control: TimeControl,
+    /// The main time remaining for each player, indexed with `piece_index`
+    remaining: [Duration; 2],
+    /// The player whose clock is currently running and when it was started
+    running: Option<(Piece, Instant)>,
+    /// The player that ran out of time (if any)
+    flagged: Option<Piece>,
+}
+
+impl Clock {
+    /// Creates a new clock where each player starts with the initial time of the time control
+    ///
+    /// The clock does not start running until `start` is called.
+    pub fn new(control: TimeControl) -> Self {
+        Self {
+            control,
+            remaining: [control.initial; 2],
+            running: None,
+            flagged: None,
+        }
+    }
+
+    /// Creates a stopped clock that continues from the given main time remaining for each player
+    /// (X first), e.g. from a saved game, with the given player having already run out of time
+    pub fn resume(control: TimeControl, remaining: [Duration; 2], flagged: Option<Piece>) -> Self {
+        Self {
+            control,
+            remaining,
+            running: None,
+            flagged,
+        }
+    }
+
+    /// Returns the time control this clock was created with
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// Returns the main time remaining for the given player, including the time spent so far on
+    /// the current turn if their clock is running
+    pub fn remaining(&self, piece: Piece) -> Duration {
+        self.remaining[piece_index(piece)].saturating_sub(self.running_elapsed(piece))
+    }
+
+    /// Returns the total amount of time the given player can still take before they run out of
+    /// time on the current turn (main time plus byo-yomi)
+    pub fn time_left(&self, piece: Piece) -> Duration {
+        let main_time = self.remaining[piece_index(piece)];
+        let total = main_time + self.control.byo_yomi.unwrap_or_default();
+        total.saturating_sub(self.running_elapsed(piece))
+    }
+
+    /// Returns true if the given player is currently in byo-yomi (has used up their main time)
+    pub fn in_byo_yomi(&self, piece: Piece) -> bool {
+        self.control.byo_yomi.is_some() && self.remaining(piece) == Duration::from_secs(0)
+    }
+
+    /// Returns a reasonable amount of time for the given player to spend on their next move
+    ///
+    /// This spreads the remaining main time over the rest of the game and always leaves a safety
+    /// margin so the player does not run out of time.
+    pub fn move_budget(&self, piece: Piece) -> Duration {
+        // Roughly the number of moves each player makes in a full game
+        const EXPECTED_MOVES_LEFT: u32 = 20;
+
+        let main_time = self.remaining(piece);
+        let budget = if main_time > Duration::from_secs(0) {
+            main_time / EXPECTED_MOVES_LEFT + self.control.increment
+        } else {
+            self.control.byo_yomi.unwrap_or_default()
+        };
+
+        // Never plan to use more than 90% of the time that is actually left
+        budget.min(self.time_left(piece) * 9 / 10)
+    }
+
+    /// Returns the main time remaining for the given player formatted as minutes and seconds
+    /// (e.g. "4:05"), noting when they are in byo-yomi
+    pub fn format_remaining(&self, piece: Piece) -> String {
+        let secs = self.remaining(piece).as_secs();
+        let time = format!("{}:{:02}", secs / 60, secs % 60);
+        if self.in_byo_yomi(piece) {
+            format!("{} (byo-yomi)", time)
+        } else {
+            time
+        }
+    }
+
+    /// Returns the player whose clock is currently running (if any)
+    pub fn running(&self) -> Option<Piece> {
+        self.running.map(|(piece, _)| piece)
+    }
+
+    /// Returns the player that ran out of time (if any)
+    pub fn flagged(&self) -> Option<Piece> {
+        self.flagged
+    }
+
+    /// Returns true if the given player has run out of time, including on the turn that is
+    /// currently in progress
+    pub fn is_out_of_time(&self, piece: Piece) -> bool {
+        self.flagged == Some(piece) || (self.running() == Some(piece) && self.time_left(piece) == Duration::from_secs(0))
+    }
+
+    /// Starts running the clock of the given player, stopping any clock that was already running
+    pub fn start(&mut self, piece: Piece) {
+        self.stop();
+        self.running = Some((piece, Instant::now()));
+    }
+
+    /// Stops the clock that is currently running (if any) and charges the elapsed time to that
+    /// player. Returns the player and the time they spent on their turn.
+    ///
+    /// If the player took longer than the time they had left, they are flagged as having run out
+    /// of time.
+    pub fn stop(&mut self) -> Option<(Piece, Duration)> {
+        let (piece, started) = self.running.take()?;
+        let elapsed = started.elapsed();
+        self.charge(piece, elapsed);
+
+        Some((piece, elapsed))
+    }
+
+    /// Charges the given amount of thinking time to the given player
+    pub(crate) fn charge(&mut self, piece: Piece, elapsed: Duration) {
+        let remaining = &mut self.remaining[piece_index(piece)];
+        if elapsed <= *remaining {
+            *remaining = *remaining - elapsed + self.control.increment;
+            return;
+        }
+
+        // Main time has run out, so the move must fit within a single byo-yomi period
+        let overtime = elapsed - *remaining;
+        *remaining = Duration::from_secs(0);
+        match self.control.byo_yomi {
+            Some(period) if overtime <= period => {},
+            _ => self.flagged = Some(piece),
+        }
+    }
+
+    /// Returns the time that has elapsed on the given player's clock during the current turn, or
+    /// zero if their clock is not running
+    fn running_elapsed(&self, piece: Piece) -> Duration {
+        match self.running {
+            Some((running, started)) if running == piece => started.elapsed(),
+            _ => Duration::from_secs(0),
+        }
+    }
+}
+
+fn piece_index(piece: Piece) -> usize {
+    match piece {
+        Piece::X => 0,
+        Piece::O => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn control(initial: u64, increment: u64, byo_yomi: Option<u64>) -> TimeControl {
+        TimeControl {
+            initial: secs(initial),
+            increment: secs(increment),
+            byo_yomi: byo_yomi.map(secs),
+        }
+    }
+
+    #[test]
+    fn increment_added_after_each_move() {
+        let mut clock = Clock::new(control(60, 5, None));
+        clock.charge(Piece::X, secs(10));
+        assert_eq!(clock.remaining(Piece::X), secs(55));
+        assert_eq!(clock.remaining(Piece::O), secs(60));
+        assert_eq!(clock.format_remaining(Piece::X), "0:55");
+        assert_eq!(clock.flagged(), None);
+    }
+
+    #[test]
+    fn move_spills_into_byo_yomi() {
+        let mut clock = Clock::new(control(60, 5, Some(30)));
+        clock.charge(Piece::O, secs(80));
+        assert_eq!(clock.remaining(Piece::O), secs(0));
+        assert!(clock.in_byo_yomi(Piece::O));
+        assert!(!clock.in_byo_yomi(Piece::X));
+        assert_eq!(clock.format_remaining(Piece::O), "0:00 (byo-yomi)");
+        assert_eq!(clock.time_left(Piece::O), secs(30));
+        assert_eq!(clock.flagged(), None);
+
+        // Every move in byo-yomi gets a fresh period
+        clock.charge(Piece::O, secs(30));
+        assert_eq!(clock.flagged(), None);
+        assert_eq!(clock.time_left(Piece::O), secs(30));
+    }
+
+    #[test]
+    fn overrun_flags_the_player() {
+        let mut clock = Clock::new(control(60, 0, Some(30)));
+        clock.charge(Piece::X, secs(91));
+        assert_eq!(clock.flagged(), Some(Piece::X));
+        assert!(clock.is_out_of_time(Piece::X));
+        assert!(!clock.is_out_of_time(Piece::O));
+
+        // Without byo-yomi, running out of main time loses immediately
+        let mut clock = Clock::new(control(60, 0, None));
+        clock.charge(Piece::O, secs(61));
+        assert_eq!(clock.flagged(), Some(Piece::O));
+    }
+
+    #[test]
+    fn move_budget_leaves_a_margin() {
+        let controls = [
+            control(600, 0, None),
+            control(60, 10, None),
+            control(5, 30, None),
+            control(0, 0, Some(10)),
+            control(20, 5, Some(10)),
+        ];
+        for &control in &controls {
+            let mut clock = Clock::new(control);
+            for elapsed in [0, 1, 3, 10, 40] {
+                clock.charge(Piece::X, secs(elapsed));
+                if clock.flagged().is_some() {
+                    break;
+                }
+                let budget = clock.move_budget(Piece::X);
+                assert!(budget > secs(0), "{:?}", control);
+                assert!(budget <= clock.time_left(Piece::X) * 9 / 10, "{:?}", control);
+            }
+        }
+    }
+}
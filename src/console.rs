@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+#[cfg(feature = "readline")]
+use std::io::IsTerminal;
+use std::sync::{Mutex, OnceLock, mpsc::{self, Sender, Receiver, RecvTimeoutError}};
+use std::thread;
+use std::time::Instant;
+
+/// Where the prompts for the player's input are written and where that input is read from
+pub trait Console {
+    /// Writes the given text (e.g. a message to the player), making sure it is shown right away
+    fn write(&mut self, text: &str) -> io::Result<()>;
+
+    /// Writes the given prompt and reads a line, returning it with its newline, or an empty
+    /// string (without even a newline) at the end of input
+    ///
+    /// The completions may be suggested to the player as they type. Giving up at the deadline (if
+    /// any) returns an error of kind `io::ErrorKind::TimedOut`.
+    fn read_line(
+        &mut self,
+        prompt: &str,
+        completions: &[String],
+        deadline: Option<Instant>,
+    ) -> io::Result<String>;
+}
+
+impl<C: Console + ?Sized> Console for &mut C {
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        (**self).write(text)
+    }
+
+    fn read_line(
+        &mut self,
+        prompt: &str,
+        completions: &[String],
+        deadline: Option<Instant>,
+    ) -> io::Result<String> {
+        (**self).read_line(prompt, completions, deadline)
+    }
+}
+
+/// Prompts on stdout and reads from stdin
+///
+/// With the `readline` feature, the line can be edited and earlier lines brought back with the
+/// arrow keys when stdin is a terminal (see `read_line_with_completions`). Ctrl-C is then treated
+/// as the end of input.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdConsole;
+
+impl Console for StdConsole {
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        stdout.write_all(text.as_bytes())?;
+        // Need to flush because output is line buffered
+        stdout.flush()
+    }
+
+    fn read_line(
+        &mut self,
+        prompt: &str,
+        completions: &[String],
+        deadline: Option<Instant>,
+    ) -> io::Result<String> {
+        #[cfg(feature = "readline")]
+        if io::stdin().is_terminal() {
+            let line = crate::read_line_with_completions(prompt, completions, deadline)?;
+            return Ok(line.map(|line| line + "\n").unwrap_or_default());
+        }
+        #[cfg(not(feature = "readline"))]
+        let _ = completions;
+
+        self.write(prompt)?;
+
+        if let Some(deadline) = deadline {
+            return read_line_until(deadline);
+        }
+
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+
+        Ok(line)
+    }
+}
+
+/// Reads lines from stdin on a background thread when asked to, so that waiting for a line can
+/// give up at a deadline while the read itself keeps blocking
+struct StdinReader {
+    requests: Sender<()>,
+    lines: Receiver<io::Result<String>>,
+    /// True if a line was asked for but not received yet (because waiting for it gave up), in
+    /// which case the next read waits for that line instead of asking for another one
+    pending: bool,
+}
+
+/// Reads a line from stdin, giving up with an error of kind `io::ErrorKind::TimedOut` once the
+/// given deadline has passed
+fn read_line_until(deadline: Instant) -> io::Result<String> {
+    static READER: OnceLock<Mutex<StdinReader>> = OnceLock::new();
+    let reader = READER.get_or_init(|| {
+        let (requests, requested) = mpsc::channel::<()>();
+        let (sender, lines) = mpsc::channel();
+        // Lines are only read when asked for, so that nothing typed for other prompts (e.g. in
+        // the terminal's raw mode) is taken by this thread
+        thread::spawn(move || {
+            for () in requested {
+                let mut line = String::new();
+                let result = io::stdin().read_line(&mut line).map(|_| line);
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+        Mutex::new(StdinReader {requests, lines, pending: false})
+    });
+    let mut reader = reader.lock().unwrap_or_else(|err| err.into_inner());
+
+    if !reader.pending {
+        reader.requests.send(()).expect("bug: the stdin reader thread should never stop");
+        reader.pending = true;
+    }
+
+    let timeout = deadline.saturating_duration_since(Instant::now());
+    match reader.lines.recv_timeout(timeout) {
+        Ok(result) => {
+            reader.pending = false;
+            result
+        },
+        Err(RecvTimeoutError::Timeout) => Err(io::Error::new(io::ErrorKind::TimedOut, "ran out of time")),
+        Err(RecvTimeoutError::Disconnected) => unreachable!("bug: the stdin reader thread should never stop"),
+    }
+}
+
+/// A console that reads from lines given up front and keeps everything written to it, so that
+/// prompting can be done without a terminal (e.g. in tests)
+#[derive(Debug, Default, Clone)]
+pub struct MemoryConsole {
+    /// The lines left to read, each with its newline (if it had one)
+    input: VecDeque<String>,
+    /// Everything written so far, including the prompts
+    output: String,
+}
+
+impl MemoryConsole {
+    /// Creates a console that reads the lines of the given text, followed by the end of input
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: input.split_inclusive('\n').map(str::to_string).collect(),
+            output: String::new(),
+        }
+    }
+
+    /// Returns everything written to the console so far, including the prompts
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+}
+
+impl Console for MemoryConsole {
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        self.output.push_str(text);
+        Ok(())
+    }
+
+    /// Reads the next line without ever timing out, since all of the input is already there
+    fn read_line(
+        &mut self,
+        prompt: &str,
+        _completions: &[String],
+        _deadline: Option<Instant>,
+    ) -> io::Result<String> {
+        self.output.push_str(prompt);
+        Ok(self.input.pop_front().unwrap_or_default())
+    }
+}
@@ -0,0 +1,173 @@
+use std::io::{self, Write, IsTerminal};
+use std::time::Instant;
+
+use crossterm::{cursor, execute, queue};
+use crossterm::style::Print;
+use crossterm::event::{
+    self,
+    Event,
+    KeyCode,
+    KeyEventKind,
+    KeyModifiers,
+    MouseButton,
+    MouseEventKind,
+    EnableMouseCapture,
+    DisableMouseCapture,
+};
+use crossterm::terminal::{self, Clear, ClearType};
+
+use crate::{Reversi, MoveInput, ParseError, DisplayOptions, write_game, tile_at};
+use crate::player::deadline;
+
+/// Clears the terminal and moves the cursor to its top left corner, so that the board can be drawn
+/// in the same place every turn instead of printing a new copy below the last one
+pub fn clear_screen() -> io::Result<()> {
+    execute!(io::stdout(), cursor::MoveTo(0, 0), Clear(ClearType::All))
+}
+
+/// Clears the terminal and prints the game at the top of it (see `clear_screen`)
+pub fn redraw_game(game: &Reversi, options: DisplayOptions) -> io::Result<()> {
+    let mut frame = Vec::new();
+    write_game(&mut frame, game, options)?;
+    let frame = String::from_utf8(frame).expect("bug: the board should always be valid UTF-8");
+    execute!(io::stdout(), cursor::MoveTo(0, 0), Clear(ClearType::All), Print(frame))
+}
+
+/// What the player did with the cursor
+enum CursorInput {
+    /// The player chose a move or asked for a hint
+    Input(MoveInput),
+    /// The player asked to type their move instead
+    Type,
+    /// The player pressed Ctrl-C or Ctrl-D
+    Quit,
+}
+
+/// Draws the board below the current line and lets the player choose their move by moving a
+/// cursor over it with the arrow keys (or `h`, `j`, `k`, `l`) and pressing Enter or Space, or by
+/// clicking on a tile
+///
+/// Pressing `?` asks for a hint and `f` asks to flip the board. Returns `None` if the player would rather type their move (by
+/// pressing `t` or Esc) or if stdin is not a terminal, so the caller should fall back to
+/// `prompt_move_or_hint`. Ctrl-C and Ctrl-D are treated like reaching the end of input. If the
+/// game is timed, `ParseError::TimedOut` is returned once the player's clock runs out.
+pub fn prompt_move_with_cursor(
+    game: &Reversi,
+    options: DisplayOptions,
+) -> Result<Option<MoveInput>, ParseError> {
+    if !io::stdin().is_terminal() || game.valid_moves().is_empty() {
+        return Ok(None);
+    }
+
+    println!("Click a tile or move with the arrow keys and press Enter to play (`?` for a hint, `t` to type your move)");
+    terminal::enable_raw_mode().map_err(ParseError::IOError)?;
+    let result = execute!(io::stdout(), EnableMouseCapture)
+        .and_then(|_| select_move(game, options, deadline(game)));
+    // The terminal has to be restored even if choosing the move failed
+    let restored = execute!(io::stdout(), DisableMouseCapture)
+        .and_then(|_| terminal::disable_raw_mode());
+
+    match result.and_then(|input| restored.map(|_| input))? {
+        CursorInput::Input(input) => Ok(Some(input)),
+        CursorInput::Type => Ok(None),
+        CursorInput::Quit => Err(ParseError::EndOfInput),
+    }
+}
+
+/// Redraws the board with the cursor after every key press until the player is done with it, or
+/// until the given deadline (if any) has passed
+fn select_move(game: &Reversi, options: DisplayOptions, deadline: Option<Instant>) -> io::Result<CursorInput> {
+    let mut stdout = io::stdout().lock();
+    let grid = game.grid();
+    let bounds = (grid.rows().len(), grid.row_len());
+
+    let mut pos = game.valid_moves()[0];
+    let mut message = String::new();
+    let mut lines = 0;
+    let input = loop {
+        if lines > 0 {
+            // Goes back to the first line of the board so that it is drawn over
+            queue!(stdout, cursor::MoveUp(lines), cursor::MoveToColumn(0))?;
+        }
+
+        let mut frame = Vec::new();
+        write_game(&mut frame, game, DisplayOptions {cursor: Some(pos), ..options})?;
+        // Raw mode does not go back to the start of the line after a newline
+        let frame = String::from_utf8(frame)
+            .expect("bug: the board should always be valid UTF-8")
+            .replace('\n', "\r\n");
+        lines = frame.matches('\n').count() as u16;
+        write!(stdout, "{}", frame)?;
+        queue!(stdout, Clear(ClearType::CurrentLine))?;
+        write!(stdout, "{}", message)?;
+        stdout.flush()?;
+        // The board may have scrolled up since it was last drawn, so this is checked every time
+        let (_, message_line) = cursor::position()?;
+        let top = message_line.saturating_sub(lines);
+
+        if let Some(deadline) = deadline {
+            if !event::poll(deadline.saturating_duration_since(Instant::now()))? {
+                write!(stdout, "\r\n")?;
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "ran out of time"));
+            }
+        }
+        let key = match event::read()? {
+            // Only presses are handled so that keys are not handled twice on some platforms
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                let clicked = mouse.row.checked_sub(top)
+                    .and_then(|y| tile_at(grid, &options, mouse.column as usize, y as usize));
+                match clicked {
+                    Some(clicked) if game.valid_moves().contains(&clicked) => {
+                        break CursorInput::Input(MoveInput::Move(clicked));
+                    },
+                    Some(clicked) => {
+                        pos = clicked;
+                        message = format!("Invalid move: `{}`. Your move must flip at least one tile.", pos);
+                    },
+                    None => {},
+                }
+                continue;
+            },
+
+            _ => continue,
+        };
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('c') | KeyCode::Char('d') => break CursorInput::Quit,
+                _ => continue,
+            }
+        }
+
+        let offset = match key.code {
+            KeyCode::Left | KeyCode::Char('h') => (0, -1),
+            KeyCode::Down | KeyCode::Char('j') => (1, 0),
+            KeyCode::Up | KeyCode::Char('k') => (-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => (0, 1),
+
+            KeyCode::Enter | KeyCode::Char(' ') if game.valid_moves().contains(&pos) => {
+                break CursorInput::Input(MoveInput::Move(pos));
+            },
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                message = format!("Invalid move: `{}`. Your move must flip at least one tile.", pos);
+                continue;
+            },
+
+            KeyCode::Char('?') => break CursorInput::Input(MoveInput::Hint),
+            KeyCode::Char('f') => break CursorInput::Input(MoveInput::Flip),
+            KeyCode::Char('t') | KeyCode::Esc => break CursorInput::Type,
+            _ => continue,
+        };
+
+        // The arrow keys move the cursor the way it looks like it should on a flipped board
+        let (drow, dcol) = if options.flip_board { (-offset.0, -offset.1) } else { offset };
+        message.clear();
+        pos = pos.offset(drow, dcol, bounds).unwrap_or(pos);
+    };
+
+    // Leaves the line with the message so that whatever is printed next starts on a new line
+    write!(stdout, "\r\n")?;
+    stdout.flush()?;
+    Ok(input)
+}
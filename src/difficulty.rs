@@ -0,0 +1,388 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rand::{Rng, rngs::ThreadRng, thread_rng};
+use thiserror::Error;
+
+use crate::{Reversi, Move, OpeningBook, CancelToken, Evaluator, WeightedEvaluator, Tablebase, Wdl, TABLEBASE_WIN_SCORE, SearchResult, SearchStats, SearchOptions, search_with_options};
+
+/// How strong the AI plays
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AiDifficulty {
+    /// Only looks one move ahead and makes lots of mistakes
+    Beginner,
+    /// Looks a couple of moves ahead
+    Easy,
+    /// Searches as deep as it can in a short amount of time
+    #[default]
+    Medium,
+    /// Thinks for longer and makes fewer mistakes
+    Hard,
+    /// Thinks for the longest and never makes a mistake on purpose
+    Expert,
+}
+
+impl AiDifficulty {
+    /// All of the difficulty levels, from easiest to hardest
+    pub const ALL: [AiDifficulty; 5] = [
+        AiDifficulty::Beginner,
+        AiDifficulty::Easy,
+        AiDifficulty::Medium,
+        AiDifficulty::Hard,
+        AiDifficulty::Expert,
+    ];
+
+    /// Returns the amount of time the AI thinks about each move in untimed games
+    pub fn think_time(self) -> Duration {
+        use AiDifficulty::*;
+        match self {
+            Beginner => Duration::from_millis(100),
+            Easy => Duration::from_millis(200),
+            Medium => Duration::from_millis(500),
+            Hard => Duration::from_secs(1),
+            Expert => Duration::from_secs(2),
+        }
+    }
+
+    /// Returns how deep the AI is allowed to search as the board fills up, or `None` if the search
+    /// is only limited by time
+    pub fn depth_schedule(self) -> Option<DepthSchedule> {
+        use AiDifficulty::*;
+        match self {
+            Beginner => Some(DepthSchedule {base: 1, extend_below: 16, empties_per_ply: 8}),
+            Easy => Some(DepthSchedule {base: 2, extend_below: 24, empties_per_ply: 6}),
+            Medium | Hard | Expert => None,
+        }
+    }
+
+    /// Returns the deepest the AI is allowed to search in a position with the given number of
+    /// empty tiles, or `None` if the search is only limited by time
+    pub fn max_depth(self, empties: usize) -> Option<usize> {
+        self.depth_schedule().map(|schedule| schedule.depth(empties))
+    }
+
+    /// Returns the largest amount of random noise added to the score of each move the AI considers
+    /// playing, unless overridden with `AiPlayer::with_noise`
+    pub fn noise(self) -> i32 {
+        use AiDifficulty::*;
+        match self {
+            Beginner => 200,
+            Easy | Medium => 100,
+            Hard => 20,
+            Expert => 0,
+        }
+    }
+
+    /// Returns true if the AI plays moves from the opening book and solves the endgame exactly
+    pub fn uses_book(self) -> bool {
+        use AiDifficulty::*;
+        match self {
+            Beginner | Easy => false,
+            Medium | Hard | Expert => true,
+        }
+    }
+
+    fn search_options(self) -> SearchOptions<'static> {
+        SearchOptions {
+            max_depth: None,
+            noise: self.noise(),
+            use_book: self.uses_book(),
+            use_endgame_solver: self.uses_book(),
+            probcut: false,
+            cancel: None,
+        }
+    }
+}
+
+/// Controls how the depth limit of the search grows as the board fills up
+///
+/// Fewer moves are available near the end of the game, so the search can look further ahead in
+/// the same amount of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthSchedule {
+    /// The depth searched while the board has more than `extend_below` empty tiles
+    pub base: usize,
+    /// The number of empty tiles below which the depth starts to increase
+    pub extend_below: usize,
+    /// The number of tiles that have to be filled for the depth to increase by another move
+    pub empties_per_ply: usize,
+}
+
+impl DepthSchedule {
+    /// A schedule that always searches to the given depth
+    pub fn constant(depth: usize) -> Self {
+        Self {base: depth, extend_below: 0, empties_per_ply: 1}
+    }
+
+    /// Returns the depth to search a position with the given number of empty tiles to
+    pub fn depth(&self, empties: usize) -> usize {
+        if empties >= self.extend_below {
+            return self.base;
+        }
+
+        let filled = self.extend_below - empties;
+        self.base + 1 + filled / self.empties_per_ply.max(1)
+    }
+}
+
+impl fmt::Display for AiDifficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use AiDifficulty::*;
+        match self {
+            Beginner => write!(f, "beginner"),
+            Easy => write!(f, "easy"),
+            Medium => write!(f, "medium"),
+            Hard => write!(f, "hard"),
+            Expert => write!(f, "expert"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Unknown difficulty: `{0}`. Expected `beginner`, `easy`, `medium`, `hard` or `expert`")]
+pub struct ParseAiDifficultyError(pub String);
+
+impl FromStr for AiDifficulty {
+    type Err = ParseAiDifficultyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AiDifficulty::ALL.iter()
+            .copied()
+            .find(|difficulty| difficulty.to_string() == s)
+            .ok_or_else(|| ParseAiDifficultyError(s.to_string()))
+    }
+}
+
+/// An AI that plays at a given difficulty level
+///
+/// By default, the AI uses the thread-local random number generator. Use `with_rng` with a seeded
+/// generator (e.g. `StdRng::seed_from_u64`) to make its choices reproducible. Searches that are
+/// cut off by the time budget may still differ between runs.
+#[derive(Clone)]
+pub struct AiPlayer<R = ThreadRng> {
+    difficulty: AiDifficulty,
+    book: OpeningBook,
+    rng: R,
+    evaluator: Arc<dyn Evaluator + Send + Sync>,
+    /// The largest amount of random noise added to the score of each move at the root
+    noise: i32,
+    /// How deep the search is allowed to go, or `None` if it is only limited by time
+    depth_schedule: Option<DepthSchedule>,
+    /// If true, the search prunes positions that a shallow search predicts are not worth
+    /// searching (Multi-ProbCut)
+    probcut: bool,
+    /// Solved positions for playing perfectly on a small board, shared between clones of this AI
+    /// so that positions solved by one are available to all of them
+    tablebase: Option<Arc<Mutex<Tablebase>>>,
+}
+
+impl<R: fmt::Debug> fmt::Debug for AiPlayer<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AiPlayer")
+            .field("difficulty", &self.difficulty)
+            .field("book", &self.book)
+            .field("rng", &self.rng)
+            .field("noise", &self.noise)
+            .field("depth_schedule", &self.depth_schedule)
+            .field("probcut", &self.probcut)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AiPlayer {
+    /// Creates an AI that plays at the given difficulty, using the builtin opening book
+    pub fn new(difficulty: AiDifficulty) -> Self {
+        Self::with_book(difficulty, OpeningBook::builtin().clone())
+    }
+
+    /// Creates an AI that plays at the given difficulty, using the given opening book
+    ///
+    /// The book is only used at difficulties where `AiDifficulty::uses_book` is true.
+    pub fn with_book(difficulty: AiDifficulty, book: OpeningBook) -> Self {
+        AiPlayer::with_rng(difficulty, book, thread_rng())
+    }
+}
+
+impl<R: Rng> AiPlayer<R> {
+    /// Creates an AI that plays at the given difficulty, using the given opening book and taking
+    /// all of its randomness from the given random number generator
+    pub fn with_rng(difficulty: AiDifficulty, book: OpeningBook, rng: R) -> Self {
+        let evaluator = Arc::new(WeightedEvaluator::default());
+        Self {
+            difficulty,
+            book,
+            rng,
+            evaluator,
+            noise: difficulty.noise(),
+            depth_schedule: difficulty.depth_schedule(),
+            probcut: false,
+            tablebase: None,
+        }
+    }
+
+    /// Replaces the evaluator used to score positions (`WeightedEvaluator::default()` by default)
+    pub fn with_evaluator<E>(mut self, evaluator: E) -> Self
+        where E: Evaluator + Send + Sync + 'static,
+    {
+        self.evaluator = Arc::new(evaluator);
+        self
+    }
+
+    /// Sets the largest amount of random noise added to the score of each move the AI considers
+    /// playing (`AiDifficulty::noise` by default)
+    ///
+    /// Use 0 for deterministic play or small values to get some variety between games without
+    /// weakening the AI much.
+    pub fn with_noise(mut self, noise: i32) -> Self {
+        self.noise = noise.max(0);
+        self
+    }
+
+    /// Returns the largest amount of random noise added to the score of each move the AI
+    /// considers playing
+    pub fn noise(&self) -> i32 {
+        self.noise
+    }
+
+    /// Sets how deep the AI is allowed to search as the board fills up, or `None` to only limit
+    /// the search by time (`AiDifficulty::depth_schedule` by default)
+    pub fn with_depth_schedule(mut self, schedule: Option<DepthSchedule>) -> Self {
+        self.depth_schedule = schedule;
+        self
+    }
+
+    /// Enables or disables Multi-ProbCut (disabled by default)
+    ///
+    /// ProbCut lets the search look deeper by skipping positions that a shallow search predicts
+    /// are not worth searching, at the risk of occasionally missing the best move.
+    pub fn with_probcut(mut self, probcut: bool) -> Self {
+        self.probcut = probcut;
+        self
+    }
+
+    /// Plays perfectly using the given tablebase whenever the game is played on the board it is
+    /// for and the position is in the tablebase (or can be solved quickly)
+    pub fn with_tablebase(mut self, tablebase: Tablebase) -> Self {
+        self.tablebase = Some(Arc::new(Mutex::new(tablebase)));
+        self
+    }
+
+    /// Returns the tablebase given to `with_tablebase`, shared with every clone of this AI and
+    /// holding every position they have solved so far
+    pub fn tablebase(&self) -> Option<&Arc<Mutex<Tablebase>>> {
+        self.tablebase.as_ref()
+    }
+
+    /// Returns the difficulty this AI plays at
+    pub fn difficulty(&self) -> AiDifficulty {
+        self.difficulty
+    }
+
+    /// Returns the amount of time this AI thinks about each move in untimed games
+    pub fn think_time(&self) -> Duration {
+        self.difficulty.think_time()
+    }
+
+    /// Returns the amount of time this AI should spend on its next move in the given game
+    ///
+    /// In timed games, the clock decides how long to think for. Otherwise, this is the same as
+    /// `think_time`.
+    pub fn move_budget(&self, game: &Reversi) -> Duration {
+        match game.clock() {
+            Some(clock) => clock.move_budget(game.current_player()),
+            None => self.think_time(),
+        }
+    }
+
+    /// Returns a move for the current player of the given game, searching for at most the given
+    /// amount of time
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the current player has no valid moves
+    pub fn compute_move(&mut self, game: &Reversi, budget: Duration) -> SearchResult {
+        if let Some(result) = self.tablebase_move(game) {
+            return result;
+        }
+
+        let options = self.search_options(game);
+        search_with_options(&mut self.rng, &*self.evaluator, game, game.valid_moves(), budget, &self.book, &options)
+    }
+
+    /// Same as `compute_move`, but stops searching as soon as the given token is canceled,
+    /// returning the best move found so far
+    ///
+    /// A move is always returned, even if the token is canceled before the search starts.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the current player has no valid moves
+    pub fn compute_move_cancelable(
+        &mut self,
+        game: &Reversi,
+        budget: Duration,
+        cancel: &CancelToken,
+    ) -> SearchResult {
+        if let Some(result) = self.tablebase_move(game) {
+            return result;
+        }
+
+        let options = SearchOptions {cancel: Some(cancel), ..self.search_options(game)};
+        search_with_options(&mut self.rng, &*self.evaluator, game, game.valid_moves(), budget, &self.book, &options)
+    }
+
+    /// Returns the perfect move from the tablebase, or `None` if there is no tablebase or it
+    /// does not have an answer for the given game
+    fn tablebase_move(&self, game: &Reversi) -> Option<SearchResult> {
+        let start = Instant::now();
+        let mut tablebase = self.tablebase.as_ref()?.lock().expect("bug: tablebase lock poisoned");
+        let (pos, wdl) = match tablebase.best_move(game)? {
+            (Move::Place(pos), wdl) => (pos, wdl),
+            _ => return None,
+        };
+
+        let score = match wdl {
+            Wdl::Win => TABLEBASE_WIN_SCORE,
+            Wdl::Draw => 0,
+            Wdl::Loss => -TABLEBASE_WIN_SCORE,
+        };
+        Some(SearchResult {
+            best_move: pos,
+            score,
+            depth: game.grid().open_count(),
+            pv: vec![Move::Place(pos)],
+            nodes: 0,
+            elapsed: start.elapsed(),
+            stats: SearchStats::default(),
+        })
+    }
+
+    /// Returns the options for searching the given game with this AI's settings
+    fn search_options(&self, game: &Reversi) -> SearchOptions<'static> {
+        let empties = game.grid().open_count();
+        SearchOptions {
+            max_depth: self.depth_schedule.map(|schedule| schedule.depth(empties)),
+            noise: self.noise,
+            probcut: self.probcut,
+            ..self.difficulty.search_options()
+        }
+    }
+}
+
+/// Returns the move an AI of the given strength would play for the current player, along with
+/// its score from the perspective of that player, without playing it
+///
+/// The suggestion is always the AI's best move, without any random noise. If the current player
+/// has no valid moves, `Move::Pass` is returned with the current evaluation of the position.
+pub fn suggest_move(game: &Reversi, strength: AiDifficulty) -> (Move, i32) {
+    if game.valid_moves().is_empty() {
+        let score = WeightedEvaluator::default().evaluate(game, game.current_player());
+        return (Move::Pass, score);
+    }
+
+    let mut ai = AiPlayer::new(strength).with_noise(0);
+    let result = ai.compute_move(game, strength.think_time());
+    (Move::Place(result.best_move), result.score)
+}
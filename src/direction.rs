@@ -0,0 +1,58 @@
+/// One of the 8 directions that can be walked from any tile on the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// All 8 directions, starting at `North` and going clockwise
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// Returns the change in (row, col) produced by taking a single step in this direction
+    ///
+    /// Rows increase going south and columns increase going east.
+    pub fn delta(self) -> (isize, isize) {
+        use Direction::*;
+        match self {
+            North => (-1, 0),
+            NorthEast => (-1, 1),
+            East => (0, 1),
+            SouthEast => (1, 1),
+            South => (1, 0),
+            SouthWest => (1, -1),
+            West => (0, -1),
+            NorthWest => (-1, -1),
+        }
+    }
+
+    /// Returns the direction pointing the opposite way
+    pub fn opposite(self) -> Self {
+        use Direction::*;
+        match self {
+            North => South,
+            NorthEast => SouthWest,
+            East => West,
+            SouthEast => NorthWest,
+            South => North,
+            SouthWest => NorthEast,
+            West => East,
+            NorthWest => SouthEast,
+        }
+    }
+}
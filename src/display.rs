@@ -1,51 +1,668 @@
-use std::fmt::Display;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::cell::Cell;
+use std::env;
+use std::rc::Rc;
+use std::io::{self, Write, IsTerminal};
+use std::thread;
+use std::time::Duration;
 
-use yansi::Paint;
+use thiserror::Error;
+use yansi::{Color, Paint, Style};
 
-use crate::{Reversi, TilePos, Piece};
+use crate::{Reversi, Grid, TilePos, Piece, Move, Theme, move_label};
 
-pub fn print_game(game: &Reversi, valid_moves: &[TilePos]) {
-    let grid = game.grid();
+/// The evaluation that fills 73% of the evaluation bar. Larger values make the bar move less.
+const EVAL_BAR_SCALE: f64 = 200.0;
 
-    print_cell(" ");
-    for col_i in 0..grid.row_len() {
-        print_cell(Paint::new(&format!("{}", (b'A' + col_i as u8) as char)));
+/// The colors of the moves in the heatmap, from the worst move to the best move
+const HEATMAP_COLORS: [Color; 5] = [
+    Color::Fixed(196),
+    Color::Fixed(208),
+    Color::Fixed(226),
+    Color::Fixed(148),
+    Color::Fixed(46),
+];
+
+/// How big each tile of the board is drawn
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RenderSize {
+    /// A single character for each tile and no lines between the tiles, for small terminals and
+    /// logs
+    Compact,
+    /// A single line for each tile, with lines between the tiles
+    #[default]
+    Normal,
+    /// Three lines for each tile, for projectors and streams
+    Large,
+}
+
+impl RenderSize {
+    /// Returns the number of characters taken up by each tile, including the line drawn after it
+    pub fn cell_width(self) -> usize {
+        match self {
+            RenderSize::Compact => 1,
+            RenderSize::Normal => 4,
+            RenderSize::Large => 8,
+        }
     }
-    println!();
 
-    print_row_sep(grid.row_len());
+    /// Returns the number of lines taken up by each row of tiles, including the line drawn after it
+    pub fn cell_height(self) -> usize {
+        match self {
+            RenderSize::Compact => 1,
+            RenderSize::Normal => 2,
+            RenderSize::Large => 4,
+        }
+    }
 
-    for (row, row_tiles) in grid.rows().iter().enumerate() {
-        print_cell(Paint::new(&format!("{}", row+1)));
-        for (col, &tile) in row_tiles.iter().enumerate() {
-            print_tile(tile, valid_moves.contains(&TilePos {row, col}));
+    /// Returns the number of characters taken up by the row labels
+    fn label_width(self) -> usize {
+        match self {
+            RenderSize::Compact => 2,
+            RenderSize::Normal | RenderSize::Large => self.cell_width(),
         }
-        println!();
+    }
 
-        print_row_sep(grid.row_len());
+    /// Returns the number of lines taken up by the column labels
+    fn header_height(self) -> usize {
+        match self {
+            RenderSize::Compact => 1,
+            // The labels are followed by a line
+            RenderSize::Normal | RenderSize::Large => 2,
+        }
     }
 }
 
-fn print_tile(tile: Option<Piece>, is_valid_move: bool) {
-    match tile {
-        Some(piece) => print_cell(piece),
+impl fmt::Display for RenderSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderSize::Compact => write!(f, "compact"),
+            RenderSize::Normal => write!(f, "normal"),
+            RenderSize::Large => write!(f, "large"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Unknown render size: `{0}`. Expected `compact`, `normal` or `large`")]
+pub struct ParseRenderSizeError(pub String);
+
+impl FromStr for RenderSize {
+    type Err = ParseRenderSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "compact" => Ok(RenderSize::Compact),
+            "normal" => Ok(RenderSize::Normal),
+            "large" => Ok(RenderSize::Large),
+            _ => Err(ParseRenderSizeError(s.to_string())),
+        }
+    }
+}
+
+/// Options for how `write_game` renders the board
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayOptions<'a> {
+    /// The tiles to mark as valid moves
+    pub valid_moves: &'a [TilePos],
+    /// If true, the valid moves are labeled with the digits or letters that can be entered to
+    /// choose them (see `move_label`) instead of the theme's valid move glyph
+    pub numbered_moves: bool,
+    /// A tile to highlight as the suggested move (e.g. a move suggested by the AI)
+    pub hint: Option<TilePos>,
+    /// The tile of the piece placed by the last move and the tiles that it flipped, to show them
+    /// in a different style (see `Reversi::last_move_flips`)
+    pub last_move: Option<(TilePos, &'a [TilePos])>,
+    /// A glyph to draw the pieces flipped by `last_move` with instead of their usual glyph, used
+    /// to show them partway through being flipped
+    pub flip_glyph: Option<&'static str>,
+    /// The tile the player is choosing their move with (e.g. with the arrow keys), shown in a
+    /// different style depending on whether it is a valid move
+    pub cursor: Option<TilePos>,
+    /// If true, the board is drawn from the perspective of `Piece::O` sitting across the table:
+    /// the rows are reversed and the columns are mirrored
+    pub flip_board: bool,
+    /// How big each tile is drawn
+    pub size: RenderSize,
+    /// If true, `write_game` writes a bar under the board showing how the discs are split between
+    /// the players
+    pub score_bar: bool,
+    /// The evaluation of the position from the perspective of `Piece::X` (e.g. the score of the
+    /// AI's last search), which `write_game` shows as a bar under the board if given
+    pub evaluation: Option<i32>,
+    /// The score of each legal move (e.g. from `analyze`), used to color the valid moves from
+    /// red for the worst move to green for the best move. Without colors, only the best move is
+    /// marked (with the theme's hint glyph).
+    pub heatmap: &'a [(Move, i32)],
+    /// The glyphs, colors and grid characters to draw the board with
+    pub theme: Theme,
+    /// If false, no terminal colors or styles are written, which is useful when the output is
+    /// not going to a terminal. Defaults to `color_enabled`.
+    pub color: bool,
+}
+
+impl<'a> Default for DisplayOptions<'a> {
+    fn default() -> Self {
+        Self {
+            valid_moves: &[],
+            numbered_moves: false,
+            hint: None,
+            last_move: None,
+            flip_glyph: None,
+            cursor: None,
+            flip_board: false,
+            size: RenderSize::default(),
+            score_bar: false,
+            evaluation: None,
+            heatmap: &[],
+            theme: Theme::default(),
+            color: color_enabled(),
+        }
+    }
+}
+
+/// Whether the board is drawn from the perspective of `Piece::O` (see `DisplayOptions::flip_board`)
+///
+/// Clones share the same orientation, so flipping the board anywhere (e.g. with the `flip`
+/// command of `HumanPrompt`) flips it everywhere it is drawn.
+#[derive(Debug, Default, Clone)]
+pub struct Orientation(Rc<Cell<bool>>);
+
+impl Orientation {
+    /// Creates an orientation that starts flipped if `flipped` is true
+    pub fn new(flipped: bool) -> Self {
+        Orientation(Rc::new(Cell::new(flipped)))
+    }
+
+    /// Returns true if the board is drawn from the perspective of `Piece::O`
+    pub fn is_flipped(&self) -> bool {
+        self.0.get()
+    }
+
+    /// Switches the board to the other player's perspective
+    pub fn toggle(&self) {
+        self.0.set(!self.0.get());
+    }
+}
+
+/// Returns true if colors should be written to stdout
+///
+/// Colors are disabled when stdout is not a terminal (e.g. when the output is piped to a file),
+/// when the `NO_COLOR` environment variable is set to anything other than an empty string (see
+/// <https://no-color.org>), or after `Paint::disable` is called.
+pub fn color_enabled() -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    Paint::is_enabled() && !no_color && io::stdout().is_terminal()
+}
 
-        None if is_valid_move => print_cell(Paint::yellow("\u{25CB}")),
-        None => print_cell(" "),
+/// Disables every color and style written with `yansi` (including the pieces written with their
+/// `Display` impl) if `color_enabled` returns false
+pub fn disable_color_if_unsupported() {
+    if !color_enabled() {
+        Paint::disable();
     }
 }
 
-fn print_cell<T: Display>(value: T) {
-    print!(" {} \u{2502}", value);
+/// Prints the game, marking the given valid moves and highlighting the piece placed by the last
+/// move and the pieces that it flipped (if given)
+pub fn print_game(game: &Reversi, valid_moves: &[TilePos], last_move: Option<(TilePos, &[TilePos])>) {
+    print_game_with(game, DisplayOptions {valid_moves, last_move, ..DisplayOptions::default()});
 }
 
-fn print_row_sep(cols: usize) {
-    const CELL_SIZE: usize = 4;
+/// Prints the game with the given move highlighted (e.g. a move suggested by the AI)
+pub fn print_game_with_hint(game: &Reversi, valid_moves: &[TilePos], hint: TilePos) {
+    print_game_with(game, DisplayOptions {valid_moves, hint: Some(hint), ..DisplayOptions::default()});
+}
+
+/// Prints the game with the given options (e.g. to draw it with a different theme)
+pub fn print_game_with(game: &Reversi, options: DisplayOptions) {
+    write_game(io::stdout().lock(), game, options).expect("failed printing to stdout");
+}
+
+/// Prints the game with the pieces flipped by `options.last_move` turning over, drawing the
+/// board once for each of the theme's `flip_frames` and then once more as it is after the move
+///
+/// Each frame is shown for `frame_time` before it is drawn over by the next one.
+pub fn animate_game(game: &Reversi, options: DisplayOptions, frame_time: Duration) {
+    let mut stdout = io::stdout().lock();
+    for &glyph in options.theme.flip_frames {
+        let mut frame = Vec::new();
+        write_game(&mut frame, game, DisplayOptions {flip_glyph: Some(glyph), ..options})
+            .expect("bug: writing to a Vec should not fail");
+        let lines = frame.iter().filter(|&&byte| byte == b'\n').count();
+
+        stdout.write_all(&frame).expect("failed printing to stdout");
+        stdout.flush().expect("failed printing to stdout");
+        thread::sleep(frame_time);
+
+        // Moves the cursor back up to the first line of the board so the next frame draws over it
+        write!(stdout, "\x1b[{}A", lines).expect("failed printing to stdout");
+    }
+
+    write_game(&mut stdout, game, options).expect("failed printing to stdout");
+}
+
+/// Writes the board of the given game, with its row and column labels, to the given writer
+///
+/// The score and evaluation bars are written under the board if they are enabled in the options.
+pub fn write_game<W: Write>(mut w: W, game: &Reversi, options: DisplayOptions) -> io::Result<()> {
+    write_grid(&mut w, game.grid(), options)?;
+
+    // The bars line up with the tiles of the board
+    let size = options.size;
+    let indent = size.label_width();
+    let (first, last) = game.playable_bounds();
+    let width = (last.col - first.col + 1) * size.cell_width();
+
+    if options.score_bar {
+        let (x_score, o_score) = game.scores();
+        let total = x_score + o_score;
+        let x_ratio = if total == 0 { 0.5 } else { x_score as f64 / total as f64 };
+        write!(w, "{:indent$}", "", indent = indent)?;
+        write_bar(&mut w, &options, x_ratio, width)?;
+        writeln!(w, " {} {} - {} {}", options.theme.x_piece.0, x_score, o_score, options.theme.o_piece.0)?;
+    }
+
+    if let Some(score) = options.evaluation {
+        // Maps the score to the chance of winning, the same way the evaluation is tuned
+        let x_ratio = 1.0 / (1.0 + (-score as f64 / EVAL_BAR_SCALE).exp());
+        write!(w, "{:indent$}", "", indent = indent)?;
+        write_bar(&mut w, &options, x_ratio, width)?;
+        writeln!(w, " eval {:+}", score)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a bar of the given width, with the given fraction of it in the colors of `Piece::X` and
+/// the rest in the colors of `Piece::O`
+fn write_bar<W: Write>(w: &mut W, options: &DisplayOptions, x_ratio: f64, width: usize) -> io::Result<()> {
+    let x_width = ((x_ratio.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    let x_part = "\u{2588}".repeat(x_width);
+    let o_part = "\u{2588}".repeat(width - x_width);
+
+    let x_color = options.theme.x_piece.1.fg_color();
+    let o_color = options.theme.o_piece.1.fg_color();
+    if options.color && x_color != o_color {
+        write!(w, "{}{}", Paint::new(x_part).fg(x_color), Paint::new(o_part).fg(o_color))
+    } else {
+        // Without colors the two parts of the bar can only be told apart by their characters
+        write!(w, "{}{}", "X".repeat(x_width), "O".repeat(width - x_width))
+    }
+}
+
+/// Writes the given grid, with its row and column labels, to the given writer
+pub fn write_grid<W: Write>(mut w: W, grid: &Grid, options: DisplayOptions) -> io::Result<()> {
+    // The order the rows and columns are drawn in
+    let order = |first: usize, last: usize| -> Vec<usize> {
+        if options.flip_board { (first..=last).rev().collect() } else { (first..=last).collect() }
+    };
+    // Only the playable part of the grid is drawn, so a smaller board isn't surrounded by blocked
+    // tiles. The labels still name the tiles of the full grid.
+    let (first, last) = grid.playable_bounds();
+    let rows = order(first.row, last.row);
+    let cols = order(first.col, last.col);
+    let column_label = |col: usize| (b'A' + col as u8) as char;
+
+    if options.size == RenderSize::Compact {
+        write!(w, "  ")?;
+        for &col in &cols {
+            write!(w, "{}", column_label(col))?;
+        }
+        writeln!(w)?;
+
+        for &row in &rows {
+            write!(w, "{} ", row + 1)?;
+            for &col in &cols {
+                let pos = TilePos {row, col};
+                write_tile(&mut w, &options, grid.rows()[row][col], grid.is_blocked(pos), pos)?;
+            }
+            writeln!(w)?;
+        }
+
+        return Ok(());
+    }
+
+    write_cell(&mut w, &options, " ")?;
+    for &col in &cols {
+        write_cell(&mut w, &options, column_label(col))?;
+    }
+    writeln!(w)?;
+
+    write_row_sep(&mut w, &options, cols.len())?;
+
+    // Large tiles have an empty line above and below the line with the piece
+    let padding_lines = (options.size.cell_height() - 2) / 2;
+    for &row in &rows {
+        write_padding_lines(&mut w, &options, padding_lines, cols.len())?;
 
+        write_cell(&mut w, &options, row + 1)?;
+        for &col in &cols {
+            let pos = TilePos {row, col};
+            write_tile(&mut w, &options, grid.rows()[row][col], grid.is_blocked(pos), pos)?;
+        }
+        writeln!(w)?;
+
+        write_padding_lines(&mut w, &options, padding_lines, cols.len())?;
+        write_row_sep(&mut w, &options, cols.len())?;
+    }
+
+    Ok(())
+}
+
+/// Returns the tile drawn at the given character column and line of a board written by
+/// `write_game` or `write_grid`, counting from the top left corner of the board, or `None` if
+/// there is no tile there (e.g. on a label or on the lines between tiles)
+///
+/// This can be used to find which tile was clicked with the mouse. The options should be the same
+/// as the ones the board was written with.
+pub fn tile_at(grid: &Grid, options: &DisplayOptions, x: usize, y: usize) -> Option<TilePos> {
+    let size = options.size;
+    // Skips past the row labels on the left and the column labels at the top
+    if x < size.label_width() || y < size.header_height() {
+        return None;
+    }
+    let (x, y) = (x - size.label_width(), y - size.header_height());
+
+    let (cell_width, cell_height) = (size.cell_width(), size.cell_height());
+    // The last character of each cell and the last line of each row are the lines between tiles
+    let on_line = x % cell_width == cell_width - 1 || y % cell_height == cell_height - 1;
+    if size != RenderSize::Compact && on_line {
+        return None;
+    }
+
+    // Only the playable part of the grid is drawn (see `write_grid`)
+    let (row, col) = (y / cell_height, x / cell_width);
+    let (first, last) = grid.playable_bounds();
+    if row > last.row - first.row || col > last.col - first.col {
+        return None;
+    }
+
+    Some(if options.flip_board {
+        TilePos {row: last.row - row, col: last.col - col}
+    } else {
+        TilePos {row: first.row + row, col: first.col + col}
+    })
+}
+
+/// Formats the board without colors, marking the valid moves of the current player. The
+/// alternate flag (`{:#}`) formats the board with plain ASCII characters.
+impl fmt::Display for Reversi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let options = DisplayOptions {
+            valid_moves: self.valid_moves(),
+            ..plain_options(f)
+        };
+        format_grid(f, self.grid(), options)
+    }
+}
+
+/// Formats the board without colors. The alternate flag (`{:#}`) formats the board with plain
+/// ASCII characters.
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_grid(f, self, plain_options(f))
+    }
+}
+
+impl Reversi {
+    /// Returns the board as it is formatted by `Display`, with plain ASCII characters if `ascii`
+    /// is true
+    pub fn to_board_string(&self, ascii: bool) -> String {
+        if ascii {
+            format!("{:#}", self)
+        } else {
+            self.to_string()
+        }
+    }
+}
+
+/// Returns the options for formatting a board without colors
+fn plain_options(f: &fmt::Formatter) -> DisplayOptions<'static> {
+    // The pieces of the high contrast theme can be told apart by their shape alone
+    let theme = if f.alternate() { Theme::minimal() } else { Theme::high_contrast() };
+    DisplayOptions {theme, color: false, ..DisplayOptions::default()}
+}
+
+fn format_grid(f: &mut fmt::Formatter, grid: &Grid, options: DisplayOptions) -> fmt::Result {
+    let mut out = Vec::new();
+    write_grid(&mut out, grid, options).map_err(|_| fmt::Error)?;
+    f.write_str(&String::from_utf8(out).expect("bug: the board should always be valid UTF-8"))
+}
+
+fn write_tile<W: Write>(
+    w: &mut W,
+    options: &DisplayOptions,
+    tile: Option<Piece>,
+    is_blocked: bool,
+    pos: TilePos,
+) -> io::Result<()> {
+    let theme = &options.theme;
+    let (glyph, style) = match tile {
+        Some(piece) => theme.piece(piece),
+
+        None if is_blocked => theme.blocked,
+        None if options.hint == Some(pos) => theme.hint,
+        None => match options.valid_moves.iter().position(|&valid_move| valid_move == pos) {
+            Some(index) if options.numbered_moves => match move_label(index) {
+                Some(label) => (label, theme.valid_move.1),
+                None => theme.valid_move,
+            },
+            Some(_) => theme.valid_move,
+            None => (" ", Style::default()),
+        },
+    };
+    let (glyph, style) = match heat(options, pos) {
+        Some(_) if tile.is_some() => (glyph, style),
+        Some(level) if options.color => (glyph, Style::new(HEATMAP_COLORS[level]).bold()),
+        // The labels of numbered moves are still needed to choose the move
+        Some(level) if level == HEATMAP_COLORS.len() - 1 && !options.numbered_moves => theme.hint,
+        _ => (glyph, style),
+    };
+
+    let glyph = match (options.last_move, options.flip_glyph) {
+        (Some((_, flips)), Some(flip_glyph)) if flips.contains(&pos) => flip_glyph,
+        _ => glyph,
+    };
+
+    let (background, brackets) = match options.last_move {
+        _ if options.cursor == Some(pos) && options.valid_moves.contains(&pos) => (theme.cursor, ('>', '<')),
+        _ if options.cursor == Some(pos) => (theme.invalid_cursor, ('!', '!')),
+        Some((placed, _)) if placed == pos => (theme.last_move, ('[', ']')),
+        Some((_, flips)) if flips.contains(&pos) => (theme.flipped, ('(', ')')),
+        _ => (Color::Unset, (' ', ' ')),
+    };
+
+    if !options.color {
+        return write_marked_cell(w, options, brackets, glyph);
+    }
+    if background == Color::Unset {
+        return write_marked_cell(w, options, brackets, style.paint(glyph));
+    }
+
+    write_cell(w, options, style.bg(background).paint(glyph))
+}
+
+/// Returns the index in `HEATMAP_COLORS` of the move to the given tile, or `None` if the tile is
+/// not in the heatmap
+///
+/// Colors are relative to the best and worst moves, so the best move is always green.
+fn heat(options: &DisplayOptions, pos: TilePos) -> Option<usize> {
+    let score = options.heatmap.iter().find_map(|&(pmove, score)| match pmove {
+        Move::Place(move_pos) if move_pos == pos => Some(score),
+        _ => None,
+    })?;
+
+    let last = HEATMAP_COLORS.len() - 1;
+    let scores = options.heatmap.iter().map(|&(_, score)| score);
+    let (worst, best) = (scores.clone().min()?, scores.max()?);
+    if best == worst {
+        return Some(last);
+    }
+
+    let fraction = (score as f64 - worst as f64) / (best as f64 - worst as f64);
+    Some((fraction * last as f64).round() as usize)
+}
+
+fn write_cell<W: Write, T: Display>(w: &mut W, options: &DisplayOptions, value: T) -> io::Result<()> {
+    write_marked_cell(w, options, (' ', ' '), value)
+}
+
+/// Writes a cell with the given characters on either side of its value instead of spaces
+fn write_marked_cell<W: Write, T: Display>(
+    w: &mut W,
+    options: &DisplayOptions,
+    (open, close): (char, char),
+    value: T,
+) -> io::Result<()> {
+    match options.size {
+        // There is no room for the markers
+        RenderSize::Compact => write!(w, "{}", value),
+        RenderSize::Normal => write!(w, "{}{}{}{}", open, value, close, options.theme.vertical),
+        RenderSize::Large => write!(w, "  {}{}{}  {}", open, value, close, options.theme.vertical),
+    }
+}
+
+/// Writes the given number of lines with only the lines between the tiles on them
+fn write_padding_lines<W: Write>(
+    w: &mut W,
+    options: &DisplayOptions,
+    lines: usize,
+    cols: usize,
+) -> io::Result<()> {
+    for _ in 0..lines {
+        for _ in 0..=cols {
+            write_cell(w, options, " ")?;
+        }
+        writeln!(w)?;
+    }
+
+    Ok(())
+}
+
+fn write_row_sep<W: Write>(w: &mut W, options: &DisplayOptions, cols: usize) -> io::Result<()> {
     for _ in 0..=cols {
-        for _ in 0..CELL_SIZE {
-            print!("\u{2500}");
+        for _ in 0..options.size.cell_width() {
+            write!(w, "{}", options.theme.horizontal)?;
         }
     }
-    println!();
+    writeln!(w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Variant;
+
+    #[test]
+    fn small_boards_are_cropped() {
+        // Only the playable 4x4 center of the grid is drawn, labelled with its tiles on the full grid
+        let game = Reversi::with_board_size(Variant::Standard, 4);
+        // Starts with a newline since a line continuation would drop the leading spaces
+        assert_eq!(format!("\n{}", game), "
+   │ C │ D │ E │ F │
+────────────────────
+ 3 │   │   │ · │   │
+────────────────────
+ 4 │   │ ● │ ○ │ · │
+────────────────────
+ 5 │ · │ ○ │ ● │   │
+────────────────────
+ 6 │   │ · │   │   │
+────────────────────
+");
+
+        let options = DisplayOptions {size: RenderSize::Compact, ..DisplayOptions::default()};
+        assert_eq!(tile_at(game.grid(), &options, 2, 1), Some(TilePos {row: 2, col: 2}));
+        assert_eq!(tile_at(game.grid(), &options, 5, 4), Some(TilePos {row: 5, col: 5}));
+        assert_eq!(tile_at(game.grid(), &options, 6, 1), None);
+        assert_eq!(tile_at(game.grid(), &options, 2, 5), None);
+
+        let flipped = DisplayOptions {flip_board: true, ..options};
+        assert_eq!(tile_at(game.grid(), &flipped, 2, 1), Some(TilePos {row: 5, col: 5}));
+    }
+
+    #[test]
+    fn write_game_to_a_buffer() {
+        let game = Reversi::new(Variant::Standard);
+        let options = DisplayOptions {
+            size: RenderSize::Compact,
+            score_bar: true,
+            evaluation: Some(0),
+            theme: Theme::minimal(),
+            color: false,
+            ..DisplayOptions::default()
+        };
+        let mut out = Vec::new();
+        write_game(&mut out, &game, options).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(!text.contains('\x1b'), "no terminal escapes without color: {:?}", text);
+        assert_eq!(text, [
+            "  ABCDEFGH",
+            "1         ",
+            "2         ",
+            "3         ",
+            "4    XO   ",
+            "5    OX   ",
+            "6         ",
+            "7         ",
+            "8         ",
+            "  XXXXOOOO X 2 - 2 O",
+            "  XXXXOOOO eval +0",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn display_the_start_position() {
+        let game = Reversi::new(Variant::Standard);
+        // Starts with a newline since a line continuation would drop the leading spaces
+        assert_eq!(format!("\n{}", game), "
+   │ A │ B │ C │ D │ E │ F │ G │ H │
+────────────────────────────────────
+ 1 │   │   │   │   │   │   │   │   │
+────────────────────────────────────
+ 2 │   │   │   │   │   │   │   │   │
+────────────────────────────────────
+ 3 │   │   │   │   │ · │   │   │   │
+────────────────────────────────────
+ 4 │   │   │   │ ● │ ○ │ · │   │   │
+────────────────────────────────────
+ 5 │   │   │ · │ ○ │ ● │   │   │   │
+────────────────────────────────────
+ 6 │   │   │   │ · │   │   │   │   │
+────────────────────────────────────
+ 7 │   │   │   │   │   │   │   │   │
+────────────────────────────────────
+ 8 │   │   │   │   │   │   │   │   │
+────────────────────────────────────
+");
+
+        // The alternate flag only uses ASCII, and a grid has no valid moves to mark
+        assert_eq!(format!("\n{:#}", game.grid()), "
+   | A | B | C | D | E | F | G | H |
+------------------------------------
+ 1 |   |   |   |   |   |   |   |   |
+------------------------------------
+ 2 |   |   |   |   |   |   |   |   |
+------------------------------------
+ 3 |   |   |   |   |   |   |   |   |
+------------------------------------
+ 4 |   |   |   | X | O |   |   |   |
+------------------------------------
+ 5 |   |   |   | O | X |   |   |   |
+------------------------------------
+ 6 |   |   |   |   |   |   |   |   |
+------------------------------------
+ 7 |   |   |   |   |   |   |   |   |
+------------------------------------
+ 8 |   |   |   |   |   |   |   |   |
+------------------------------------
+");
+        assert!(format!("{:#}", game).is_ascii());
+        assert_eq!(game.to_board_string(true), format!("{:#}", game));
+        assert_eq!(game.to_board_string(false), game.to_string());
+    }
 }
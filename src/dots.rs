@@ -0,0 +1,513 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+use yansi::Paint;
+
+use crate::{Piece, TilePos, Game, Heuristic, negamax, Player};
+
+/// The number of boxes in each row and column of the board by default
+pub const DOTS_DEFAULT_SIZE: usize = 3;
+
+/// The largest number of boxes in each row or column of the board
+pub const DOTS_MAX_SIZE: usize = 9;
+
+/// A line between two neighboring dots
+///
+/// Dots are named like tiles (e.g. "A1" for the top-left dot), so every edge is given by the dot
+/// at its top or left end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DotsEdge {
+    /// The line from the given dot to the dot on its right
+    Horizontal(TilePos),
+    /// The line from the given dot to the dot below it
+    Vertical(TilePos),
+}
+
+impl DotsEdge {
+    /// Returns the dots at both ends of the edge, top or left first
+    pub fn dots(self) -> (TilePos, TilePos) {
+        match self {
+            DotsEdge::Horizontal(dot) => (dot, TilePos {row: dot.row, col: dot.col + 1}),
+            DotsEdge::Vertical(dot) => (dot, TilePos {row: dot.row + 1, col: dot.col}),
+        }
+    }
+}
+
+/// Writes the edge as its two dots, e.g. "A1-B1"
+impl fmt::Display for DotsEdge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (start, end) = self.dots();
+        write!(f, "{}-{}", start, end)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid edge: `{0}`. Expected two neighboring dots like `A1-B1`")]
+pub struct ParseDotsEdgeError(pub String);
+
+impl FromStr for DotsEdge {
+    type Err = ParseDotsEdgeError;
+
+    /// Parses the two dots at the ends of an edge in either order, separated by `-` or whitespace
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseDotsEdgeError(s.to_string());
+
+        let dots = s.split(|c: char| c == '-' || c.is_whitespace())
+            .filter(|dot| !dot.is_empty())
+            .map(|dot| dot.parse::<TilePos>().map_err(|_| err()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (a, b) = match dots[..] {
+            [a, b] => (a, b),
+            _ => return Err(err()),
+        };
+
+        if a.row == b.row && a.col.abs_diff(b.col) == 1 {
+            Ok(DotsEdge::Horizontal(TilePos {row: a.row, col: a.col.min(b.col)}))
+        } else if a.col == b.col && a.row.abs_diff(b.row) == 1 {
+            Ok(DotsEdge::Vertical(TilePos {row: a.row.min(b.row), col: a.col}))
+        } else {
+            Err(err())
+        }
+    }
+}
+
+/// A game of Dots and Boxes
+///
+/// Players take turns drawing a line between two neighboring dots. A player who draws the fourth
+/// side of a box claims it and must draw another line. The game ends once every line is drawn,
+/// and the player with the most boxes wins. `Piece::X` moves first.
+#[derive(Debug, Clone)]
+pub struct DotsAndBoxes {
+    /// The number of rows of boxes
+    rows: usize,
+    /// The number of columns of boxes
+    cols: usize,
+    /// Whether each horizontal edge is drawn, indexed by its left dot (row by row)
+    horizontal: Vec<bool>,
+    /// Whether each vertical edge is drawn, indexed by its top dot (row by row)
+    vertical: Vec<bool>,
+    /// The player that claimed each box, if any, indexed by its top-left dot (row by row)
+    boxes: Vec<Option<Piece>>,
+    /// The player whose turn it is currently
+    current_player: Piece,
+    /// The last edge drawn, if any
+    last_move: Option<DotsEdge>,
+}
+
+impl Default for DotsAndBoxes {
+    fn default() -> Self {
+        Self::new(DOTS_DEFAULT_SIZE, DOTS_DEFAULT_SIZE)
+    }
+}
+
+impl DotsAndBoxes {
+    /// Creates a new game with the given number of rows and columns of boxes
+    ///
+    /// # Panics
+    ///
+    /// Panics if either size is zero or larger than `DOTS_MAX_SIZE`.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        assert!((1..=DOTS_MAX_SIZE).contains(&rows) && (1..=DOTS_MAX_SIZE).contains(&cols),
+            "bug: unsupported board size {}x{}", rows, cols);
+
+        Self {
+            rows,
+            cols,
+            horizontal: vec![false; (rows + 1) * cols],
+            vertical: vec![false; rows * (cols + 1)],
+            boxes: vec![None; rows * cols],
+            current_player: Piece::X,
+            last_move: None,
+        }
+    }
+
+    /// Returns the number of rows of boxes
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns of boxes
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the player that claimed the box with the given top-left dot, if any
+    pub fn box_owner(&self, pos: TilePos) -> Option<Piece> {
+        self.box_index(pos).and_then(|index| self.boxes[index])
+    }
+
+    /// Returns the number of boxes the given player has claimed
+    pub fn score(&self, player: Piece) -> usize {
+        self.boxes.iter().filter(|&&owner| owner == Some(player)).count()
+    }
+
+    /// Returns the last edge drawn, if any
+    pub fn last_move(&self) -> Option<DotsEdge> {
+        self.last_move
+    }
+
+    /// Returns true if the given edge is drawn
+    pub fn has_edge(&self, edge: DotsEdge) -> bool {
+        self.edge_index(edge).is_some_and(|index| match edge {
+            DotsEdge::Horizontal(_) => self.horizontal[index],
+            DotsEdge::Vertical(_) => self.vertical[index],
+        })
+    }
+
+    /// Returns true if the given edge is on the board and not drawn yet
+    pub fn is_valid_move(&self, edge: DotsEdge) -> bool {
+        self.edge_index(edge).is_some() && !self.has_edge(edge)
+    }
+
+    /// Returns every edge that has not been drawn yet
+    pub fn valid_moves(&self) -> Vec<DotsEdge> {
+        let horizontal = (0..=self.rows)
+            .flat_map(|row| (0..self.cols).map(move |col| DotsEdge::Horizontal(TilePos {row, col})));
+        let vertical = (0..self.rows)
+            .flat_map(|row| (0..=self.cols).map(move |col| DotsEdge::Vertical(TilePos {row, col})));
+        horizontal.chain(vertical).filter(|&edge| !self.has_edge(edge)).collect()
+    }
+
+    /// Draws the given edge for the current player, claiming any boxes it completes
+    ///
+    /// The turn only passes to the other player if no box was completed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edge is not valid.
+    pub fn make_move(&mut self, edge: DotsEdge) {
+        assert!(self.is_valid_move(edge), "bug: attempt to draw the edge `{}`, which is not valid", edge);
+
+        let index = self.edge_index(edge).expect("bug: valid edges should be on the board");
+        match edge {
+            DotsEdge::Horizontal(_) => self.horizontal[index] = true,
+            DotsEdge::Vertical(_) => self.vertical[index] = true,
+        }
+
+        let mut completed = false;
+        for pos in self.edge_boxes(edge) {
+            if self.sides(pos) == 4 {
+                let index = self.box_index(pos).expect("bug: edge boxes should be on the board");
+                self.boxes[index] = Some(self.current_player);
+                completed = true;
+            }
+        }
+
+        self.last_move = Some(edge);
+        if !completed {
+            self.current_player = self.current_player.opposite();
+        }
+    }
+
+    /// Returns the four edges around the box with the given top-left dot
+    fn box_edges(pos: TilePos) -> [DotsEdge; 4] {
+        [
+            DotsEdge::Horizontal(pos),
+            DotsEdge::Horizontal(TilePos {row: pos.row + 1, col: pos.col}),
+            DotsEdge::Vertical(pos),
+            DotsEdge::Vertical(TilePos {row: pos.row, col: pos.col + 1}),
+        ]
+    }
+
+    /// Returns the number of drawn edges around the box with the given top-left dot
+    fn sides(&self, pos: TilePos) -> usize {
+        Self::box_edges(pos).iter().filter(|&&edge| self.has_edge(edge)).count()
+    }
+
+    /// Returns the (one or two) boxes on either side of the given edge
+    fn edge_boxes(&self, edge: DotsEdge) -> Vec<TilePos> {
+        let (dot, before) = match edge {
+            DotsEdge::Horizontal(dot) => (dot, dot.row.checked_sub(1).map(|row| TilePos {row, col: dot.col})),
+            DotsEdge::Vertical(dot) => (dot, dot.col.checked_sub(1).map(|col| TilePos {row: dot.row, col})),
+        };
+        before.into_iter().chain(Some(dot))
+            .filter(|&pos| self.box_index(pos).is_some())
+            .collect()
+    }
+
+    /// Returns the index of the box with the given top-left dot, or `None` if there is no such box
+    fn box_index(&self, pos: TilePos) -> Option<usize> {
+        if pos.row < self.rows && pos.col < self.cols {
+            Some(pos.row * self.cols + pos.col)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the index of the given edge in `horizontal` or `vertical`, or `None` if it is not on
+    /// the board
+    fn edge_index(&self, edge: DotsEdge) -> Option<usize> {
+        match edge {
+            DotsEdge::Horizontal(dot) if dot.row <= self.rows && dot.col < self.cols => {
+                Some(dot.row * self.cols + dot.col)
+            },
+            DotsEdge::Vertical(dot) if dot.row < self.rows && dot.col <= self.cols => {
+                Some(dot.row * (self.cols + 1) + dot.col)
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the top-left dots of every box
+    fn box_positions(&self) -> impl Iterator<Item=TilePos> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |row| (0..cols).map(move |col| TilePos {row, col}))
+    }
+}
+
+impl Game for DotsAndBoxes {
+    type Move = DotsEdge;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<DotsEdge> {
+        self.valid_moves()
+    }
+
+    fn play_move(&mut self, edge: DotsEdge) {
+        self.make_move(edge);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.boxes.iter().all(Option::is_some)
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        if !self.is_game_over() {
+            return None;
+        }
+
+        let x = self.score(Piece::X);
+        let o = self.score(Piece::O);
+        if x > o {
+            Some(Piece::X)
+        } else if o > x {
+            Some(Piece::O)
+        } else {
+            None
+        }
+    }
+}
+
+/// Draws the dots with the edges drawn between them and the owner of each claimed box inside it,
+/// highlighting the last edge drawn
+impl fmt::Display for DotsAndBoxes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let edge = |f: &mut fmt::Formatter, edge: DotsEdge, line: &str, blank: &str| {
+            if !self.has_edge(edge) {
+                write!(f, "{}", blank)
+            } else if self.last_move == Some(edge) {
+                write!(f, "{}", Paint::yellow(line).bold())
+            } else {
+                write!(f, "{}", line)
+            }
+        };
+
+        write!(f, "   A")?;
+        for col in 1..=self.cols {
+            write!(f, "   {}", (b'A' + col as u8) as char)?;
+        }
+        writeln!(f)?;
+
+        for row in 0..=self.rows {
+            write!(f, "{:<3}", row + 1)?;
+            for col in 0..=self.cols {
+                write!(f, "+")?;
+                if col < self.cols {
+                    edge(f, DotsEdge::Horizontal(TilePos {row, col}), "---", "   ")?;
+                }
+            }
+            writeln!(f)?;
+
+            if row == self.rows {
+                break;
+            }
+            write!(f, "   ")?;
+            for col in 0..=self.cols {
+                edge(f, DotsEdge::Vertical(TilePos {row, col}), "|", " ")?;
+                if let Some(index) = self.box_index(TilePos {row, col}) {
+                    match self.boxes[index] {
+                        Some(owner) => write!(f, " {} ", owner)?,
+                        None => write!(f, "   ")?,
+                    }
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An AI that plays Dots and Boxes by searching a number of moves ahead (see `negamax`)
+///
+/// Positions are evaluated by assuming the player to move takes every box that is ready to be
+/// taken, then counting the boxes each player has along with the long chains left on the board.
+/// By the long chain rule, the first player wants the number of dots plus the number of long
+/// chains to be even, since that forces their opponent to open the first long chain.
+#[derive(Debug, Clone, Copy)]
+pub struct DotsAi {
+    /// How many moves ahead to search
+    depth: usize,
+}
+
+impl Default for DotsAi {
+    fn default() -> Self {
+        Self {depth: 4}
+    }
+}
+
+impl DotsAi {
+    /// Creates an AI that searches the given number of moves ahead
+    pub fn new(depth: usize) -> Self {
+        Self {depth}
+    }
+
+    /// Returns how many moves ahead the AI searches
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the edge the AI would draw for the current player, or `None` if the game is over
+    pub fn choose_move(&self, game: &DotsAndBoxes) -> Option<DotsEdge> {
+        negamax(game, self.depth, self).map(|(edge, _)| edge)
+    }
+}
+
+/// Returns the number of chains of three or more boxes with two sides drawn, where drawing any
+/// edge in the chain lets the other player take the whole chain
+fn long_chains(game: &DotsAndBoxes) -> usize {
+    let in_chain = |pos: TilePos| game.box_owner(pos).is_none() && game.sides(pos) == 2;
+
+    let mut visited = vec![false; game.boxes.len()];
+    let mut chains = 0;
+    for start in game.box_positions() {
+        let start_index = game.box_index(start).expect("bug: box positions should be on the board");
+        if visited[start_index] || !in_chain(start) {
+            continue;
+        }
+
+        // Boxes are in the same chain if the edge between them is not drawn
+        let mut len = 0;
+        let mut stack = vec![start];
+        visited[start_index] = true;
+        while let Some(pos) = stack.pop() {
+            len += 1;
+            for edge in DotsAndBoxes::box_edges(pos) {
+                if game.has_edge(edge) {
+                    continue;
+                }
+                for next in game.edge_boxes(edge) {
+                    let index = game.box_index(next).expect("bug: edge boxes should be on the board");
+                    if !visited[index] && in_chain(next) {
+                        visited[index] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+
+        if len >= 3 {
+            chains += 1;
+        }
+    }
+    chains
+}
+
+impl Heuristic<DotsAndBoxes> for DotsAi {
+    fn evaluate(&self, game: &DotsAndBoxes, player: Piece) -> i32 {
+        let mut game = game.clone();
+        while let Some(edge) = game.valid_moves().into_iter().find(|&edge| completes_box(&game, edge)) {
+            game.make_move(edge);
+        }
+
+        let mut score = 100 * (game.score(player) as i32 - game.score(player.opposite()) as i32);
+
+        let chains = long_chains(&game);
+        if chains > 0 {
+            let dots = (game.rows + 1) * (game.cols + 1);
+            let favored = if (dots + chains).is_multiple_of(2) { Piece::X } else { Piece::O };
+            score += if favored == player { 150 } else { -150 };
+        }
+
+        score
+    }
+
+    fn order_moves(&self, game: &DotsAndBoxes, moves: &mut Vec<DotsEdge>) {
+        // Take boxes first, then draw edges that do not give any boxes away
+        moves.sort_by_key(|&edge| {
+            if completes_box(game, edge) {
+                0
+            } else if game.edge_boxes(edge).iter().all(|&pos| game.sides(pos) < 2) {
+                1
+            } else {
+                2
+            }
+        });
+    }
+}
+
+/// Returns true if drawing the given edge would complete at least one box
+fn completes_box(game: &DotsAndBoxes, edge: DotsEdge) -> bool {
+    game.edge_boxes(edge).iter().any(|&pos| game.sides(pos) == 3)
+}
+
+/// Plays the move chosen by `DotsAi::choose_move`
+impl Player<DotsAndBoxes> for DotsAi {
+    fn choose_move(&mut self, game: &DotsAndBoxes) -> DotsEdge {
+        DotsAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(game: &mut DotsAndBoxes, edges: &[&str]) {
+        for edge in edges {
+            game.make_move(edge.parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn parse_edges() {
+        assert_eq!("B1-A1".parse(), Ok(DotsEdge::Horizontal(TilePos {row: 0, col: 0})));
+        assert_eq!("a2 a1".parse(), Ok(DotsEdge::Vertical(TilePos {row: 0, col: 0})));
+        assert!("A1-B2".parse::<DotsEdge>().is_err());
+        assert!("A1".parse::<DotsEdge>().is_err());
+    }
+
+    #[test]
+    fn completing_box_moves_again() {
+        let mut game = DotsAndBoxes::new(2, 2);
+        play(&mut game, &["A1-B1", "A1-A2", "A2-B2"]);
+        assert_eq!(game.current_player(), Piece::O);
+
+        play(&mut game, &["B1-B2"]);
+        assert_eq!(game.box_owner(TilePos {row: 0, col: 0}), Some(Piece::O));
+        assert_eq!(game.current_player(), Piece::O);
+    }
+
+    #[test]
+    fn edge_completes_two_boxes() {
+        let mut game = DotsAndBoxes::new(1, 2);
+        play(&mut game, &["A1-B1", "B1-C1", "A2-B2", "B2-C2", "A1-A2", "C1-C2"]);
+        assert_eq!(game.current_player(), Piece::X);
+
+        play(&mut game, &["B1-B2"]);
+        assert_eq!(game.score(Piece::X), 2);
+        assert!(game.is_game_over());
+        assert_eq!(Game::winner(&game), Some(Piece::X));
+    }
+
+    #[test]
+    fn counts_long_chains() {
+        let mut game = DotsAndBoxes::new(1, 3);
+        // Draw the top and bottom of a row of three boxes
+        play(&mut game, &["A1-B1", "B1-C1", "C1-D1", "A2-B2", "B2-C2", "C2-D2"]);
+        assert_eq!(long_chains(&game), 1);
+    }
+}
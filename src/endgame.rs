@@ -0,0 +1,224 @@
+use crate::{Reversi, TilePos, Move, Piece, Variant, CancelToken};
+use crate::ai::order_moves;
+
+/// The number of empty tiles at or below which the AI switches to solving the game exactly
+pub const ENDGAME_EMPTIES: usize = 14;
+
+/// The result of solving the rest of a game exactly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EndgameSolution {
+    /// The best move for the current player
+    pub best_move: TilePos,
+    /// The final disc differential (current player's pieces minus opponent's pieces) reached with
+    /// perfect play from both players. In anti-reversi, this is negated so that a higher score is
+    /// still better for the current player.
+    pub score: i32,
+    /// True if `score` is the exact final differential. If false, only the sign of the score is
+    /// known to be correct (win, loss or draw).
+    pub exact: bool,
+    /// The number of positions visited while solving
+    pub nodes: u64,
+}
+
+/// Solves the rest of the game by searching every possible sequence of moves to the end
+///
+/// The game is first solved for a win, loss or draw, which is much faster than finding the exact
+/// final score. If there is time left, the game is then solved for the best final disc
+/// differential. Returns `None` if the current player has no moves or if the given token is
+/// canceled before the game could even be solved for a win, loss or draw.
+pub fn solve_endgame(game: &Reversi, cancel: &CancelToken) -> Option<EndgameSolution> {
+    if game.valid_moves().is_empty() {
+        return None;
+    }
+
+    let mut game = game.clone();
+    let mut solver = Solver {cancel, nodes: 0};
+
+    // A window of (-1, 1) only tells us whether the game is won, lost or drawn
+    let (best_move, wld_score) = solver.solve_root(&mut game, -1, 1)?;
+    let mut solution = EndgameSolution {
+        best_move,
+        score: wld_score.signum(),
+        exact: false,
+        nodes: solver.nodes,
+    };
+
+    // Knowing the outcome narrows down the window for finding the exact score
+    let (alpha, beta) = match wld_score.signum() {
+        1 => (0, i32::MAX),
+        -1 => (-i32::MAX, 0),
+        _ => return Some(EndgameSolution {exact: true, ..solution}),
+    };
+    let exact = solver.solve_root(&mut game, alpha, beta);
+    solution.nodes = solver.nodes;
+    if let Some((best_move, score)) = exact {
+        solution = EndgameSolution {best_move, score, exact: true, ..solution};
+    }
+
+    Some(solution)
+}
+
+struct Solver<'a> {
+    /// The search is abandoned as soon as this is canceled
+    cancel: &'a CancelToken,
+    /// The number of positions visited so far
+    nodes: u64,
+}
+
+impl Solver<'_> {
+    /// Searches every move of the current player, returning the best move and its score
+    fn solve_root(&mut self, game: &mut Reversi, mut alpha: i32, beta: i32) -> Option<(TilePos, i32)> {
+        let mut moves = game.valid_moves().to_vec();
+        order_moves(game, &mut moves, None);
+
+        let mut best = None;
+        for pmove in moves {
+            let token = game.apply(Move::Place(pmove));
+            let result = self.solve(game, -beta, -alpha, false);
+            game.unapply(token);
+            let score = -result?;
+
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((pmove, score));
+            }
+
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// Negamax with alpha-beta pruning, always searching to the end of the game
+    ///
+    /// Returns `None` if the search was canceled before it was completed.
+    fn solve(&mut self, game: &mut Reversi, mut alpha: i32, beta: i32, skipped: bool) -> Option<i32> {
+        if self.cancel.is_canceled() {
+            return None;
+        }
+        self.nodes += 1;
+
+        let nmoves = game.valid_moves().len();
+        if game.grid().is_full() || (skipped && nmoves == 0) {
+            return Some(final_score(game));
+        }
+
+        // No valid moves, so skip the turn
+        if nmoves == 0 {
+            let token = game.apply(Move::Pass);
+            let result = self.solve(game, -beta, -alpha, true);
+            game.unapply(token);
+            return Some(-result?);
+        }
+
+        let mut max_score = -i32::MAX;
+        // Indexing is fine because unapplying a move restores the exact same valid moves
+        for i in 0..nmoves {
+            let pmove = game.valid_moves()[i];
+            let token = game.apply(Move::Place(pmove));
+            let result = self.solve(game, -beta, -alpha, false);
+            game.unapply(token);
+            let score = -result?;
+
+            max_score = max_score.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        Some(max_score)
+    }
+}
+
+/// Returns the final disc differential from the perspective of the current player
+fn final_score(game: &Reversi) -> i32 {
+    let (x_score, o_score) = game.scores();
+    let diff = x_score as i32 - o_score as i32;
+    let diff = match game.current_player() {
+        Piece::X => diff,
+        Piece::O => -diff,
+    };
+
+    // Having fewer pieces is better in anti-reversi
+    match game.variant() {
+        Variant::Standard => diff,
+        Variant::Anti => -diff,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    /// Plain negamax without any pruning or move ordering, returning the final score of the game
+    /// with perfect play like `final_score`
+    fn negamax(game: &Reversi, skipped: bool) -> i32 {
+        let moves = game.valid_moves();
+        if game.grid().is_full() || (skipped && moves.is_empty()) {
+            return final_score(game);
+        }
+
+        if moves.is_empty() {
+            let mut next = game.clone();
+            next.pass();
+            return -negamax(&next, true);
+        }
+
+        moves.iter().map(|&pos| {
+            let mut next = game.clone();
+            next.make_move(pos);
+            -negamax(&next, false)
+        }).max().unwrap()
+    }
+
+    /// Returns a position with the given number of empty tiles reached with random moves, or
+    /// `None` if the game ended before then
+    fn random_position(variant: Variant, empties: u32, rng: &mut StdRng) -> Option<Reversi> {
+        let mut game = Reversi::new(variant);
+        loop {
+            let (x_score, o_score) = game.scores();
+            if game.is_game_over() {
+                return None;
+            }
+            if 64 - x_score - o_score <= empties && !game.valid_moves().is_empty() {
+                return Some(game);
+            }
+
+            match game.valid_moves() {
+                [] => game.pass(),
+                moves => {
+                    let pos = moves[rng.gen_range(0, moves.len())];
+                    game.make_move(pos);
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_plain_negamax() {
+        let mut rng = StdRng::seed_from_u64(1523);
+        let mut solved = 0;
+        for &variant in [Variant::Standard, Variant::Anti].iter().cycle().take(12) {
+            let game = match random_position(variant, 7, &mut rng) {
+                Some(game) => game,
+                None => continue,
+            };
+
+            let solution = solve_endgame(&game, &CancelToken::new()).unwrap();
+            assert!(solution.exact);
+            assert_eq!(solution.score, negamax(&game, false), "{:?}", game.history());
+
+            // The best move must actually reach the solved score
+            let mut next = game.clone();
+            next.make_move(solution.best_move);
+            assert_eq!(-negamax(&next, false), solution.score);
+            solved += 1;
+        }
+        assert!(solved >= 6);
+    }
+}
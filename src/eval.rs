@@ -0,0 +1,432 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::{Reversi, Piece, Variant, Grid, TilePos};
+
+/// Scores positions for the AI
+///
+/// A higher score means that the current state of the board is better for the given player. The
+/// score for one player should be the negative of the score for the other player.
+///
+/// Any closure that takes the game and the player is also an evaluator, so custom evaluations
+/// can be given to `AiPlayer::with_evaluator` without changing the search.
+pub trait Evaluator {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32;
+
+    /// Returns a short name for this evaluator, used to identify it in weights files
+    fn name(&self) -> &str {
+        "custom"
+    }
+}
+
+impl<F: Fn(&Reversi, Piece) -> i32> Evaluator for F {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        self(game, player)
+    }
+}
+
+/// The difference between the number of pieces each player has
+///
+/// In anti-reversi, having fewer pieces is better.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Material;
+
+impl Evaluator for Material {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        let (x_score, o_score) = game.scores();
+        let diff = x_score as i32 - o_score as i32;
+        let diff = match player {
+            Piece::X => diff,
+            Piece::O => -diff,
+        };
+
+        match game.variant() {
+            Variant::Standard => diff,
+            Variant::Anti => -diff,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "material"
+    }
+}
+
+/// The value of holding each tile of the board in standard games, from the perspective of the
+/// player holding it
+///
+/// Corners can never be flipped, so they are worth the most. The squares next to corners are
+/// penalized because taking them usually gives the opponent the corner.
+const STANDARD_WEIGHTS: [[i32; 8]; 8] = [
+    [100, -20, 10,  5,  5, 10, -20, 100],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [ 10,  -2, -1, -1, -1, -1,  -2,  10],
+    [  5,  -2, -1, -1, -1, -1,  -2,   5],
+    [  5,  -2, -1, -1, -1, -1,  -2,   5],
+    [ 10,  -2, -1, -1, -1, -1,  -2,  10],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [100, -20, 10,  5,  5, 10, -20, 100],
+];
+
+/// The value of holding each tile of the board in anti-reversi games
+///
+/// Stable discs can never be gotten rid of, so corners and edges are bad to hold. The squares
+/// next to corners are good because they force the opponent to take the corner.
+const ANTI_WEIGHTS: [[i32; 8]; 8] = [
+    [-100,  20, -10, -5, -5, -10,  20, -100],
+    [  20,  30,   2,  2,  2,   2,  30,   20],
+    [ -10,   2,   1,  1,  1,   1,   2,  -10],
+    [  -5,   2,   1,  1,  1,   1,   2,   -5],
+    [  -5,   2,   1,  1,  1,   1,   2,   -5],
+    [ -10,   2,   1,  1,  1,   1,   2,  -10],
+    [  20,  30,   2,  2,  2,   2,  30,   20],
+    [-100,  20, -10, -5, -5, -10,  20, -100],
+];
+
+/// Returns the table of positional weights for the given variant on a board with the given
+/// playable part (see `Reversi::playable_bounds`)
+///
+/// The tables are for the full 8x8 board. Smaller boards use the same table with its middle rows
+/// and columns left out, so that the corners, edges and the squares next to the corners are
+/// weighted the same wherever the edges of the board are. Tiles outside of the board are worth 0.
+fn position_weights(variant: Variant, (first, last): (TilePos, TilePos)) -> [[i32; 8]; 8] {
+    let full_weights = match variant {
+        Variant::Standard => &STANDARD_WEIGHTS,
+        Variant::Anti => &ANTI_WEIGHTS,
+    };
+    if first == (TilePos {row: 0, col: 0}) && last == (TilePos {row: 7, col: 7}) {
+        return *full_weights;
+    }
+
+    // Maps an index in the playable part of one axis to the index in the full table that is the
+    // same distance from the nearest edge
+    let full_index = |index: usize, first: usize, last: usize| {
+        let (from_start, from_end) = (index - first, last - index);
+        if from_start <= from_end { from_start } else { 7 - from_end }
+    };
+
+    let mut weights = [[0; 8]; 8];
+    for row in first.row..=last.row {
+        for col in first.col..=last.col {
+            weights[row][col] = full_weights[full_index(row, first.row, last.row)][full_index(col, first.col, last.col)];
+        }
+    }
+
+    weights
+}
+
+/// The value of the tiles each player holds, based on a table of weights for each tile of the
+/// board (corners are good, the squares next to them are bad, etc.)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Positional;
+
+impl Evaluator for Positional {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        let weights = position_weights(game.variant(), game.playable_bounds());
+        let mut score = 0;
+        for (row_tiles, row_weights) in game.grid().rows().iter().zip(&weights) {
+            for (&tile, &weight) in row_tiles.iter().zip(row_weights) {
+                match tile {
+                    Some(piece) if piece == player => score += weight,
+                    Some(_) => score -= weight,
+                    None => {},
+                }
+            }
+        }
+
+        score
+    }
+
+    fn name(&self) -> &str {
+        "positional"
+    }
+}
+
+/// The difference between the number of valid moves each player has
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Mobility;
+
+impl Evaluator for Mobility {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        game.mobility(player) as i32 - game.mobility(player.opposite()) as i32
+    }
+
+    fn name(&self) -> &str {
+        "mobility"
+    }
+}
+
+/// The difference between the potential mobility of each player (see
+/// `Reversi::potential_mobility`)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PotentialMobility;
+
+impl Evaluator for PotentialMobility {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        game.potential_mobility(player) as i32 - game.potential_mobility(player.opposite()) as i32
+    }
+
+    fn name(&self) -> &str {
+        "potential_mobility"
+    }
+}
+
+/// The difference between the number of stable discs each player has
+///
+/// In anti-reversi, having discs that can never be flipped is bad.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Stability;
+
+impl Evaluator for Stability {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        let diff = game.stable_discs(player) as i32 - game.stable_discs(player.opposite()) as i32;
+        match game.variant() {
+            Variant::Standard => diff,
+            Variant::Anti => -diff,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "stability"
+    }
+}
+
+/// The difference between the number of frontier discs each player has, negated since frontier
+/// discs give the opponent more moves
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Frontier;
+
+impl Evaluator for Frontier {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        game.frontier_discs(player.opposite()) as i32 - game.frontier_discs(player) as i32
+    }
+
+    fn name(&self) -> &str {
+        "frontier"
+    }
+}
+
+/// Returns the number of moves in a game on the given grid if it is played until the board is
+/// full, used to measure how far along a game is
+///
+/// This is 60 on the full 8x8 board, since it starts with 4 pieces.
+pub(crate) fn total_moves(grid: &Grid) -> usize {
+    grid.playable_count().saturating_sub(4).max(1)
+}
+
+/// A single term of a `WeightedEvaluator`
+pub struct Term {
+    pub evaluator: Box<dyn Evaluator + Send + Sync>,
+    /// The weight of the term at the very start of the game
+    pub opening_weight: i32,
+    /// The weight of the term once the board is full
+    pub endgame_weight: i32,
+}
+
+impl fmt::Debug for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Term")
+            .field("opening_weight", &self.opening_weight)
+            .field("endgame_weight", &self.endgame_weight)
+            .finish()
+    }
+}
+
+/// Evaluates positions with a weighted sum of other evaluators
+///
+/// The weight of each term changes smoothly from its opening weight to its endgame weight as the
+/// board fills up, since different things are important at different stages of the game.
+#[derive(Debug)]
+pub struct WeightedEvaluator {
+    terms: Vec<Term>,
+}
+
+impl Default for WeightedEvaluator {
+    /// The evaluation used by the AI
+    fn default() -> Self {
+        Self::new()
+            .with_term(Material, 1, 1)
+            .with_term(Positional, 1, 1)
+            // Having more moves than the opponent matters most early in the game, when there is
+            // still lots of room to maneuver
+            .with_term(Mobility, 10, 0)
+            .with_term(PotentialMobility, 4, 0)
+            // Stable discs are guaranteed to count at the end of the game, so they become more
+            // important as the game goes on
+            .with_term(Stability, 0, 20)
+    }
+}
+
+impl WeightedEvaluator {
+    /// Creates an evaluator with no terms, which scores every position as zero
+    pub fn new() -> Self {
+        Self {terms: Vec::new()}
+    }
+
+    /// Adds a term with the given weights
+    pub fn with_term<E>(mut self, evaluator: E, opening_weight: i32, endgame_weight: i32) -> Self
+        where E: Evaluator + Send + Sync + 'static,
+    {
+        self.terms.push(Term {evaluator: Box::new(evaluator), opening_weight, endgame_weight});
+        self
+    }
+
+    /// Returns the terms of this evaluator
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    /// Returns the terms of this evaluator so that their weights can be changed
+    pub fn terms_mut(&mut self) -> &mut [Term] {
+        &mut self.terms
+    }
+
+    /// Returns how much each term contributes to the evaluation of the given position for the
+    /// given player, along with the name of the term (see `Evaluator::name`)
+    ///
+    /// The contributions add up to the evaluation, apart from rounding.
+    pub fn breakdown(&self, game: &Reversi, player: Piece) -> Vec<(&str, i32)> {
+        let total_moves = total_moves(game.grid()) as i32;
+        let empties = (game.grid().open_count() as i32).min(total_moves);
+        let filled = total_moves - empties;
+
+        self.terms.iter().map(|term| {
+            let weight = term.opening_weight * empties + term.endgame_weight * filled;
+            let value = if weight != 0 { term.evaluator.evaluate(game, player) * weight } else { 0 };
+            (term.evaluator.name(), value / total_moves)
+        }).collect()
+    }
+
+    /// Loads the weights of the terms of this evaluator from a file written by `write_weights`
+    pub fn load_weights<P: AsRef<Path>>(&mut self, path: P) -> Result<(), WeightsError> {
+        let text = fs::read_to_string(path)?;
+        self.parse_weights(&text)
+    }
+
+    /// Parses weights in the format written by `write_weights`: one term per line with its name,
+    /// opening weight and endgame weight separated by whitespace (e.g. "mobility 10 0")
+    ///
+    /// Empty lines and lines starting with `#` are ignored. Terms that are not listed keep their
+    /// current weights.
+    pub fn parse_weights(&mut self, text: &str) -> Result<(), WeightsError> {
+        for (i, line) in text.lines().enumerate() {
+            let line_num = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || WeightsError::InvalidLine {line: line_num, text: line.to_string()};
+            let parts: Vec<_> = line.split_whitespace().collect();
+            let (name, opening_weight, endgame_weight) = match parts[..] {
+                [name, opening, endgame] => (
+                    name,
+                    opening.parse().map_err(|_| invalid())?,
+                    endgame.parse().map_err(|_| invalid())?,
+                ),
+                _ => return Err(invalid()),
+            };
+
+            let term = self.terms.iter_mut()
+                .find(|term| term.evaluator.name() == name)
+                .ok_or_else(|| WeightsError::UnknownTerm {line: line_num, name: name.to_string()})?;
+            term.opening_weight = opening_weight;
+            term.endgame_weight = endgame_weight;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the weights of the terms of this evaluator in the format read by `parse_weights`
+    pub fn write_weights<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "# term opening_weight endgame_weight")?;
+        for term in &self.terms {
+            writeln!(out, "{} {} {}", term.evaluator.name(), term.opening_weight, term.endgame_weight)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WeightsError {
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+    #[error("Line {line}: expected a term name and two weights, found `{text}`")]
+    InvalidLine {
+        line: usize,
+        text: String,
+    },
+    #[error("Line {line}: unknown term `{name}`")]
+    UnknownTerm {
+        line: usize,
+        name: String,
+    },
+}
+
+impl Evaluator for WeightedEvaluator {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        let total_moves = total_moves(game.grid()) as i32;
+        let empties = (game.grid().open_count() as i32).min(total_moves);
+        let filled = total_moves - empties;
+
+        let mut score = 0;
+        for term in &self.terms {
+            let weight = term.opening_weight * empties + term.endgame_weight * filled;
+            if weight != 0 {
+                score += term.evaluator.evaluate(game, player) * weight;
+            }
+        }
+
+        score / total_moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_follow_the_edges_of_smaller_boards() {
+        let full = Reversi::default();
+        assert_eq!(position_weights(Variant::Standard, full.playable_bounds()), STANDARD_WEIGHTS);
+        assert_eq!(total_moves(full.grid()), 60);
+
+        let small = Reversi::with_board_size(Variant::Standard, 6);
+        let weights = position_weights(Variant::Standard, small.playable_bounds());
+        // The corners of the 6x6 board are worth as much as the corners of the full board
+        for &(row, col) in &[(1, 1), (1, 6), (6, 1), (6, 6)] {
+            assert_eq!(weights[row][col], 100);
+        }
+        assert_eq!((weights[2][2], weights[1][2], weights[1][3]), (-50, -20, 10));
+        assert_eq!(weights[0], [0; 8]);
+        assert_eq!(weights[4][0], 0);
+        assert_eq!(total_moves(small.grid()), 32);
+
+        let anti = position_weights(Variant::Anti, small.playable_bounds());
+        assert_eq!((anti[1][1], anti[2][2]), (-100, 30));
+    }
+
+    #[test]
+    fn positional_prefers_corners_of_smaller_boards() {
+        let mut game = Reversi::with_board_size(Variant::Standard, 6);
+        let x_square = TilePos {row: 2, col: 2};
+        let corner = TilePos {row: 1, col: 1};
+
+        let mut grid = game.grid().clone();
+        grid.place(corner, Piece::X);
+        let with_corner = Reversi::from_grid(Variant::Standard, grid, Piece::O);
+        let mut grid = game.grid().clone();
+        grid.place(x_square, Piece::X);
+        let with_x_square = Reversi::from_grid(Variant::Standard, grid, Piece::O);
+        assert!(Positional.evaluate(&with_corner, Piece::X) > Positional.evaluate(&with_x_square, Piece::X));
+
+        // Phases are measured against the number of tiles on the smaller board
+        game.make_move(game.valid_moves()[0]);
+        let evaluator = WeightedEvaluator::new().with_term(Material, 0, 32);
+        assert_eq!(evaluator.evaluate(&game, Piece::X), 3);
+    }
+}
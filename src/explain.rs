@@ -0,0 +1,107 @@
+use std::cmp::Reverse;
+
+use crate::{Reversi, TilePos, Move, Piece, WeightedEvaluator};
+use crate::ai::square_priority;
+
+/// The most reasons taken from the evaluation terms in a single explanation
+const MAX_TERM_REASONS: usize = 2;
+
+/// Explains in plain words why the given move is a good choice for the current player of the
+/// given game (e.g. "takes corner A1" or "limits opponent to 3 moves")
+///
+/// The reasons come from comparing the position after the move with the positions after every
+/// other valid move, term by term, using the default evaluation. Terms where the move does better
+/// than the other moves on average are described, starting with the biggest difference.
+///
+/// # Panics
+///
+/// Panics if the move is not one of the valid moves of the current player
+pub fn explain_move(game: &Reversi, pmove: TilePos) -> Vec<String> {
+    assert!(game.valid_moves().contains(&pmove), "bug: cannot explain an invalid move");
+
+    let evaluator = WeightedEvaluator::default();
+    let player = game.current_player();
+    let after = |pos: TilePos| {
+        let mut game = game.clone();
+        let _ = game.apply(Move::Place(pos));
+        game
+    };
+
+    let chosen = after(pmove);
+    let others: Vec<_> = game.valid_moves().iter()
+        .filter(|&&pos| pos != pmove)
+        .map(|&pos| (pos, after(pos)))
+        .collect();
+
+    let mut reasons = Vec::new();
+    match square_priority(game, pmove) {
+        0 => reasons.push(format!("takes corner {}", pmove)),
+        3 => {},
+        // X-squares usually give the opponent the corner next to them
+        _ if others.iter().any(|&(pos, _)| square_priority(game, pos) == 3) => {
+            reasons.push("avoids X-square".to_string());
+        },
+        _ => {},
+    }
+
+    if others.is_empty() {
+        reasons.push("is the only move".to_string());
+        return reasons;
+    }
+
+    // How much better the chosen move does than the average of the other moves on each term
+    let chosen_terms = evaluator.breakdown(&chosen, player);
+    let other_terms: Vec<_> = others.iter()
+        .map(|(_, other)| evaluator.breakdown(other, player))
+        .collect();
+    let mut advantages: Vec<_> = chosen_terms.iter().enumerate().map(|(i, &(name, value))| {
+        let total: i32 = other_terms.iter().map(|terms| terms[i].1).sum();
+        (name, value - total / others.len() as i32)
+    }).filter(|&(_, advantage)| advantage > 0).collect();
+    advantages.sort_by_key(|&(_, advantage)| Reverse(advantage));
+
+    for (name, _) in advantages.into_iter().take(MAX_TERM_REASONS) {
+        if let Some(reason) = describe_term(name, game, &chosen, pmove, player) {
+            reasons.push(reason);
+        }
+    }
+
+    if reasons.is_empty() {
+        reasons.push("scores best in the search".to_string());
+    }
+
+    reasons
+}
+
+/// Describes what the move does for the evaluation term with the given name
+fn describe_term(
+    name: &str,
+    before: &Reversi,
+    after: &Reversi,
+    pmove: TilePos,
+    player: Piece,
+) -> Option<String> {
+    let opponent = player.opposite();
+    Some(match name {
+        "mobility" => match after.mobility(opponent) {
+            0 => "forces opponent to pass".to_string(),
+            1 => "limits opponent to 1 move".to_string(),
+            moves => format!("limits opponent to {} moves", moves),
+        },
+        "potential_mobility" | "frontier" => "keeps fewer discs on the frontier".to_string(),
+        "stability" => {
+            let gained = after.stable_discs(player).saturating_sub(before.stable_discs(player));
+            match gained {
+                0 => return None,
+                1 => "gains 1 stable disc".to_string(),
+                gained => format!("gains {} stable discs", gained),
+            }
+        },
+        "material" => match before.count_flips(pmove) {
+            1 => "flips 1 disc".to_string(),
+            flips => format!("flips {} discs", flips),
+        },
+        "positional" => "plays to a strong square".to_string(),
+        _ => return None,
+    })
+}
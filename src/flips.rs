@@ -0,0 +1,51 @@
+use std::ops::Deref;
+
+use crate::TilePos;
+
+/// The most tiles that can ever be flipped by a single move
+///
+/// Each of the 4 lines through a tile (horizontal, vertical and the 2 diagonals) has at most 8
+/// tiles. One of those is the tile where the piece is placed and at least one more must be the
+/// player's own piece, leaving at most 6 flips per line.
+pub const MAX_FLIPS: usize = 4 * 6;
+
+/// A fixed-capacity, stack-allocated list of the tiles flipped by a move
+#[derive(Debug, Clone, Copy)]
+pub struct Flips {
+    tiles: [TilePos; MAX_FLIPS],
+    len: usize,
+}
+
+impl Default for Flips {
+    fn default() -> Self {
+        Self {
+            tiles: [TilePos {row: 0, col: 0}; MAX_FLIPS],
+            len: 0,
+        }
+    }
+}
+
+impl Deref for Flips {
+    type Target = [TilePos];
+
+    fn deref(&self) -> &Self::Target {
+        &self.tiles[..self.len]
+    }
+}
+
+impl Flips {
+    /// Adds a tile to the end of the list
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list already contains `MAX_FLIPS` tiles
+    pub(crate) fn push(&mut self, pos: TilePos) {
+        self.tiles[self.len] = pos;
+        self.len += 1;
+    }
+
+    /// Shortens the list to the given length, removing any tiles after that
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+
+use rand::Rng;
+
+use crate::{Piece, Seat};
+
+/// A turn-based game between players who can all see everything about the game
+///
+/// This is all that the parts of the crate that work with any game (e.g. `negamax`) need to know
+/// about it. Each game still has its own methods for everything else, such as drawing its board.
+///
+/// Most games have two players, who are identified by their `Piece` and implement
+/// `current_player` and `winner`. Games with more players identify them by their `Seat` instead
+/// and implement `player_count`, `current_seat` and `winning_seat`. Each pair of methods is
+/// implemented in terms of the other by default, so one of them has to be implemented. Searches
+/// like `negamax` only work with two players.
+///
+/// Games of chance (e.g. with dice) are modeled with chance nodes: positions where the next thing
+/// to happen is a random event instead of a player's choice (see `is_chance_node`). The outcomes
+/// of the event are moves like any other, but they are picked at random by the game itself.
+pub trait Game: Clone {
+    /// A move that a player can make on their turn
+    type Move: Clone + PartialEq + fmt::Debug + fmt::Display;
+
+    /// Returns the player whose turn it is
+    ///
+    /// In games where a player can move more than once in a row, this is still the same player
+    /// after their move.
+    ///
+    /// # Panics
+    ///
+    /// By default, this panics if the current player's seat is not one of the first two.
+    fn current_player(&self) -> Piece {
+        self.current_seat().piece().expect("bug: a game with more than two players has no current piece")
+    }
+
+    /// Returns the number of players, who take their turns in the order of their seats
+    fn player_count(&self) -> usize {
+        2
+    }
+
+    /// Returns the seat of the player whose turn it is (see `current_player`)
+    fn current_seat(&self) -> Seat {
+        self.current_player().into()
+    }
+
+    /// Returns every move that the current player can make, or no moves once the game is over
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    /// Makes the given move for the current player
+    ///
+    /// # Panics
+    ///
+    /// May panic if the move is not one of the moves returned by `legal_moves`.
+    fn play_move(&mut self, pmove: Self::Move);
+
+    /// Returns true if the game has ended
+    fn is_game_over(&self) -> bool;
+
+    /// Returns the player that won the game, or `None` if the game ended in a tie or is not over
+    ///
+    /// # Panics
+    ///
+    /// By default, this panics if the winner's seat is not one of the first two.
+    fn winner(&self) -> Option<Piece> {
+        self.winning_seat().map(|seat| seat.piece().expect("bug: a game with more than two players has no winning piece"))
+    }
+
+    /// Returns the seat of the player that won the game (see `winner`)
+    fn winning_seat(&self) -> Option<Seat> {
+        self.winner().map(Seat::from)
+    }
+
+    /// Returns the name that the player in the given seat is shown with (e.g. "X")
+    ///
+    /// By default, the first two seats are shown as their pieces.
+    fn seat_name(&self, seat: Seat) -> String {
+        match seat.piece() {
+            Some(piece) => piece.to_string(),
+            None => seat.to_string(),
+        }
+    }
+
+    /// Returns true if the given move passes the turn to the other player without doing anything
+    /// else
+    ///
+    /// When passing is the only legal move, game loops (e.g. `run_game`) make it without asking
+    /// the player. Games without passes do not need to implement this.
+    fn is_pass(&self, _pmove: &Self::Move) -> bool {
+        false
+    }
+
+    /// Returns true if the next thing to happen is a random event (e.g. a dice roll) instead of a
+    /// move chosen by the current player
+    ///
+    /// At a chance node, `legal_moves` returns the possible outcomes of the event, `play_move`
+    /// makes the given outcome happen, and `current_player` is the player the event is for (e.g.
+    /// the player about to roll). Games without chance do not need to implement this.
+    fn is_chance_node(&self) -> bool {
+        false
+    }
+
+    /// Returns every possible outcome of the random event at a chance node along with its
+    /// probability, which add up to 1
+    ///
+    /// By default, every outcome returned by `legal_moves` is equally likely.
+    fn chance_outcomes(&self) -> Vec<(Self::Move, f64)> {
+        let outcomes = self.legal_moves();
+        let probability = 1.0 / outcomes.len() as f64;
+        outcomes.into_iter().map(|outcome| (outcome, probability)).collect()
+    }
+
+    /// Picks the outcome of the random event at a chance node and makes it happen, returning the
+    /// outcome
+    ///
+    /// Games of chance own the random number generator that the outcomes are picked with, so that
+    /// a game created from a seed always plays out the same way (see `pick_outcome`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the game is not at a chance node. Games without chance do not need to implement
+    /// this.
+    fn play_chance(&mut self) -> Self::Move {
+        panic!("bug: attempt to play a random event in a game without chance nodes")
+    }
+}
+
+/// Picks one of the given outcomes at random, each with the probability given alongside it
+///
+/// # Panics
+///
+/// Panics if there are no outcomes.
+pub fn pick_outcome<M: Clone, R: Rng + ?Sized>(rng: &mut R, outcomes: &[(M, f64)]) -> M {
+    let total: f64 = outcomes.iter().map(|&(_, probability)| probability).sum();
+    let mut choice = rng.gen::<f64>() * total;
+    for (outcome, probability) in outcomes {
+        if choice < *probability {
+            return outcome.clone();
+        }
+        choice -= probability;
+    }
+
+    // Rounding errors can leave a tiny bit of the total over
+    let (outcome, _) = outcomes.last().expect("bug: attempt to pick from no outcomes");
+    outcome.clone()
+}
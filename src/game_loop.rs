@@ -0,0 +1,486 @@
+use std::fmt;
+
+use crate::{Game, Piece, Seat, Player, PlayerAction, Clock, StdConsole, prompt};
+
+/// How a game played by `run_game` ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEnd {
+    /// The game was played until it was over (see `Game::winner`)
+    Finished,
+    /// The given player ran out of time on the clock, which loses the game
+    TimeForfeit(Piece),
+    /// A player quit before the game was over
+    Quit,
+}
+
+impl GameEnd {
+    /// Returns the seat of the player that won the given game, which ended this way, or `None` if
+    /// it ended in a tie or was not finished
+    pub fn winner<G: Game>(self, game: &G) -> Option<Seat> {
+        match self {
+            GameEnd::Finished => game.winning_seat(),
+            GameEnd::TimeForfeit(piece) => Some(piece.opposite().into()),
+            GameEnd::Quit => None,
+        }
+    }
+}
+
+/// Everything a game loop shows to the players (see `run_game`)
+pub trait Ui<G: Game> {
+    /// Shows the game at the start of the current player's turn, along with the time left on the
+    /// clock (if the game is timed)
+    fn show_turn(&mut self, game: &G, clock: Option<&Clock>);
+
+    /// Shows a move that the player in the given seat just made. The game already includes the
+    /// move.
+    fn show_move(&mut self, _game: &G, _seat: Seat, _pmove: &G::Move) {}
+
+    /// Shows that the player in the given seat passes because passing is their only legal move.
+    /// The game does not include the pass yet.
+    fn show_pass(&mut self, game: &G, seat: Seat) {
+        println!("{} has no moves and passes", game.seat_name(seat));
+    }
+
+    /// Shows the outcome of a random event (e.g. a dice roll) for the player in the given seat,
+    /// which the game already includes
+    fn show_chance(&mut self, game: &G, seat: Seat, outcome: &G::Move) {
+        println!("{} rolled {}", game.seat_name(seat), outcome);
+    }
+
+    /// Shows the game once it has ended
+    fn show_end(&mut self, game: &G, end: GameEnd);
+
+    /// Carries out an action other than a move or quitting that the current player asked for
+    /// (e.g. `PlayerAction::Undo`), after which the current player's turn starts over
+    ///
+    /// By default, no other actions are supported.
+    fn run_action(&mut self, _game: &mut G, _action: PlayerAction<G::Move>) {
+        println!("That command is not available in this game.\n");
+    }
+}
+
+/// Plays the given game until it is over or a player quits, asking each player for their moves
+/// in turn and showing the game with the given UI. Returns the game as it was when it ended.
+///
+/// There is one player for each seat of the game (see `Game::player_count`), in the order of their
+/// seats, e.g. X first in two-player games. When passing is the current player's only legal move
+/// (see `Game::is_pass`), it is made for them without asking. Random events (see
+/// `Game::is_chance_node`) are played by the game itself.
+///
+/// # Panics
+///
+/// Panics if the number of players does not match the number of seats.
+pub fn run_game<G, U>(game: G, players: impl Into<Vec<Box<dyn Player<G>>>>, ui: U) -> (G, GameEnd)
+    where G: Game,
+          U: Ui<G>,
+{
+    play(game, players.into(), ui, None)
+}
+
+/// Plays the given two-player game the same way as `run_game`, but with each player's turns timed
+/// by the given clock. A player who runs out of time loses.
+pub fn run_game_with_clock<G, U>(game: G, players: [Box<dyn Player<G>>; 2], ui: U, clock: Clock) -> (G, GameEnd)
+    where G: Game,
+          U: Ui<G>,
+{
+    play(game, players.into(), ui, Some(clock))
+}
+
+fn play<G, U>(mut game: G, mut players: Vec<Box<dyn Player<G>>>, mut ui: U, mut clock: Option<Clock>) -> (G, GameEnd)
+    where G: Game,
+          U: Ui<G>,
+{
+    assert_eq!(players.len(), game.player_count(), "bug: the game needs one player for each of its seats");
+
+    loop {
+        if game.is_game_over() {
+            ui.show_end(&game, GameEnd::Finished);
+            return (game, GameEnd::Finished);
+        }
+
+        let seat = game.current_seat();
+        if game.is_chance_node() {
+            let outcome = game.play_chance();
+            ui.show_chance(&game, seat, &outcome);
+            continue;
+        }
+
+        ui.show_turn(&game, clock.as_ref());
+
+        let legal_moves = game.legal_moves();
+        if let [pmove] = &legal_moves[..] {
+            if game.is_pass(pmove) {
+                ui.show_pass(&game, seat);
+                game.play_move(pmove.clone());
+                continue;
+            }
+        }
+
+        // Clocks are only used in two-player games
+        let mut timed = clock.as_mut().map(|clock| (clock, game.current_player()));
+        if let Some((clock, player)) = &mut timed {
+            clock.start(*player);
+        }
+        let action = players[seat.index()].choose_action(&game);
+        if let Some((clock, player)) = timed {
+            clock.stop();
+            if clock.flagged() == Some(player) {
+                let end = GameEnd::TimeForfeit(player);
+                ui.show_end(&game, end);
+                return (game, end);
+            }
+        }
+
+        match action {
+            PlayerAction::Move(pmove) => {
+                game.play_move(pmove.clone());
+                ui.show_move(&game, seat, &pmove);
+            },
+            PlayerAction::Quit => {
+                ui.show_end(&game, GameEnd::Quit);
+                return (game, GameEnd::Quit);
+            },
+            action => ui.run_action(&mut game, action),
+        }
+    }
+}
+
+/// Turns a game into text (e.g. the board)
+type RenderFn<G> = Box<dyn Fn(&G) -> String>;
+
+/// Turns the text a player entered into a move, or returns why it is not a valid move
+type ParseFn<G> = Box<dyn Fn(&G, &str) -> Result<<G as Game>::Move, String>>;
+
+/// A `Ui` that prints the game to stdout as text, using its `Display` implementation unless
+/// another way to draw it is given (see `with_board`)
+pub struct TextUi<G> {
+    /// Draws the board, or `None` to use the game's `Display` implementation
+    board: Option<RenderFn<G>>,
+    /// Returns lines to show under the board (e.g. the score), if any
+    status: Option<RenderFn<G>>,
+    /// Which players' moves (in the order of their seats) are announced after they are made, with
+    /// every player's moves announced if there are no more entries
+    announced: Vec<bool>,
+}
+
+impl<G> Default for TextUi<G> {
+    fn default() -> Self {
+        Self {
+            board: None,
+            status: None,
+            announced: Vec::new(),
+        }
+    }
+}
+
+impl<G> TextUi<G> {
+    /// Creates a UI that announces every move
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how the board is drawn
+    pub fn with_board(mut self, board: impl Fn(&G) -> String + 'static) -> Self {
+        self.board = Some(Box::new(board));
+        self
+    }
+
+    /// Sets the lines shown under the board every turn and at the end of the game (e.g. the
+    /// score)
+    pub fn with_status(mut self, status: impl Fn(&G) -> String + 'static) -> Self {
+        self.status = Some(Box::new(status));
+        self
+    }
+
+    /// Sets which players' moves (in the order of their seats, X first) are announced after they
+    /// are made. Announcing only the AI's moves makes them easier to spot, since the players
+    /// already know their own moves.
+    pub fn with_announced(mut self, announced: impl Into<Vec<bool>>) -> Self {
+        self.announced = announced.into();
+        self
+    }
+}
+
+impl<G: Game + fmt::Display> TextUi<G> {
+    /// Prints the board and the status lines
+    fn print_game(&self, game: &G) {
+        println!();
+        match &self.board {
+            Some(board) => print!("{}", board(game)),
+            None => println!("{}", game),
+        }
+        if let Some(status) = &self.status {
+            let status = status(game);
+            if !status.is_empty() {
+                println!("{}", status.trim_end());
+            }
+        }
+    }
+}
+
+impl<G: Game + fmt::Display> Ui<G> for TextUi<G> {
+    fn show_turn(&mut self, game: &G, clock: Option<&Clock>) {
+        self.print_game(game);
+        if let Some(clock) = clock {
+            println!("Time: {} {} | {} {}", Piece::X, clock.format_remaining(Piece::X),
+                Piece::O, clock.format_remaining(Piece::O));
+        }
+        println!("The current piece is: {}", game.seat_name(game.current_seat()));
+    }
+
+    fn show_move(&mut self, game: &G, seat: Seat, pmove: &G::Move) {
+        if self.announced.get(seat.index()).copied().unwrap_or(true) {
+            println!("{} played {}", game.seat_name(seat), pmove);
+        }
+    }
+
+    fn show_end(&mut self, game: &G, end: GameEnd) {
+        match end {
+            GameEnd::Finished => self.print_game(game),
+            GameEnd::TimeForfeit(piece) => println!("{} ran out of time", piece),
+            GameEnd::Quit => return,
+        }
+
+        match end.winner(game) {
+            Some(winner) => println!("The winner is: {}", game.seat_name(winner)),
+            None => println!("The game ended with a tie"),
+        }
+    }
+}
+
+/// A human player of any game who types their moves at a prompt on stdin
+///
+/// By default, moves are entered the same way they are written (ignoring case), but any other way
+/// of reading them can be given with `with_parser`. Entering `moves` lists every legal move.
+/// Entering `quit` or an empty line (e.g. at the end of input) quits the game.
+pub struct PromptPlayer<G: Game> {
+    /// Turns the entered text into a move, or returns why it is not a valid move
+    parse: ParseFn<G>,
+    /// Returns the text to prompt for a move with
+    message: RenderFn<G>,
+}
+
+impl<G: Game> Default for PromptPlayer<G> {
+    fn default() -> Self {
+        Self {
+            parse: Box::new(|game, input| {
+                game.legal_moves().into_iter()
+                    .find(|pmove| pmove.to_string().eq_ignore_ascii_case(input))
+                    .ok_or_else(|| format!("Invalid move: `{}`. Enter `moves` to list the valid moves.", input))
+            }),
+            message: Box::new(|game| match game.legal_moves().first() {
+                Some(example) => format!("Enter your move (e.g. {}): ", example),
+                None => "Enter your move: ".to_string(),
+            }),
+        }
+    }
+}
+
+impl<G: Game> PromptPlayer<G> {
+    /// Creates a player whose moves are entered the same way they are written
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how the entered text is turned into a move. The parser returns the message to show
+    /// if the text is not a valid move in the given game.
+    pub fn with_parser(mut self, parse: impl Fn(&G, &str) -> Result<G::Move, String> + 'static) -> Self {
+        self.parse = Box::new(parse);
+        self
+    }
+
+    /// Sets the text to prompt for a move with in the given game
+    pub fn with_message(mut self, message: impl Fn(&G) -> String + 'static) -> Self {
+        self.message = Box::new(message);
+        self
+    }
+}
+
+impl<G: Game> Player<G> for PromptPlayer<G> {
+    /// Prompts for moves until a valid move is entered
+    ///
+    /// # Panics
+    ///
+    /// Panics if the player quits, since a move has to be returned. Use `choose_action` to
+    /// handle quitting.
+    fn choose_move(&mut self, game: &G) -> G::Move {
+        match self.choose_action(game) {
+            PlayerAction::Move(pmove) => pmove,
+            _ => panic!("the player quit instead of entering a move"),
+        }
+    }
+
+    fn choose_action(&mut self, game: &G) -> PlayerAction<G::Move> {
+        loop {
+            let line = match prompt(StdConsole, &(self.message)(game)) {
+                Ok(line) => line,
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    return PlayerAction::Quit;
+                },
+            };
+            let input = line.trim();
+            if line.is_empty() || input.eq_ignore_ascii_case("quit") {
+                println!();
+                return PlayerAction::Quit;
+            }
+
+            if input.eq_ignore_ascii_case("moves") {
+                let moves: Vec<_> = game.legal_moves().iter().map(|pmove| pmove.to_string()).collect();
+                println!("Valid moves: {}\n", moves.join(", "));
+                continue;
+            }
+
+            match (self.parse)(game, input) {
+                Ok(pmove) => return PlayerAction::Move(pmove),
+                Err(err) => println!("{}\n", err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{Nim, NimMove, NimVariant, TimeControl};
+
+    /// Takes one object from the first pile that has any, and can be slowed down
+    struct TakeOne {
+        delay: Duration,
+    }
+
+    impl Player<Nim> for TakeOne {
+        fn choose_move(&mut self, game: &Nim) -> NimMove {
+            std::thread::sleep(self.delay);
+            let pile = game.piles().iter().position(|&size| size > 0).unwrap();
+            NimMove {pile: pile + 1, count: 1}
+        }
+    }
+
+    /// Records everything the game loop shows
+    #[derive(Default)]
+    struct Recorder {
+        turns: usize,
+        moves: Vec<String>,
+        end: Option<GameEnd>,
+    }
+
+    impl<G: Game> Ui<G> for &mut Recorder {
+        fn show_turn(&mut self, _game: &G, _clock: Option<&Clock>) {
+            self.turns += 1;
+        }
+
+        fn show_move(&mut self, _game: &G, seat: Seat, pmove: &G::Move) {
+            let player = match seat.piece() {
+                Some(piece) => format!("{:?}", piece),
+                None => seat.to_string(),
+            };
+            self.moves.push(format!("{} {}", player, pmove));
+        }
+
+        fn show_end(&mut self, _game: &G, end: GameEnd) {
+            self.end = Some(end);
+        }
+    }
+
+    fn take_one(delay: Duration) -> Box<dyn Player<Nim>> {
+        Box::new(TakeOne {delay})
+    }
+
+    /// A game where three players take turns counting down, and whoever reaches zero wins
+    #[derive(Debug, Clone)]
+    struct Countdown {
+        count: u32,
+        seat: Seat,
+        winner: Option<Seat>,
+    }
+
+    impl Game for Countdown {
+        type Move = u32;
+
+        fn player_count(&self) -> usize {
+            3
+        }
+
+        fn current_seat(&self) -> Seat {
+            self.seat
+        }
+
+        fn legal_moves(&self) -> Vec<u32> {
+            match self.count {
+                0 => Vec::new(),
+                count => vec![count - 1],
+            }
+        }
+
+        fn play_move(&mut self, count: u32) {
+            self.count = count;
+            if count == 0 {
+                self.winner = Some(self.seat);
+            }
+            self.seat = self.seat.next(self.player_count());
+        }
+
+        fn is_game_over(&self) -> bool {
+            self.count == 0
+        }
+
+        fn winning_seat(&self) -> Option<Seat> {
+            self.winner
+        }
+    }
+
+    /// Plays the only legal move
+    struct CountDown;
+
+    impl Player<Countdown> for CountDown {
+        fn choose_move(&mut self, game: &Countdown) -> u32 {
+            game.legal_moves()[0]
+        }
+    }
+
+    #[test]
+    fn plays_until_game_over() {
+        let mut recorder = Recorder::default();
+        let game = Nim::new(vec![2, 1], NimVariant::Normal);
+        let players = [take_one(Duration::from_secs(0)), take_one(Duration::from_secs(0))];
+        let (game, end) = run_game(game, players, &mut recorder);
+
+        assert_eq!(end, GameEnd::Finished);
+        assert_eq!(recorder.turns, 3);
+        assert_eq!(recorder.moves, ["X 1:1", "O 1:1", "X 2:1"]);
+        assert_eq!(recorder.end, Some(GameEnd::Finished));
+        assert_eq!(end.winner(&game), Some(Piece::X.into()));
+    }
+
+    #[test]
+    fn rotates_among_every_seat() {
+        let mut recorder = Recorder::default();
+        let game = Countdown {count: 5, seat: Seat(0), winner: None};
+        let players: Vec<Box<dyn Player<Countdown>>> = vec![Box::new(CountDown), Box::new(CountDown), Box::new(CountDown)];
+        let (game, end) = run_game(game, players, &mut recorder);
+
+        assert_eq!(recorder.moves, ["X 4", "O 3", "Player 3 2", "X 1", "O 0"]);
+        assert_eq!(end.winner(&game), Some(Seat(1)));
+        assert_eq!(game.winner(), Some(Piece::O));
+    }
+
+    #[test]
+    fn running_out_of_time_loses() {
+        let mut recorder = Recorder::default();
+        let game = Nim::new(vec![5], NimVariant::Normal);
+        let players = [take_one(Duration::from_secs(0)), take_one(Duration::from_millis(50))];
+        let clock = Clock::new(TimeControl {
+            initial: Duration::from_millis(20),
+            increment: Duration::from_secs(0),
+            byo_yomi: None,
+        });
+        let (game, end) = run_game_with_clock(game, players, &mut recorder, clock);
+
+        assert_eq!(end, GameEnd::TimeForfeit(Piece::O));
+        assert_eq!(end.winner(&game), Some(Piece::X.into()));
+        assert_eq!(recorder.moves, ["X 1:1"]);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{Reversi, Move};
+
+/// A position in a game, whose children (the positions after each possible move) are only
+/// generated when they are asked for
+///
+/// Every child owns a copy of its game, which makes the tree easy to explore and hold on to. The
+/// AI's search does not use it for that reason: it makes and unmakes moves on a single game
+/// instead (see `Reversi::apply`) so that it never has to copy a position.
+#[derive(Debug, Clone)]
+pub struct GameTree {
+    game: Reversi,
+    /// The move that led to this position, or `None` for the root of the tree
+    last_move: Option<Move>,
+}
+
+impl GameTree {
+    /// Creates a tree rooted at the current position of the given game
+    pub fn new(game: Reversi) -> Self {
+        Self {game, last_move: None}
+    }
+
+    /// Returns the game at this position
+    pub fn game(&self) -> &Reversi {
+        &self.game
+    }
+
+    /// Returns the move that led to this position, or `None` for the root of the tree
+    pub fn last_move(&self) -> Option<Move> {
+        self.last_move
+    }
+
+    /// Returns true if the game is over at this position, so it has no children
+    pub fn is_terminal(&self) -> bool {
+        self.game.is_game_over()
+    }
+
+    /// Returns an iterator over the children of this position: one for every valid move of the
+    /// current player, or a single child for passing if the current player has no valid moves
+    ///
+    /// Terminal positions have no children.
+    pub fn children(&self) -> Children<'_> {
+        Children {tree: self, next: 0}
+    }
+
+    /// Calls the given function for this position and every position below it, up to the given
+    /// depth (0 only visits this position). Positions are visited in depth-first order, with each
+    /// parent visited before its children.
+    ///
+    /// The function is given each position along with its depth below this position.
+    pub fn visit<F: FnMut(&GameTree, usize)>(&self, max_depth: usize, mut visitor: F) {
+        self.visit_inner(0, max_depth, &mut visitor);
+    }
+
+    fn visit_inner<F: FnMut(&GameTree, usize)>(&self, depth: usize, max_depth: usize, visitor: &mut F) {
+        visitor(self, depth);
+        if depth >= max_depth {
+            return;
+        }
+
+        for child in self.children() {
+            child.visit_inner(depth + 1, max_depth, visitor);
+        }
+    }
+
+    /// Returns the number of positions exactly the given depth below this position, counting
+    /// terminal positions above that depth as well
+    ///
+    /// This is useful for checking that moves are being generated correctly (often called
+    /// "perft").
+    pub fn count_leaves(&self, depth: usize) -> u64 {
+        if depth == 0 || self.is_terminal() {
+            return 1;
+        }
+
+        self.children().map(|child| child.count_leaves(depth - 1)).sum()
+    }
+}
+
+/// An iterator over the children of a position in a `GameTree`
+///
+/// Created with `GameTree::children`
+#[derive(Debug, Clone)]
+pub struct Children<'a> {
+    tree: &'a GameTree,
+    /// The index of the next child to generate
+    next: usize,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = GameTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let game = &self.tree.game;
+        if game.is_game_over() {
+            return None;
+        }
+
+        let valid_moves = game.valid_moves();
+        let pmove = match valid_moves.get(self.next) {
+            Some(&pos) => Move::Place(pos),
+            // Passing is only allowed when there are no other moves
+            None if valid_moves.is_empty() && self.next == 0 => Move::Pass,
+            None => return None,
+        };
+        self.next += 1;
+
+        let mut game = game.clone();
+        let _ = game.apply(pmove);
+        Some(GameTree {game, last_move: Some(pmove)})
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let game = &self.tree.game;
+        let total = if game.is_game_over() {
+            0
+        } else {
+            game.valid_moves().len().max(1)
+        };
+        let remaining = total.saturating_sub(self.next);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Children<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use crate::Variant;
+
+    #[test]
+    fn perft() {
+        // The well-known number of positions after each number of moves from the start of a game
+        let expected = [1, 4, 12, 56, 244, 1396, 8200, 55092];
+        let tree = GameTree::new(Reversi::new(Variant::Standard));
+        for (depth, &count) in expected.iter().enumerate() {
+            assert_eq!(tree.count_leaves(depth), count, "depth {}", depth);
+        }
+    }
+
+    #[test]
+    fn children_include_a_pass() {
+        let mut rng = StdRng::seed_from_u64(1557);
+        let mut passes = 0;
+        for _ in 0..20 {
+            let mut game = Reversi::new(Variant::Standard);
+            while !game.is_game_over() {
+                let tree = GameTree::new(game.clone());
+                let children: Vec<_> = tree.children().collect();
+                assert_eq!(tree.children().len(), children.len());
+
+                if game.valid_moves().is_empty() {
+                    assert_eq!(children.len(), 1);
+                    assert_eq!(children[0].last_move(), Some(Move::Pass));
+                    passes += 1;
+                } else {
+                    let moves: Vec<_> = children.iter().map(|child| child.last_move()).collect();
+                    let expected: Vec<_> = game.valid_moves().iter().map(|&pos| Some(Move::Place(pos))).collect();
+                    assert_eq!(moves, expected);
+                }
+
+                let child = &children[rng.gen_range(0, children.len())];
+                game = child.game().clone();
+            }
+
+            assert!(GameTree::new(game).children().next().is_none());
+        }
+        assert!(passes > 0);
+    }
+
+    #[test]
+    fn visit_depths() {
+        let tree = GameTree::new(Reversi::new(Variant::Standard));
+        let mut counts = [0; 4];
+        let mut last_depth = 0;
+        tree.visit(3, |position, depth| {
+            // Depth-first, with every parent visited right before its first child
+            assert!(depth <= last_depth + 1);
+            assert_eq!(position.last_move().is_none(), depth == 0);
+            last_depth = depth;
+            counts[depth] += 1;
+        });
+        assert_eq!(counts, [1, 4, 12, 56]);
+    }
+}
@@ -0,0 +1,643 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Piece, TilePos, Game};
+use crate::zobrist::splitmix64;
+
+/// The number of intersections on each side of the board by default
+pub const GO_DEFAULT_SIZE: usize = 9;
+
+/// The smallest number of intersections on each side of the board
+pub const GO_MIN_SIZE: usize = 5;
+
+/// The largest number of intersections on each side of the board
+pub const GO_MAX_SIZE: usize = 19;
+
+/// The number of points given to the second player by default to make up for moving second
+pub const GO_DEFAULT_KOMI: f64 = 6.5;
+
+/// The keys used to hash the stones on the board, indexed by point (row by row on the largest
+/// board) and then by piece
+const STONE_KEYS: [[u64; 2]; GO_MAX_SIZE * GO_MAX_SIZE] = generate_stone_keys();
+
+/// Generates the keys with a fixed seed so that hashes are the same every time the program runs
+const fn generate_stone_keys() -> [[u64; 2]; GO_MAX_SIZE * GO_MAX_SIZE] {
+    let mut keys = [[0; 2]; GO_MAX_SIZE * GO_MAX_SIZE];
+    let mut state = 0x60B0A4D5C0FFEE;
+    let mut i = 0;
+    while i < keys.len() {
+        let (next_state, x_key) = splitmix64(state);
+        let (next_state, o_key) = splitmix64(next_state);
+        keys[i] = [x_key, o_key];
+        state = next_state;
+        i += 1;
+    }
+
+    keys
+}
+
+/// The row and column offsets of the intersections next to an intersection
+static NEIGHBOR_OFFSETS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// A move in Go
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GoMove {
+    /// Place a stone on the given intersection
+    Place(TilePos),
+    /// Pass the turn to the other player. The game ends once both players pass in a row.
+    Pass,
+}
+
+impl fmt::Display for GoMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoMove::Place(pos) => write!(f, "{}", pos),
+            GoMove::Pass => write!(f, "pass"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid move: `{0}`. Expected an intersection like `C3` or `pass`")]
+pub struct ParseGoMoveError(pub String);
+
+impl FromStr for GoMove {
+    type Err = ParseGoMoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("pass") {
+            return Ok(GoMove::Pass);
+        }
+
+        s.parse().map(GoMove::Place).map_err(|_| ParseGoMoveError(s.to_string()))
+    }
+}
+
+/// How the score of each player is counted at the end of the game
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GoScoring {
+    /// The number of stones a player has on the board plus the empty intersections surrounded only
+    /// by their stones (Chinese rules)
+    #[default]
+    Area,
+    /// The number of empty intersections surrounded only by a player's stones plus the number of
+    /// stones they captured (Japanese rules)
+    Territory,
+}
+
+impl fmt::Display for GoScoring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoScoring::Area => write!(f, "area"),
+            GoScoring::Territory => write!(f, "territory"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Unknown scoring rule: `{0}`. Expected `area` or `territory`")]
+pub struct ParseGoScoringError(pub String);
+
+impl FromStr for GoScoring {
+    type Err = ParseGoScoringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "area" => Ok(GoScoring::Area),
+            "territory" => Ok(GoScoring::Territory),
+            _ => Err(ParseGoScoringError(s.to_string())),
+        }
+    }
+}
+
+/// Which earlier positions a move is not allowed to repeat
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GoKoRule {
+    /// A move cannot recreate the position from before the opponent's last move, which stops
+    /// players from recapturing back and forth in a ko forever
+    Simple,
+    /// A move cannot recreate any earlier position (positional superko), which also rules out
+    /// longer cycles like triple ko
+    #[default]
+    Superko,
+}
+
+impl fmt::Display for GoKoRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoKoRule::Simple => write!(f, "simple"),
+            GoKoRule::Superko => write!(f, "superko"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Unknown ko rule: `{0}`. Expected `simple` or `superko`")]
+pub struct ParseGoKoRuleError(pub String);
+
+impl FromStr for GoKoRule {
+    type Err = ParseGoKoRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "simple" => Ok(GoKoRule::Simple),
+            "superko" => Ok(GoKoRule::Superko),
+            _ => Err(ParseGoKoRuleError(s.to_string())),
+        }
+    }
+}
+
+/// The reason a stone cannot be placed on an intersection
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum GoIllegalMove {
+    #[error("That intersection is not on the board")]
+    OffBoard,
+    #[error("That intersection already has a stone on it")]
+    Occupied,
+    #[error("That stone would have no liberties")]
+    Suicide,
+    #[error("That move would retake the ko right away")]
+    Ko,
+    #[error("That move would repeat an earlier position")]
+    Superko,
+}
+
+/// The board after placing a stone, before it is committed to the game
+struct Placement {
+    stones: Vec<Option<Piece>>,
+    captured: usize,
+    hash: u64,
+}
+
+/// A game of Go on a small board
+///
+/// Players take turns placing stones on the intersections of the board. A group of connected
+/// stones is captured once every intersection next to it is filled by the opponent. Placing a
+/// stone that would be captured right away is not allowed, nor is repeating an earlier position
+/// (see `GoKoRule`). The game ends once both players pass in a row. Every stone left on the board
+/// is counted as alive, so dead stones should be captured before passing. `Piece::X` plays
+/// first, like black does.
+#[derive(Debug, Clone)]
+pub struct Go {
+    /// The number of intersections on each side of the board
+    size: usize,
+    /// The stones on the board, stored row by row
+    stones: Vec<Option<Piece>>,
+    /// The player whose turn it is currently
+    current_player: Piece,
+    /// The number of stones captured by each player, X first
+    captures: [usize; 2],
+    /// Every move made so far, in order
+    history: Vec<GoMove>,
+    /// The hash of the stones on the board before the first move and after every move
+    positions: Vec<u64>,
+    /// The points added to the second player's score
+    komi: f64,
+    /// How the score is counted at the end of the game
+    scoring: GoScoring,
+    /// Which earlier positions a move cannot repeat
+    ko_rule: GoKoRule,
+}
+
+impl Default for Go {
+    fn default() -> Self {
+        Self::new(GO_DEFAULT_SIZE)
+    }
+}
+
+impl Go {
+    /// Creates a new game on an empty board of the given size
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size is not between `GO_MIN_SIZE` and `GO_MAX_SIZE`.
+    pub fn new(size: usize) -> Self {
+        assert!((GO_MIN_SIZE..=GO_MAX_SIZE).contains(&size), "bug: unsupported board size {}", size);
+
+        Self {
+            size,
+            stones: vec![None; size * size],
+            current_player: Piece::X,
+            captures: [0; 2],
+            history: Vec::new(),
+            positions: vec![0],
+            komi: GO_DEFAULT_KOMI,
+            scoring: GoScoring::default(),
+            ko_rule: GoKoRule::default(),
+        }
+    }
+
+    /// Sets the points added to the second player's score
+    pub fn with_komi(mut self, komi: f64) -> Self {
+        self.komi = komi;
+        self
+    }
+
+    /// Sets how the score is counted at the end of the game
+    pub fn with_scoring(mut self, scoring: GoScoring) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Sets which earlier positions a move cannot repeat
+    pub fn with_ko_rule(mut self, ko_rule: GoKoRule) -> Self {
+        self.ko_rule = ko_rule;
+        self
+    }
+
+    /// Returns the number of intersections on each side of the board
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the points added to the second player's score
+    pub fn komi(&self) -> f64 {
+        self.komi
+    }
+
+    /// Returns how the score is counted at the end of the game
+    pub fn scoring(&self) -> GoScoring {
+        self.scoring
+    }
+
+    /// Returns the stone on the given intersection, or `None` if it is empty or off the board
+    pub fn stone(&self, pos: TilePos) -> Option<Piece> {
+        self.index(pos).and_then(|index| self.stones[index])
+    }
+
+    /// Returns the number of stones the given player has captured
+    pub fn captures(&self, player: Piece) -> usize {
+        self.captures[player_index(player)]
+    }
+
+    /// Returns every move made so far, in order
+    pub fn history(&self) -> &[GoMove] {
+        &self.history
+    }
+
+    /// Returns `Ok` if the current player can place a stone on the given intersection, or the
+    /// reason they cannot
+    pub fn check_move(&self, pos: TilePos) -> Result<(), GoIllegalMove> {
+        self.try_place(pos).map(|_| ())
+    }
+
+    /// Returns every move the current player can make, or no moves once the game is over
+    pub fn valid_moves(&self) -> Vec<GoMove> {
+        if self.is_over() {
+            return Vec::new();
+        }
+
+        let mut moves: Vec<_> = (0..self.size * self.size)
+            .map(|index| TilePos {row: index / self.size, col: index % self.size})
+            .filter(|&pos| self.check_move(pos).is_ok())
+            .map(GoMove::Place)
+            .collect();
+        moves.push(GoMove::Pass);
+        moves
+    }
+
+    /// Makes the given move for the current player, capturing any of the opponent's stones left
+    /// without liberties
+    ///
+    /// # Panics
+    ///
+    /// Panics if the game is over or a stone cannot be placed on the given intersection.
+    pub fn make_move(&mut self, pmove: GoMove) {
+        assert!(!self.is_over(), "bug: attempt to make the move `{}` after the game is over", pmove);
+
+        let player = self.current_player;
+        let hash = match pmove {
+            GoMove::Place(pos) => {
+                let placement = self.try_place(pos)
+                    .unwrap_or_else(|err| panic!("bug: attempt to make the move `{}`, which is not valid: {}", pmove, err));
+                self.stones = placement.stones;
+                self.captures[player_index(player)] += placement.captured;
+                placement.hash
+            },
+            GoMove::Pass => self.hash(),
+        };
+
+        self.positions.push(hash);
+        self.history.push(pmove);
+        self.current_player = player.opposite();
+    }
+
+    /// Returns true if both players passed in a row
+    pub fn is_over(&self) -> bool {
+        self.history.ends_with(&[GoMove::Pass, GoMove::Pass])
+    }
+
+    /// Returns the score of the given player according to the scoring rule, including komi
+    pub fn score(&self, player: Piece) -> f64 {
+        let territory = self.territory(player);
+        let points = match self.scoring {
+            GoScoring::Area => territory + self.stones.iter().filter(|&&stone| stone == Some(player)).count(),
+            GoScoring::Territory => territory + self.captures(player),
+        };
+
+        let komi = if player == Piece::O { self.komi } else { 0.0 };
+        points as f64 + komi
+    }
+
+    /// Returns the number of empty intersections that are surrounded only by the given player's
+    /// stones
+    pub fn territory(&self, player: Piece) -> usize {
+        let mut visited = vec![false; self.stones.len()];
+        let mut territory = 0;
+        for start in 0..self.stones.len() {
+            if visited[start] || self.stones[start].is_some() {
+                continue;
+            }
+
+            let mut region = 0;
+            let mut borders = (false, false);
+            let mut stack = vec![start];
+            visited[start] = true;
+            while let Some(index) = stack.pop() {
+                region += 1;
+                for next in self.neighbors(index) {
+                    match self.stones[next] {
+                        Some(stone) if stone == player => borders.0 = true,
+                        Some(_) => borders.1 = true,
+                        None if !visited[next] => {
+                            visited[next] = true;
+                            stack.push(next);
+                        },
+                        None => {},
+                    }
+                }
+            }
+
+            if borders == (true, false) {
+                territory += region;
+            }
+        }
+        territory
+    }
+
+    /// Returns the board that would result from the current player placing a stone on the given
+    /// intersection, or the reason they cannot
+    fn try_place(&self, pos: TilePos) -> Result<Placement, GoIllegalMove> {
+        let index = self.index(pos).ok_or(GoIllegalMove::OffBoard)?;
+        if self.stones[index].is_some() {
+            return Err(GoIllegalMove::Occupied);
+        }
+
+        let player = self.current_player;
+        let mut stones = self.stones.clone();
+        stones[index] = Some(player);
+        let mut hash = self.hash() ^ stone_key(index, player);
+
+        let mut captured = 0;
+        for next in self.neighbors(index) {
+            if stones[next] != Some(player.opposite()) {
+                continue;
+            }
+            let (group, liberties) = self.group(&stones, next);
+            if liberties == 0 {
+                for stone in group {
+                    stones[stone] = None;
+                    hash ^= stone_key(stone, player.opposite());
+                    captured += 1;
+                }
+            }
+        }
+
+        if self.group(&stones, index).1 == 0 {
+            return Err(GoIllegalMove::Suicide);
+        }
+
+        match self.ko_rule {
+            GoKoRule::Simple => {
+                let before_last_move = self.positions.len().checked_sub(2).map(|i| self.positions[i]);
+                if before_last_move == Some(hash) {
+                    return Err(GoIllegalMove::Ko);
+                }
+            },
+            GoKoRule::Superko => {
+                if self.positions.contains(&hash) {
+                    let is_ko = self.positions.len() >= 2 && self.positions[self.positions.len() - 2] == hash;
+                    return Err(if is_ko { GoIllegalMove::Ko } else { GoIllegalMove::Superko });
+                }
+            },
+        }
+
+        Ok(Placement {stones, captured, hash})
+    }
+
+    /// Returns the stones in the group containing the given stone, along with the number of
+    /// liberties (empty intersections next to the group) it has
+    fn group(&self, stones: &[Option<Piece>], start: usize) -> (Vec<usize>, usize) {
+        let player = stones[start];
+        let mut group = vec![start];
+        let mut liberties = Vec::new();
+        let mut i = 0;
+        while i < group.len() {
+            for next in self.neighbors(group[i]) {
+                if stones[next].is_none() {
+                    if !liberties.contains(&next) {
+                        liberties.push(next);
+                    }
+                } else if stones[next] == player && !group.contains(&next) {
+                    group.push(next);
+                }
+            }
+            i += 1;
+        }
+
+        (group, liberties.len())
+    }
+
+    /// Returns the hash of the stones currently on the board
+    fn hash(&self) -> u64 {
+        self.positions[self.positions.len() - 1]
+    }
+
+    /// Returns the index of the given intersection in `stones`, or `None` if it is off the board
+    fn index(&self, pos: TilePos) -> Option<usize> {
+        if pos.row < self.size && pos.col < self.size {
+            Some(pos.row * self.size + pos.col)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the indexes of the intersections next to the given one
+    fn neighbors(&self, index: usize) -> impl Iterator<Item=usize> {
+        let size = self.size;
+        let pos = TilePos {row: index / size, col: index % size};
+        NEIGHBOR_OFFSETS.iter()
+            .filter_map(move |&(drow, dcol)| pos.offset(drow, dcol, (size, size)))
+            .map(move |pos| pos.row * size + pos.col)
+    }
+}
+
+/// Returns the key for the given piece being on the intersection with the given index
+fn stone_key(index: usize, piece: Piece) -> u64 {
+    STONE_KEYS[index][player_index(piece)]
+}
+
+/// Returns the index of the given player in arrays with a value for each player
+fn player_index(player: Piece) -> usize {
+    match player {
+        Piece::X => 0,
+        Piece::O => 1,
+    }
+}
+
+impl Game for Go {
+    type Move = GoMove;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<GoMove> {
+        self.valid_moves()
+    }
+
+    fn play_move(&mut self, pmove: GoMove) {
+        self.make_move(pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.is_over()
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        if !self.is_over() {
+            return None;
+        }
+
+        let x = self.score(Piece::X);
+        let o = self.score(Piece::O);
+        if x > o {
+            Some(Piece::X)
+        } else if o > x {
+            Some(Piece::O)
+        } else {
+            None
+        }
+    }
+
+    fn is_pass(&self, pmove: &GoMove) -> bool {
+        *pmove == GoMove::Pass
+    }
+}
+
+/// Draws the board with the columns labeled with letters and the rows with numbers, marking the
+/// last stone placed with brackets
+impl fmt::Display for Go {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let last_move = match self.history.last() {
+            Some(&GoMove::Place(pos)) => Some(pos),
+            _ => None,
+        };
+
+        write!(f, "   ")?;
+        for col in 0..self.size {
+            write!(f, " {}", (b'A' + col as u8) as char)?;
+        }
+        writeln!(f)?;
+
+        for row in 0..self.size {
+            write!(f, "{:>2} ", row + 1)?;
+            for col in 0..self.size {
+                let pos = TilePos {row, col};
+                let before = if col > 0 && last_move == Some(TilePos {row, col: col - 1}) { ']' }
+                    else if last_move == Some(pos) { '[' }
+                    else { ' ' };
+                write!(f, "{}", before)?;
+                match self.stone(pos) {
+                    Some(piece) => write!(f, "{}", piece)?,
+                    None => write!(f, "·")?,
+                }
+            }
+            let after = if last_move == Some(TilePos {row, col: self.size - 1}) { "]" } else { "" };
+            writeln!(f, "{}", after)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a game with the given stones on the board, with X to move
+    fn setup(size: usize, x: &[&str], o: &[&str]) -> Go {
+        let mut game = Go::new(size);
+        let mut hash = 0;
+        for (stones, piece) in [(x, Piece::X), (o, Piece::O)] {
+            for stone in stones {
+                let index = game.index(stone.parse().unwrap()).unwrap();
+                game.stones[index] = Some(piece);
+                hash ^= stone_key(index, piece);
+            }
+        }
+        game.positions = vec![hash];
+        game
+    }
+
+    fn place(game: &mut Go, pos: &str) {
+        game.make_move(GoMove::Place(pos.parse().unwrap()));
+    }
+
+    #[test]
+    fn captures_group_without_liberties() {
+        let mut game = setup(5, &["A2", "B3"], &["A1", "B1", "B2"]);
+        place(&mut game, "C1");
+        assert_eq!(game.stone("B1".parse().unwrap()), Some(Piece::O));
+        game.make_move(GoMove::Pass);
+
+        place(&mut game, "C2");
+        assert_eq!(game.captures(Piece::X), 3);
+        assert_eq!(game.stone("A1".parse().unwrap()), None);
+        assert_eq!(game.stone("B2".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn suicide_is_illegal() {
+        let game = setup(5, &[], &["B1", "A2"]);
+        assert_eq!(game.check_move("A1".parse().unwrap()), Err(GoIllegalMove::Suicide));
+        assert_eq!(game.check_move("B1".parse().unwrap()), Err(GoIllegalMove::Occupied));
+    }
+
+    #[test]
+    fn ko_cannot_be_retaken_right_away() {
+        for ko_rule in [GoKoRule::Simple, GoKoRule::Superko] {
+            let mut game = setup(5, &["B1", "A2", "B3"], &["C1", "D2", "C3", "B2"]).with_ko_rule(ko_rule);
+            place(&mut game, "C2");
+            assert_eq!(game.captures(Piece::X), 1);
+            assert_eq!(game.check_move("B2".parse().unwrap()), Err(GoIllegalMove::Ko));
+
+            // After playing elsewhere, the ko can be retaken
+            place(&mut game, "E5");
+            place(&mut game, "E4");
+            assert_eq!(game.check_move("B2".parse().unwrap()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn area_and_territory_scoring() {
+        let x = ["B1", "B2", "B3", "B4", "B5"];
+        let o = ["D1", "D2", "D3", "D4", "D5"];
+        let mut game = setup(5, &x, &o).with_komi(0.5);
+        game.make_move(GoMove::Pass);
+        game.make_move(GoMove::Pass);
+        assert!(game.is_game_over());
+        assert!(game.valid_moves().is_empty());
+
+        assert_eq!(game.score(Piece::X), 10.0);
+        assert_eq!(game.score(Piece::O), 10.5);
+        assert_eq!(Game::winner(&game), Some(Piece::O));
+
+        let game = game.with_scoring(GoScoring::Territory).with_komi(0.0);
+        assert_eq!(game.score(Piece::X), 5.0);
+        assert_eq!(Game::winner(&game), None);
+    }
+}
@@ -0,0 +1,946 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Piece, TilePos, Game, Heuristic, negamax, Player};
+
+/// The size of the board that Gomoku is usually played on
+pub const GOMOKU_STANDARD_SIZE: usize = 15;
+/// The size of a Go board, which Gomoku is also often played on
+pub const GOMOKU_LARGE_SIZE: usize = 19;
+/// The smallest board supported, which leaves room for every opening restriction
+pub const GOMOKU_MIN_SIZE: usize = 9;
+/// The largest board supported, which is as large as a Go board
+pub const GOMOKU_MAX_SIZE: usize = GOMOKU_LARGE_SIZE;
+
+/// The number of pairs of stones a player has to capture to win Pente
+pub const PENTE_CAPTURES_TO_WIN: usize = 5;
+
+/// The four directions that lines can go in. The other four directions are the same lines walked
+/// backwards.
+const LINE_DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+/// The restrictions on where the first player (X, who plays black) can place their first stones,
+/// which make up for the advantage of moving first
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GomokuOpening {
+    /// Stones can be placed anywhere
+    #[default]
+    Free,
+    /// The first stone must be placed in the center, and the first player's second stone (the
+    /// third stone of the game) must be at least 3 intersections away from the center
+    Pro,
+    /// Same as `Pro`, but the first player's second stone must be at least 4 intersections away
+    /// from the center
+    LongPro,
+}
+
+impl GomokuOpening {
+    /// Returns how far from the center the first player's second stone must be placed, or `None`
+    /// if it can be placed anywhere
+    fn min_distance(self) -> Option<usize> {
+        match self {
+            GomokuOpening::Free => None,
+            GomokuOpening::Pro => Some(3),
+            GomokuOpening::LongPro => Some(4),
+        }
+    }
+}
+
+impl fmt::Display for GomokuOpening {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GomokuOpening::Free => write!(f, "free"),
+            GomokuOpening::Pro => write!(f, "pro"),
+            GomokuOpening::LongPro => write!(f, "long-pro"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Unknown opening rule: `{0}`. Expected `free`, `pro` or `long-pro`")]
+pub struct ParseGomokuOpeningError(pub String);
+
+impl FromStr for GomokuOpening {
+    type Err = ParseGomokuOpeningError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "free" => Ok(GomokuOpening::Free),
+            "pro" => Ok(GomokuOpening::Pro),
+            "long-pro" => Ok(GomokuOpening::LongPro),
+            _ => Err(ParseGomokuOpeningError(s.to_string())),
+        }
+    }
+}
+
+/// The rules a game on a Gomoku board is played with
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GomokuRules {
+    /// One stone per turn, and five or more in a row wins
+    #[default]
+    Gomoku,
+    /// After the first player's first stone, each turn places two stones, and six or more in a
+    /// row wins
+    Connect6,
+    /// Like `Gomoku`, but placing a stone on each side of exactly two of the opponent's stones in
+    /// a line captures them, and capturing `PENTE_CAPTURES_TO_WIN` pairs also wins
+    Pente,
+}
+
+impl GomokuRules {
+    /// Returns the number of stones in a row needed to win
+    pub fn win_length(self) -> usize {
+        match self {
+            GomokuRules::Gomoku | GomokuRules::Pente => 5,
+            GomokuRules::Connect6 => 6,
+        }
+    }
+
+    /// Returns the number of stones placed each turn, except for the first turn of the game,
+    /// which is always a single stone
+    pub fn stones_per_turn(self) -> usize {
+        match self {
+            GomokuRules::Gomoku | GomokuRules::Pente => 1,
+            GomokuRules::Connect6 => 2,
+        }
+    }
+}
+
+impl fmt::Display for GomokuRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GomokuRules::Gomoku => write!(f, "gomoku"),
+            GomokuRules::Connect6 => write!(f, "connect6"),
+            GomokuRules::Pente => write!(f, "pente"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Unknown rules: `{0}`. Expected `gomoku`, `connect6` or `pente`")]
+pub struct ParseGomokuRulesError(pub String);
+
+impl FromStr for GomokuRules {
+    type Err = ParseGomokuRulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gomoku" => Ok(GomokuRules::Gomoku),
+            "connect6" => Ok(GomokuRules::Connect6),
+            "pente" => Ok(GomokuRules::Pente),
+            _ => Err(ParseGomokuRulesError(s.to_string())),
+        }
+    }
+}
+
+/// A turn on a Gomoku board: the stones placed by the current player, in order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GomokuMove {
+    /// A single stone, as in every turn of Gomoku and Pente
+    Single(TilePos),
+    /// Two stones, as in every turn of Connect6 after the first
+    Pair(TilePos, TilePos),
+}
+
+impl GomokuMove {
+    /// Returns the stones placed by the move, in order
+    pub fn stones(self) -> Vec<TilePos> {
+        match self {
+            GomokuMove::Single(pos) => vec![pos],
+            GomokuMove::Pair(first, second) => vec![first, second],
+        }
+    }
+}
+
+/// Writes the intersections of the stones placed, e.g. "H8" or "H8,J9"
+impl fmt::Display for GomokuMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GomokuMove::Single(pos) => write!(f, "{}", pos),
+            GomokuMove::Pair(first, second) => write!(f, "{},{}", first, second),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid move: `{0}`. Expected one or two intersections like `H8` or `H8,J9`")]
+pub struct ParseGomokuMoveError(pub String);
+
+impl FromStr for GomokuMove {
+    type Err = ParseGomokuMoveError;
+
+    /// Parses one or two intersections, separated by `,` or whitespace
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseGomokuMoveError(s.to_string());
+
+        let stones = s.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|stone| !stone.is_empty())
+            .map(|stone| stone.parse::<TilePos>().map_err(|_| err()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match stones[..] {
+            [pos] => Ok(GomokuMove::Single(pos)),
+            [first, second] => Ok(GomokuMove::Pair(first, second)),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// A game of Gomoku (five in a row): players take turns placing stones on the intersections of a
+/// square board, and the first to get five or more stones in a row in any direction wins
+///
+/// `Piece::X` plays first, like black does. The game is a tie if the board fills up without
+/// either player winning. The same board is also used for the Connect6 and Pente variants (see
+/// `GomokuRules`).
+#[derive(Debug, Clone)]
+pub struct Gomoku {
+    /// The number of intersections on each side of the board
+    size: usize,
+    /// The stones on the board, stored row by row
+    stones: Vec<Option<Piece>>,
+    /// The restrictions on the first player's opening stones
+    opening: GomokuOpening,
+    /// The rules the game is played with
+    rules: GomokuRules,
+    /// The player whose turn it is currently
+    current_player: Piece,
+    /// Every stone placed so far, in order, including stones that were captured later
+    history: Vec<TilePos>,
+    /// The stones placed so far in the current turn, for rules with more than one stone per turn
+    turn_stones: Vec<TilePos>,
+    /// The stones placed in the last complete turn
+    last_turn: Vec<TilePos>,
+    /// The number of pairs of stones each player has captured (X first), in Pente
+    captures: [usize; 2],
+    /// The stones that won the game, in order along their line, or empty if no one has won by
+    /// getting enough stones in a row
+    winning_line: Vec<TilePos>,
+    /// The player who won the game, if any
+    winner: Option<Piece>,
+}
+
+impl Default for Gomoku {
+    fn default() -> Self {
+        Self::new(GOMOKU_STANDARD_SIZE, GomokuOpening::default())
+    }
+}
+
+impl Gomoku {
+    /// Creates a new game on an empty board with the given number of intersections on each side
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size is not between `GOMOKU_MIN_SIZE` and `GOMOKU_MAX_SIZE`.
+    pub fn new(size: usize, opening: GomokuOpening) -> Self {
+        assert!((GOMOKU_MIN_SIZE..=GOMOKU_MAX_SIZE).contains(&size),
+            "bug: the board size must be between {} and {}", GOMOKU_MIN_SIZE, GOMOKU_MAX_SIZE);
+
+        Self {
+            size,
+            stones: vec![None; size * size],
+            opening,
+            rules: GomokuRules::default(),
+            current_player: Piece::X,
+            history: Vec::new(),
+            turn_stones: Vec::new(),
+            last_turn: Vec::new(),
+            captures: [0; 2],
+            winning_line: Vec::new(),
+            winner: None,
+        }
+    }
+
+    /// Sets the rules the game is played with
+    pub fn with_rules(mut self, rules: GomokuRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Returns the number of intersections on each side of the board
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the restrictions on the first player's opening stones
+    pub fn opening(&self) -> GomokuOpening {
+        self.opening
+    }
+
+    /// Returns the rules the game is played with
+    pub fn rules(&self) -> GomokuRules {
+        self.rules
+    }
+
+    /// Returns the intersection in the middle of the board
+    pub fn center(&self) -> TilePos {
+        TilePos {row: self.size / 2, col: self.size / 2}
+    }
+
+    /// Returns the stone at the given position, or `None` if it is empty or off the board
+    pub fn stone(&self, pos: TilePos) -> Option<Piece> {
+        if pos.row >= self.size || pos.col >= self.size {
+            return None;
+        }
+
+        self.stones[pos.row * self.size + pos.col]
+    }
+
+    /// Returns every stone placed so far, in order, including stones that were captured later
+    pub fn history(&self) -> &[TilePos] {
+        &self.history
+    }
+
+    /// Returns the number of pairs of stones the given player has captured, which is always zero
+    /// unless the game is played with the Pente rules
+    pub fn captures(&self, player: Piece) -> usize {
+        self.captures[player_index(player)]
+    }
+
+    /// Returns the stones that won the game, in order along their line, or an empty slice if no
+    /// one has won (yet) by getting enough stones in a row
+    pub fn winning_line(&self) -> &[TilePos] {
+        &self.winning_line
+    }
+
+    /// Returns the number of stones the current player still has to place to finish their turn
+    pub fn stones_to_place(&self) -> usize {
+        // Only the first player's first turn has fewer stones, and that is the only turn that
+        // finishes with no stones in the history
+        let turn_length = if self.history.len() == self.turn_stones.len() {
+            1
+        } else {
+            self.rules.stones_per_turn()
+        };
+        let empty = self.stones.iter().filter(|stone| stone.is_none()).count();
+        (turn_length - self.turn_stones.len()).min(empty)
+    }
+
+    /// Returns true if the current player can place their next stone at the given position
+    pub fn can_place(&self, pos: TilePos) -> bool {
+        self.can_place_nth(pos, self.history.len())
+    }
+
+    /// Returns true if the current player could place the stone with the given index in the game
+    /// at the given position, if the stones before it did not change the board
+    fn can_place_nth(&self, pos: TilePos, index: usize) -> bool {
+        pos.row < self.size && pos.col < self.size
+            && self.stone(pos).is_none()
+            && !self.is_game_over()
+            && self.opening_allows(pos, index)
+    }
+
+    /// Returns true if the current player can make the given move, placing every stone they have
+    /// left to place this turn
+    pub fn is_valid_move(&self, pmove: GomokuMove) -> bool {
+        let next = self.history.len();
+        match pmove {
+            GomokuMove::Single(pos) => self.stones_to_place() == 1 && self.can_place(pos),
+            // Pairs are only used in Connect6, which has no captures to change the board between
+            // the two stones
+            GomokuMove::Pair(first, second) => self.stones_to_place() == 2 && first != second
+                && self.can_place_nth(first, next) && self.can_place_nth(second, next + 1),
+        }
+    }
+
+    /// Returns every move the current player can make, or no moves once the game is over
+    pub fn valid_moves(&self) -> Vec<GomokuMove> {
+        if self.is_game_over() {
+            return Vec::new();
+        }
+
+        let next = self.history.len();
+        let positions: Vec<_> = (0..self.size)
+            .flat_map(|row| (0..self.size).map(move |col| TilePos {row, col}))
+            .filter(|&pos| self.stone(pos).is_none())
+            .collect();
+        if self.stones_to_place() == 1 {
+            return positions.into_iter()
+                .filter(|&pos| self.opening_allows(pos, next))
+                .map(GomokuMove::Single)
+                .collect();
+        }
+
+        // Each pair of stones is only listed once, in an order that the opening rules allow
+        let mut moves = Vec::new();
+        for (i, &first) in positions.iter().enumerate() {
+            for &second in &positions[i + 1..] {
+                if self.opening_allows(first, next) && self.opening_allows(second, next + 1) {
+                    moves.push(GomokuMove::Pair(first, second));
+                } else if self.opening_allows(second, next) && self.opening_allows(first, next + 1) {
+                    moves.push(GomokuMove::Pair(second, first));
+                }
+            }
+        }
+        moves
+    }
+
+    /// Returns true if the opening restrictions allow the stone with the given index in the game
+    /// to be placed at the given position
+    fn opening_allows(&self, pos: TilePos, index: usize) -> bool {
+        let min_distance = match self.opening.min_distance() {
+            Some(min_distance) => min_distance,
+            None => return true,
+        };
+
+        let center = self.center();
+        // The first player's second stone comes after the second player's first turn
+        let second_stone = 1 + self.rules.stones_per_turn();
+        match index {
+            0 => pos == center,
+            index if index == second_stone => {
+                pos.row.abs_diff(center.row).max(pos.col.abs_diff(center.col)) >= min_distance
+            },
+            _ => true,
+        }
+    }
+
+    /// Makes the given move for the current player and passes the turn to the other player
+    ///
+    /// # Panics
+    ///
+    /// Panics if the move is not valid (see `is_valid_move`).
+    pub fn make_move(&mut self, pmove: GomokuMove) {
+        assert!(self.is_valid_move(pmove), "bug: attempt to make the move `{}`, which is not valid", pmove);
+
+        for pos in pmove.stones() {
+            // The rest of the turn is skipped if a stone wins the game
+            if self.is_game_over() {
+                break;
+            }
+            self.place(pos);
+        }
+    }
+
+    /// Places one stone for the current player at the given position, passing the turn to the
+    /// other player once the current player has placed every stone for their turn
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stone cannot be placed there (see `can_place`).
+    pub fn place(&mut self, pos: TilePos) {
+        assert!(self.can_place(pos), "bug: attempt to place a stone at `{}`, which is not a valid move", pos);
+
+        let player = self.current_player;
+        self.stones[pos.row * self.size + pos.col] = Some(player);
+        self.history.push(pos);
+        self.turn_stones.push(pos);
+
+        if self.rules == GomokuRules::Pente {
+            let bounds = (self.size, self.size);
+            for (drow, dcol) in self.capture_directions(pos, player) {
+                for steps in 1..=2 {
+                    if let Some(captured) = pos.offset(drow * steps, dcol * steps, bounds) {
+                        self.stones[captured.row * self.size + captured.col] = None;
+                    }
+                }
+                self.captures[player_index(player)] += 1;
+            }
+            if self.captures(player) >= PENTE_CAPTURES_TO_WIN {
+                self.winner = Some(player);
+            }
+        }
+
+        for &direction in &LINE_DIRECTIONS {
+            let line = self.line_through(pos, direction, player);
+            if line.len() >= self.rules.win_length() {
+                self.winning_line = line;
+                self.winner = Some(player);
+                break;
+            }
+        }
+
+        if self.stones_to_place() == 0 || self.is_game_over() {
+            self.last_turn = std::mem::take(&mut self.turn_stones);
+            self.current_player = player.opposite();
+        }
+    }
+
+    /// Returns the directions in which a stone of the given player at the given position would
+    /// capture a pair of the opponent's stones, by enclosing exactly two of them in a line
+    fn capture_directions(&self, pos: TilePos, player: Piece) -> Vec<(isize, isize)> {
+        let bounds = (self.size, self.size);
+        LINE_DIRECTIONS.iter()
+            .flat_map(|&(drow, dcol)| [(drow, dcol), (-drow, -dcol)])
+            .filter(|&(drow, dcol)| {
+                let stone_at = |steps: isize| pos.offset(drow * steps, dcol * steps, bounds)
+                    .and_then(|pos| self.stone(pos));
+                stone_at(1) == Some(player.opposite())
+                    && stone_at(2) == Some(player.opposite())
+                    && stone_at(3) == Some(player)
+            })
+            .collect()
+    }
+
+    /// Returns the unbroken line of the given player's stones that goes through the given
+    /// position in the given direction (and its opposite), in order along the line
+    ///
+    /// The position itself is always included, even if it is empty.
+    fn line_through(&self, pos: TilePos, direction: (isize, isize), player: Piece) -> Vec<TilePos> {
+        let (drow, dcol) = direction;
+        let backwards = self.run_from(pos, (-drow, -dcol), player);
+        let forwards = self.run_from(pos, direction, player);
+
+        let mut line: Vec<_> = backwards.into_iter().rev().collect();
+        line.push(pos);
+        line.extend(forwards);
+        line
+    }
+
+    /// Returns the stones of the given player that follow the given position in the given
+    /// direction without a gap
+    fn run_from(&self, pos: TilePos, direction: (isize, isize), player: Piece) -> Vec<TilePos> {
+        let bounds = (self.size, self.size);
+        let mut run = Vec::new();
+        let mut current = pos;
+        while let Some(next) = current.offset(direction.0, direction.1, bounds) {
+            if self.stone(next) != Some(player) {
+                break;
+            }
+            run.push(next);
+            current = next;
+        }
+        run
+    }
+
+    /// Returns true if the intersection past the end of the given run, going in the given
+    /// direction from the given position, is on the board and empty
+    fn is_open_after(&self, pos: TilePos, direction: (isize, isize), run_len: usize) -> bool {
+        let steps = run_len as isize + 1;
+        pos.offset(direction.0 * steps, direction.1 * steps, (self.size, self.size))
+            .is_some_and(|end| self.stone(end).is_none())
+    }
+
+    /// Returns true if every intersection of the board has a stone on it
+    pub fn is_full(&self) -> bool {
+        self.stones.iter().all(|stone| stone.is_some())
+    }
+}
+
+/// Returns the index of the given player in arrays with a value for each player
+fn player_index(player: Piece) -> usize {
+    match player {
+        Piece::X => 0,
+        Piece::O => 1,
+    }
+}
+
+impl Game for Gomoku {
+    type Move = GomokuMove;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<GomokuMove> {
+        self.valid_moves()
+    }
+
+    fn play_move(&mut self, pmove: GomokuMove) {
+        self.make_move(pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.winner.is_some() || self.is_full()
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        self.winner
+    }
+}
+
+/// Draws the board with the columns labeled with letters and the rows with numbers, marking the
+/// stones placed in the last turn (or so far in the current turn) with brackets
+impl fmt::Display for Gomoku {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let last_turn = if self.turn_stones.is_empty() { &self.last_turn } else { &self.turn_stones };
+        let marked = |row: usize, col: usize| last_turn.contains(&TilePos {row, col});
+
+        write!(f, "   ")?;
+        for col in 0..self.size {
+            write!(f, " {}", (b'A' + col as u8) as char)?;
+        }
+        writeln!(f)?;
+
+        for row in 0..self.size {
+            write!(f, "{:>2} ", row + 1)?;
+            for col in 0..self.size {
+                let pos = TilePos {row, col};
+                let after_marked = col > 0 && marked(row, col - 1);
+                let before = match (after_marked, marked(row, col)) {
+                    (true, true) => '|',
+                    (true, false) => ']',
+                    (false, true) => '[',
+                    (false, false) => ' ',
+                };
+                write!(f, "{}", before)?;
+                match self.stone(pos) {
+                    Some(piece) => write!(f, "{}", piece)?,
+                    None => write!(f, "·")?,
+                }
+            }
+            let after = if marked(row, self.size - 1) { "]" } else { "" };
+            writeln!(f, "{}", after)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Scores a run of stones in a row by how close it is to winning, given the number of stones in a
+/// row needed to win and how many of its ends are open (0, 1 or 2)
+///
+/// A run with both ends blocked can never win, so it is worth nothing. A run one stone short of
+/// winning with one open end is a threat that has to be answered right away, so it is worth as
+/// much as an open run two stones short of winning.
+fn run_score(len: usize, open_ends: usize, win_length: usize) -> i32 {
+    match (win_length.saturating_sub(len), open_ends) {
+        (0, _) => 100_000,
+        (_, 0) => 0,
+        (1, 2) => 10_000,
+        (1, _) | (2, 2) => 1_000,
+        (2, _) | (3, 2) => 100,
+        (3, _) | (4, 2) => 10,
+        _ => 1,
+    }
+}
+
+/// The score of each pair of stones captured in Pente
+const CAPTURE_SCORE: i32 = 2_000;
+
+/// An AI that plays on a Gomoku board by searching a few moves ahead (see `negamax`)
+///
+/// Only the stones near the stones already on the board are searched, ordered by the threats they
+/// make and block, so that forcing moves (e.g. making or blocking an open four) are searched first
+/// and everything else can be skipped. In Connect6, the pairs searched are made from the best of
+/// those stones. In Pente, captures are worth about as much as an open three.
+#[derive(Debug, Clone, Copy)]
+pub struct GomokuAi {
+    /// How many moves ahead to search
+    depth: usize,
+    /// The most moves searched in each position, keeping only those with the biggest threats
+    max_candidates: usize,
+}
+
+impl Default for GomokuAi {
+    fn default() -> Self {
+        Self {depth: 4, max_candidates: 10}
+    }
+}
+
+impl GomokuAi {
+    /// Creates an AI that searches the given number of moves ahead
+    pub fn new(depth: usize) -> Self {
+        Self {depth, ..Self::default()}
+    }
+
+    /// Returns how many moves ahead the AI searches
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Sets the most moves searched in each position. Searching fewer moves lets the AI search
+    /// further ahead in the same time, at the risk of missing a good move.
+    pub fn with_max_candidates(mut self, max_candidates: usize) -> Self {
+        self.max_candidates = max_candidates.max(1);
+        self
+    }
+
+    /// Returns the move the AI would play for the current player, or `None` if the game is over
+    pub fn choose_move(&self, game: &Gomoku) -> Option<GomokuMove> {
+        negamax(game, self.depth, self).map(|(pmove, _)| pmove)
+    }
+
+    /// Returns how much the lines the given player would make by placing a stone at the given
+    /// (empty) position are worth, along with the pairs it would capture
+    fn threat_score(game: &Gomoku, pos: TilePos, player: Piece) -> i32 {
+        let win_length = game.rules.win_length();
+        let lines: i32 = LINE_DIRECTIONS.iter().map(|&(drow, dcol)| {
+            let forwards = game.run_from(pos, (drow, dcol), player).len();
+            let backwards = game.run_from(pos, (-drow, -dcol), player).len();
+            let open_ends = game.is_open_after(pos, (drow, dcol), forwards) as usize
+                + game.is_open_after(pos, (-drow, -dcol), backwards) as usize;
+            run_score(forwards + backwards + 1, open_ends, win_length)
+        }).sum();
+
+        let captures = match game.rules {
+            GomokuRules::Pente => game.capture_directions(pos, player).len() as i32,
+            GomokuRules::Gomoku | GomokuRules::Connect6 => 0,
+        };
+        lines + captures * CAPTURE_SCORE
+    }
+
+    /// Returns how much the given player's runs of stones and captures are worth
+    fn runs_score(game: &Gomoku, player: Piece) -> i32 {
+        let win_length = game.rules.win_length();
+        let mut score = game.captures(player) as i32 * CAPTURE_SCORE;
+        for row in 0..game.size {
+            for col in 0..game.size {
+                let pos = TilePos {row, col};
+                if game.stone(pos) != Some(player) {
+                    continue;
+                }
+
+                for &(drow, dcol) in &LINE_DIRECTIONS {
+                    // Each run is only counted from its first stone
+                    let before = pos.offset(-drow, -dcol, (game.size, game.size));
+                    if before.is_some_and(|before| game.stone(before) == Some(player)) {
+                        continue;
+                    }
+
+                    let len = game.run_from(pos, (drow, dcol), player).len() + 1;
+                    let open_ends = game.is_open_after(pos, (drow, dcol), len - 1) as usize
+                        + game.is_open_after(pos, (-drow, -dcol), 0) as usize;
+                    score += run_score(len, open_ends, win_length);
+                }
+            }
+        }
+        score
+    }
+
+    /// Returns the best places for the current player's next stone, best first, keeping at most
+    /// `max_candidates` of them
+    fn candidate_stones(&self, game: &Gomoku) -> Vec<TilePos> {
+        let mut stones: Vec<_> = (0..game.size)
+            .flat_map(|row| (0..game.size).map(move |col| TilePos {row, col}))
+            .filter(|&pos| game.can_place(pos))
+            .collect();
+
+        // Stones far from every stone rarely matter, unless there are no places near any stone
+        // (e.g. on an empty board or because of the opening restrictions)
+        let bounds = (game.size, game.size);
+        let near_stone = |pos: TilePos| {
+            (-2..=2).any(|drow| (-2..=2).any(|dcol| {
+                pos.offset(drow, dcol, bounds).is_some_and(|near| game.stone(near).is_some())
+            }))
+        };
+        if stones.iter().any(|&pos| near_stone(pos)) {
+            stones.retain(|&pos| near_stone(pos));
+        }
+
+        let player = game.current_player;
+        let center = game.center();
+        let mut scored: Vec<_> = stones.iter().map(|&pos| {
+            // Making a threat is slightly better than blocking one of the same size, since the
+            // player is the one to move
+            let attack = Self::threat_score(game, pos, player);
+            let defense = Self::threat_score(game, pos, player.opposite());
+            let distance = pos.row.abs_diff(center.row) + pos.col.abs_diff(center.col);
+            (attack * 10 + defense * 9, distance, pos)
+        }).collect();
+        // Ties are broken by how close the stones are to the center
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        scored.into_iter().take(self.max_candidates).map(|(_, _, pos)| pos).collect()
+    }
+}
+
+impl Heuristic<Gomoku> for GomokuAi {
+    fn evaluate(&self, game: &Gomoku, player: Piece) -> i32 {
+        Self::runs_score(game, player) - Self::runs_score(game, player.opposite())
+    }
+
+    fn candidate_moves(&self, game: &Gomoku) -> Vec<GomokuMove> {
+        let stones = self.candidate_stones(game);
+        if game.stones_to_place() < 2 {
+            return stones.into_iter().map(GomokuMove::Single).collect();
+        }
+
+        // Listing every pair of stones would be far too slow, so only pairs of the best stones are
+        // searched. The second stone is scored after placing the first so that the two can make a
+        // line together.
+        let player = game.current_player;
+        let mut scored = Vec::new();
+        for (i, &first) in stones.iter().enumerate() {
+            let mut next = game.clone();
+            next.place(first);
+            let first_score = Self::threat_score(game, first, player) * 10
+                + Self::threat_score(game, first, player.opposite()) * 9;
+
+            for &second in &stones[i + 1..] {
+                let pmove = GomokuMove::Pair(first, second);
+                if !game.is_valid_move(pmove) {
+                    continue;
+                }
+
+                let second_score = if next.is_game_over() {
+                    0
+                } else {
+                    Self::threat_score(&next, second, player) * 10
+                        + Self::threat_score(&next, second, player.opposite()) * 9
+                };
+                scored.push((first_score + second_score, pmove));
+            }
+        }
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+
+        scored.into_iter().take(self.max_candidates).map(|(_, pmove)| pmove).collect()
+    }
+}
+
+/// Plays the move chosen by `GomokuAi::choose_move`
+impl Player<Gomoku> for GomokuAi {
+    fn choose_move(&mut self, game: &Gomoku) -> GomokuMove {
+        GomokuAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(s: &str) -> TilePos {
+        s.parse().unwrap()
+    }
+
+    fn single(s: &str) -> GomokuMove {
+        GomokuMove::Single(pos(s))
+    }
+
+    fn play(game: &mut Gomoku, moves: &str) {
+        for pmove in moves.split_whitespace() {
+            game.place(pos(pmove));
+        }
+    }
+
+    #[test]
+    fn five_in_a_row_wins() {
+        for moves in [
+            "A1 A2 B1 B2 C1 C2 D1 D2 E1",
+            "H1 A1 H2 A3 H3 A5 H4 A7 H5",
+            "C3 A1 D4 A3 E5 A5 F6 A7 G7",
+            "G3 A1 F4 A3 E5 A5 D6 A7 C7",
+        ] {
+            let mut game = Gomoku::default();
+            play(&mut game, moves);
+            assert!(game.is_game_over(), "{}", moves);
+            assert_eq!(Game::winner(&game), Some(Piece::X), "{}", moves);
+            assert_eq!(game.winning_line().len(), 5);
+            assert!(game.legal_moves().is_empty());
+        }
+    }
+
+    #[test]
+    fn broken_line_does_not_win() {
+        let mut game = Gomoku::default();
+        play(&mut game, "A1 O1 B1 O2 C1 E1 D1 O3 F1");
+        assert!(!game.is_game_over());
+        // Filling the gap makes six in a row, which also wins
+        play(&mut game, "O4 G1 O6 H1");
+        assert!(!game.is_game_over());
+    }
+
+    #[test]
+    fn overline_wins() {
+        let mut game = Gomoku::default();
+        play(&mut game, "A1 O1 B1 O2 C1 O3 E1 O5 F1 O7 D1");
+        assert_eq!(Game::winner(&game), Some(Piece::X));
+        assert_eq!(game.winning_line().len(), 6);
+    }
+
+    #[test]
+    fn pro_opening() {
+        let game = Gomoku::new(GOMOKU_STANDARD_SIZE, GomokuOpening::Pro);
+        assert_eq!(game.legal_moves(), [GomokuMove::Single(game.center())]);
+
+        let mut game = game;
+        play(&mut game, "H8 H9");
+        assert!(!game.can_place(pos("J10")));
+        assert!(!game.can_place(pos("F6")));
+        assert!(game.can_place(pos("K11")));
+        assert!(game.can_place(pos("E8")));
+        play(&mut game, "E8");
+        assert!(game.can_place(pos("J10")));
+
+        let mut game = Gomoku::new(GOMOKU_STANDARD_SIZE, GomokuOpening::LongPro);
+        play(&mut game, "H8 H9");
+        assert!(!game.can_place(pos("E8")));
+        assert!(game.can_place(pos("D8")));
+    }
+
+    #[test]
+    fn ai_wins_and_blocks() {
+        let ai = GomokuAi::new(2);
+
+        let mut game = Gomoku::default();
+        play(&mut game, "H8 A1 H9 A3 H10 A5 H11");
+        // O has to block X's four on one of its ends
+        let block = ai.choose_move(&game).unwrap();
+        assert!(block == single("H7") || block == single("H12"), "{}", block);
+
+        let mut game = Gomoku::default();
+        play(&mut game, "H8 A1 H9 A2 H10 A3 H11 A4");
+        assert!(matches!(ai.choose_move(&game), Some(win) if win == single("H7") || win == single("H12")));
+    }
+
+    #[test]
+    fn parse_moves() {
+        assert_eq!("H8".parse(), Ok(single("H8")));
+        assert_eq!("H8,J9".parse(), Ok(GomokuMove::Pair(pos("H8"), pos("J9"))));
+        assert_eq!(" H8 J9 ".parse(), Ok(GomokuMove::Pair(pos("H8"), pos("J9"))));
+        assert!("H8,J9,K10".parse::<GomokuMove>().is_err());
+        assert!("".parse::<GomokuMove>().is_err());
+    }
+
+    #[test]
+    fn connect6_turns() {
+        let mut game = Gomoku::default().with_rules(GomokuRules::Connect6);
+        // The first turn is a single stone, and every turn after that is two stones
+        assert_eq!(game.stones_to_place(), 1);
+        assert!(!game.is_valid_move("H8,J9".parse().unwrap()));
+        game.make_move(single("H8"));
+        assert_eq!(game.current_player(), Piece::O);
+        assert_eq!(game.stones_to_place(), 2);
+        assert!(!game.is_valid_move(single("A1")));
+        assert!(!game.is_valid_move("A1,A1".parse().unwrap()));
+
+        game.place(pos("A1"));
+        assert_eq!(game.current_player(), Piece::O);
+        assert_eq!(game.stones_to_place(), 1);
+        game.place(pos("A2"));
+        assert_eq!(game.current_player(), Piece::X);
+
+        // Five in a row is not enough to win, but six is
+        play(&mut game, "H9 H10 B1 B2 H11 H12 C1 C2");
+        assert!(!game.is_game_over());
+        game.make_move("H13,N1".parse().unwrap());
+        assert_eq!(Game::winner(&game), Some(Piece::X));
+        assert_eq!(game.winning_line().len(), 6);
+    }
+
+    #[test]
+    fn connect6_ai_wins_with_pair() {
+        let mut game = Gomoku::default().with_rules(GomokuRules::Connect6);
+        play(&mut game, "H8 A1 A3 H9 H10 A5 A7 H11 N1 A9 A11");
+        // X has four in a row and two stones to place, which is enough to make six
+        let mut next = game.clone();
+        next.make_move(GomokuAi::new(1).choose_move(&game).unwrap());
+        assert_eq!(Game::winner(&next), Some(Piece::X));
+    }
+
+    #[test]
+    fn pente_captures() {
+        let mut game = Gomoku::default().with_rules(GomokuRules::Pente);
+        play(&mut game, "H8 H9 A1 H10 H11");
+        // X enclosed O's pair on H9 and H10
+        assert_eq!(game.captures(Piece::X), 1);
+        assert_eq!(game.stone(pos("H9")), None);
+        assert_eq!(game.stone(pos("H10")), None);
+        // Moving into an enclosed position is safe
+        play(&mut game, "H9 A2 H10");
+        assert_eq!(game.stone(pos("H9")), Some(Piece::O));
+        assert_eq!(game.captures(Piece::X), 1);
+
+        // A single stone or three stones in a row cannot be captured
+        let mut game = Gomoku::default().with_rules(GomokuRules::Pente);
+        play(&mut game, "C3 D3 A1 E3 B1 F3 G3");
+        assert_eq!(game.captures(Piece::X), 0);
+
+        // Capturing enough pairs wins
+        let mut game = Gomoku::default().with_rules(GomokuRules::Pente);
+        game.captures = [PENTE_CAPTURES_TO_WIN - 1, 0];
+        play(&mut game, "H8 H9 A1 H10 H11");
+        assert_eq!(Game::winner(&game), Some(Piece::X));
+        assert!(game.winning_line().is_empty());
+    }
+}
@@ -1,7 +1,11 @@
-use crate::{Piece, TilePos};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Piece, TilePos, Direction};
 
 /// A non-empty grid with rows and columns of tables
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Grid {
     /// The tiles of the grid, stored row-by-row. Each tile is either empty (`None`), or contains
     /// a single `Piece`.
@@ -9,14 +13,21 @@ pub struct Grid {
     /// `tiles[r]` represents row r
     /// `tiles[r][c]` represents the tile at row r and column c
     tiles: [[Option<Piece>; 8]; 8],
+    /// The tiles that are permanently blocked and can never hold a piece
+    ///
+    /// A blocked tile is always empty in `tiles`. This allows for boards that are not perfectly
+    /// rectangular (e.g. octagonal boards).
+    blocked: [[bool; 8]; 8],
 }
 
 impl Grid {
     /// Returns true if the grid is completely full (no empty tiles left)
+    ///
+    /// Blocked tiles are never considered empty.
     pub fn is_full(&self) -> bool {
-        for row in &self.tiles {
-            for tile in row {
-                if tile.is_none() {
+        for (row_tiles, row_blocked) in self.tiles.iter().zip(&self.blocked) {
+            for (tile, &blocked) in row_tiles.iter().zip(row_blocked) {
+                if tile.is_none() && !blocked {
                     return false;
                 }
             }
@@ -25,6 +36,50 @@ impl Grid {
         true
     }
 
+    /// Returns the number of tiles that are empty and not blocked
+    pub fn open_count(&self) -> usize {
+        let mut count = 0;
+        for (row_tiles, row_blocked) in self.tiles.iter().zip(&self.blocked) {
+            for (tile, &blocked) in row_tiles.iter().zip(row_blocked) {
+                if tile.is_none() && !blocked {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Returns the number of tiles that are not blocked, whether or not they hold a piece
+    pub fn playable_count(&self) -> usize {
+        self.blocked.iter().flatten().filter(|&&blocked| !blocked).count()
+    }
+
+    /// Returns the top-left and bottom-right corners of the smallest rectangle that contains every
+    /// tile that is not blocked (e.g. the 6x6 center of the grid of `Reversi::with_board_size`)
+    ///
+    /// If every tile is blocked, the whole grid is returned.
+    pub fn playable_bounds(&self) -> (TilePos, TilePos) {
+        let mut bounds: Option<(TilePos, TilePos)> = None;
+        for (row, row_blocked) in self.blocked.iter().enumerate() {
+            for (col, &blocked) in row_blocked.iter().enumerate() {
+                if blocked {
+                    continue;
+                }
+
+                bounds = Some(match bounds {
+                    Some((first, last)) => (
+                        TilePos {row: first.row.min(row), col: first.col.min(col)},
+                        TilePos {row: last.row.max(row), col: last.col.max(col)},
+                    ),
+                    None => (TilePos {row, col}, TilePos {row, col}),
+                });
+            }
+        }
+
+        bounds.unwrap_or((TilePos {row: 0, col: 0}, TilePos {row: self.col_len() - 1, col: self.row_len() - 1}))
+    }
+
     /// Returns the length of each row (i.e. the number of columns)
     pub fn row_len(&self) -> usize {
         self.tiles[0].len()
@@ -41,17 +96,407 @@ impl Grid {
     }
 
     /// Returns the tile at the given position
+    ///
+    /// Blocked tiles never contain a piece. Use `is_blocked` to tell them apart from empty tiles.
     pub fn tile(&self, pos: TilePos) -> Option<Piece> {
         self.tiles[pos.row][pos.col]
     }
 
+    /// Returns true if the tile at the given position is blocked
+    pub fn is_blocked(&self, pos: TilePos) -> bool {
+        self.blocked[pos.row][pos.col]
+    }
+
+    /// Returns true if the tile at the given position is empty and not blocked (i.e. a piece
+    /// could be placed there)
+    pub fn is_open(&self, pos: TilePos) -> bool {
+        self.tile(pos).is_none() && !self.is_blocked(pos)
+    }
+
+    /// Permanently blocks the tile at the given position so that no piece can be placed on it
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the position is outside the boundary of the board or if the tile
+    /// already contains a piece
+    pub fn block(&mut self, pos: TilePos) {
+        assert!(self.tile(pos).is_none(), "bug: attempt to block a tile that contains a piece");
+        self.blocked[pos.row][pos.col] = true;
+    }
+
     /// Places the given piece on the tile at the given position, overwriting the piece that was
     /// previously at that position (if any)
     ///
     /// # Panics
     ///
-    /// This method panics if the position is outside the boundary of the board
+    /// This method panics if the position is outside the boundary of the board or if the tile
+    /// is blocked
     pub fn place(&mut self, pos: TilePos, piece: Piece) {
+        assert!(!self.is_blocked(pos), "bug: attempt to place a piece on a blocked tile");
         self.tiles[pos.row][pos.col] = Some(piece);
     }
+
+    /// Removes the piece on the tile at the given position (if any), leaving the tile empty
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the position is outside the boundary of the board
+    pub fn clear(&mut self, pos: TilePos) {
+        self.tiles[pos.row][pos.col] = None;
+    }
+
+    /// Returns an iterator over the positions of the tiles starting from (but not including) the
+    /// given position and walking in the given direction until the edge of the grid is reached
+    pub fn ray(&self, pos: TilePos, direction: Direction) -> Ray {
+        Ray {
+            pos,
+            delta: direction.delta(),
+            nrows: self.col_len(),
+            ncols: self.row_len(),
+        }
+    }
+
+    /// Returns the tiles that can be reached from the tile at the given position by only stepping
+    /// (in any of the 8 directions) onto tiles with the same piece, including the tile itself
+    ///
+    /// Returns an empty list if the tile is empty.
+    pub fn connected_tiles(&self, pos: TilePos) -> Vec<TilePos> {
+        let mut visited = [[false; 8]; 8];
+        self.flood_fill(pos, &mut visited)
+    }
+
+    /// Returns every group of connected tiles with the given piece (see `connected_tiles`),
+    /// ordered by the first tile of each group in row-by-row order
+    pub fn connected_groups(&self, piece: Piece) -> Vec<Vec<TilePos>> {
+        let mut visited = [[false; 8]; 8];
+        let mut groups = Vec::new();
+        for row in 0..self.col_len() {
+            for col in 0..self.row_len() {
+                let pos = TilePos {row, col};
+                if self.tile(pos) == Some(piece) && !visited[row][col] {
+                    groups.push(self.flood_fill(pos, &mut visited));
+                }
+            }
+        }
+
+        groups
+    }
+
+    /// Returns the tiles connected to the given position that have not been visited yet, marking
+    /// each of them as visited
+    fn flood_fill(&self, pos: TilePos, visited: &mut [[bool; 8]; 8]) -> Vec<TilePos> {
+        let piece = match self.tile(pos) {
+            Some(piece) => piece,
+            None => return Vec::new(),
+        };
+
+        let bounds = (self.col_len(), self.row_len());
+        let mut tiles = Vec::new();
+        let mut stack = vec![pos];
+        visited[pos.row][pos.col] = true;
+        while let Some(pos) = stack.pop() {
+            tiles.push(pos);
+            for direction in Direction::ALL {
+                let (drow, dcol) = direction.delta();
+                let next = match pos.offset(drow, dcol, bounds) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                if self.tile(next) == Some(piece) && !visited[next.row][next.col] {
+                    visited[next.row][next.col] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        tiles
+    }
+
+    /// Returns every tile that differs between this grid and the other grid, along with the
+    /// contents of that tile in this grid and in the other grid (in that order)
+    ///
+    /// Tiles are returned in row-by-row order.
+    pub fn diff(&self, other: &Grid) -> Vec<(TilePos, Option<Piece>, Option<Piece>)> {
+        let mut changes = Vec::new();
+        for (row, (row_tiles, other_row_tiles)) in self.tiles.iter().zip(&other.tiles).enumerate() {
+            for (col, (&tile, &other_tile)) in row_tiles.iter().zip(other_row_tiles).enumerate() {
+                if tile != other_tile {
+                    changes.push((TilePos {row, col}, tile, other_tile));
+                }
+            }
+        }
+
+        changes
+    }
+
+    /// Returns a copy of the grid rotated 90 degrees clockwise
+    pub fn rotate90(&self) -> Self {
+        Self {
+            tiles: rotate90(&self.tiles),
+            blocked: rotate90(&self.blocked),
+        }
+    }
+
+    /// Returns a copy of the grid mirrored from left to right (i.e. the order of the columns is
+    /// reversed)
+    pub fn mirror_horizontal(&self) -> Self {
+        let mut mirrored = self.clone();
+        for row in &mut mirrored.tiles {
+            row.reverse();
+        }
+        for row in &mut mirrored.blocked {
+            row.reverse();
+        }
+
+        mirrored
+    }
+
+    /// Returns a copy of the grid mirrored from top to bottom (i.e. the order of the rows is
+    /// reversed)
+    pub fn mirror_vertical(&self) -> Self {
+        let mut mirrored = self.clone();
+        mirrored.tiles.reverse();
+        mirrored.blocked.reverse();
+
+        mirrored
+    }
+
+    /// Returns all 8 symmetries of the grid (the 4 rotations and the 4 rotations of its mirror
+    /// image). The first symmetry is always a copy of this grid.
+    pub fn symmetries(&self) -> [Self; 8] {
+        let r0 = self.clone();
+        let r1 = r0.rotate90();
+        let r2 = r1.rotate90();
+        let r3 = r2.rotate90();
+
+        let m0 = r0.mirror_horizontal();
+        let m1 = m0.rotate90();
+        let m2 = m1.rotate90();
+        let m3 = m2.rotate90();
+
+        [r0, r1, r2, r3, m0, m1, m2, m3]
+    }
+
+    /// Returns the canonical form of this grid: the lexicographically smallest of its symmetries
+    ///
+    /// Two grids that are rotations or reflections of each other always have the same canonical
+    /// form.
+    pub fn canonical(&self) -> Self {
+        let index = self.canonical_symmetry();
+        self.symmetries()[index].clone()
+    }
+
+    /// Returns the index (into the array returned by `symmetries`) of the canonical form of this
+    /// grid
+    pub fn canonical_symmetry(&self) -> usize {
+        let symmetries = self.symmetries();
+        (0..symmetries.len()).min_by_key(|&i| &symmetries[i])
+            .expect("bug: a grid always has at least one symmetry")
+    }
+
+    /// Returns where the tile at the given position ends up in the symmetry with the given index
+    /// (using the same order as `symmetries`)
+    pub fn map_to_symmetry(&self, pos: TilePos, index: usize) -> TilePos {
+        let n = self.col_len();
+        // The mirrored symmetries mirror first and then rotate
+        let mut pos = if index >= 4 {
+            TilePos {row: pos.row, col: n - 1 - pos.col}
+        } else {
+            pos
+        };
+        for _ in 0..index % 4 {
+            pos = TilePos {row: pos.col, col: n - 1 - pos.row};
+        }
+
+        pos
+    }
+
+    /// Returns the position of the tile that ends up at the given position in the symmetry with
+    /// the given index (the inverse of `map_to_symmetry`)
+    pub fn map_from_symmetry(&self, pos: TilePos, index: usize) -> TilePos {
+        if index >= 4 {
+            // Every mirrored symmetry is a reflection, so it is its own inverse
+            self.map_to_symmetry(pos, index)
+        } else {
+            // Undo a rotation by rotating the rest of the way around
+            self.map_to_symmetry(pos, (4 - index) % 4)
+        }
+    }
+
+    /// Returns the grid as a string of 64 characters, row by row, with `X` and `O` for pieces,
+    /// `-` for empty tiles and `#` for blocked tiles
+    ///
+    /// The string can be parsed back into a grid with `str::parse`.
+    pub fn to_position_string(&self) -> String {
+        let mut position = String::new();
+        for (row_tiles, row_blocked) in self.tiles.iter().zip(&self.blocked) {
+            for (tile, &blocked) in row_tiles.iter().zip(row_blocked) {
+                position.push(match tile {
+                    Some(Piece::X) => 'X',
+                    Some(Piece::O) => 'O',
+                    None if blocked => '#',
+                    None => '-',
+                });
+            }
+        }
+
+        position
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid position: `{0}`. Expected 64 tiles of `X`, `O`, `-` or `#`")]
+pub struct ParseGridError(pub String);
+
+impl FromStr for Grid {
+    type Err = ParseGridError;
+
+    /// Parses a grid in the format returned by `to_position_string`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseGridError(s.to_string());
+        if s.chars().count() != 64 {
+            return Err(err());
+        }
+
+        let mut grid = Grid::default();
+        for (i, ch) in s.chars().enumerate() {
+            let pos = TilePos {row: i / 8, col: i % 8};
+            match ch {
+                'X' => grid.place(pos, Piece::X),
+                'O' => grid.place(pos, Piece::O),
+                '#' => grid.block(pos),
+                '-' => {},
+                _ => return Err(err()),
+            }
+        }
+
+        Ok(grid)
+    }
+}
+
+/// Returns a copy of the given square array rotated 90 degrees clockwise
+fn rotate90<T: Copy + Default>(values: &[[T; 8]; 8]) -> [[T; 8]; 8] {
+    let n = values.len();
+    let mut rotated = [[T::default(); 8]; 8];
+    for (row, row_values) in values.iter().enumerate() {
+        for (col, &value) in row_values.iter().enumerate() {
+            // The top row becomes the rightmost column
+            rotated[col][n - 1 - row] = value;
+        }
+    }
+
+    rotated
+}
+
+/// An iterator over the positions of the tiles in a single direction on the grid
+///
+/// Created with `Grid::ray`
+#[derive(Debug, Clone)]
+pub struct Ray {
+    /// The last position yielded (or the starting position if nothing has been yielded yet)
+    pos: TilePos,
+    /// The change in (row, col) for each step
+    delta: (isize, isize),
+    nrows: usize,
+    ncols: usize,
+}
+
+impl Iterator for Ray {
+    type Item = TilePos;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (drow, dcol) = self.delta;
+        // Stops once we hit one of the boundaries of the grid
+        self.pos = self.pos.offset(drow, dcol, (self.nrows, self.ncols))?;
+        Some(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A grid with no symmetries of its own, so every symmetry of it is a different grid
+    fn asymmetric_grid() -> Grid {
+        "XX------\
+         -O------\
+         ---XO---\
+         ---OX#--\
+         --------\
+         ------O-\
+         --------\
+         #-------".parse().unwrap()
+    }
+
+    #[test]
+    fn four_rotations_are_the_identity() {
+        let grid = asymmetric_grid();
+        let rotated = grid.rotate90();
+        assert_ne!(rotated, grid);
+        assert_eq!(rotated.rotate90().rotate90().rotate90(), grid);
+        assert_eq!(grid.mirror_horizontal().mirror_horizontal(), grid);
+        assert_eq!(grid.mirror_vertical().mirror_vertical(), grid);
+        // A half turn is the same as mirroring in both directions
+        assert_eq!(rotated.rotate90(), grid.mirror_horizontal().mirror_vertical());
+    }
+
+    #[test]
+    fn symmetries_share_a_canonical_form() {
+        let grid = asymmetric_grid();
+        let symmetries = grid.symmetries();
+        assert_eq!(symmetries[0], grid);
+        for (i, symmetry) in symmetries.iter().enumerate() {
+            for other in &symmetries[i + 1..] {
+                assert_ne!(symmetry, other);
+            }
+        }
+
+        let canonical = grid.canonical();
+        for symmetry in &symmetries {
+            assert_eq!(symmetry.canonical(), canonical);
+        }
+        assert_eq!(symmetries[grid.canonical_symmetry()], canonical);
+    }
+
+    #[test]
+    fn map_positions_to_and_from_symmetries() {
+        let grid = asymmetric_grid();
+        for (index, symmetry) in grid.symmetries().iter().enumerate() {
+            for row in 0..8 {
+                for col in 0..8 {
+                    let pos = TilePos {row, col};
+                    let mapped = grid.map_to_symmetry(pos, index);
+                    assert_eq!(grid.map_from_symmetry(mapped, index), pos);
+                    assert_eq!(symmetry.tile(mapped), grid.tile(pos));
+                    assert_eq!(symmetry.is_blocked(mapped), grid.is_blocked(pos));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn diff_place_flip_and_remove() {
+        let before = asymmetric_grid();
+        assert!(before.diff(&before).is_empty());
+
+        let mut after = before.clone();
+        let placed = TilePos {row: 2, col: 2};
+        let flipped = TilePos {row: 3, col: 3};
+        let removed = TilePos {row: 5, col: 6};
+        after.place(placed, Piece::O);
+        after.place(flipped, Piece::X);
+        after.clear(removed);
+
+        assert_eq!(before.diff(&after), vec![
+            (placed, None, Some(Piece::O)),
+            (flipped, Some(Piece::O), Some(Piece::X)),
+            (removed, Some(Piece::O), None),
+        ]);
+        // Diffing in the other direction swaps the before and after tiles
+        assert_eq!(after.diff(&before), vec![
+            (placed, Some(Piece::O), None),
+            (flipped, Some(Piece::X), Some(Piece::O)),
+            (removed, None, Some(Piece::O)),
+        ]);
+    }
 }
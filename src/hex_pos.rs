@@ -0,0 +1,66 @@
+/// The offsets of (q, r) to each of the six neighbors of a hex, going counterclockwise starting
+/// from the neighbor to the right
+pub const HEX_DIRECTIONS: [(isize, isize); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+/// Represents the position of a hex on a board of hexagons (or of holes that each have six
+/// neighbors), in axial coordinates
+///
+/// Moving right increases `q`, and moving down a row increases `r` (and shifts half a hex to the
+/// left). The third coordinate, `s`, is implied by `q + r + s = 0` and increases moving up and to
+/// the left. The center of the board is usually at (0, 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HexPos {
+    pub q: isize,
+    pub r: isize,
+}
+
+impl HexPos {
+    /// Returns the third coordinate of the position, which is implied by the other two
+    pub fn s(self) -> isize {
+        -self.q - self.r
+    }
+
+    /// Returns the position the given number of hexes away from this one in the given direction,
+    /// which is one of `HEX_DIRECTIONS`
+    pub fn offset(self, direction: (isize, isize), distance: isize) -> HexPos {
+        let (dq, dr) = direction;
+        HexPos {q: self.q + dq * distance, r: self.r + dr * distance}
+    }
+
+    /// Returns the six positions next to this one, in the order of `HEX_DIRECTIONS`
+    pub fn neighbors(self) -> [HexPos; 6] {
+        HEX_DIRECTIONS.map(|direction| self.offset(direction, 1))
+    }
+
+    /// Returns the number of steps it takes to get from this position to the other one
+    pub fn distance(self, other: HexPos) -> usize {
+        let dq = (self.q - other.q).unsigned_abs();
+        let dr = (self.r - other.r).unsigned_abs();
+        let ds = (self.s() - other.s()).unsigned_abs();
+        dq.max(dr).max(ds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbors_are_one_step_away() {
+        let pos = HexPos {q: 2, r: -3};
+        for neighbor in pos.neighbors().iter() {
+            assert_eq!(pos.distance(*neighbor), 1);
+            assert_eq!(neighbor.q + neighbor.r + neighbor.s(), 0);
+        }
+        assert_eq!(pos.offset(HEX_DIRECTIONS[3], 2), HexPos {q: 0, r: -3});
+    }
+
+    #[test]
+    fn distance_along_and_across_lines() {
+        let center = HexPos {q: 0, r: 0};
+        assert_eq!(center.distance(center), 0);
+        assert_eq!(center.distance(HexPos {q: 4, r: -4}), 4);
+        assert_eq!(center.distance(HexPos {q: 2, r: 3}), 5);
+        assert_eq!(HexPos {q: -1, r: 2}.distance(HexPos {q: 3, r: -2}), 4);
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt::Write;
+
+use crate::{Reversi, Piece, TerminationReason, SvgOptions, render_svg};
+
+/// The styles of the page written by `render_html`
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em auto; max-width: 40em; color: #222; }
+h1 { font-size: 1.4em; }
+.controls { display: flex; align-items: center; gap: 0.5em; margin: 1em 0; }
+.controls input { flex: 1; }
+.caption { font-weight: bold; }
+ol { columns: 4; padding-left: 2em; }
+li { cursor: pointer; }
+li.current { background: #cfe8d6; }
+";
+
+/// The script that steps through the positions of the page written by `render_html`
+const SCRIPT: &str = "\
+const frames = document.querySelectorAll('.frame');
+const moves = document.querySelectorAll('li[data-move]');
+const slider = document.getElementById('move');
+function show(n) {
+  n = Math.max(0, Math.min(frames.length - 1, n));
+  frames.forEach((frame, i) => frame.hidden = i !== n);
+  moves.forEach(item => item.classList.toggle('current', Number(item.dataset.move) === n));
+  slider.value = n;
+}
+slider.addEventListener('input', () => show(Number(slider.value)));
+document.getElementById('first').addEventListener('click', () => show(0));
+document.getElementById('prev').addEventListener('click', () => show(Number(slider.value) - 1));
+document.getElementById('next').addEventListener('click', () => show(Number(slider.value) + 1));
+document.getElementById('last').addEventListener('click', () => show(frames.length - 1));
+moves.forEach(item => item.addEventListener('click', () => show(Number(item.dataset.move))));
+document.addEventListener('keydown', event => {
+  const n = Number(slider.value);
+  switch (event.key) {
+    case 'ArrowLeft': show(n - 1); break;
+    case 'ArrowRight': show(n + 1); break;
+    case 'Home': show(0); break;
+    case 'End': show(frames.length - 1); break;
+  }
+});
+show(frames.length - 1);
+";
+
+/// Renders every position of the given game as a standalone HTML page that can be stepped
+/// through move by move, with a slider, buttons, the arrow keys or by clicking on the list of
+/// moves
+///
+/// The positions are replayed from the history of the game (see `Reversi::at_move`) and drawn
+/// with `render_svg`, so the page does not need anything other than a web browser to view. It
+/// starts at the last position, which is usually the end of the game.
+pub fn render_html(game: &Reversi, options: &SvgOptions) -> String {
+    let history = game.history();
+    let (x_score, o_score) = game.scores();
+
+    // Writing to a String never fails, so the results of `write!` are ignored
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Reversi game</title>\n");
+    let _ = write!(html, "<style>\n{}</style>\n</head>\n<body>\n", STYLE);
+    let _ = writeln!(html, "<h1>Reversi game ({} variant)</h1>", game.variant());
+    let _ = writeln!(html, "<p>{}</p>", describe_result(game, x_score, o_score));
+
+    html.push_str("<div class=\"controls\">\n");
+    html.push_str("<button id=\"first\">&#x23EE;</button>\n<button id=\"prev\">&#x25C0;</button>\n");
+    let _ = writeln!(html, "<input id=\"move\" type=\"range\" min=\"0\" max=\"{}\" value=\"{}\">",
+        history.len(), history.len());
+    html.push_str("<button id=\"next\">&#x25B6;</button>\n<button id=\"last\">&#x23ED;</button>\n");
+    html.push_str("</div>\n");
+
+    for n in 0..=history.len() {
+        let position = game.at_move(n);
+        let (x_score, o_score) = position.scores();
+        let caption = match n {
+            0 => "Start".to_string(),
+            // The player who made the move is the one who was to move before it
+            _ => format!("{}. {} {}", n, player_name(game.at_move(n - 1).current_player()), history[n - 1]),
+        };
+
+        let _ = writeln!(html, "<div class=\"frame\" hidden>");
+        let _ = writeln!(html, "<p><span class=\"caption\">{}</span> &mdash; X {} | O {}</p>",
+            caption, x_score, o_score);
+        html.push_str(&render_svg(&position, options));
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("<ol>\n");
+    for (i, pmove) in history.iter().enumerate() {
+        let _ = writeln!(html, "<li data-move=\"{}\">{}</li>", i + 1, pmove);
+    }
+    html.push_str("</ol>\n");
+
+    let _ = write!(html, "<script>\n{}</script>\n</body>\n</html>\n", SCRIPT);
+    html
+}
+
+/// Returns the name of the given piece as it is written in the page (the glyph used by its
+/// `Display` impl depends on the terminal theme)
+fn player_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "X",
+        Piece::O => "O",
+    }
+}
+
+/// Describes how the game ended, or that it is still in progress
+fn describe_result(game: &Reversi, x_score: u32, o_score: u32) -> String {
+    let result = match game.winner() {
+        _ if !game.is_game_over() => format!("Game in progress after {} moves", game.history().len()),
+        Some(winner) => format!("{} wins, X {} | O {}", player_name(winner), x_score, o_score),
+        None => format!("Draw, X {} | O {}", x_score, o_score),
+    };
+
+    match game.termination() {
+        Some(TerminationReason::Resignation(piece)) => format!("{} ({} resigned)", result, player_name(piece)),
+        Some(TerminationReason::TimeForfeit(piece)) => format!("{} ({} ran out of time)", result, player_name(piece)),
+        _ => result,
+    }
+}
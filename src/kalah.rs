@@ -0,0 +1,358 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Piece, Game, Heuristic, negamax, Player};
+
+/// The number of pits on each player's side of the board, not counting their store
+pub const KALAH_PITS: usize = 6;
+
+/// The number of seeds in each pit at the start of a standard game
+pub const KALAH_DEFAULT_SEEDS: usize = 4;
+
+/// The number of places that hold seeds: each player's pits followed by their store
+const HOLES: usize = 2 * (KALAH_PITS + 1);
+
+/// A move in Kalah: the pit (from 1 to `KALAH_PITS`, counting from the player's left) whose seeds
+/// are sown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KalahMove(pub usize);
+
+impl fmt::Display for KalahMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid pit: `{0}`. Expected a number from 1 to 6")]
+pub struct ParseKalahMoveError(pub String);
+
+impl FromStr for KalahMove {
+    type Err = ParseKalahMoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse() {
+            Ok(pit) if (1..=KALAH_PITS).contains(&pit) => Ok(KalahMove(pit)),
+            _ => Err(ParseKalahMoveError(s.to_string())),
+        }
+    }
+}
+
+/// A game of Kalah, the most common Mancala game in the West
+///
+/// Each player has a row of pits and a store to their right. On their turn, a player picks up
+/// every seed in one of their pits and sows them one at a time into the following pits and
+/// stores counterclockwise, skipping their opponent's store. If the last seed lands in their own
+/// store, they move again. If it lands in one of their own empty pits across from a pit with
+/// seeds in it, they capture both pits into their store. Once either player has no seeds left in
+/// their pits, the other player puts their remaining seeds in their store and the player with the
+/// most seeds in their store wins. `Piece::X` moves first.
+#[derive(Debug, Clone)]
+pub struct Kalah {
+    /// The seeds in each hole: X's pits from left to right, X's store, then O's pits from left to
+    /// right (from O's side of the board) and O's store. Sowing goes through them in order.
+    holes: [usize; HOLES],
+    /// The player whose turn it is currently
+    current_player: Piece,
+    /// The last move made, if any
+    last_move: Option<(Piece, KalahMove)>,
+}
+
+impl Default for Kalah {
+    fn default() -> Self {
+        Self::new(KALAH_DEFAULT_SEEDS)
+    }
+}
+
+impl Kalah {
+    /// Creates a new game with the given number of seeds in each pit
+    pub fn new(seeds: usize) -> Self {
+        let mut holes = [seeds; HOLES];
+        holes[store_index(Piece::X)] = 0;
+        holes[store_index(Piece::O)] = 0;
+
+        Self {
+            holes,
+            current_player: Piece::X,
+            last_move: None,
+        }
+    }
+
+    /// Returns the number of seeds in the given pit (from 1 to `KALAH_PITS`) of the given player
+    pub fn seeds(&self, player: Piece, pit: usize) -> usize {
+        self.holes[pit_index(player, pit)]
+    }
+
+    /// Returns the number of seeds in the given player's store
+    pub fn store(&self, player: Piece) -> usize {
+        self.holes[store_index(player)]
+    }
+
+    /// Returns the last move made and the player who made it, if any
+    pub fn last_move(&self) -> Option<(Piece, KalahMove)> {
+        self.last_move
+    }
+
+    /// Returns the moves the current player can make: every one of their pits with seeds in it
+    pub fn valid_moves(&self) -> Vec<KalahMove> {
+        (1..=KALAH_PITS)
+            .filter(|&pit| self.seeds(self.current_player, pit) > 0)
+            .map(KalahMove)
+            .collect()
+    }
+
+    /// Returns true if the current player can sow the seeds of the given pit
+    pub fn is_valid_move(&self, pmove: KalahMove) -> bool {
+        (1..=KALAH_PITS).contains(&pmove.0) && self.seeds(self.current_player, pmove.0) > 0
+    }
+
+    /// Sows the seeds of the given pit for the current player
+    ///
+    /// Unlike in most games, the turn only passes to the other player if the last seed does not
+    /// land in the current player's store.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the move is not valid.
+    pub fn make_move(&mut self, pmove: KalahMove) {
+        assert!(self.is_valid_move(pmove), "bug: attempt to sow pit `{}`, which is not valid", pmove);
+
+        let player = self.current_player;
+        let mut hole = pit_index(player, pmove.0);
+        let mut seeds = std::mem::take(&mut self.holes[hole]);
+        while seeds > 0 {
+            hole = (hole + 1) % HOLES;
+            if hole == store_index(player.opposite()) {
+                continue;
+            }
+            self.holes[hole] += 1;
+            seeds -= 1;
+        }
+
+        let own_pits = pit_index(player, 1)..store_index(player);
+        let opposite = HOLES - 2 - hole;
+        if own_pits.contains(&hole) && self.holes[hole] == 1 && self.holes[opposite] > 0 {
+            let captured = self.holes[hole] + self.holes[opposite];
+            self.holes[hole] = 0;
+            self.holes[opposite] = 0;
+            self.holes[store_index(player)] += captured;
+        }
+
+        // Once either side is empty, the rest of the seeds go to the player whose side they are on
+        let side_empty = |player| (1..=KALAH_PITS).all(|pit| self.seeds(player, pit) == 0);
+        if side_empty(Piece::X) || side_empty(Piece::O) {
+            for &player in &[Piece::X, Piece::O] {
+                let remaining: usize = (1..=KALAH_PITS).map(|pit| std::mem::take(&mut self.holes[pit_index(player, pit)])).sum();
+                self.holes[store_index(player)] += remaining;
+            }
+        }
+
+        self.last_move = Some((player, pmove));
+        if hole != store_index(player) {
+            self.current_player = player.opposite();
+        }
+    }
+}
+
+/// Returns the index in `Kalah::holes` of the given pit (from 1 to `KALAH_PITS`) of the given
+/// player
+fn pit_index(player: Piece, pit: usize) -> usize {
+    match player {
+        Piece::X => pit - 1,
+        Piece::O => KALAH_PITS + pit,
+    }
+}
+
+/// Returns the index in `Kalah::holes` of the given player's store
+fn store_index(player: Piece) -> usize {
+    match player {
+        Piece::X => KALAH_PITS,
+        Piece::O => HOLES - 1,
+    }
+}
+
+impl Game for Kalah {
+    type Move = KalahMove;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<KalahMove> {
+        self.valid_moves()
+    }
+
+    fn play_move(&mut self, pmove: KalahMove) {
+        self.make_move(pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        // All of the seeds are put in the stores once the game ends
+        (1..=KALAH_PITS).all(|pit| self.seeds(self.current_player, pit) == 0)
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        if !self.is_game_over() {
+            return None;
+        }
+
+        let x = self.store(Piece::X);
+        let o = self.store(Piece::O);
+        if x > o {
+            Some(Piece::X)
+        } else if o > x {
+            Some(Piece::O)
+        } else {
+            None
+        }
+    }
+}
+
+/// Draws the board as a ring of holes with O's pits along the top (numbered from O's point of
+/// view), X's pits along the bottom, and each player's store at their right end
+impl fmt::Display for Kalah {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "     {}  ", Piece::O)?;
+        for pit in (1..=KALAH_PITS).rev() {
+            write!(f, "{:>4}", format!("({})", pit))?;
+        }
+        writeln!(f)?;
+
+        write!(f, "        ")?;
+        for pit in (1..=KALAH_PITS).rev() {
+            write!(f, "{:>4}", self.seeds(Piece::O, pit))?;
+        }
+        writeln!(f)?;
+
+        write!(f, "  [{:>2}]  ", self.store(Piece::O))?;
+        write!(f, "{:width$}", "", width = 4 * KALAH_PITS)?;
+        writeln!(f, "  [{:>2}]", self.store(Piece::X))?;
+
+        write!(f, "        ")?;
+        for pit in 1..=KALAH_PITS {
+            write!(f, "{:>4}", self.seeds(Piece::X, pit))?;
+        }
+        writeln!(f)?;
+
+        write!(f, "     {}  ", Piece::X)?;
+        for pit in 1..=KALAH_PITS {
+            write!(f, "{:>4}", format!("({})", pit))?;
+        }
+        writeln!(f)
+    }
+}
+
+/// An AI that plays Kalah by searching a number of moves ahead (see `negamax`)
+///
+/// Positions are evaluated by the difference between the seeds in each player's store.
+#[derive(Debug, Clone, Copy)]
+pub struct KalahAi {
+    /// How many moves ahead to search
+    depth: usize,
+}
+
+impl Default for KalahAi {
+    fn default() -> Self {
+        Self {depth: 10}
+    }
+}
+
+impl KalahAi {
+    /// Creates an AI that searches the given number of moves ahead
+    pub fn new(depth: usize) -> Self {
+        Self {depth}
+    }
+
+    /// Returns how many moves ahead the AI searches
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the move the AI would play for the current player, or `None` if the game is over
+    pub fn choose_move(&self, game: &Kalah) -> Option<KalahMove> {
+        negamax(game, self.depth, self).map(|(pmove, _)| pmove)
+    }
+}
+
+impl Heuristic<Kalah> for KalahAi {
+    fn evaluate(&self, game: &Kalah, player: Piece) -> i32 {
+        game.store(player) as i32 - game.store(player.opposite()) as i32
+    }
+
+    fn order_moves(&self, game: &Kalah, moves: &mut Vec<KalahMove>) {
+        // Moves that end in the player's store give them another turn, so they are tried first
+        let player = game.current_player;
+        moves.sort_by_key(|&KalahMove(pit)| game.seeds(player, pit) != KALAH_PITS + 1 - pit);
+    }
+}
+
+/// Plays the move chosen by `KalahAi::choose_move`
+impl Player<Kalah> for KalahAi {
+    fn choose_move(&mut self, game: &Kalah) -> KalahMove {
+        KalahAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_turn_in_own_store() {
+        let mut game = Kalah::default();
+        // The last of the 4 seeds in pit 3 lands in the store
+        game.make_move(KalahMove(3));
+        assert_eq!(game.store(Piece::X), 1);
+        assert_eq!(game.current_player(), Piece::X);
+
+        game.make_move(KalahMove(1));
+        assert_eq!(game.current_player(), Piece::O);
+    }
+
+    #[test]
+    fn sowing_skips_opponent_store() {
+        let mut game = Kalah::new(0);
+        game.holes[pit_index(Piece::X, 6)] = 9;
+        game.holes[pit_index(Piece::X, 2)] = 1;
+        game.holes[pit_index(Piece::O, 6)] = 1;
+        game.make_move(KalahMove(6));
+
+        assert_eq!(game.store(Piece::X), 1);
+        assert_eq!(game.store(Piece::O), 0);
+        // One seed in each of O's pits, then around to X's first two pits
+        assert_eq!(game.seeds(Piece::O, 6), 2);
+        assert_eq!(game.seeds(Piece::X, 1), 1);
+        assert_eq!(game.seeds(Piece::X, 2), 2);
+    }
+
+    #[test]
+    fn capture_from_empty_pit() {
+        let mut game = Kalah::new(0);
+        game.holes[pit_index(Piece::X, 1)] = 2;
+        game.holes[pit_index(Piece::X, 6)] = 1;
+        // O's pit 4 is across from X's pit 3
+        game.holes[pit_index(Piece::O, 4)] = 5;
+        game.holes[pit_index(Piece::O, 1)] = 1;
+        game.make_move(KalahMove(1));
+
+        assert_eq!(game.store(Piece::X), 6);
+        assert_eq!(game.seeds(Piece::X, 3), 0);
+        assert_eq!(game.seeds(Piece::O, 4), 0);
+        assert_eq!(game.current_player(), Piece::O);
+    }
+
+    #[test]
+    fn empty_side_ends_game() {
+        let mut game = Kalah::new(0);
+        game.holes[pit_index(Piece::X, 6)] = 1;
+        game.holes[pit_index(Piece::O, 2)] = 3;
+        game.make_move(KalahMove(6));
+
+        assert!(game.is_game_over());
+        assert_eq!(game.store(Piece::X), 1);
+        assert_eq!(game.store(Piece::O), 3);
+        assert_eq!(Game::winner(&game), Some(Piece::O));
+    }
+}
@@ -1,15 +1,117 @@
 mod prompt;
+mod console;
+#[cfg(feature = "cursor")]
+mod cursor;
+#[cfg(feature = "readline")]
+mod readline;
 mod display;
+mod theme;
+mod svg;
+mod html;
 mod ai;
+mod explain;
+mod eval;
+mod tune;
+#[cfg(feature = "mlp")]
+mod mlp;
+mod difficulty;
+mod ponder;
+mod cancel;
+mod player;
+mod transposition;
+mod book;
+mod endgame;
+mod tablebase;
+mod game_tree;
 mod tile_pos;
+mod hex_pos;
+mod moves;
 mod piece;
+mod seat;
+mod direction;
 mod grid;
+mod zobrist;
+mod flips;
+mod variant;
+mod clock;
+mod observer;
 mod reversi;
+mod save;
+mod notation;
+mod archive;
+mod stats;
+mod game;
+mod search;
+mod gomoku;
+mod checkers;
+mod morris;
+mod kalah;
+mod dots;
+mod go;
+mod nim;
+mod breakthrough;
+mod xot;
+mod wthor;
+mod lines_of_action;
+mod game_loop;
+mod backgammon;
+mod chinese_checkers;
 
 pub use prompt::*;
+pub use console::*;
+#[cfg(feature = "cursor")]
+pub use cursor::*;
+#[cfg(feature = "readline")]
+pub use readline::*;
 pub use display::*;
+pub use theme::*;
+pub use svg::*;
+pub use html::*;
 pub use ai::*;
+pub use explain::*;
+pub use eval::*;
+pub use tune::*;
+#[cfg(feature = "mlp")]
+pub use mlp::*;
+pub use difficulty::*;
+pub use ponder::*;
+pub use cancel::*;
+pub use player::*;
+pub use transposition::*;
+pub use book::*;
+pub use endgame::*;
+pub use tablebase::*;
+pub use game_tree::*;
 pub use tile_pos::*;
+pub use hex_pos::*;
+pub use moves::*;
 pub use piece::*;
+pub use seat::*;
+pub use direction::*;
 pub use grid::*;
+pub use zobrist::*;
+pub use flips::*;
+pub use variant::*;
+pub use clock::*;
+pub use observer::*;
 pub use reversi::*;
+pub use save::*;
+pub use notation::*;
+pub use archive::*;
+pub use stats::*;
+pub use game::*;
+pub use search::*;
+pub use gomoku::*;
+pub use checkers::*;
+pub use morris::*;
+pub use kalah::*;
+pub use dots::*;
+pub use go::*;
+pub use nim::*;
+pub use breakthrough::*;
+pub use xot::*;
+pub use wthor::*;
+pub use lines_of_action::*;
+pub use game_loop::*;
+pub use backgammon::*;
+pub use chinese_checkers::*;
@@ -0,0 +1,380 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Piece, TilePos, Grid, Direction, Game, Heuristic, negamax, Player};
+
+/// The number of rows and columns on the board
+const BOARD_SIZE: usize = 8;
+
+/// A move in Lines of Action: a piece moving in a straight line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinesOfActionMove {
+    /// The tile the piece moves from
+    pub from: TilePos,
+    /// The tile the piece moves to
+    pub to: TilePos,
+}
+
+/// Writes the move as its two tiles, e.g. "B1-B3"
+impl fmt::Display for LinesOfActionMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.from, self.to)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid move: `{0}`. Expected two tiles like `B1-B3`")]
+pub struct ParseLinesOfActionMoveError(pub String);
+
+impl FromStr for LinesOfActionMove {
+    type Err = ParseLinesOfActionMoveError;
+
+    /// Parses the tile moved from and the tile moved to, separated by `-`, `x` or whitespace
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseLinesOfActionMoveError(s.to_string());
+
+        let tiles = s.split(|c: char| c == '-' || c == 'x' || c.is_whitespace())
+            .filter(|tile| !tile.is_empty())
+            .map(|tile| tile.parse::<TilePos>().map_err(|_| err()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match tiles[..] {
+            [from, to] => Ok(LinesOfActionMove {from, to}),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// A game of Lines of Action
+///
+/// `Piece::X` starts with six pieces on the top row and six on the bottom row, and `Piece::O`
+/// starts with six pieces on each of the left and right columns. On their turn, a player moves one
+/// of their pieces in a straight line (in any of the 8 directions) exactly as many tiles as there
+/// are pieces of either player on that whole line. A piece can jump over its own pieces but not
+/// over the opponent's pieces, and it captures an opponent's piece by landing on it.
+///
+/// The first player to connect all of their pieces into a single group (stepping in any of the 8
+/// directions) wins. If a move connects the pieces of both players at once, the player who moved
+/// wins. A player who cannot move loses. `Piece::X` moves first.
+#[derive(Debug, Clone)]
+pub struct LinesOfAction {
+    /// The pieces on the board
+    grid: Grid,
+    /// The player whose turn it is currently
+    current_player: Piece,
+    /// The last move made, if any
+    last_move: Option<LinesOfActionMove>,
+    /// The player who connected all of their pieces, if any
+    winner: Option<Piece>,
+}
+
+impl Default for LinesOfAction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinesOfAction {
+    /// Creates a new game with the pieces in their starting positions
+    pub fn new() -> Self {
+        let mut grid = Grid::default();
+        for i in 1..BOARD_SIZE - 1 {
+            grid.place(TilePos {row: 0, col: i}, Piece::X);
+            grid.place(TilePos {row: BOARD_SIZE - 1, col: i}, Piece::X);
+            grid.place(TilePos {row: i, col: 0}, Piece::O);
+            grid.place(TilePos {row: i, col: BOARD_SIZE - 1}, Piece::O);
+        }
+
+        Self {
+            grid,
+            current_player: Piece::X,
+            last_move: None,
+            winner: None,
+        }
+    }
+
+    /// Returns the pieces on the board
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Returns the last move made, if any
+    pub fn last_move(&self) -> Option<LinesOfActionMove> {
+        self.last_move
+    }
+
+    /// Returns true if the current player can make the given move
+    pub fn is_valid_move(&self, pmove: LinesOfActionMove) -> bool {
+        self.winner.is_none() && self.piece_moves(pmove.from).contains(&pmove)
+    }
+
+    /// Returns every move the current player can make, or no moves once the game is over
+    pub fn valid_moves(&self) -> Vec<LinesOfActionMove> {
+        if self.winner.is_some() {
+            return Vec::new();
+        }
+
+        (0..BOARD_SIZE * BOARD_SIZE)
+            .flat_map(|index| self.piece_moves(TilePos {row: index / BOARD_SIZE, col: index % BOARD_SIZE}))
+            .collect()
+    }
+
+    /// Returns the moves of the current player's piece on the given tile, if there is one
+    fn piece_moves(&self, from: TilePos) -> Vec<LinesOfActionMove> {
+        let player = self.current_player;
+        if from.row >= BOARD_SIZE || from.col >= BOARD_SIZE || self.grid.tile(from) != Some(player) {
+            return Vec::new();
+        }
+
+        Direction::ALL.iter().filter_map(|&direction| {
+            let distance = self.line_count(from, direction);
+            let mut to = None;
+            for (step, pos) in self.grid.ray(from, direction).take(distance).enumerate() {
+                match self.grid.tile(pos) {
+                    // The piece can only stop on an empty tile or an opponent's piece
+                    Some(piece) if step + 1 == distance && piece == player => return None,
+                    // The piece cannot jump over the opponent's pieces
+                    Some(piece) if step + 1 < distance && piece != player => return None,
+                    _ => to = Some(pos),
+                }
+            }
+
+            // The ray is shorter than the distance if the move would leave the board
+            to.filter(|&to| to.row.abs_diff(from.row).max(to.col.abs_diff(from.col)) == distance)
+                .map(|to| LinesOfActionMove {from, to})
+        }).collect()
+    }
+
+    /// Returns the number of pieces of either player on the whole line through the given tile
+    /// in the given direction, including the piece on that tile
+    fn line_count(&self, pos: TilePos, direction: Direction) -> usize {
+        let count = |direction| self.grid.ray(pos, direction).filter(|&pos| self.grid.tile(pos).is_some()).count();
+        count(direction) + count(direction.opposite()) + 1
+    }
+
+    /// Makes the given move for the current player and passes the turn to the other player
+    ///
+    /// # Panics
+    ///
+    /// Panics if the move is not valid.
+    pub fn make_move(&mut self, pmove: LinesOfActionMove) {
+        assert!(self.is_valid_move(pmove), "bug: attempt to make the move `{}`, which is not valid", pmove);
+
+        let player = self.current_player;
+        self.grid.clear(pmove.from);
+        self.grid.place(pmove.to, player);
+
+        // A capture can connect the opponent's pieces too, but the player who moved wins then
+        if self.is_connected(player) {
+            self.winner = Some(player);
+        } else if self.is_connected(player.opposite()) {
+            self.winner = Some(player.opposite());
+        }
+
+        self.last_move = Some(pmove);
+        self.current_player = player.opposite();
+    }
+
+    /// Returns true if all of the given player's pieces form a single connected group
+    pub fn is_connected(&self, player: Piece) -> bool {
+        self.grid.connected_groups(player).len() <= 1
+    }
+
+    /// Returns the number of pieces the given player has left
+    pub fn count_pieces(&self, player: Piece) -> usize {
+        self.grid.rows().iter().flatten().filter(|&&tile| tile == Some(player)).count()
+    }
+}
+
+impl Game for LinesOfAction {
+    type Move = LinesOfActionMove;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<LinesOfActionMove> {
+        self.valid_moves()
+    }
+
+    fn play_move(&mut self, pmove: LinesOfActionMove) {
+        self.make_move(pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.valid_moves().is_empty()
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        match self.winner {
+            Some(winner) => Some(winner),
+            // The player who cannot move loses
+            None if self.is_game_over() => Some(self.current_player.opposite()),
+            None => None,
+        }
+    }
+}
+
+/// Formats the board the same way as a Reversi board, without colors. The alternate flag (`{:#}`)
+/// formats the board with plain ASCII characters.
+impl fmt::Display for LinesOfAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.grid, f)
+    }
+}
+
+/// An AI that plays Lines of Action by searching a number of moves ahead (see `negamax`)
+///
+/// Positions are evaluated by how close each player is to connecting their pieces: how many
+/// separate groups they have, and how far their pieces are from the center of all their pieces.
+/// Pieces in the middle of the board get a small bonus since they have more ways to connect.
+#[derive(Debug, Clone, Copy)]
+pub struct LinesOfActionAi {
+    /// How many moves ahead to search
+    depth: usize,
+}
+
+impl Default for LinesOfActionAi {
+    fn default() -> Self {
+        Self {depth: 3}
+    }
+}
+
+impl LinesOfActionAi {
+    /// Creates an AI that searches the given number of moves ahead
+    pub fn new(depth: usize) -> Self {
+        Self {depth}
+    }
+
+    /// Returns how many moves ahead the AI searches
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the move the AI would play for the current player, or `None` if the game is over
+    pub fn choose_move(&self, game: &LinesOfAction) -> Option<LinesOfActionMove> {
+        negamax(game, self.depth, self).map(|(pmove, _)| pmove)
+    }
+
+    /// Returns how well connected the given player's pieces are, higher being better
+    fn connectedness(game: &LinesOfAction, player: Piece) -> i32 {
+        let groups = game.grid.connected_groups(player);
+        let pieces: Vec<_> = groups.iter().flatten().copied().collect();
+        if pieces.is_empty() {
+            return 0;
+        }
+
+        // The distance of each piece from the center of mass, counting diagonal steps as one
+        let count = pieces.len() as i32;
+        let center_row = pieces.iter().map(|pos| pos.row as i32).sum::<i32>() * 10 / count;
+        let center_col = pieces.iter().map(|pos| pos.col as i32).sum::<i32>() * 10 / count;
+        let spread: i32 = pieces.iter()
+            .map(|pos| (pos.row as i32 * 10 - center_row).abs().max((pos.col as i32 * 10 - center_col).abs()))
+            .sum();
+
+        let central = pieces.iter()
+            .filter(|pos| (2..BOARD_SIZE - 2).contains(&pos.row) && (2..BOARD_SIZE - 2).contains(&pos.col))
+            .count() as i32;
+
+        -50 * groups.len() as i32 - spread / count + 5 * central
+    }
+}
+
+impl Heuristic<LinesOfAction> for LinesOfActionAi {
+    fn evaluate(&self, game: &LinesOfAction, player: Piece) -> i32 {
+        Self::connectedness(game, player) - Self::connectedness(game, player.opposite())
+    }
+
+    fn order_moves(&self, game: &LinesOfAction, moves: &mut Vec<LinesOfActionMove>) {
+        // Captures first
+        moves.sort_by_key(|pmove| game.grid.tile(pmove.to).is_none());
+    }
+}
+
+/// Plays the move chosen by `LinesOfActionAi::choose_move`
+impl Player<LinesOfAction> for LinesOfActionAi {
+    fn choose_move(&mut self, game: &LinesOfAction) -> LinesOfActionMove {
+        LinesOfActionAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pmove(s: &str) -> LinesOfActionMove {
+        s.parse().unwrap()
+    }
+
+    /// Creates a game with only the given pieces on the board, with X to move
+    fn setup(x: &[&str], o: &[&str]) -> LinesOfAction {
+        let mut game = LinesOfAction::new();
+        game.grid = Grid::default();
+        for (tiles, piece) in [(x, Piece::X), (o, Piece::O)] {
+            for tile in tiles {
+                game.grid.place(tile.parse().unwrap(), piece);
+            }
+        }
+        game
+    }
+
+    #[test]
+    fn distance_is_pieces_on_line() {
+        let game = LinesOfAction::new();
+        // B1 has 2 pieces on its column, 6 on its row and 2 on its diagonal
+        assert!(game.is_valid_move(pmove("B1-B3")));
+        assert!(game.is_valid_move(pmove("B1-H1")));
+        assert!(game.is_valid_move(pmove("B1-D3")));
+        assert!(!game.is_valid_move(pmove("B1-B2")));
+        assert_eq!(game.valid_moves().len(), 36);
+    }
+
+    #[test]
+    fn jumps_own_pieces_but_not_opponents() {
+        // 3 pieces on the row, so D4 moves 3 tiles
+        let game = setup(&["D4", "E4", "A1"], &["B4"]);
+        assert!(game.is_valid_move(pmove("D4-G4")));
+        assert!(!game.is_valid_move(pmove("D4-A4")));
+
+        // Landing on an opponent's piece captures it
+        let mut game = setup(&["D4", "A1"], &["F4", "H8", "A8"]);
+        game.make_move(pmove("D4xF4"));
+        assert_eq!(game.count_pieces(Piece::O), 2);
+        assert!(!game.is_game_over());
+    }
+
+    #[test]
+    fn connecting_pieces_wins() {
+        let mut game = setup(&["A1", "B2", "D2"], &["H8", "H6"]);
+        assert!(!game.is_game_over());
+        game.make_move(pmove("D2-C3"));
+        assert!(game.is_game_over());
+        assert_eq!(Game::winner(&game), Some(Piece::X));
+
+        // Capturing the piece that kept the opponent's pieces apart connects both players, so the
+        // player who moved wins
+        let mut game = setup(&["A1", "D2"], &["B2", "G7", "H8"]);
+        game.make_move(pmove("D2xB2"));
+        assert!(game.is_connected(Piece::O));
+        assert_eq!(Game::winner(&game), Some(Piece::X));
+    }
+
+    #[test]
+    fn connected_groups() {
+        let game = setup(&["A1", "B2", "C3", "E5", "H8"], &["A8"]);
+        let groups = game.grid.connected_groups(Piece::X);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].len(), 3);
+        assert_eq!(game.grid.connected_tiles("C3".parse().unwrap()).len(), 3);
+        assert!(game.grid.connected_tiles("A2".parse().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn ai_finds_win() {
+        let game = setup(&["A1", "B2", "D1"], &["H8", "F6", "H4"]);
+        let mut next = game.clone();
+        next.make_move(LinesOfActionAi::new(2).choose_move(&game).unwrap());
+        assert_eq!(Game::winner(&next), Some(Piece::X));
+    }
+}
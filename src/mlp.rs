@@ -0,0 +1,149 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::{Reversi, Piece, Evaluator};
+
+/// The number of inputs of every model: one for each tile of the board
+const INPUTS: usize = 64;
+
+#[derive(Debug, Error)]
+pub enum MlpError {
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+    #[error("Line {line}: invalid number `{text}`")]
+    InvalidNumber {
+        line: usize,
+        text: String,
+    },
+    #[error("Invalid layer sizes: the first layer must have 64 inputs and the last layer must have 1 output")]
+    InvalidLayers,
+    #[error("Expected {expected} weights and biases, found {found}")]
+    WrongWeightCount {
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// A single fully connected layer of a model
+#[derive(Debug, Clone)]
+struct Layer {
+    inputs: usize,
+    outputs: usize,
+    /// The weight of each input for each output, stored row-by-row (one row per output)
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+}
+
+impl Layer {
+    fn forward(&self, input: &[f32], output: &mut Vec<f32>) {
+        output.clear();
+        for (row, bias) in self.weights.chunks(self.inputs).zip(&self.biases) {
+            let sum: f32 = row.iter().zip(input).map(|(w, x)| w * x).sum();
+            output.push(sum + bias);
+        }
+    }
+}
+
+/// Evaluates positions with a small neural network (a multilayer perceptron) loaded from a file
+///
+/// The network is given one input for each tile of the board, row by row: 1 for the pieces of
+/// the player being evaluated, -1 for the pieces of their opponent and 0 for empty or blocked
+/// tiles. Every hidden layer uses ReLU activations and the single output of the last layer is
+/// rounded and used as the score. The network is expected to have been trained for the variant it
+/// is used with.
+#[derive(Debug, Clone)]
+pub struct MlpEvaluator {
+    layers: Vec<Layer>,
+}
+
+impl MlpEvaluator {
+    /// Loads a model from a file
+    ///
+    /// The first line lists the size of each layer, starting with the 64 inputs and ending with
+    /// the single output (e.g. "64 32 1"). It is followed by the weights and biases of each layer
+    /// in order: first the weights for each output of the layer, one row of inputs after
+    /// another, and then the bias of each output. Numbers may be split across lines in any way.
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MlpError> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    /// Parses a model in the format described in `load`
+    pub fn parse(text: &str) -> Result<Self, MlpError> {
+        let mut lines = text.lines().enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let sizes = match lines.next() {
+            Some((line_num, line)) => line.split_whitespace()
+                .map(|text| text.parse::<usize>()
+                    .map_err(|_| MlpError::InvalidNumber {line: line_num, text: text.to_string()}))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => return Err(MlpError::InvalidLayers),
+        };
+        if sizes.len() < 2 || sizes[0] != INPUTS || sizes[sizes.len() - 1] != 1 || sizes.contains(&0) {
+            return Err(MlpError::InvalidLayers);
+        }
+
+        let mut numbers = Vec::new();
+        for (line_num, line) in lines {
+            for text in line.split_whitespace() {
+                let number = text.parse::<f32>()
+                    .map_err(|_| MlpError::InvalidNumber {line: line_num, text: text.to_string()})?;
+                numbers.push(number);
+            }
+        }
+
+        let expected: usize = sizes.windows(2).map(|pair| pair[0] * pair[1] + pair[1]).sum();
+        if numbers.len() != expected {
+            return Err(MlpError::WrongWeightCount {expected, found: numbers.len()});
+        }
+
+        let mut numbers = numbers.into_iter();
+        let layers = sizes.windows(2).map(|pair| {
+            let (inputs, outputs) = (pair[0], pair[1]);
+            let weights = numbers.by_ref().take(inputs * outputs).collect();
+            let biases = numbers.by_ref().take(outputs).collect();
+            Layer {inputs, outputs, weights, biases}
+        }).collect();
+
+        Ok(Self {layers})
+    }
+
+    /// Returns the size of each layer of the model, starting with the inputs
+    pub fn layer_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![INPUTS];
+        sizes.extend(self.layers.iter().map(|layer| layer.outputs));
+        sizes
+    }
+}
+
+impl Evaluator for MlpEvaluator {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        let mut input: Vec<f32> = game.grid().rows().iter().flatten().map(|tile| match tile {
+            Some(piece) if *piece == player => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }).collect();
+
+        let mut output = Vec::new();
+        let last = self.layers.len() - 1;
+        for (i, layer) in self.layers.iter().enumerate() {
+            layer.forward(&input, &mut output);
+            if i != last {
+                output.iter_mut().for_each(|value| *value = value.max(0.0));
+            }
+            std::mem::swap(&mut input, &mut output);
+        }
+
+        input[0].round() as i32
+    }
+
+    fn name(&self) -> &str {
+        "mlp"
+    }
+}
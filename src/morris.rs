@@ -0,0 +1,547 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Piece, TilePos, Game, Heuristic, negamax, Player};
+
+/// The number of pieces each player places at the start of the game
+pub const MORRIS_PIECES: usize = 9;
+
+/// The number of moves in a row (counting both players) without a mill after which the game is a
+/// draw, once all of the pieces have been placed
+pub const MORRIS_DRAW_PLIES: usize = 50;
+
+/// The number of points on the board
+const POINT_COUNT: usize = 24;
+
+/// The positions of the points when the board is drawn on a 7x7 grid, which also gives each point
+/// its name (e.g. "A1" for the top-left corner)
+const POINTS: [TilePos; POINT_COUNT] = [
+    TilePos {row: 0, col: 0}, TilePos {row: 0, col: 3}, TilePos {row: 0, col: 6},
+    TilePos {row: 1, col: 1}, TilePos {row: 1, col: 3}, TilePos {row: 1, col: 5},
+    TilePos {row: 2, col: 2}, TilePos {row: 2, col: 3}, TilePos {row: 2, col: 4},
+    TilePos {row: 3, col: 0}, TilePos {row: 3, col: 1}, TilePos {row: 3, col: 2},
+    TilePos {row: 3, col: 4}, TilePos {row: 3, col: 5}, TilePos {row: 3, col: 6},
+    TilePos {row: 4, col: 2}, TilePos {row: 4, col: 3}, TilePos {row: 4, col: 4},
+    TilePos {row: 5, col: 1}, TilePos {row: 5, col: 3}, TilePos {row: 5, col: 5},
+    TilePos {row: 6, col: 0}, TilePos {row: 6, col: 3}, TilePos {row: 6, col: 6},
+];
+
+/// The lines of three points (by their index in `POINTS`) that form a mill. Every line on the
+/// board is one of these, so two points are connected if they are next to each other in a mill.
+const MILLS: [[usize; 3]; 16] = [
+    // Horizontal lines
+    [0, 1, 2], [3, 4, 5], [6, 7, 8], [9, 10, 11],
+    [12, 13, 14], [15, 16, 17], [18, 19, 20], [21, 22, 23],
+    // Vertical lines
+    [0, 9, 21], [3, 10, 18], [6, 11, 15], [1, 4, 7],
+    [16, 19, 22], [8, 12, 17], [5, 13, 20], [2, 14, 23],
+];
+
+/// Returns the index of the point at the given position, or `None` if there is no point there
+fn point_index(pos: TilePos) -> Option<usize> {
+    POINTS.iter().position(|&point| point == pos)
+}
+
+/// Returns the indexes of the points connected to the given point by a line
+fn neighbors(point: usize) -> impl Iterator<Item=usize> {
+    MILLS.iter().flat_map(move |mill| {
+        let index = mill.iter().position(|&p| p == point);
+        let before = index.filter(|&i| i > 0).map(|i| mill[i - 1]);
+        let after = index.filter(|&i| i < 2).map(|i| mill[i + 1]);
+        before.into_iter().chain(after)
+    })
+}
+
+/// A move in Nine Men's Morris
+///
+/// While a player still has pieces to place, every move places a piece (`from` is `None`).
+/// Afterwards, every move moves a piece from one point to another. If the move forms a mill, the
+/// player also removes one of the opponent's pieces.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MorrisMove {
+    /// The point the piece is moved from, or `None` if a new piece is placed
+    pub from: Option<TilePos>,
+    /// The point the piece is placed or moved to
+    pub to: TilePos,
+    /// The opponent's piece that is removed, if the move forms a mill
+    pub remove: Option<TilePos>,
+}
+
+/// Writes the move as e.g. "D2" for placing a piece, "D2-D3" for moving one, followed by e.g.
+/// "xA1" if a piece is removed
+impl fmt::Display for MorrisMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(from) = self.from {
+            write!(f, "{}-", from)?;
+        }
+        write!(f, "{}", self.to)?;
+        if let Some(remove) = self.remove {
+            write!(f, "x{}", remove)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid move: `{0}`. Expected something like `D2`, `D2-D3` or `D2-D3xA1`")]
+pub struct ParseMorrisMoveError(pub String);
+
+impl FromStr for MorrisMove {
+    type Err = ParseMorrisMoveError;
+
+    /// Parses a move in the format written by `Display`, checking only that every point is on the
+    /// board. Spaces are ignored and the letters are not case-sensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseMorrisMoveError(s.to_string());
+        let point = |point: &str| match point.parse::<TilePos>() {
+            Ok(pos) if point_index(pos).is_some() => Ok(pos),
+            _ => Err(err()),
+        };
+
+        let input: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let (pmove, remove) = match input.split_once(['x', 'X']) {
+            Some((pmove, remove)) => (pmove, Some(point(remove)?)),
+            None => (&input[..], None),
+        };
+        let (from, to) = match pmove.split_once('-') {
+            Some((from, to)) => (Some(point(from)?), point(to)?),
+            None => (None, point(pmove)?),
+        };
+
+        Ok(MorrisMove {from, to, remove})
+    }
+}
+
+/// A game of Nine Men's Morris
+///
+/// The board is a graph of 24 points connected by lines. The game has three phases:
+///
+/// 1. Placement: players take turns placing their nine pieces on empty points.
+/// 2. Movement: players take turns moving a piece along a line to an empty neighboring point.
+/// 3. Flying: a player with only three pieces left may move a piece to any empty point.
+///
+/// Whenever a player forms a mill (three of their pieces along a line), they remove one of their
+/// opponent's pieces, which cannot be in a mill unless all of them are. A player loses once they
+/// have fewer than three pieces or cannot move. `Piece::X` moves first.
+#[derive(Debug, Clone)]
+pub struct Morris {
+    /// The pieces on each point, indexed like `POINTS`
+    points: [Option<Piece>; POINT_COUNT],
+    /// The number of pieces that each player still has to place, X first
+    in_hand: [usize; 2],
+    /// The player whose turn it is currently
+    current_player: Piece,
+    /// The last move made, if any
+    last_move: Option<MorrisMove>,
+    /// The number of moves made since the last mill, once all of the pieces have been placed
+    quiet_plies: usize,
+}
+
+impl Default for Morris {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Morris {
+    /// Creates a new game with an empty board
+    pub fn new() -> Self {
+        Self {
+            points: [None; POINT_COUNT],
+            in_hand: [MORRIS_PIECES; 2],
+            current_player: Piece::X,
+            last_move: None,
+            quiet_plies: 0,
+        }
+    }
+
+    /// Returns the piece on the given point, or `None` if it is empty or not a point on the board
+    pub fn piece(&self, pos: TilePos) -> Option<Piece> {
+        point_index(pos).and_then(|point| self.points[point])
+    }
+
+    /// Returns the number of pieces the given player still has to place
+    pub fn pieces_in_hand(&self, player: Piece) -> usize {
+        self.in_hand[player_index(player)]
+    }
+
+    /// Returns the number of pieces the given player has on the board
+    pub fn pieces_on_board(&self, player: Piece) -> usize {
+        self.points.iter().filter(|&&piece| piece == Some(player)).count()
+    }
+
+    /// Returns true if the given player may move their pieces to any empty point
+    pub fn can_fly(&self, player: Piece) -> bool {
+        self.pieces_in_hand(player) == 0 && self.pieces_on_board(player) == 3
+    }
+
+    /// Returns the last move made, if any
+    pub fn last_move(&self) -> Option<MorrisMove> {
+        self.last_move
+    }
+
+    /// Returns true if the game ended in a draw because nobody formed a mill in a long time (see
+    /// `MORRIS_DRAW_PLIES`)
+    pub fn is_draw(&self) -> bool {
+        self.quiet_plies >= MORRIS_DRAW_PLIES
+    }
+
+    /// Returns true if the given player has lost because they have fewer than three pieces left
+    fn is_out_of_pieces(&self, player: Piece) -> bool {
+        self.pieces_in_hand(player) + self.pieces_on_board(player) < 3
+    }
+
+    /// Returns every move that the current player can make, or no moves once the game is over
+    pub fn valid_moves(&self) -> Vec<MorrisMove> {
+        let player = self.current_player;
+        if self.is_draw() || self.is_out_of_pieces(player) {
+            return Vec::new();
+        }
+
+        let empty: Vec<_> = (0..POINT_COUNT).filter(|&point| self.points[point].is_none()).collect();
+        let mut steps = Vec::new();
+        if self.pieces_in_hand(player) > 0 {
+            steps.extend(empty.iter().map(|&to| (None, to)));
+        } else {
+            let flying = self.can_fly(player);
+            for from in (0..POINT_COUNT).filter(|&point| self.points[point] == Some(player)) {
+                if flying {
+                    steps.extend(empty.iter().map(|&to| (Some(from), to)));
+                } else {
+                    steps.extend(neighbors(from).filter(|&to| self.points[to].is_none()).map(|to| (Some(from), to)));
+                }
+            }
+        }
+
+        let removable = self.removable_pieces(player.opposite());
+        let mut moves = Vec::new();
+        for (from, to) in steps {
+            let pmove = MorrisMove {from: from.map(|from| POINTS[from]), to: POINTS[to], remove: None};
+            if self.forms_mill(from, to, player) && !removable.is_empty() {
+                moves.extend(removable.iter().map(|&remove| MorrisMove {remove: Some(POINTS[remove]), ..pmove}));
+            } else {
+                moves.push(pmove);
+            }
+        }
+        moves
+    }
+
+    /// Returns true if the given move is one of the moves the current player can make
+    pub fn is_valid_move(&self, pmove: MorrisMove) -> bool {
+        self.valid_moves().contains(&pmove)
+    }
+
+    /// Makes the given move for the current player and passes the turn to the other player
+    ///
+    /// # Panics
+    ///
+    /// Panics if the move is not valid.
+    pub fn make_move(&mut self, pmove: MorrisMove) {
+        assert!(self.is_valid_move(pmove), "bug: attempt to make the move `{}`, which is not valid", pmove);
+
+        let player = self.current_player;
+        let to = point_index(pmove.to).expect("bug: valid moves should be on the board");
+        match pmove.from {
+            Some(from) => {
+                let from = point_index(from).expect("bug: valid moves should be on the board");
+                self.points[from] = None;
+            },
+            None => self.in_hand[player_index(player)] -= 1,
+        }
+        self.points[to] = Some(player);
+
+        if let Some(remove) = pmove.remove {
+            let remove = point_index(remove).expect("bug: valid moves should be on the board");
+            self.points[remove] = None;
+        }
+
+        let placing = self.in_hand.iter().any(|&count| count > 0);
+        self.quiet_plies = if placing || pmove.remove.is_some() { 0 } else { self.quiet_plies + 1 };
+        self.last_move = Some(pmove);
+        self.current_player = player.opposite();
+    }
+
+    /// Returns true if moving the given player's piece (or placing a new one if `from` is `None`)
+    /// to the given point would form a mill
+    fn forms_mill(&self, from: Option<usize>, to: usize, player: Piece) -> bool {
+        MILLS.iter()
+            .filter(|mill| mill.contains(&to))
+            .any(|mill| mill.iter().all(|&point| point == to || (Some(point) != from && self.points[point] == Some(player))))
+    }
+
+    /// Returns true if the piece on the given point is part of a mill
+    fn in_mill(&self, point: usize) -> bool {
+        let player = self.points[point];
+        player.is_some() && MILLS.iter()
+            .filter(|mill| mill.contains(&point))
+            .any(|mill| mill.iter().all(|&other| self.points[other] == player))
+    }
+
+    /// Returns the points of the given player's pieces that can be removed by their opponent
+    ///
+    /// Pieces in a mill can only be removed if every piece is in a mill.
+    fn removable_pieces(&self, player: Piece) -> Vec<usize> {
+        let pieces: Vec<_> = (0..POINT_COUNT).filter(|&point| self.points[point] == Some(player)).collect();
+        let outside_mills: Vec<_> = pieces.iter().copied().filter(|&point| !self.in_mill(point)).collect();
+        if outside_mills.is_empty() {
+            pieces
+        } else {
+            outside_mills
+        }
+    }
+}
+
+/// Returns the index of the given player in arrays with a value for each player
+fn player_index(player: Piece) -> usize {
+    match player {
+        Piece::X => 0,
+        Piece::O => 1,
+    }
+}
+
+impl Game for Morris {
+    type Move = MorrisMove;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<MorrisMove> {
+        self.valid_moves()
+    }
+
+    fn play_move(&mut self, pmove: MorrisMove) {
+        self.make_move(pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.valid_moves().is_empty()
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        if self.is_draw() || !self.is_game_over() {
+            return None;
+        }
+
+        // The player who cannot move or has too few pieces loses
+        Some(self.current_player.opposite())
+    }
+}
+
+/// Draws the board with its lines, followed by the number of pieces each player has left to
+/// place while there are any
+impl fmt::Display for Morris {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Every point is drawn 4 columns apart and 2 rows apart
+        const WIDTH: usize = 6 * 4 + 1;
+        const HEIGHT: usize = 6 * 2 + 1;
+        let mut canvas = [[' '; WIDTH]; HEIGHT];
+        for mill in &MILLS {
+            let start = POINTS[mill[0]];
+            let end = POINTS[mill[2]];
+            if start.row == end.row {
+                for c in &mut canvas[start.row * 2][start.col * 4..=end.col * 4] {
+                    *c = '-';
+                }
+            } else {
+                for line in &mut canvas[start.row * 2..=end.row * 2] {
+                    line[start.col * 4] = '|';
+                }
+            }
+        }
+
+        write!(f, "   A")?;
+        for col in 1..7 {
+            write!(f, "   {}", (b'A' + col as u8) as char)?;
+        }
+        writeln!(f)?;
+
+        for (y, line) in canvas.iter().enumerate() {
+            if y % 2 == 0 {
+                write!(f, "{:<3}", y / 2 + 1)?;
+            } else {
+                write!(f, "   ")?;
+            }
+
+            for (x, &c) in line.iter().enumerate() {
+                let pos = TilePos {row: y / 2, col: x / 4};
+                match point_index(pos) {
+                    Some(point) if y % 2 == 0 && x % 4 == 0 => match self.points[point] {
+                        Some(piece) => write!(f, "{}", piece)?,
+                        None => write!(f, "+")?,
+                    },
+                    _ => write!(f, "{}", c)?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        if self.in_hand.iter().any(|&count| count > 0) {
+            writeln!(f)?;
+            write!(f, "Pieces left to place: {} {}, {} {}", Piece::X, self.in_hand[0], Piece::O, self.in_hand[1])?;
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An AI that plays Nine Men's Morris by searching a number of moves ahead (see `negamax`)
+///
+/// Positions are evaluated by the number of pieces each player has left, along with how many
+/// mills they have or could complete and how many moves they have available.
+#[derive(Debug, Clone, Copy)]
+pub struct MorrisAi {
+    /// How many moves ahead to search
+    depth: usize,
+}
+
+impl Default for MorrisAi {
+    fn default() -> Self {
+        Self {depth: 4}
+    }
+}
+
+impl MorrisAi {
+    /// Creates an AI that searches the given number of moves ahead
+    pub fn new(depth: usize) -> Self {
+        Self {depth}
+    }
+
+    /// Returns how many moves ahead the AI searches
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the move the AI would play for the current player, or `None` if the game is over
+    pub fn choose_move(&self, game: &Morris) -> Option<MorrisMove> {
+        negamax(game, self.depth, self).map(|(pmove, _)| pmove)
+    }
+
+    /// Returns how good the position is for the given player, ignoring their opponent
+    fn evaluate_player(&self, game: &Morris, player: Piece) -> i32 {
+        let pieces = game.pieces_in_hand(player) + game.pieces_on_board(player);
+
+        let mut mills = 0;
+        let mut open_mills = 0;
+        for mill in &MILLS {
+            let own = mill.iter().filter(|&&point| game.points[point] == Some(player)).count();
+            let empty = mill.iter().filter(|&&point| game.points[point].is_none()).count();
+            match (own, empty) {
+                (3, _) => mills += 1,
+                (2, 1) => open_mills += 1,
+                _ => {},
+            }
+        }
+
+        let mobility = if game.pieces_in_hand(player) > 0 || game.can_fly(player) {
+            0
+        } else {
+            (0..POINT_COUNT)
+                .filter(|&point| game.points[point] == Some(player))
+                .flat_map(neighbors)
+                .filter(|&to| game.points[to].is_none())
+                .count()
+        };
+
+        100 * pieces as i32 + 20 * mills + 10 * open_mills + 2 * mobility as i32
+    }
+}
+
+impl Heuristic<Morris> for MorrisAi {
+    fn evaluate(&self, game: &Morris, player: Piece) -> i32 {
+        self.evaluate_player(game, player) - self.evaluate_player(game, player.opposite())
+    }
+
+    fn order_moves(&self, _game: &Morris, moves: &mut Vec<MorrisMove>) {
+        // Moves that remove a piece are usually better
+        moves.sort_by_key(|pmove| pmove.remove.is_none());
+    }
+}
+
+/// Plays the move chosen by `MorrisAi::choose_move`
+impl Player<Morris> for MorrisAi {
+    fn choose_move(&mut self, game: &Morris) -> MorrisMove {
+        MorrisAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(game: &mut Morris, moves: &[&str]) {
+        for pmove in moves {
+            game.make_move(pmove.parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn board_graph() {
+        // Corners have two neighbors, the middles of the sides of the middle square have four
+        let corner = point_index("A1".parse().unwrap()).unwrap();
+        assert_eq!(neighbors(corner).count(), 2);
+        let middle = point_index("D2".parse().unwrap()).unwrap();
+        assert_eq!(neighbors(middle).count(), 4);
+        assert!("D4".parse::<MorrisMove>().is_err());
+    }
+
+    #[test]
+    fn mill_removes_piece() {
+        let mut game = Morris::new();
+        play(&mut game, &["A1", "G7", "D1", "G4"]);
+        // Completing the mill requires removing one of O's pieces
+        let moves = game.valid_moves();
+        assert!(!moves.contains(&"G1".parse().unwrap()));
+        assert!(moves.contains(&"G1xG7".parse().unwrap()));
+
+        play(&mut game, &["G1xG7"]);
+        assert_eq!(game.pieces_on_board(Piece::O), 1);
+        assert_eq!(game.pieces_in_hand(Piece::O), 7);
+    }
+
+    #[test]
+    fn pieces_in_mills_are_protected() {
+        let mut game = Morris::new();
+        play(&mut game, &["A1", "A4", "D1", "A7", "F2", "D7", "B2", "G7xB2"]);
+        // O has a mill along the bottom, so only the piece outside of it can be removed
+        play(&mut game, &["B2", "F6"]);
+        assert!(game.is_valid_move("D2xF6".parse().unwrap()));
+        assert!(!game.is_valid_move("D2xA7".parse().unwrap()));
+    }
+
+    #[test]
+    fn movement_and_flying() {
+        let mut game = Morris::new();
+        game.in_hand = [0, 0];
+        for (point, piece) in [("A1", Piece::X), ("D1", Piece::X), ("G4", Piece::X), ("B2", Piece::X), ("F6", Piece::O), ("C5", Piece::O), ("A7", Piece::O)] {
+            let point = point_index(point.parse().unwrap()).unwrap();
+            game.points[point] = Some(piece);
+        }
+
+        // X slides along the lines
+        assert!(game.is_valid_move("B2-D2".parse().unwrap()));
+        assert!(!game.is_valid_move("B2-E3".parse().unwrap()));
+        game.make_move("B2-D2".parse().unwrap());
+
+        // O has three pieces, so they can fly anywhere
+        assert!(game.can_fly(Piece::O));
+        assert!(game.is_valid_move("A7-B2".parse().unwrap()));
+    }
+
+    #[test]
+    fn too_few_pieces_loses() {
+        let mut game = Morris::new();
+        game.in_hand = [0, 0];
+        for (point, piece) in [("A1", Piece::X), ("D1", Piece::X), ("G4", Piece::X), ("F6", Piece::O), ("C5", Piece::O), ("A7", Piece::O)] {
+            let point = point_index(point.parse().unwrap()).unwrap();
+            game.points[point] = Some(piece);
+        }
+
+        game.make_move("G4-G1xF6".parse().unwrap());
+        assert!(game.is_game_over());
+        assert_eq!(game.winner(), Some(Piece::X));
+    }
+}
@@ -0,0 +1,24 @@
+use std::fmt;
+
+use crate::TilePos;
+
+/// A single move that can be made by a player on their turn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Move {
+    /// Place a piece on the tile at the given position
+    Place(TilePos),
+    /// Skip the turn (only valid when there are no other moves available)
+    Pass,
+    /// Give up and end the game, losing immediately
+    Resign,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Move::Place(pos) => write!(f, "{}", pos),
+            Move::Pass => write!(f, "pass"),
+            Move::Resign => write!(f, "resign"),
+        }
+    }
+}
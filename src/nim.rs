@@ -0,0 +1,296 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Piece, Game, Player};
+
+/// The sizes of the piles at the start of a game by default
+pub const NIM_DEFAULT_PILES: [usize; 3] = [3, 4, 5];
+
+/// Who wins a game of Nim
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NimVariant {
+    /// The player who takes the last object wins
+    #[default]
+    Normal,
+    /// The player who takes the last object loses
+    Misere,
+}
+
+impl fmt::Display for NimVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NimVariant::Normal => write!(f, "normal"),
+            NimVariant::Misere => write!(f, "misère"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Unknown Nim variant: `{0}`. Expected `normal` or `misere`")]
+pub struct ParseNimVariantError(pub String);
+
+impl FromStr for NimVariant {
+    type Err = ParseNimVariantError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(NimVariant::Normal),
+            "misere" | "misère" => Ok(NimVariant::Misere),
+            _ => Err(ParseNimVariantError(s.to_string())),
+        }
+    }
+}
+
+/// A move in Nim: taking some number of objects from one pile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NimMove {
+    /// The pile to take from, starting at 1
+    pub pile: usize,
+    /// The number of objects to take, at least 1
+    pub count: usize,
+}
+
+/// Writes the move as the pile followed by the number of objects taken, e.g. "2:3" for taking 3
+/// objects from pile 2
+impl fmt::Display for NimMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.pile, self.count)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid move: `{0}`. Expected a pile and the number to take from it, like `2:3` or `2 3`")]
+pub struct ParseNimMoveError(pub String);
+
+impl FromStr for NimMove {
+    type Err = ParseNimMoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseNimMoveError(s.to_string());
+
+        let numbers = s.split(|c: char| c == ':' || c.is_whitespace())
+            .filter(|number| !number.is_empty())
+            .map(|number| number.parse::<usize>().map_err(|_| err()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match numbers[..] {
+            [pile, count] if pile > 0 && count > 0 => Ok(NimMove {pile, count}),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// A game of Nim
+///
+/// Players take turns taking any number of objects from a single pile. In the normal variant, the
+/// player who takes the last object wins. In the misère variant, they lose. `Piece::X` moves
+/// first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nim {
+    /// The number of objects left in each pile
+    piles: Vec<usize>,
+    /// Who wins the game
+    variant: NimVariant,
+    /// The player whose turn it is currently
+    current_player: Piece,
+}
+
+impl Default for Nim {
+    fn default() -> Self {
+        Self::new(NIM_DEFAULT_PILES.to_vec(), NimVariant::default())
+    }
+}
+
+impl Nim {
+    /// Creates a new game with piles of the given sizes
+    pub fn new(piles: Vec<usize>, variant: NimVariant) -> Self {
+        Self {
+            piles,
+            variant,
+            current_player: Piece::X,
+        }
+    }
+
+    /// Returns the number of objects left in each pile
+    pub fn piles(&self) -> &[usize] {
+        &self.piles
+    }
+
+    /// Returns who wins the game
+    pub fn variant(&self) -> NimVariant {
+        self.variant
+    }
+
+    /// Returns true if the current player can make the given move
+    pub fn is_valid_move(&self, pmove: NimMove) -> bool {
+        pmove.pile >= 1 && pmove.count >= 1
+            && self.piles.get(pmove.pile - 1).is_some_and(|&size| pmove.count <= size)
+    }
+
+    /// Returns every move the current player can make
+    pub fn valid_moves(&self) -> Vec<NimMove> {
+        self.piles.iter().enumerate()
+            .flat_map(|(index, &size)| (1..=size).map(move |count| NimMove {pile: index + 1, count}))
+            .collect()
+    }
+
+    /// Makes the given move for the current player and passes the turn to the other player
+    ///
+    /// # Panics
+    ///
+    /// Panics if the move is not valid.
+    pub fn make_move(&mut self, pmove: NimMove) {
+        assert!(self.is_valid_move(pmove), "bug: attempt to make the move `{}`, which is not valid", pmove);
+
+        self.piles[pmove.pile - 1] -= pmove.count;
+        self.current_player = self.current_player.opposite();
+    }
+
+    /// Returns the Sprague-Grundy value of the position: the XOR of the sizes of the piles
+    ///
+    /// In the normal variant, the player to move can force a win if and only if this is not zero.
+    pub fn grundy_value(&self) -> usize {
+        self.piles.iter().fold(0, |value, &size| value ^ size)
+    }
+
+    /// Returns true if the player to move can force a win
+    pub fn is_winning(&self) -> bool {
+        match self.variant {
+            NimVariant::Normal => self.grundy_value() != 0,
+            // Misère Nim plays like normal Nim until every pile has at most one object, at which
+            // point the player to move wins if there is an even number of piles left
+            NimVariant::Misere if self.piles.iter().all(|&size| size <= 1) => {
+                self.piles.iter().filter(|&&size| size == 1).count().is_multiple_of(2)
+            },
+            NimVariant::Misere => self.grundy_value() != 0,
+        }
+    }
+}
+
+impl Game for Nim {
+    type Move = NimMove;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    fn legal_moves(&self) -> Vec<NimMove> {
+        self.valid_moves()
+    }
+
+    fn play_move(&mut self, pmove: NimMove) {
+        self.make_move(pmove);
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.piles.iter().all(|&size| size == 0)
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        if !self.is_game_over() {
+            return None;
+        }
+
+        match self.variant {
+            // The player who took the last object is the one who just moved
+            NimVariant::Normal => Some(self.current_player.opposite()),
+            NimVariant::Misere => Some(self.current_player),
+        }
+    }
+}
+
+/// Draws each pile as a row of sticks, numbered from 1
+impl fmt::Display for Nim {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, &size) in self.piles.iter().enumerate() {
+            writeln!(f, "{:>2}: {:<width$} ({})", index + 1, "|".repeat(size), size,
+                width = self.piles.iter().copied().max().unwrap_or(0))?;
+        }
+        Ok(())
+    }
+}
+
+/// An AI that plays Nim perfectly using the Sprague-Grundy value of each position (see
+/// `Nim::grundy_value`)
+///
+/// From a winning position, it always moves to a position that is losing for its opponent. From a
+/// losing position, it takes a single object from the largest pile to make the game last as long
+/// as possible.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NimAi;
+
+impl NimAi {
+    /// Returns the move the AI would play for the current player, or `None` if the game is over
+    pub fn choose_move(&self, game: &Nim) -> Option<NimMove> {
+        if game.is_game_over() {
+            return None;
+        }
+
+        let winning_move = game.valid_moves().into_iter().find(|&pmove| {
+            let mut next = game.clone();
+            next.make_move(pmove);
+            // Taking the last object is only good in the normal variant
+            let loses_by_taking_last = next.is_game_over() && next.variant == NimVariant::Misere;
+            !loses_by_taking_last && (next.is_game_over() || !next.is_winning())
+        });
+
+        winning_move.or_else(|| {
+            let (index, _) = game.piles.iter().enumerate().max_by_key(|&(_, &size)| size)?;
+            Some(NimMove {pile: index + 1, count: 1})
+        })
+    }
+}
+
+/// Plays the move chosen by `NimAi::choose_move`
+impl Player<Nim> for NimAi {
+    fn choose_move(&mut self, game: &Nim) -> NimMove {
+        NimAi::choose_move(self, game).expect("bug: the AI should always have a move while the game is in progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Heuristic, negamax, WIN_SCORE};
+
+    /// Scores every unfinished position the same, so that only wins and losses matter
+    struct Exhaustive;
+
+    impl Heuristic<Nim> for Exhaustive {
+        fn evaluate(&self, _game: &Nim, _player: Piece) -> i32 {
+            0
+        }
+    }
+
+    #[test]
+    fn parse_moves() {
+        assert_eq!("2:3".parse(), Ok(NimMove {pile: 2, count: 3}));
+        assert_eq!(" 1 4 ".parse(), Ok(NimMove {pile: 1, count: 4}));
+        assert!("0:1".parse::<NimMove>().is_err());
+        assert!("2".parse::<NimMove>().is_err());
+    }
+
+    #[test]
+    fn ai_matches_exhaustive_search() {
+        for variant in [NimVariant::Normal, NimVariant::Misere] {
+            for a in 0..=3 {
+                for b in 0..=3 {
+                    for c in 1..=4 {
+                        let game = Nim::new(vec![a, b, c], variant);
+                        let (_, score) = negamax(&game, 16, &Exhaustive).unwrap();
+                        let winning = score > WIN_SCORE / 2;
+                        assert_eq!(game.is_winning(), winning, "{:?}", game);
+
+                        // From a winning position, the AI should move to a losing one
+                        let mut next = game.clone();
+                        next.make_move(NimAi.choose_move(&game).unwrap());
+                        let wins = Game::winner(&next) == Some(game.current_player())
+                            || (!next.is_game_over() && !next.is_winning());
+                        assert_eq!(wins, winning, "{:?}", game);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,805 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::{Reversi, Piece, Move, Variant, Clock, TimeControl, GameStatus, TerminationReason};
+
+/// The longest line of moves written in a game record, as is customary for PGN
+const MAX_LINE_LENGTH: usize = 79;
+
+/// How a recorded game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameResult {
+    /// The given player won the game
+    Win(Piece),
+    /// The game ended in a tie
+    Draw,
+    /// The game was not finished, or its result is not known
+    #[default]
+    Unfinished,
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameResult::Win(Piece::X) => write!(f, "1-0"),
+            GameResult::Win(Piece::O) => write!(f, "0-1"),
+            GameResult::Draw => write!(f, "1/2-1/2"),
+            GameResult::Unfinished => write!(f, "*"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid result: `{0}`. Expected `1-0`, `0-1`, `1/2-1/2` or `*`")]
+pub struct ParseGameResultError(pub String);
+
+impl FromStr for GameResult {
+    type Err = ParseGameResultError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(GameResult::Win(Piece::X)),
+            "0-1" => Ok(GameResult::Win(Piece::O)),
+            "1/2-1/2" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Unfinished),
+            _ => Err(ParseGameResultError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum NotationError {
+    #[error("Line {line}: invalid tag `{text}`. Expected a tag like `[Name \"value\"]`")]
+    InvalidTag {
+        line: usize,
+        text: String,
+    },
+    #[error("Invalid value for the {tag} tag: `{value}`")]
+    InvalidTagValue {
+        tag: String,
+        value: String,
+    },
+    #[error("Line {line}: invalid move `{text}`")]
+    InvalidMove {
+        line: usize,
+        text: String,
+    },
+    #[error("Line {line}: invalid clock time `{text}`. Expected a time like `0:04:58`")]
+    InvalidClock {
+        line: usize,
+        text: String,
+    },
+    #[error("Line {line}: comment is never closed with `}}`")]
+    UnterminatedComment {
+        line: usize,
+    },
+    #[error("Line {line}: comment does not follow a move")]
+    CommentWithoutMove {
+        line: usize,
+    },
+    #[error("Line {line}: the result {found} does not match the Result tag ({expected})")]
+    ResultMismatch {
+        line: usize,
+        expected: GameResult,
+        found: GameResult,
+    },
+    #[error("Move {index} of the game ({pmove}) is not a valid move")]
+    IllegalMove {
+        index: usize,
+        pmove: Move,
+    },
+    #[error("The game is recorded as {recorded}, but its moves lead to {actual}")]
+    WrongResult {
+        recorded: GameResult,
+        actual: GameResult,
+    },
+    #[error("Expected a single game, found {0}")]
+    WrongGameCount(usize),
+}
+
+/// A move in a game record, along with its annotations
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotatedMove {
+    pub pmove: Move,
+    /// The main time left on the clock of the player who made the move, right after they made it
+    pub clock: Option<Duration>,
+    /// A comment about the move
+    pub comment: Option<String>,
+}
+
+impl NotatedMove {
+    /// Creates a move with no annotations
+    pub fn new(pmove: Move) -> Self {
+        Self {pmove, clock: None, comment: None}
+    }
+}
+
+/// A finished (or unfinished) game written down in a portable text format modeled after PGN
+///
+/// A record starts with tags like `[X "Alice"]` giving information about the game, followed by
+/// its moves. For example:
+///
+/// ```text
+/// [Event "Club night"]
+/// [Date "2026.10.16"]
+/// [X "Alice"]
+/// [O "Bob"]
+/// [Variant "standard"]
+/// [TimeControl "300+2"]
+/// [Termination "normal"]
+/// [Result "1-0"]
+///
+/// 1. E3 {[%clk 0:05:01] the usual opening} F5 {[%clk 0:04:58]} 2. E6 ...
+/// 1-0
+/// ```
+///
+/// Moves are written like they are entered (`E3`, `pass` or `resign`), optionally followed by a
+/// comment in braces. A `[%clk h:mm:ss]` command in a comment gives the time left on the clock of
+/// the player who made the move. The move list ends with the result of the game. Move numbers
+/// count pairs of moves and are only there to help people read the record.
+///
+/// The tags understood by `to_game` are:
+///
+/// * `Variant` - the rules of the game, e.g. `anti` (`standard` if missing)
+/// * `Position` - the starting position, if it is not the usual one, in the format written by
+///   `Reversi::to_position_str`, e.g. `--X...-- O`
+/// * `TimeControl` - the main time and increment in seconds, e.g. `300+2`, optionally followed by
+///   a byo-yomi period, e.g. `300+0+30`
+/// * `Termination` - why the game ended: `normal`, `resignation`, `time forfeit` or
+///   `unterminated`
+///
+/// Every other tag (e.g. `Event`, `Date` and the names of the players in `X` and `O`) is kept as
+/// is. The `Result` tag is stored in `result` rather than with the other tags.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GameRecord {
+    /// The tags of the record in the order they are written, not including the result
+    tags: Vec<(String, String)>,
+    /// Every move of the game, in order
+    pub moves: Vec<NotatedMove>,
+    /// How the game ended
+    pub result: GameResult,
+}
+
+impl GameRecord {
+    /// Creates an empty record with no tags or moves
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a record of the given game with its rules, starting position, moves, clock times
+    /// and result
+    ///
+    /// If the players of the game are known (see `Reversi::set_players`), they are recorded as
+    /// "Human" or as the AI and its difficulty. Tags that are not known from the game itself,
+    /// like `Date`, can be added with `set_tag`.
+    pub fn from_game(game: &Reversi) -> Self {
+        let mut record = Self::new();
+        record.set_tag("Variant", game.variant().to_string());
+        if let Some(players) = game.players() {
+            let name = |is_ai: bool| if is_ai {
+                format!("AI ({})", players.difficulty)
+            } else {
+                "Human".to_string()
+            };
+            record.set_tag("X", name(players.ai_players[0]));
+            record.set_tag("O", name(players.ai_players[1]));
+        }
+
+        let start = game.at_move(0);
+        let usual_start = Reversi::new(game.variant());
+        if start.grid() != usual_start.grid() || start.current_player() != usual_start.current_player() {
+            record.set_tag("Position", start.to_position_str());
+        }
+
+        // The clock is replayed from the time taken by each move. Think times are only recorded
+        // while a game is timed, so clock times are left out if the clock was added mid-game.
+        let think_times = game.think_times();
+        let mut clock = game.clock()
+            .filter(|_| think_times.len() >= game.history().len())
+            .map(|clock| Clock::new(clock.control()));
+        if let Some(control) = game.clock().map(Clock::control) {
+            record.set_tag("TimeControl", format_time_control(control));
+        }
+
+        for (i, &pmove) in game.history().iter().enumerate() {
+            let mut notated = NotatedMove::new(pmove);
+            if let (Some(clock), Some(&(piece, think_time))) = (&mut clock, think_times.get(i)) {
+                clock.charge(piece, think_time);
+                notated.clock = Some(clock.remaining(piece));
+            }
+            record.moves.push(notated);
+        }
+
+        let termination = match game.termination() {
+            None => "unterminated",
+            Some(TerminationReason::FullBoard) | Some(TerminationReason::DoublePass) => "normal",
+            Some(TerminationReason::Resignation(_)) => "resignation",
+            Some(TerminationReason::TimeForfeit(_)) => "time forfeit",
+        };
+        record.set_tag("Termination", termination);
+        record.result = result_of(game);
+
+        record
+    }
+
+    /// Returns every tag of the record in the order they are written, not including the result
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// Returns the value of the tag with the given name, if the record has it
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of the tag with the given name, replacing its previous value (if any)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not made of letters, digits and underscores starting with a letter,
+    /// or if it is `Result`, which is stored in `result` instead.
+    pub fn set_tag(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let (name, value) = (name.into(), value.into());
+        assert!(is_tag_name(&name), "bug: invalid tag name `{}`", name);
+        assert_ne!(name, "Result", "bug: the result of a game record should be set with its `result` field");
+
+        match self.tags.iter_mut().find(|(tag, _)| *tag == name) {
+            Some((_, old_value)) => *old_value = value,
+            None => self.tags.push((name, value)),
+        }
+    }
+
+    /// Sets the value of the tag with the given name (see `set_tag`)
+    pub fn with_tag(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set_tag(name, value);
+        self
+    }
+
+    /// Replays the recorded game, checking that every move is valid and that the game ends with
+    /// the recorded result
+    ///
+    /// If the game is timed, its clock is restored from the last clock time recorded for each
+    /// player. A game that is recorded as won before it was over is ended by the loser resigning,
+    /// or by them running out of time if the `Termination` tag says so.
+    pub fn to_game(&self) -> Result<Reversi, NotationError> {
+        let invalid = |tag: &str, value: &str| NotationError::InvalidTagValue {tag: tag.to_string(), value: value.to_string()};
+
+        let variant = match self.tag("Variant") {
+            Some(value) => value.parse().map_err(|_| invalid("Variant", value))?,
+            None => Variant::default(),
+        };
+        let mut game = match self.tag("Position") {
+            Some(value) => Reversi::from_position_str(variant, value).map_err(|_| invalid("Position", value))?,
+            None => Reversi::new(variant),
+        };
+        let mut timing = match self.tag("TimeControl") {
+            Some(value) => {
+                let control = parse_time_control(value).ok_or_else(|| invalid("TimeControl", value))?;
+                Some((control, [control.initial; 2]))
+            },
+            None => None,
+        };
+
+        for (index, notated) in self.moves.iter().enumerate() {
+            let legal = !game.is_game_over() && match notated.pmove {
+                Move::Place(pos) => game.valid_moves().contains(&pos),
+                Move::Pass => game.valid_moves().is_empty(),
+                Move::Resign => true,
+            };
+            if !legal {
+                return Err(NotationError::IllegalMove {index: index + 1, pmove: notated.pmove});
+            }
+
+            if let (Some((_, remaining)), Some(clock)) = (&mut timing, notated.clock) {
+                let index = match game.current_player() {
+                    Piece::X => 0,
+                    Piece::O => 1,
+                };
+                remaining[index] = clock;
+            }
+            let _ = game.apply(notated.pmove);
+        }
+
+        let mut flagged = None;
+        if let GameResult::Win(winner) = self.result {
+            if !game.is_game_over() {
+                if self.tag("Termination") == Some("time forfeit") && timing.is_some() {
+                    flagged = Some(winner.opposite());
+                } else {
+                    game.resign(winner.opposite());
+                }
+            }
+        }
+        let clock = timing.map(|(control, remaining)| Clock::resume(control, remaining, flagged));
+        game.resume_timing(clock, Vec::new());
+
+        let actual = result_of(&game);
+        if self.result != GameResult::Unfinished && self.result != actual {
+            return Err(NotationError::WrongResult {recorded: self.result, actual});
+        }
+
+        Ok(game)
+    }
+}
+
+impl fmt::Display for GameRecord {
+    /// Writes the record in the format described in `GameRecord`, which can be parsed back with
+    /// `str::parse` or `parse_records`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, escape_tag_value(value))?;
+        }
+        writeln!(f, "[Result \"{}\"]", self.result)?;
+        writeln!(f)?;
+
+        let mut tokens = Vec::new();
+        for (i, notated) in self.moves.iter().enumerate() {
+            if i % 2 == 0 {
+                tokens.push(format!("{}.", i / 2 + 1));
+            }
+            tokens.push(notated.pmove.to_string());
+
+            let mut annotations = Vec::new();
+            if let Some(clock) = notated.clock {
+                annotations.push(format!("[%clk {}]", format_clock(clock)));
+            }
+            if let Some(comment) = &notated.comment {
+                // Comments end at the first closing brace, so they cannot contain one
+                annotations.push(comment.replace('}', ""));
+            }
+            if !annotations.is_empty() {
+                tokens.push(format!("{{{}}}", annotations.join(" ")));
+            }
+        }
+        tokens.push(self.result.to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = NotationError;
+
+    /// Parses a single game record in the format described in `GameRecord`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut records = parse_records(s)?;
+        match records.len() {
+            1 => Ok(records.remove(0)),
+            count => Err(NotationError::WrongGameCount(count)),
+        }
+    }
+}
+
+/// Parses any number of game records in the format described in `GameRecord`, one after the
+/// other
+///
+/// Each record ends with its result, or where the tags of the next record begin. The moves are
+/// not checked for legality until the game is replayed with `GameRecord::to_game`.
+pub fn parse_records(text: &str) -> Result<Vec<GameRecord>, NotationError> {
+    let mut records = Vec::new();
+    let mut record = GameRecord::new();
+    // The result given by the Result tag of the current record, if any
+    let mut result_tag = None;
+    // True once anything has been read for the current record
+    let mut started = false;
+    // True once the moves of the current record have started
+    let mut in_moves = false;
+
+    for (line, token) in tokenize(text)? {
+        match token {
+            Token::Tag(name, value) => {
+                if in_moves {
+                    record.result = result_tag.take().unwrap_or_default();
+                    records.push(std::mem::take(&mut record));
+                    in_moves = false;
+                }
+
+                if name == "Result" {
+                    result_tag = Some(value.parse().map_err(|_| NotationError::InvalidTagValue {tag: name, value})?);
+                } else {
+                    record.set_tag(name, value);
+                }
+                started = true;
+            },
+
+            Token::Comment(text) => {
+                let notated = record.moves.last_mut().ok_or(NotationError::CommentWithoutMove {line})?;
+                parse_comment(notated, &text, line)?;
+            },
+
+            Token::Word(word) => {
+                if let Ok(result) = word.parse::<GameResult>() {
+                    match result_tag.take() {
+                        Some(expected) if expected != result => {
+                            return Err(NotationError::ResultMismatch {line, expected, found: result});
+                        },
+                        _ => {},
+                    }
+                    record.result = result;
+                    records.push(std::mem::take(&mut record));
+                    started = false;
+                    in_moves = false;
+                    continue;
+                }
+
+                started = true;
+                in_moves = true;
+                let pmove = match strip_move_number(&word) {
+                    "" => continue,
+                    pmove => pmove,
+                };
+                let pmove = parse_move(pmove).ok_or_else(|| NotationError::InvalidMove {line, text: word.clone()})?;
+                record.moves.push(NotatedMove::new(pmove));
+            },
+        }
+    }
+
+    if started {
+        record.result = result_tag.unwrap_or_default();
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// A piece of text in a game record
+#[derive(Debug)]
+enum Token {
+    /// A tag with its name and (unescaped) value
+    Tag(String, String),
+    /// The text between a pair of braces
+    Comment(String),
+    /// A move, move number or result
+    Word(String),
+}
+
+/// Splits the given text into tokens, along with the line number that each token starts on
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, NotationError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\n' => line += 1,
+            _ if ch.is_whitespace() => {},
+
+            // Tags always take up the rest of their line
+            '[' => {
+                let rest: String = chars.by_ref().take_while(|&ch| ch != '\n').collect();
+                let (name, value) = parse_tag(&rest).ok_or_else(|| NotationError::InvalidTag {
+                    line,
+                    text: format!("[{}", rest.trim_end()),
+                })?;
+                tokens.push((line, Token::Tag(name, value)));
+                line += 1;
+            },
+
+            '{' => {
+                let start = line;
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(ch) => {
+                            if ch == '\n' {
+                                line += 1;
+                            }
+                            comment.push(ch);
+                        },
+                        None => return Err(NotationError::UnterminatedComment {line: start}),
+                    }
+                }
+                tokens.push((start, Token::Comment(comment)));
+            },
+
+            _ => {
+                let mut word = ch.to_string();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || ch == '{' || ch == '[' {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push((line, Token::Word(word)));
+            },
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses the rest of a tag after its opening `[`, returning its name and unescaped value
+fn parse_tag(text: &str) -> Option<(String, String)> {
+    let text = text.trim_end().strip_suffix(']')?;
+    let (name, value) = text.split_once(char::is_whitespace)?;
+    if !is_tag_name(name) {
+        return None;
+    }
+
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => unescaped.push(chars.next()?),
+            '"' => return None,
+            _ => unescaped.push(ch),
+        }
+    }
+
+    Some((name.to_string(), unescaped))
+}
+
+/// Returns true if the given text can be used as the name of a tag
+fn is_tag_name(name: &str) -> bool {
+    name.starts_with(|ch: char| ch.is_ascii_alphabetic()) && name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// Escapes the quotes and backslashes in the value of a tag so it can be written between quotes
+fn escape_tag_value(value: &str) -> String {
+    let mut escaped = String::new();
+    for ch in value.chars() {
+        match ch {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            },
+            // Tags must fit on a single line
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Returns the given word without the move number it starts with, if any (e.g. "12." or "1.E3")
+fn strip_move_number(word: &str) -> &str {
+    let digits = word.len() - word.trim_start_matches(|ch: char| ch.is_ascii_digit()).len();
+    match word[digits..].strip_prefix('.') {
+        Some(rest) if digits > 0 => rest.trim_start_matches('.'),
+        _ => word,
+    }
+}
+
+/// Parses a move written like it is entered: a tile like "E3", "pass" or "resign"
+fn parse_move(text: &str) -> Option<Move> {
+    if text.eq_ignore_ascii_case("pass") {
+        Some(Move::Pass)
+    } else if text.eq_ignore_ascii_case("resign") {
+        Some(Move::Resign)
+    } else {
+        text.parse().ok().map(Move::Place)
+    }
+}
+
+/// Adds the clock time and comment in the given comment text to the move it follows
+fn parse_comment(notated: &mut NotatedMove, text: &str, line: usize) -> Result<(), NotationError> {
+    let mut text = text.to_string();
+    if let Some(start) = text.find("[%clk") {
+        let invalid = |text: &str| NotationError::InvalidClock {line, text: text.trim().to_string()};
+        let end = start + text[start..].find(']').ok_or_else(|| invalid(&text[start + 5..]))?;
+        let value = &text[start + 5..end];
+        notated.clock = Some(parse_clock(value.trim()).ok_or_else(|| invalid(value))?);
+        text.replace_range(start..=end, "");
+    }
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !text.is_empty() {
+        notated.comment = Some(match notated.comment.take() {
+            Some(comment) => format!("{} {}", comment, text),
+            None => text,
+        });
+    }
+
+    Ok(())
+}
+
+/// Formats a clock time as hours, minutes and seconds, e.g. "0:04:58"
+fn format_clock(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Parses a clock time like "0:04:58", "4:58" or "0:04:58.25"
+fn parse_clock(text: &str) -> Option<Duration> {
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let parts: Vec<_> = whole.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let mut secs = 0;
+    for part in parts {
+        if !is_number(part) {
+            return None;
+        }
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+
+    let millis = match fraction {
+        "" => 0,
+        _ if is_number(fraction) && fraction.len() <= 3 => format!("{:0<3}", fraction).parse().ok()?,
+        _ => return None,
+    };
+
+    Some(Duration::from_secs(secs) + Duration::from_millis(millis))
+}
+
+/// Formats a time control as its main time and increment in seconds, followed by its byo-yomi
+/// period if it has one, e.g. "300+2" or "300+0+30"
+fn format_time_control(control: TimeControl) -> String {
+    let mut text = format!("{}+{}", control.initial.as_secs(), control.increment.as_secs());
+    if let Some(byo_yomi) = control.byo_yomi {
+        text += &format!("+{}", byo_yomi.as_secs());
+    }
+    text
+}
+
+/// Parses a time control written by `format_time_control`
+fn parse_time_control(text: &str) -> Option<TimeControl> {
+    let secs: Vec<u64> = text.split('+').map(|part| part.trim().parse().ok()).collect::<Option<_>>()?;
+    let (initial, increment, byo_yomi) = match secs[..] {
+        [initial, increment] => (initial, increment, None),
+        [initial, increment, byo_yomi] => (initial, increment, Some(byo_yomi)),
+        _ => return None,
+    };
+
+    Some(TimeControl {
+        initial: Duration::from_secs(initial),
+        increment: Duration::from_secs(increment),
+        byo_yomi: byo_yomi.map(Duration::from_secs),
+    })
+}
+
+/// Returns the result of the given game so far
+fn result_of(game: &Reversi) -> GameResult {
+    match game.status() {
+        GameStatus::InProgress => GameResult::Unfinished,
+        GameStatus::Finished {winner: Some(winner), ..} => GameResult::Win(winner),
+        GameStatus::Finished {winner: None, ..} => GameResult::Draw,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(s: &str) -> crate::TilePos {
+        s.parse().unwrap()
+    }
+
+    /// Plays the first valid move (or passes) until the game is over
+    fn finished_game(mut game: Reversi) -> Reversi {
+        while !game.is_game_over() {
+            match game.valid_moves().first() {
+                Some(&pos) => { game.make_move(pos); },
+                None => game.pass(),
+            }
+        }
+        game
+    }
+
+    #[test]
+    fn writes_and_reads_finished_games() {
+        let mut game = Reversi::with_board_size(Variant::Anti, 6);
+        game.set_clock(Clock::new(TimeControl {
+            initial: Duration::from_secs(300),
+            increment: Duration::from_secs(2),
+            byo_yomi: None,
+        }));
+        let game = finished_game(game);
+
+        let mut record = GameRecord::from_game(&game).with_tag("Event", "Test \"game\"");
+        record.moves[0].comment = Some("a quiet {start}".to_string());
+        assert_eq!(record.tag("Variant"), Some("anti"));
+        assert_eq!(record.tag("TimeControl"), Some("300+2"));
+        // The first move took almost no time, so X gained most of the increment
+        let clock = record.moves[0].clock.unwrap();
+        assert!(clock > Duration::from_secs(301) && clock <= Duration::from_secs(302));
+        assert_ne!(record.result, GameResult::Unfinished);
+
+        let text = record.to_string();
+        assert!(text.contains("[Event \"Test \\\"game\\\"\"]"));
+        assert!(text.contains("1. "));
+        assert!(text.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+
+        let parsed: GameRecord = text.parse().unwrap();
+        assert_eq!(parsed.tag("Event"), Some("Test \"game\""));
+        assert_eq!(parsed.moves[0].comment.as_deref(), Some("a quiet {start"));
+        assert_eq!(parsed.moves.len(), record.moves.len());
+        assert_eq!(parsed.result, record.result);
+
+        let replayed = parsed.to_game().unwrap();
+        assert_eq!(replayed.history(), game.history());
+        assert_eq!(replayed.grid(), game.grid());
+        assert_eq!(replayed.at_move(0).grid(), game.at_move(0).grid());
+        assert_eq!(replayed.clock().unwrap().control(), game.clock().unwrap().control());
+    }
+
+    #[test]
+    fn parses_handwritten_records() {
+        let text = "
+            [X \"Alice\"]
+            [O \"Bob \\\\ friends\"]
+            [TimeControl \"60+0+10\"]
+
+            1. E3 {[%clk 0:00:58.5] solid} F5 2.F6 {opening
+            book} {[%clk 0:59]} 0-1
+
+            [Result \"*\"]
+            1... E3 *
+        ";
+        let records = parse_records(text).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let record = &records[0];
+        assert_eq!(record.tag("O"), Some("Bob \\ friends"));
+        let moves: Vec<_> = record.moves.iter().map(|notated| notated.pmove).collect();
+        assert_eq!(moves, vec![Move::Place(pos("E3")), Move::Place(pos("F5")), Move::Place(pos("F6"))]);
+        assert_eq!(record.moves[0].clock, Some(Duration::from_millis(58_500)));
+        assert_eq!(record.moves[0].comment.as_deref(), Some("solid"));
+        assert_eq!(record.moves[2].clock, Some(Duration::from_secs(59)));
+        assert_eq!(record.moves[2].comment.as_deref(), Some("opening book"));
+        assert_eq!(record.result, GameResult::Win(Piece::O));
+
+        // The game was not over, so X must have resigned
+        let game = record.to_game().unwrap();
+        assert_eq!(game.termination(), Some(TerminationReason::Resignation(Piece::X)));
+        let clock = game.clock().unwrap();
+        assert_eq!(clock.control().byo_yomi, Some(Duration::from_secs(10)));
+        assert_eq!(clock.remaining(Piece::X), Duration::from_secs(59));
+        assert_eq!(clock.remaining(Piece::O), Duration::from_secs(60));
+
+        assert_eq!(records[1].tags(), &[]);
+        assert_eq!(records[1].moves.len(), 1);
+        assert_eq!(records[1].result, GameResult::Unfinished);
+    }
+
+    #[test]
+    fn rejects_invalid_records() {
+        let parse = |text: &str| text.parse::<GameRecord>().and_then(|record| record.to_game());
+        assert!(matches!(parse("[X Alice]\n1. E3 *"), Err(NotationError::InvalidTag {line: 1, ..})));
+        assert!(matches!(parse("1. E3 Q9Q *"), Err(NotationError::InvalidMove {line: 1, ..})));
+        assert!(matches!(parse("1. E3\n{unfinished"), Err(NotationError::UnterminatedComment {line: 2})));
+        assert!(matches!(parse("{first} 1. E3 *"), Err(NotationError::CommentWithoutMove {line: 1})));
+        assert!(matches!(parse("1. E3 {[%clk soon]} *"), Err(NotationError::InvalidClock {..})));
+        assert!(matches!(parse("[Result \"1-0\"]\n1. E3 0-1"), Err(NotationError::ResultMismatch {line: 2, ..})));
+        assert!(matches!(parse("[Variant \"chess\"]\n*"), Err(NotationError::InvalidTagValue {..})));
+        assert_eq!(parse("1. E3 A1 *").unwrap_err(), NotationError::IllegalMove {index: 2, pmove: Move::Place(pos("A1"))});
+        assert!(matches!(parse("1. E3 1/2-1/2"), Err(NotationError::WrongResult {..})));
+        assert_eq!(parse("1. E3 * 1. E3 *").unwrap_err(), NotationError::WrongGameCount(2));
+    }
+
+    #[test]
+    fn records_custom_starting_positions() {
+        let mut game = Reversi::default();
+        game.make_move(game.valid_moves()[0]);
+        let start = Reversi::from_grid(Variant::Standard, game.grid().clone(), Piece::O);
+
+        let record = GameRecord::from_game(&start);
+        assert!(record.tag("Position").unwrap().ends_with(" O"));
+        assert_eq!(record.tag("Termination"), Some("unterminated"));
+        assert!(record.tag("TimeControl").is_none());
+
+        let replayed = record.to_string().parse::<GameRecord>().unwrap().to_game().unwrap();
+        assert_eq!(replayed.grid(), start.grid());
+        assert_eq!(replayed.current_player(), Piece::O);
+        assert!(replayed.clock().is_none());
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt;
+
+use crate::{Reversi, Piece, TilePos};
+
+/// Receives notifications about changes to a game
+///
+/// Register an observer with `Reversi::subscribe`. Every method has a default implementation
+/// that does nothing, so only the events of interest need to be implemented.
+///
+/// Each method is called *after* the change has been made to the game.
+pub trait GameObserver {
+    /// Called when the given player places a piece at the given position
+    fn on_move(&mut self, _game: &Reversi, _player: Piece, _pos: TilePos) {}
+
+    /// Called when pieces are flipped to the given player as a result of a move
+    fn on_flips(&mut self, _game: &Reversi, _player: Piece, _flips: &[TilePos]) {}
+
+    /// Called when the given player skips their turn
+    fn on_pass(&mut self, _game: &Reversi, _player: Piece) {}
+
+    /// Called when the game has ended, with the winner (or `None` for a tie)
+    fn on_game_over(&mut self, _game: &Reversi, _winner: Option<Piece>) {}
+}
+
+/// The observers subscribed to a game
+///
+/// Observers are tied to a single game instance, so cloning a game (e.g. to search through
+/// possible moves) does not clone its observers.
+#[derive(Default)]
+pub(crate) struct Observers(pub(crate) Vec<Box<dyn GameObserver + Send>>);
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Observers::default()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
@@ -2,19 +2,24 @@ use std::fmt;
 
 use yansi::Paint;
 
+use crate::Theme;
+
 /// Represents the different colors/types of pieces
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Piece {
     X,
     O,
 }
 
+/// Writes the piece the way the default theme draws it, or as its letter when colors are disabled
+/// since the pieces of the default theme only differ by their color
 impl fmt::Display for Piece {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Piece::X => write!(f, "{}", Paint::red("\u{25CF}")),
-            Piece::O => write!(f, "{}", Paint::blue("\u{25CF}")),
+        if !Paint::is_enabled() {
+            return write!(f, "{:?}", self);
         }
+
+        write!(f, "{}", Theme::default().paint_piece(*self))
     }
 }
 
@@ -0,0 +1,379 @@
+use std::cell::RefCell;
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Instant;
+
+use rand::{Rng, rngs::ThreadRng, thread_rng, seq::SliceRandom};
+
+use crate::{Game, Reversi, Move, TilePos, AiPlayer, AiDifficulty, ParseError, MoveInput, Theme, DisplayOptions, Orientation, RenderSize, Console, StdConsole, prompt_move_until, confirm, print_game_with, suggest_move};
+#[cfg(feature = "cursor")]
+use crate::prompt_move_with_cursor;
+
+/// Anything that can decide which move to make on its turn
+///
+/// Players of Reversi are the default, but a player can be written for any `Game` (see
+/// `run_game`).
+pub trait Player<G: Game = Reversi> {
+    /// Returns the move to make for the current player of the given game
+    ///
+    /// In Reversi, if the current player has no valid moves, the returned move should be
+    /// `Move::Pass` (or `Move::Resign`).
+    fn choose_move(&mut self, game: &G) -> G::Move;
+
+    /// Returns what the current player of the given game wants to do on their turn
+    ///
+    /// Only players that can ask for more than a move (e.g. to undo their last move) need to
+    /// implement this. A game loop that can handle those requests should call this instead of
+    /// `choose_move`.
+    fn choose_action(&mut self, game: &G) -> PlayerAction<G::Move> {
+        PlayerAction::Move(self.choose_move(game))
+    }
+}
+
+/// What a player wants to do on their turn, as returned by `Player::choose_action`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayerAction<M = Move> {
+    /// Make the given move
+    Move(M),
+    /// Take back moves until it is this player's turn again
+    Undo,
+    /// Play the moves taken back by the last `Undo` again
+    Redo,
+    /// Save the game to the given file
+    Save(PathBuf),
+    /// Load the game saved in the given file
+    Load(PathBuf),
+    /// Stop playing without finishing the game
+    Quit,
+}
+
+/// A human player that enters their moves on stdin
+///
+/// Entering `hint` shows the move the AI would play on the board and entering `flip` shows the
+/// board from the other player's side. The other commands (see `MoveInput`) are returned from
+/// `choose_action` for the game loop to handle. Reaching the end of input (or failing to read
+/// from stdin) resigns the game.
+///
+/// Before resigning, quitting with moves that have not been saved or loading over a game in
+/// progress, the player is asked to confirm unless confirmations are turned off (see
+/// `with_confirmations`).
+#[derive(Debug, Clone)]
+pub struct HumanPrompt {
+    /// How strong the AI that suggests hints plays
+    hint_strength: AiDifficulty,
+    /// The theme the board is drawn with when showing a hint
+    theme: Theme,
+    /// If true, the valid moves can be chosen by entering their labels (see `move_label`)
+    numbered_moves: bool,
+    /// Which side the board is drawn from, toggled by the `flip` command
+    orientation: Orientation,
+    /// How big the tiles of the board are drawn when showing a hint
+    render_size: RenderSize,
+    /// If true, moves are chosen by moving a cursor over the board instead of typing them
+    #[cfg(feature = "cursor")]
+    cursor: bool,
+    /// If true, the player is asked to confirm actions that would lose the game or its moves
+    confirmations: bool,
+    /// The moves of the game as of the last `save` command, shared by clones so that either
+    /// player saving the game counts
+    saved: Rc<RefCell<Vec<Move>>>,
+}
+
+impl Default for HumanPrompt {
+    fn default() -> Self {
+        Self {
+            hint_strength: AiDifficulty::default(),
+            theme: Theme::default(),
+            numbered_moves: false,
+            orientation: Orientation::default(),
+            render_size: RenderSize::default(),
+            #[cfg(feature = "cursor")]
+            cursor: false,
+            confirmations: true,
+            saved: Rc::default(),
+        }
+    }
+}
+
+impl HumanPrompt {
+    /// Creates a player whose hints are suggested by an AI of the default difficulty
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how strong the AI that suggests hints plays
+    pub fn with_hint_strength(mut self, strength: AiDifficulty) -> Self {
+        self.hint_strength = strength;
+        self
+    }
+
+    /// Sets the theme the board is drawn with when showing a hint
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets how big the tiles of the board are drawn when showing a hint
+    pub fn with_render_size(mut self, size: RenderSize) -> Self {
+        self.render_size = size;
+        self
+    }
+
+    /// Sets the orientation toggled by the `flip` command. Share it with everything else that draws
+    /// the board so that they all flip together.
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Allows the valid moves to be chosen by entering the labels they are shown with on the board
+    /// (see `DisplayOptions::numbered_moves`)
+    pub fn with_numbered_moves(mut self, numbered_moves: bool) -> Self {
+        self.numbered_moves = numbered_moves;
+        self
+    }
+
+    /// Lets moves be chosen by moving a cursor over the board with the arrow keys (see
+    /// `prompt_move_with_cursor`). Moves can still be typed when stdin is not a terminal or if the
+    /// player asks to.
+    #[cfg(feature = "cursor")]
+    pub fn with_cursor(mut self, cursor: bool) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// Sets whether the player is asked to confirm resigning, quitting with unsaved moves and
+    /// loading over a game in progress. Turn this off when the input is not typed by a person
+    /// (e.g. it is piped from a file).
+    pub fn with_confirmations(mut self, confirmations: bool) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Asks the given question if confirmations are turned on, returning true if the player
+    /// confirmed it or was not asked
+    fn confirmed(&self, question: &str) -> bool {
+        if !self.confirmations {
+            return true;
+        }
+
+        match confirm(StdConsole, question) {
+            Ok(answer) => answer,
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                false
+            },
+        }
+    }
+
+    /// Returns the options the board is drawn with, marking the given valid moves
+    fn display_options<'a>(&self, valid_moves: &'a [TilePos]) -> DisplayOptions<'a> {
+        DisplayOptions {
+            valid_moves,
+            numbered_moves: self.numbered_moves,
+            theme: self.theme,
+            flip_board: self.orientation.is_flipped(),
+            size: self.render_size,
+            ..DisplayOptions::default()
+        }
+    }
+
+    /// Prompts for the move of the current player of the given game, with the cursor if enabled
+    fn prompt_input(&self, game: &Reversi) -> Result<MoveInput, ParseError> {
+        #[cfg(feature = "cursor")]
+        if self.cursor {
+            if let Some(input) = prompt_move_with_cursor(game, self.display_options(game.valid_moves()))? {
+                return Ok(input);
+            }
+        }
+
+        prompt_move_until(StdConsole, game.valid_moves(), self.numbered_moves, deadline(game))
+    }
+}
+
+impl Player for HumanPrompt {
+    fn choose_move(&mut self, game: &Reversi) -> Move {
+        loop {
+            match self.choose_action(game) {
+                PlayerAction::Move(pmove) => return pmove,
+                // Without a game loop to stop, quitting is the same as giving up
+                PlayerAction::Quit => return Move::Resign,
+                _ => println!("That command is not available in this game.\n"),
+            }
+        }
+    }
+
+    fn choose_action(&mut self, game: &Reversi) -> PlayerAction {
+        let valid_moves = game.valid_moves();
+        if valid_moves.is_empty() {
+            let message = "No moves available. Skipping turn. Press enter to continue...";
+            return PlayerAction::Move(match StdConsole.read_line(message, &[], deadline(game)) {
+                // An empty line (not even a newline) means we reached EOF
+                Ok(line) if line.is_empty() => Move::Resign,
+                Ok(_) => Move::Pass,
+                Err(err) if err.kind() == io::ErrorKind::TimedOut => prompt_error_move(ParseError::TimedOut),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    Move::Resign
+                },
+            });
+        }
+
+        loop {
+            let input = match self.prompt_input(game) {
+                Ok(input) => input,
+                Err(err) => return PlayerAction::Move(prompt_error_move(err)),
+            };
+
+            match input {
+                MoveInput::Move(pmove) => return PlayerAction::Move(Move::Place(pmove)),
+                MoveInput::Undo => return PlayerAction::Undo,
+                MoveInput::Redo => return PlayerAction::Redo,
+
+                MoveInput::Save(path) => {
+                    *self.saved.borrow_mut() = game.history().to_vec();
+                    return PlayerAction::Save(path);
+                },
+
+                MoveInput::Resign => if self.confirmed("Are you sure you want to resign?") {
+                    return PlayerAction::Move(Move::Resign);
+                },
+                MoveInput::Quit => {
+                    let unsaved = !game.history().is_empty() && *self.saved.borrow() != game.history();
+                    if !unsaved || self.confirmed("The game has not been saved. Are you sure you want to quit?") {
+                        return PlayerAction::Quit;
+                    }
+                },
+                MoveInput::Load(path) => {
+                    let in_progress = !game.history().is_empty();
+                    if !in_progress || self.confirmed("This replaces the game in progress. Are you sure?") {
+                        return PlayerAction::Load(path);
+                    }
+                },
+
+                MoveInput::Hint => {
+                    let (hint, score) = suggest_move(game, self.hint_strength);
+                    if let Move::Place(pos) = hint {
+                        println!();
+                        print_game_with(game, DisplayOptions {
+                            hint: Some(pos),
+                            ..self.display_options(valid_moves)
+                        });
+                        println!();
+                    }
+                    println!("Hint: {} (score: {})", hint, score);
+                },
+
+                MoveInput::Best(strength) => {
+                    let (best, score) = suggest_move(game, strength.unwrap_or(self.hint_strength));
+                    println!("Playing the AI's choice: {} (score: {})", best, score);
+                    return PlayerAction::Move(best);
+                },
+
+                MoveInput::Flip => {
+                    self.orientation.toggle();
+                    println!();
+                    print_game_with(game, self.display_options(valid_moves));
+                    println!();
+                },
+
+                MoveInput::Moves => {
+                    let moves: Vec<_> = valid_moves.iter().map(TilePos::to_string).collect();
+                    println!("Valid moves: {}\n", moves.join(" "));
+                },
+
+                MoveInput::History if game.history().is_empty() => println!("No moves have been made yet\n"),
+                MoveInput::History => {
+                    for (i, pmove) in game.history().iter().enumerate() {
+                        println!("{:>3}. {}", i + 1, pmove);
+                    }
+                    println!();
+                },
+
+                // Passing is only allowed without any valid moves, which is handled above
+                MoveInput::Pass => println!("You cannot pass while you have a valid move.\n"),
+            }
+        }
+    }
+}
+
+/// Returns when the current player of the given game runs out of time, or `None` if the game is
+/// not timed
+pub(crate) fn deadline(game: &Reversi) -> Option<Instant> {
+    game.clock().map(|clock| Instant::now() + clock.time_left(game.current_player()))
+}
+
+/// Returns the move to make when the human player could not be prompted for their move
+fn prompt_error_move(err: ParseError) -> Move {
+    match err {
+        ParseError::EndOfInput => {
+            // Print a final newline
+            println!();
+            Move::Resign
+        },
+
+        ParseError::InvalidInput(_) => unreachable!(),
+
+        // Any move made once the clock has run out loses the game on time (see `Clock::flagged`),
+        // so passing ends it with `TerminationReason::TimeForfeit`
+        ParseError::TimedOut => {
+            println!("\nYou ran out of time.");
+            Move::Pass
+        },
+
+        ParseError::IOError(err) => {
+            eprintln!("Error: {}", err);
+            Move::Resign
+        },
+    }
+}
+
+/// An AI that picks one of its valid moves at random
+#[derive(Debug, Clone)]
+pub struct RandomAi<R = ThreadRng> {
+    rng: R,
+}
+
+impl RandomAi {
+    /// Creates an AI that uses the thread-local random number generator
+    pub fn new() -> Self {
+        Self::with_rng(thread_rng())
+    }
+}
+
+impl Default for RandomAi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Rng> RandomAi<R> {
+    /// Creates an AI that takes all of its randomness from the given random number generator
+    pub fn with_rng(rng: R) -> Self {
+        Self {rng}
+    }
+}
+
+impl<R: Rng> Player for RandomAi<R> {
+    fn choose_move(&mut self, game: &Reversi) -> Move {
+        match game.valid_moves().choose(&mut self.rng) {
+            Some(&pmove) => Move::Place(pmove),
+            None => Move::Pass,
+        }
+    }
+}
+
+/// The negamax AI. In timed games, the clock decides how long it thinks for. Otherwise, it thinks
+/// for the amount of time given by its difficulty.
+impl<R: Rng> Player for AiPlayer<R> {
+    fn choose_move(&mut self, game: &Reversi) -> Move {
+        if game.valid_moves().is_empty() {
+            println!("No moves available. Skipping turn.");
+            return Move::Pass;
+        }
+
+        let budget = self.move_budget(game);
+        Move::Place(self.compute_move(game, budget).best_move)
+    }
+}
@@ -0,0 +1,95 @@
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::{Reversi, Move, TilePos, AiPlayer, SearchResult, CancelToken};
+
+/// How often to check whether a ponder search has finished while waiting for it
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A search running in the background on the opponent's time
+///
+/// After making its move, the AI predicts the opponent's reply and starts searching the position
+/// after that reply while the opponent is still thinking. If the opponent plays the predicted
+/// move, the AI can answer right away using the search that is already in progress.
+#[derive(Debug)]
+pub struct Ponder {
+    /// The reply the search is based on
+    predicted: TilePos,
+    /// When the search was started
+    started: Instant,
+    /// Canceled to stop the search early
+    cancel: CancelToken,
+    /// The thread running the search (only `None` once the search has been finished)
+    handle: Option<JoinHandle<SearchResult>>,
+}
+
+impl Ponder {
+    /// Starts pondering the position that results from the opponent playing the predicted move
+    /// in the given game, searching for at most the given amount of time
+    ///
+    /// The search is done with the given AI on a background thread. Returns `None` if the
+    /// predicted move is not valid or if the AI would have no moves to search after it.
+    pub fn start<R>(
+        mut ai: AiPlayer<R>,
+        game: &Reversi,
+        predicted: TilePos,
+        max_time: Duration,
+    ) -> Option<Self>
+        where R: Rng + Send + 'static,
+    {
+        if !game.valid_moves().contains(&predicted) {
+            return None;
+        }
+
+        let mut game = game.clone();
+        let _ = game.apply(Move::Place(predicted));
+        if game.is_game_over() || game.valid_moves().is_empty() {
+            return None;
+        }
+
+        let cancel = CancelToken::new();
+        let handle = {
+            let cancel = cancel.clone();
+            thread::spawn(move || ai.compute_move_cancelable(&game, max_time, &cancel))
+        };
+
+        Some(Self {predicted, started: Instant::now(), cancel, handle: Some(handle)})
+    }
+
+    /// Returns the opponent's reply that is being pondered
+    pub fn predicted(&self) -> TilePos {
+        self.predicted
+    }
+
+    /// Finishes pondering now that the opponent has played the given move
+    ///
+    /// If the opponent played the predicted move, the search is given until the given budget has
+    /// passed since it started (or until it finishes on its own) and its result is returned.
+    /// Otherwise, the search is stopped and `None` is returned.
+    pub fn finish(mut self, actual: Move, budget: Duration) -> Option<SearchResult> {
+        let handle = self.handle.take().expect("bug: ponder search was already finished");
+        let hit = actual == Move::Place(self.predicted);
+        if hit {
+            while self.started.elapsed() < budget && !handle.is_finished() {
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+
+        self.cancel.cancel();
+        let result = handle.join().expect("bug: ponder thread panicked");
+        if hit {
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for Ponder {
+    /// Stops the search so that the thread does not keep running in the background
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
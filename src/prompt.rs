@@ -1,8 +1,10 @@
-use std::io::{self, Write};
+use std::io;
+use std::path::PathBuf;
+use std::time::Instant;
 
 use thiserror::Error;
 
-use crate::{TilePos};
+use crate::{TilePos, Console, AiDifficulty};
 
 #[derive(Debug, Error)]
 pub enum ParseError {
@@ -10,74 +12,354 @@ pub enum ParseError {
     EndOfInput,
     #[error("Invalid input: `{0}`")]
     InvalidInput(String),
+    #[error("Ran out of time")]
+    TimedOut,
     #[error(transparent)]
     IOError(io::Error),
 }
 
-pub fn prompt(prompt: &str) -> Result<String, io::Error> {
-    print!("{}", prompt);
-    // Need to flush because output is line buffered
-    io::stdout().flush()?;
+impl From<io::Error> for ParseError {
+    /// Reads that gave up at their deadline (see `Console::read_line`) become `ParseError::TimedOut`
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::TimedOut => ParseError::TimedOut,
+            _ => ParseError::IOError(err),
+        }
+    }
+}
+
+/// Writes the given prompt to the given console and reads a line, returning an empty string
+/// (without even a newline) at the end of input
+pub fn prompt<C: Console>(mut console: C, prompt: &str) -> Result<String, io::Error> {
+    console.read_line(prompt, &[], None)
+}
+
+/// Asks the given yes or no question (e.g. "Resign the game?") on the given console, returning true
+/// only if the player answers yes
+///
+/// Anything other than `y` or `yes` (in any case) is taken as no, including reaching the end of
+/// input, so that nothing is lost by pressing Enter by mistake.
+pub fn confirm<C: Console>(mut console: C, question: &str) -> Result<bool, io::Error> {
+    let answer = console.read_line(&format!("{} [y/N] ", question), &[], None)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+/// The labels given to the valid moves when they are numbered, in order
+const MOVE_LABELS: &str = "123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Returns the label of the valid move at the given index when the valid moves are numbered, or
+/// `None` if there are too many valid moves for that move to have a label
+pub fn move_label(index: usize) -> Option<&'static str> {
+    MOVE_LABELS.get(index..index + 1)
+}
+
+/// The commands that can be entered instead of a move, as listed by `help`
+const COMMANDS_HELP: &str = "\
+Commands:
+  hint         Show the move the AI would play
+  best [level] Play the move the AI would play for you, e.g. `best hard` (also `play hint`)
+  moves        List your valid moves
+  history      List the moves made so far
+  flip         Show the board from the other player's side
+  undo         Take back your last move
+  redo         Play a move you took back again
+  save <file>  Save the game to a file
+  load <file>  Load a game saved to a file
+  pass         Skip your turn when you have no valid moves
+  resign       Give up the game
+  quit         Stop playing without finishing the game
+";
+
+/// The names of the commands, which are suggested as the player types along with their valid moves
+const COMMAND_NAMES: &[&str] = &[
+    "help", "hint", "best", "moves", "history", "flip", "undo", "redo", "save", "load", "pass", "resign",
+    "quit",
+];
+
+/// Something the player entered when asked for their move
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveInput {
+    /// A valid move to make
+    Move(TilePos),
+    /// The player asked for a hint (by entering `hint`)
+    Hint,
+    /// The player asked for the move the AI would play for them to be played, by an AI of the
+    /// given strength or the one that gives hints (by entering `best`, `best <difficulty>` or
+    /// `play hint`)
+    Best(Option<AiDifficulty>),
+    /// The player asked to see the board from the other player's side (by entering `flip`)
+    Flip,
+    /// The player asked to see their valid moves (by entering `moves`)
+    Moves,
+    /// The player asked to see the moves made so far (by entering `history`)
+    History,
+    /// The player asked to take back their last move (by entering `undo`)
+    Undo,
+    /// The player asked to play a move they took back again (by entering `redo`)
+    Redo,
+    /// The player asked to save the game to the given file (by entering `save <file>`)
+    Save(PathBuf),
+    /// The player asked to load the game saved in the given file (by entering `load <file>`)
+    Load(PathBuf),
+    /// The player asked to skip their turn (by entering `pass`), which is only allowed when they
+    /// have no valid moves
+    Pass,
+    /// The player gave up the game (by entering `resign`)
+    Resign,
+    /// The player asked to stop playing without finishing the game (by entering `quit`)
+    Quit,
+}
+
+/// Repeatedly prompt for the move on the given console until a valid one is returned or EOF is
+/// recieved
+pub fn prompt_move<C: Console>(mut console: C, valid_moves: &[TilePos]) -> Result<TilePos, ParseError> {
+    loop {
+        match prompt_move_input(&mut console, valid_moves, "Enter your move (e.g. A1): ", false, None)? {
+            MoveInput::Move(pmove) => return Ok(pmove),
+            _ => console.write("Commands are not available here. Enter something like 'A1'.\n\n")?,
+        }
+    }
+}
 
-    let mut line = String::new();
-    io::stdin().read_line(&mut line)?;
+/// Repeatedly prompt for the move until a valid one or a command (e.g. `hint` or `undo`) is
+/// returned, or EOF is recieved
+///
+/// Entering `help` lists the commands. If `numbered` is true, the label of a valid move (see
+/// `move_label`) can be entered instead of its position.
+pub fn prompt_move_or_hint<C: Console>(
+    console: C,
+    valid_moves: &[TilePos],
+    numbered: bool,
+) -> Result<MoveInput, ParseError> {
+    prompt_move_until(console, valid_moves, numbered, None)
+}
 
-    Ok(line)
+/// Same as `prompt_move_or_hint`, but returns `ParseError::TimedOut` if nothing has been entered
+/// by the given deadline (e.g. when the player's clock runs out)
+pub fn prompt_move_until<C: Console>(
+    console: C,
+    valid_moves: &[TilePos],
+    numbered: bool,
+    deadline: Option<Instant>,
+) -> Result<MoveInput, ParseError> {
+    if numbered {
+        prompt_move_input(console, valid_moves, "Enter your move (e.g. A1 or 1) or a command (`help` to list them): ", true, deadline)
+    } else {
+        prompt_move_input(console, valid_moves, "Enter your move (e.g. A1) or a command (`help` to list them): ", false, deadline)
+    }
 }
 
-/// Repeatedly prompt for the move until a valid one is returned or EOF is recieved
-pub fn prompt_move(valid_moves: &[TilePos]) -> Result<TilePos, ParseError> {
+fn prompt_move_input<C: Console>(
+    mut console: C,
+    valid_moves: &[TilePos],
+    message: &str,
+    numbered: bool,
+    deadline: Option<Instant>,
+) -> Result<MoveInput, ParseError> {
+    let completions: Vec<_> = valid_moves.iter().map(TilePos::to_string)
+        .chain(COMMAND_NAMES.iter().map(|name| name.to_string()))
+        .collect();
     loop {
-        let line = prompt("Enter your move (e.g. A1): ").map_err(ParseError::IOError)?;
+        let line = console.read_line(message, &completions, deadline)?;
         if line.is_empty() {
             // Reached EOF, quit
             break Err(ParseError::EndOfInput);
         }
 
-        match parse_move(line) {
+        // Surrounding whitespace (including the "\r" of a CRLF line ending) is ignored
+        let input = line.trim();
+        if input.is_empty() {
+            continue;
+        }
+
+        if input.eq_ignore_ascii_case("help") {
+            console.write(&format!("{}\n", COMMANDS_HELP))?;
+            continue;
+        }
+
+        match parse_command(input) {
+            Ok(Some(input)) => return Ok(input),
+            Ok(None) => {},
+            Err(message) => {
+                console.write(&format!("{}\n\n", message))?;
+                continue;
+            },
+        }
+
+        if numbered {
+            if let Some(&pmove) = valid_moves.iter().enumerate()
+                .find(|&(i, _)| move_label(i) == Some(input))
+                .map(|(_, pmove)| pmove) {
+                return Ok(MoveInput::Move(pmove));
+            }
+        }
+
+        match parse_move(input) {
             Ok(pmove) => {
                 if !valid_moves.contains(&pmove) {
-                    println!("Invalid move: `{}`. Your move must flip at least one tile.\n", pmove.to_string());
+                    console.write(&format!("Invalid move: `{}`. Your move must flip at least one tile.\n\n", pmove))?;
                     continue;
                 }
 
-                return Ok(pmove);
+                return Ok(MoveInput::Move(pmove));
             },
 
-            Err(ParseError::InvalidInput(inp)) => println!("Invalid input: `{}`. Enter something like 'A1'.\n", inp.trim_end_matches('\n')),
-            err@Err(ParseError::EndOfInput) |
-            err@Err(ParseError::IOError(_)) => return err,
+            Err(ParseError::InvalidInput(inp)) => {
+                console.write(&format!("Invalid input: `{}`. Enter something like 'A1'.\n\n", inp))?;
+            },
+            Err(err@ParseError::EndOfInput) |
+            Err(err@ParseError::TimedOut) |
+            Err(err@ParseError::IOError(_)) => return Err(err),
         }
     }
 }
 
+/// Parses a move (e.g. "A1") or a command (e.g. "undo" or "save game.txt") entered by the player,
+/// without checking that the move is valid
+///
+/// This accepts everything that `prompt_move_or_hint` does other than `help` and the labels of
+/// numbered moves, so that moves and commands can be read from somewhere other than the prompt.
+pub fn parse_input(input: &str) -> Result<MoveInput, String> {
+    let input = input.trim();
+    if let Some(command) = parse_command(input)? {
+        return Ok(command);
+    }
+
+    parse_move(input).map(MoveInput::Move).map_err(|err| err.to_string())
+}
+
+/// Parses the command the player entered, returning `None` if the input is not a command (e.g.
+/// because it is a move) or an error message if the command is missing its argument
+fn parse_command(input: &str) -> Result<Option<MoveInput>, String> {
+    let (command, argument) = match input.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (input, ""),
+    };
+    let file = || if argument.is_empty() {
+        Err(format!("Missing the file to {}. Enter something like `{} game.txt`.", command, command))
+    } else {
+        Ok(PathBuf::from(argument))
+    };
+
+    Ok(Some(match command.to_ascii_lowercase().as_str() {
+        "hint" => MoveInput::Hint,
+        "best" if argument.is_empty() => MoveInput::Best(None),
+        "best" => MoveInput::Best(Some(argument.to_ascii_lowercase().parse().map_err(|_| {
+            let levels: Vec<_> = AiDifficulty::ALL.iter().map(AiDifficulty::to_string).collect();
+            format!("Unknown AI strength: `{}`. Enter one of: {}", argument, levels.join(", "))
+        })?)),
+        "play" if argument.eq_ignore_ascii_case("hint") => MoveInput::Best(None),
+        "flip" => MoveInput::Flip,
+        "moves" => MoveInput::Moves,
+        "history" => MoveInput::History,
+        "undo" => MoveInput::Undo,
+        "redo" => MoveInput::Redo,
+        "save" => MoveInput::Save(file()?),
+        "load" => MoveInput::Load(file()?),
+        "pass" => MoveInput::Pass,
+        "resign" => MoveInput::Resign,
+        "quit" | "exit" => MoveInput::Quit,
+        _ => return Ok(None),
+    }))
+}
+
 /// Parses a move from an input string in the format "A1" or "1A" where "A" is the column and "1"
-/// is the row. The move string is not case-sensitive.
-fn parse_move(line: String) -> Result<TilePos, ParseError> {
-    fn byte_to_usize(byte: u8, start: u8) -> usize {
-        (byte - start) as usize
+/// is the row. The move string is not case-sensitive, and whitespace around it (such as the line
+/// ending, with or without a "\r") is ignored.
+fn parse_move(input: &str) -> Result<TilePos, ParseError> {
+    let input = input.trim();
+    match input.parse() {
+        Ok(pmove) => Ok(pmove),
+        Err(_) => Err(ParseError::InvalidInput(input.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::MemoryConsole;
+
+    fn parsed(input: &str) -> Option<TilePos> {
+        parse_move(input).ok()
+    }
+
+    #[test]
+    fn parse_move_line_endings() {
+        let a1 = Some(TilePos {row: 0, col: 0});
+        assert_eq!(parsed("A1\n"), a1);
+        assert_eq!(parsed("A1\r\n"), a1);
+        // Piped input may not end with a newline
+        assert_eq!(parsed("A1"), a1);
+        assert_eq!(parsed("  a1 \t\n"), a1);
+        assert_eq!(parsed("1a\r\n"), a1);
+    }
+
+    #[test]
+    fn parse_move_multi_digit_rows() {
+        assert_eq!(parsed("B10\n"), Some(TilePos {row: 9, col: 1}));
+        assert_eq!(parsed("12c"), Some(TilePos {row: 11, col: 2}));
+    }
+
+    #[test]
+    fn parse_move_invalid() {
+        for input in ["", "\n", "\r\n", "   ", "A", "1", "A0", "AB1", "A1B", "A-1", "é1", "1é"] {
+            match parse_move(input) {
+                Err(ParseError::InvalidInput(inp)) => assert_eq!(inp, input.trim()),
+                result => panic!("expected `{:?}` to be invalid, got {:?}", input, result),
+            }
+        }
+    }
+
+    #[test]
+    fn prompt_move_until_valid() {
+        let valid_moves = [TilePos {row: 2, col: 4}, TilePos {row: 3, col: 5}];
+        let mut console = MemoryConsole::new("\nZ\nA1\nhelp\nf4\r\n");
+        assert_eq!(prompt_move(&mut console, &valid_moves).ok(), Some(TilePos {row: 3, col: 5}));
+
+        let output = console.output();
+        assert_eq!(output.matches("Enter your move").count(), 5);
+        assert!(output.contains("Invalid input: `Z`"));
+        assert!(output.contains("Invalid move: `A1`"));
+        assert!(output.contains(COMMANDS_HELP));
+    }
+
+    #[test]
+    fn prompt_move_commands_and_labels() {
+        let valid_moves = [TilePos {row: 2, col: 4}, TilePos {row: 3, col: 5}];
+        let mut console = MemoryConsole::new("load\nundo\n2");
+        let input = prompt_move_or_hint(&mut console, &valid_moves, true).ok();
+        assert_eq!(input, Some(MoveInput::Undo));
+        assert!(console.output().contains("Missing the file to load"));
+
+        let input = prompt_move_or_hint(&mut console, &valid_moves, true).ok();
+        assert_eq!(input, Some(MoveInput::Move(TilePos {row: 3, col: 5})));
+    }
+
+    #[test]
+    fn prompt_end_of_input() {
+        let mut console = MemoryConsole::new("");
+        assert!(matches!(prompt_move(&mut console, &[]), Err(ParseError::EndOfInput)));
+        assert_eq!(prompt(&mut console, "Continue? ").ok().as_deref(), Some(""));
+        assert_eq!(console.output(), "Enter your move (e.g. A1): Continue? ");
+    }
+
+    #[test]
+    fn confirm_defaults_to_no() {
+        let mut console = MemoryConsole::new("y\n YES \r\n\nno\nyep\n");
+        let answers: Vec<_> = (0..6).map(|_| confirm(&mut console, "Quit?").unwrap()).collect();
+        assert_eq!(answers, [true, true, false, false, false, false]);
+        assert!(console.output().starts_with("Quit? [y/N] "));
     }
 
-    let bytes = line.as_bytes();
-    // Leave off the newline when matching
-    match &bytes[0..bytes.len()-1] {
-        [b'A' ..= b'H', b'1' ..= b'8'] => Ok(TilePos {
-            row: byte_to_usize(bytes[1], b'1'),
-            col: byte_to_usize(bytes[0], b'A'),
-        }),
-        [b'a' ..= b'h', b'1' ..= b'8'] => Ok(TilePos {
-            row: byte_to_usize(bytes[1], b'1'),
-            col: byte_to_usize(bytes[0], b'a'),
-        }),
-        [b'1' ..= b'8', b'A' ..= b'H'] => Ok(TilePos {
-            row: byte_to_usize(bytes[0], b'1'),
-            col: byte_to_usize(bytes[1], b'A'),
-        }),
-        [b'1' ..= b'8', b'a' ..= b'h'] => Ok(TilePos {
-            row: byte_to_usize(bytes[0], b'1'),
-            col: byte_to_usize(bytes[1], b'a'),
-        }),
-
-        _ => Err(ParseError::InvalidInput(line)),
+    #[test]
+    fn parse_commands() {
+        assert_eq!(parse_command("HINT"), Ok(Some(MoveInput::Hint)));
+        assert_eq!(parse_command("save  game.txt"), Ok(Some(MoveInput::Save(PathBuf::from("game.txt")))));
+        assert!(parse_command("load").is_err());
+        assert_eq!(parse_command("play hint"), Ok(Some(MoveInput::Best(None))));
+        assert_eq!(parse_command("best Hard"), Ok(Some(MoveInput::Best(Some(AiDifficulty::Hard)))));
+        assert!(parse_command("best strong").is_err());
+        assert_eq!(parse_command("A1"), Ok(None));
     }
 }
@@ -0,0 +1,387 @@
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crossterm::{cursor, queue};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, Clear, ClearType};
+use yansi::Paint;
+
+/// The most lines kept in the input history
+const MAX_HISTORY: usize = 100;
+
+/// The lines entered so far, oldest first, shared by every prompt so that the up arrow can bring
+/// back moves and commands entered at earlier prompts
+static HISTORY: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Prints the given prompt and reads a line from the terminal with line editing
+///
+/// The line can be edited with the arrow keys, Home, End, Backspace, Delete and the usual Ctrl
+/// shortcuts (Ctrl-A, Ctrl-E, Ctrl-U). The up and down arrows go through the lines entered at
+/// earlier prompts. Returns `None` if the player pressed Ctrl-C, or Ctrl-D on an empty line, so
+/// that the caller can end the game cleanly instead of the process being killed mid-render.
+///
+/// Stdin must be a terminal.
+pub fn read_line(prompt: &str) -> io::Result<Option<String>> {
+    read_line_with_completions(prompt, &[], None)
+}
+
+/// Same as `read_line`, but suggests the given completions as the player types and gives up with
+/// an error of kind `io::ErrorKind::TimedOut` at the given deadline (if any)
+///
+/// When only one completion starts with the line typed so far, the rest of it is shown dimmed
+/// after the cursor and can be accepted with Tab or the right arrow. When several do, Tab
+/// completes as much of them as they have in common, and lists them if there is nothing more to
+/// complete. Completions are matched without regard to case.
+pub fn read_line_with_completions(
+    prompt: &str,
+    completions: &[String],
+    deadline: Option<Instant>,
+) -> io::Result<Option<String>> {
+    terminal::enable_raw_mode()?;
+    let result = edit_line(prompt, completions, deadline);
+    // The terminal has to be restored even if reading the line failed
+    let restored = terminal::disable_raw_mode();
+    let line = result?;
+    restored?;
+
+    // Raw mode does not move to the next line when Enter is pressed
+    println!();
+    if let Some(line) = &line {
+        add_history(line);
+    }
+    Ok(line)
+}
+
+/// Adds a line to the input history, skipping empty lines and repeats of the last line
+fn add_history(line: &str) {
+    let mut history = HISTORY.lock().unwrap_or_else(|err| err.into_inner());
+    if line.trim().is_empty() || history.last().map(String::as_str) == Some(line) {
+        return;
+    }
+
+    history.push(line.to_string());
+    if history.len() > MAX_HISTORY {
+        history.remove(0);
+    }
+}
+
+/// Returns the completions that start with the given text
+fn matching<'a>(text: &str, completions: &'a [String]) -> Vec<&'a str> {
+    completions.iter()
+        .filter(|completion| {
+            completion.len() >= text.len()
+                && completion.is_char_boundary(text.len())
+                && completion[..text.len()].eq_ignore_ascii_case(text)
+        })
+        .map(String::as_str)
+        .collect()
+}
+
+/// Returns the longest prefix that all of the given completions share
+fn common_prefix<'a>(completions: &[&'a str]) -> &'a str {
+    let first = completions[0];
+    let len = completions[1..].iter().fold(first.len(), |len, completion| {
+        first.char_indices().zip(completion.chars())
+            .take_while(|&((i, a), b)| i < len && a.eq_ignore_ascii_case(&b))
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0)
+    });
+    &first[..len]
+}
+
+/// Lets the player edit a line until they press Enter, redrawing it after every key press
+fn edit_line(prompt: &str, completions: &[String], deadline: Option<Instant>) -> io::Result<Option<String>> {
+    let history = HISTORY.lock().unwrap_or_else(|err| err.into_inner()).clone();
+    let mut editor = LineEditor::new(completions, history);
+    let mut stdout = io::stdout().lock();
+
+    loop {
+        let suggestion = editor.suggestion();
+        queue!(stdout, cursor::MoveToColumn(0))?;
+        write!(stdout, "{}{}", prompt, editor.text())?;
+        if !suggestion.is_empty() {
+            write!(stdout, "{}", Paint::new(suggestion).dimmed())?;
+        }
+        queue!(
+            stdout,
+            Clear(ClearType::UntilNewLine),
+            cursor::MoveToColumn((prompt.chars().count() + editor.pos) as u16),
+        )?;
+        stdout.flush()?;
+
+        if let Some(deadline) = deadline {
+            if !event::poll(deadline.saturating_duration_since(Instant::now()))? {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "ran out of time"));
+            }
+        }
+        let KeyEvent {code, modifiers, ..} = match event::read()? {
+            // Only presses are handled so that keys are not handled twice on some platforms
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        match editor.handle_key(code, modifiers) {
+            KeyResult::Continue => {},
+            KeyResult::Done(line) => return Ok(line),
+            // Lists the completions below the line, which is then drawn again under them
+            KeyResult::ListCompletions(matches) => write!(stdout, "\r\n{}\r\n", matches.join("  "))?,
+        }
+    }
+}
+
+/// What `edit_line` should do after a key press
+#[derive(Debug, PartialEq, Eq)]
+enum KeyResult<'a> {
+    /// Keep editing the line
+    Continue,
+    /// Stop editing, returning the line or `None` if the player gave up on it
+    Done(Option<String>),
+    /// Show the given completions of the line and keep editing it
+    ListCompletions(Vec<&'a str>),
+}
+
+/// The line being edited and the position of the cursor in it, updated for each key press
+#[derive(Debug)]
+struct LineEditor<'a> {
+    completions: &'a [String],
+    /// The lines entered at earlier prompts, oldest first
+    history: Vec<String>,
+    line: Vec<char>,
+    /// The position of the cursor in the line, in characters
+    pos: usize,
+    /// The index in the history of the line being shown, or `history.len()` for the new line
+    history_index: usize,
+    /// The new line is kept while going through the history so that it can be returned to
+    new_line: Vec<char>,
+}
+
+impl<'a> LineEditor<'a> {
+    fn new(completions: &'a [String], history: Vec<String>) -> Self {
+        let history_index = history.len();
+        Self {completions, history, line: Vec::new(), pos: 0, history_index, new_line: Vec::new()}
+    }
+
+    fn text(&self) -> String {
+        self.line.iter().collect()
+    }
+
+    /// Returns the rest of the only completion of the line, shown after the cursor when it is at
+    /// the end of the line, or an empty string if there isn't exactly one completion
+    fn suggestion(&self) -> &'a str {
+        let text = self.text();
+        match matching(&text, self.completions)[..] {
+            [completion] if !text.is_empty() && self.pos == self.line.len() => &completion[text.len()..],
+            _ => "",
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> KeyResult<'a> {
+        let suggestion = self.suggestion();
+        let (line, pos) = (&mut self.line, &mut self.pos);
+
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            match code {
+                KeyCode::Char('c') => return KeyResult::Done(None),
+                KeyCode::Char('d') if line.is_empty() => return KeyResult::Done(None),
+                KeyCode::Char('d') if *pos < line.len() => {
+                    line.remove(*pos);
+                },
+                KeyCode::Char('a') => *pos = 0,
+                KeyCode::Char('e') => *pos = line.len(),
+                KeyCode::Char('u') => {
+                    line.drain(..*pos);
+                    *pos = 0;
+                },
+                _ => {},
+            }
+            return KeyResult::Continue;
+        }
+
+        match code {
+            KeyCode::Enter => return KeyResult::Done(Some(line.iter().collect())),
+
+            KeyCode::Tab | KeyCode::Right if !suggestion.is_empty() => {
+                line.extend(suggestion.chars());
+                *pos = line.len();
+            },
+            KeyCode::Tab => {
+                let typed: String = line[..*pos].iter().collect();
+                let matches = matching(&typed, self.completions);
+                if matches.is_empty() {
+                    return KeyResult::Continue;
+                }
+
+                let prefix = common_prefix(&matches);
+                if prefix.len() > typed.len() {
+                    line.splice(..*pos, prefix.chars());
+                    *pos = prefix.chars().count();
+                } else {
+                    return KeyResult::ListCompletions(matches);
+                }
+            },
+
+            KeyCode::Char(ch) => {
+                line.insert(*pos, ch);
+                *pos += 1;
+            },
+            KeyCode::Backspace if *pos > 0 => {
+                *pos -= 1;
+                line.remove(*pos);
+            },
+            KeyCode::Delete if *pos < line.len() => {
+                line.remove(*pos);
+            },
+
+            KeyCode::Left => *pos = pos.saturating_sub(1),
+            KeyCode::Right => *pos = (*pos + 1).min(line.len()),
+            KeyCode::Home => *pos = 0,
+            KeyCode::End => *pos = line.len(),
+
+            KeyCode::Up if self.history_index > 0 => {
+                if self.history_index == self.history.len() {
+                    self.new_line = line.clone();
+                }
+                self.history_index -= 1;
+                *line = self.history[self.history_index].chars().collect();
+                *pos = line.len();
+            },
+            KeyCode::Down if self.history_index < self.history.len() => {
+                self.history_index += 1;
+                *line = match self.history.get(self.history_index) {
+                    Some(entry) => entry.chars().collect(),
+                    None => self.new_line.clone(),
+                };
+                *pos = line.len();
+            },
+
+            _ => {},
+        }
+
+        KeyResult::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    fn press<'a>(editor: &mut LineEditor<'a>, code: KeyCode) -> KeyResult<'a> {
+        editor.handle_key(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl<'a>(editor: &mut LineEditor<'a>, ch: char) -> KeyResult<'a> {
+        editor.handle_key(KeyCode::Char(ch), KeyModifiers::CONTROL)
+    }
+
+    fn type_text(editor: &mut LineEditor<'_>, text: &str) {
+        for ch in text.chars() {
+            assert_eq!(press(editor, KeyCode::Char(ch)), KeyResult::Continue);
+        }
+    }
+
+    #[test]
+    fn edit_the_line() {
+        let mut editor = LineEditor::new(&[], Vec::new());
+        type_text(&mut editor, "d3");
+        press(&mut editor, KeyCode::Left);
+        type_text(&mut editor, "x");
+        assert_eq!((editor.text().as_str(), editor.pos), ("dx3", 2));
+
+        press(&mut editor, KeyCode::Backspace);
+        press(&mut editor, KeyCode::Home);
+        press(&mut editor, KeyCode::Delete);
+        assert_eq!((editor.text().as_str(), editor.pos), ("3", 0));
+        // Nothing to delete before the start of the line
+        press(&mut editor, KeyCode::Backspace);
+        press(&mut editor, KeyCode::Left);
+        assert_eq!((editor.text().as_str(), editor.pos), ("3", 0));
+
+        type_text(&mut editor, "e");
+        ctrl(&mut editor, 'e');
+        assert_eq!(editor.pos, 2);
+        press(&mut editor, KeyCode::Right);
+        assert_eq!(editor.pos, 2);
+        type_text(&mut editor, "f4");
+        ctrl(&mut editor, 'a');
+        ctrl(&mut editor, 'd');
+        assert_eq!((editor.text().as_str(), editor.pos), ("3f4", 0));
+        press(&mut editor, KeyCode::End);
+        press(&mut editor, KeyCode::Left);
+        ctrl(&mut editor, 'u');
+        assert_eq!((editor.text().as_str(), editor.pos), ("4", 0));
+
+        assert_eq!(press(&mut editor, KeyCode::Enter), KeyResult::Done(Some("4".to_string())));
+    }
+
+    #[test]
+    fn give_up_on_the_line() {
+        let mut editor = LineEditor::new(&[], Vec::new());
+        assert_eq!(ctrl(&mut editor, 'd'), KeyResult::Done(None));
+
+        type_text(&mut editor, "d3");
+        // Ctrl-D only ends the input on an empty line, and there is nothing after the cursor here
+        assert_eq!(ctrl(&mut editor, 'd'), KeyResult::Continue);
+        assert_eq!(editor.text(), "d3");
+        assert_eq!(ctrl(&mut editor, 'c'), KeyResult::Done(None));
+    }
+
+    #[test]
+    fn go_through_the_history() {
+        let mut editor = LineEditor::new(&[], strings(&["d3", "undo"]));
+        type_text(&mut editor, "c4");
+
+        press(&mut editor, KeyCode::Up);
+        assert_eq!((editor.text().as_str(), editor.pos), ("undo", 4));
+        press(&mut editor, KeyCode::Up);
+        assert_eq!(editor.text(), "d3");
+        // There is nothing older than the first line
+        press(&mut editor, KeyCode::Up);
+        assert_eq!(editor.text(), "d3");
+
+        press(&mut editor, KeyCode::Down);
+        assert_eq!(editor.text(), "undo");
+        // The line being typed is still there after going through the history
+        press(&mut editor, KeyCode::Down);
+        assert_eq!((editor.text().as_str(), editor.pos), ("c4", 2));
+        press(&mut editor, KeyCode::Down);
+        assert_eq!(editor.text(), "c4");
+    }
+
+    #[test]
+    fn complete_the_line() {
+        let completions = strings(&["help", "hint", "history", "undo"]);
+        let mut editor = LineEditor::new(&completions, Vec::new());
+        assert_eq!(editor.suggestion(), "");
+
+        // Only one completion starts with "u", so the rest of it is suggested
+        type_text(&mut editor, "U");
+        assert_eq!(editor.suggestion(), "ndo");
+        press(&mut editor, KeyCode::Right);
+        assert_eq!((editor.text().as_str(), editor.pos), ("Undo", 4));
+        assert_eq!(editor.suggestion(), "");
+
+        // Tab completes what several completions have in common, then lists them
+        let mut editor = LineEditor::new(&completions, Vec::new());
+        type_text(&mut editor, "h");
+        assert_eq!(press(&mut editor, KeyCode::Tab), KeyResult::ListCompletions(vec!["help", "hint", "history"]));
+        assert_eq!(editor.text(), "h");
+        type_text(&mut editor, "i");
+        assert_eq!(editor.suggestion(), "");
+        assert_eq!(press(&mut editor, KeyCode::Tab), KeyResult::ListCompletions(vec!["hint", "history"]));
+        type_text(&mut editor, "s");
+        assert_eq!(press(&mut editor, KeyCode::Tab), KeyResult::Continue);
+        assert_eq!((editor.text().as_str(), editor.pos), ("history", 7));
+
+        // Nothing to complete
+        let mut editor = LineEditor::new(&completions, Vec::new());
+        type_text(&mut editor, "d3");
+        assert_eq!(press(&mut editor, KeyCode::Tab), KeyResult::Continue);
+        assert_eq!(editor.text(), "d3");
+    }
+}
@@ -1,17 +1,144 @@
-use crate::{Grid, Piece, TilePos};
+use std::mem;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::zobrist::{self, zobrist_hash};
+use crate::{Grid, Flips, Piece, TilePos, Move, Variant, Direction, Clock, GameObserver, Observers, Game, PlayerConfig};
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid position: `{0}`. Expected 64 tiles of `X`, `O`, `-` or `#` followed by the player to move, `X` or `O`")]
+pub struct ParsePositionError(pub String);
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ParseAsciiBoardError {
+    #[error("Line {line}: `{tile}` is not a tile. Expected `X`, `O`, `.` or `#`")]
+    InvalidTile {
+        line: usize,
+        tile: String,
+    },
+    #[error("Line {line}: expected 8 tiles, found {found}")]
+    WrongRowLength {
+        line: usize,
+        found: usize,
+    },
+    #[error("Expected 8 rows of tiles, found {0}")]
+    WrongRowCount(usize),
+    #[error("Line {line}: the player to move was already given")]
+    DuplicatePlayer {
+        line: usize,
+    },
+}
+
+/// A position drawn as a grid of tiles, parsed by `parse_ascii_board`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiBoard {
+    pub grid: Grid,
+    /// The player to move, if the drawing says (e.g. "O to move")
+    pub player: Option<Piece>,
+}
+
+/// The characters drawn between tiles and around the board, which are ignored
+const BOARD_BORDERS: &[char] = &['|', '│', '-', '─', '+', '┼', '=', '├', '┤', '┬', '┴', '┌', '┐', '└', '┘'];
+
+/// The reason that a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// Every tile on the grid has a piece
+    FullBoard,
+    /// Neither player has any valid moves left, so both would have to pass
+    DoublePass,
+    /// The given player ran out of time on their clock
+    TimeForfeit(Piece),
+    /// The given player resigned
+    Resignation(Piece),
+}
+
+/// Whether a game is still being played or has finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    /// The game is still being played
+    InProgress,
+    /// The game has ended
+    Finished {
+        /// The player that won the game, or `None` if the game ended in a tie
+        winner: Option<Piece>,
+        /// The reason that the game ended
+        reason: TerminationReason,
+    },
+}
+
+/// The information needed to undo a move made with `Reversi::apply`
+///
+/// Must be passed back to `Reversi::unapply` on the same game, in the reverse order that the
+/// moves were applied.
+#[derive(Debug)]
+#[must_use = "the move cannot be undone without its undo token"]
+pub struct UndoToken {
+    /// The move that was made
+    pmove: Move,
+    /// The player that made the move
+    player: Piece,
+    /// The tiles that were flipped by the move
+    flips: Flips,
+}
 
 #[derive(Debug, Clone)]
 pub struct Reversi {
+    /// The rules that this game is being played with
+    variant: Variant,
+    /// The grid at the start of the game, before any moves were made
+    initial_grid: Grid,
+    /// The player that made the first move
+    first_player: Piece,
+    /// Every move made so far (and not undone), in order
+    history: Vec<Move>,
+    /// The tiles flipped by each move in `history`, with a bit set for each flipped tile (bit
+    /// `row * 8 + col`), so that the last move can be shown without replaying the game
+    flip_history: Vec<u64>,
     grid: Grid,
     /// The player whose turn it is currently
     current_player: Piece,
+    /// The Zobrist hash of the grid and current player, updated incrementally with each move
+    hash: u64,
     /// The valid moves for the current player
     valid_moves: Vec<TilePos>,
+    /// The player that resigned the game (if any)
+    resigned: Option<Piece>,
+    /// The clock used to track the time each player has left (if the game is timed)
+    clock: Option<Clock>,
+    /// The time taken by each move made with `make_move` or `pass`, in the order they were made
+    think_times: Vec<(Piece, Duration)>,
+    /// The observers notified whenever a move is made with `apply`
+    observers: Observers,
+    /// Who is playing each side, if known, so that it can be saved with the game
+    players: Option<PlayerConfig>,
+    /// The playable part of the grid (see `Grid::playable_bounds`), which never changes since
+    /// tiles are only blocked before the game starts
+    bounds: (TilePos, TilePos),
 }
 
 impl Default for Reversi {
     /// Creates a new reversi game with the default pieces placed
     fn default() -> Self {
+        Self::new(Variant::default())
+    }
+}
+
+impl Reversi {
+    /// Creates a new reversi game with the default pieces placed, played with the given rules
+    pub fn new(variant: Variant) -> Self {
+        Self::with_blocked_tiles(variant, &[])
+    }
+
+    /// Creates a new reversi game on a board where the given tiles are permanently blocked
+    ///
+    /// This can be used to play on boards with custom shapes (e.g. an octagonal board).
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the blocked tiles is one of the tiles where the default pieces are placed
+    pub fn with_blocked_tiles(variant: Variant, blocked: &[TilePos]) -> Self {
         let mut grid = Grid::default();
         // The default piece are placed in a 2x2 grid of alternating colors
         grid.place(TilePos {row: 3, col: 3}, Piece::X);
@@ -19,29 +146,229 @@ impl Default for Reversi {
         grid.place(TilePos {row: 4, col: 3}, Piece::O);
         grid.place(TilePos {row: 4, col: 4}, Piece::X);
 
+        for &pos in blocked {
+            assert!(grid.tile(pos).is_none(), "bug: cannot block a tile that has a default piece");
+            grid.block(pos);
+        }
+
         // X always goes first
-        let current_player = Piece::X;
-        let valid_moves = compute_valid_moves(&grid, current_player);
+        Self::from_grid(variant, grid, Piece::X)
+    }
+
+    /// Creates a new game on a smaller square board with the given number of tiles on each side,
+    /// made by blocking every tile outside of the center of the full board
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size is not an even number between 2 and 8
+    pub fn with_board_size(variant: Variant, size: usize) -> Self {
+        assert!((2..=8).contains(&size) && size.is_multiple_of(2), "bug: invalid board size: {}", size);
+
+        // The board is centered, so the same number of rows and columns are cut off on each side
+        let margin = (8 - size) / 2;
+        let inside = |i: usize| (margin..margin + size).contains(&i);
+        let mut blocked = Vec::new();
+        for row in 0..8 {
+            for col in 0..8 {
+                if !inside(row) || !inside(col) {
+                    blocked.push(TilePos {row, col});
+                }
+            }
+        }
+
+        Self::with_blocked_tiles(variant, &blocked)
+    }
+
+    /// Creates a new game starting from the given grid, with the given player moving first
+    ///
+    /// This can be used to set up positions recorded from other games. The game has no history,
+    /// so moves made before this position cannot be undone.
+    pub fn from_grid(variant: Variant, grid: Grid, current_player: Piece) -> Self {
+        let mut valid_moves = Vec::new();
+        compute_valid_moves(&grid, current_player, &mut valid_moves);
+        let hash = zobrist_hash(&grid, current_player);
+        let bounds = grid.playable_bounds();
 
         Self {
+            variant,
+            initial_grid: grid.clone(),
+            first_player: current_player,
+            history: Vec::new(),
+            flip_history: Vec::new(),
             grid,
             current_player,
+            hash,
             valid_moves,
+            resigned: None,
+            clock: None,
+            think_times: Vec::new(),
+            observers: Observers::default(),
+            players: None,
+            bounds,
         }
     }
-}
 
-impl Reversi {
+    /// Creates a new game starting from a position written by `to_position_str`
+    ///
+    /// The space between the tiles and the player to move may be left out. Like `from_grid`, the
+    /// game has no history.
+    pub fn from_position_str(variant: Variant, s: &str) -> Result<Self, ParsePositionError> {
+        let err = || ParsePositionError(s.to_string());
+        let position = s.trim();
+        let (tiles, player) = match position.char_indices().last() {
+            Some((index, player)) => (position[..index].trim_end(), player),
+            None => return Err(err()),
+        };
+
+        let player = match player {
+            'X' => Piece::X,
+            'O' => Piece::O,
+            _ => return Err(err()),
+        };
+        let grid = tiles.parse().map_err(|_| err())?;
+        Ok(Self::from_grid(variant, grid, player))
+    }
+
+    /// Returns the current position on a single line: the grid as a position string (see
+    /// `Grid::to_position_string`), a space and then the player to move, `X` or `O`
+    ///
+    /// The rules and history of the game are not included. The position can be set up again with
+    /// `from_position_str`.
+    pub fn to_position_str(&self) -> String {
+        format!("{} {:?}", self.grid.to_position_string(), self.current_player)
+    }
+
+    /// Creates a new game starting from a position drawn as a grid (see `parse_ascii_board`),
+    /// with X to move unless the drawing says otherwise
+    ///
+    /// Like `from_grid`, the game has no history.
+    pub fn from_ascii_board(variant: Variant, s: &str) -> Result<Self, ParseAsciiBoardError> {
+        let board = parse_ascii_board(s)?;
+        Ok(Self::from_grid(variant, board.grid, board.player.unwrap_or(Piece::X)))
+    }
+
+    /// Registers an observer to be notified about every move made in this game
+    ///
+    /// Observers are not notified when a move is undone with `unapply`. Observers must be `Send`
+    /// so that games can be moved between threads (e.g. by the AI).
+    pub fn subscribe(&mut self, observer: impl GameObserver + Send + 'static) {
+        self.observers.0.push(Box::new(observer));
+    }
+
+    /// Plays this game with the given clock, immediately starting the clock of the current player
+    pub fn set_clock(&mut self, mut clock: Clock) {
+        clock.start(self.current_player);
+        self.clock = Some(clock);
+    }
+
+    /// Records who is playing each side, so that it is saved along with the game (see `save`)
+    pub fn set_players(&mut self, players: PlayerConfig) {
+        self.players = Some(players);
+    }
+
+    /// Returns who is playing each side, if it was recorded with `set_players`
+    pub fn players(&self) -> Option<PlayerConfig> {
+        self.players
+    }
+
+    /// Continues the timing of a saved game with the given clock and think times, restarting the
+    /// clock of the current player unless the game is over
+    pub(crate) fn resume_timing(&mut self, clock: Option<Clock>, think_times: Vec<(Piece, Duration)>) {
+        self.think_times = think_times;
+        self.clock = clock;
+        let game_over = self.is_game_over();
+        if let Some(clock) = &mut self.clock {
+            if !game_over {
+                clock.start(self.current_player);
+            }
+        }
+    }
+
+    /// Returns the clock of this game, or `None` if the game is not timed
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// Returns the time taken by each move made with `make_move` or `pass` so far, along with
+    /// the player that made it
+    ///
+    /// Think times are only recorded while the game has a clock.
+    pub fn think_times(&self) -> &[(Piece, Duration)] {
+        &self.think_times
+    }
+
+    /// Returns the rules that this game is being played with
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Returns every move made so far, in the order they were made
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// Returns a new game in the position reached after the first `n` moves of this game
+    ///
+    /// `at_move(0)` is the starting position and `at_move(history().len())` is the current
+    /// position. The returned game has no clock or observers, but keeps the players recorded with
+    /// `set_players`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the number of moves made so far.
+    pub fn at_move(&self, n: usize) -> Self {
+        assert!(n <= self.history.len(),
+            "bug: cannot go to move {} in a game with only {} moves", n, self.history.len());
+
+        let mut game = Self::from_grid(self.variant, self.initial_grid.clone(), self.first_player);
+        game.players = self.players;
+        for &pmove in &self.history[..n] {
+            let _ = game.apply(pmove);
+        }
+
+        game
+    }
+
+    /// Returns the tile of the piece placed by the last move and the tiles that it flipped (row by
+    /// row), or `None` if the last move was not a placement (or no moves have been made yet)
+    pub fn last_move_flips(&self) -> Option<(TilePos, Vec<TilePos>)> {
+        let pos = match self.history.last() {
+            Some(&Move::Place(pos)) => pos,
+            _ => return None,
+        };
+
+        let mask = self.flip_history.last().copied().unwrap_or(0);
+        let flips = (0..64)
+            .filter(|bit| mask & (1 << bit) != 0)
+            .map(|bit| TilePos {row: bit / 8, col: bit % 8})
+            .collect();
+        Some((pos, flips))
+    }
+
     /// Returns the grid
     pub fn grid(&self) -> &Grid {
         &self.grid
     }
 
+    /// Returns the top-left and bottom-right corners of the playable part of the board (see
+    /// `Grid::playable_bounds`)
+    pub fn playable_bounds(&self) -> (TilePos, TilePos) {
+        self.bounds
+    }
+
     /// Returns the current player
     pub fn current_player(&self) -> Piece {
         self.current_player
     }
 
+    /// Returns the Zobrist hash of the current position (the grid and the current player)
+    ///
+    /// Two games with the same pieces on the grid and the same current player always have the
+    /// same hash, regardless of the moves that were made to get there.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
     /// Returns the current scores for each player as a tuple: (x score, o score)
     pub fn scores(&self) -> (u32, u32) {
         let mut x_score = 0;
@@ -60,62 +387,682 @@ impl Reversi {
         (x_score, o_score)
     }
 
+    /// Returns the player that would win if the game ended with the current scores, or `None`
+    /// if the game would end in a tie
+    ///
+    /// A player that has run out of time or resigned always loses, regardless of the scores.
+    pub fn winner(&self) -> Option<Piece> {
+        match self.termination() {
+            Some(TerminationReason::TimeForfeit(piece)) |
+            Some(TerminationReason::Resignation(piece)) => return Some(piece.opposite()),
+            _ => {},
+        }
+
+        let (x_score, o_score) = self.scores();
+
+        use std::cmp::Ordering::*;
+        let x_ahead = match x_score.cmp(&o_score) {
+            Greater => true,
+            Less => false,
+            Equal => return None,
+        };
+
+        // In anti-reversi, having more pieces means you are losing
+        let x_wins = match self.variant {
+            Variant::Standard => x_ahead,
+            Variant::Anti => !x_ahead,
+        };
+
+        Some(if x_wins { Piece::X } else { Piece::O })
+    }
+
+    /// Returns true if the game has ended
+    pub fn is_game_over(&self) -> bool {
+        self.termination().is_some()
+    }
+
+    /// Returns whether the game is still in progress, or the result of the game if it finished
+    pub fn status(&self) -> GameStatus {
+        match self.termination() {
+            Some(reason) => GameStatus::Finished {winner: self.winner(), reason},
+            None => GameStatus::InProgress,
+        }
+    }
+
+    /// Returns the reason that the game ended, or `None` if the game is still in progress
+    pub fn termination(&self) -> Option<TerminationReason> {
+        if let Some(piece) = self.resigned {
+            return Some(TerminationReason::Resignation(piece));
+        }
+        if let Some(piece) = self.clock.as_ref().and_then(|clock| clock.flagged()) {
+            return Some(TerminationReason::TimeForfeit(piece));
+        }
+        if self.grid.is_full() {
+            return Some(TerminationReason::FullBoard);
+        }
+        if !self.valid_moves.is_empty() {
+            return None;
+        }
+
+        let opponent = self.current_player.opposite();
+        if self.legal_moves_iter(opponent).next().is_none() {
+            Some(TerminationReason::DoublePass)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the mobility of the given player: the number of valid moves they would have if it
+    /// was their turn
+    pub fn mobility(&self, piece: Piece) -> usize {
+        if piece == self.current_player {
+            return self.valid_moves.len();
+        }
+
+        self.legal_moves_iter(piece).count()
+    }
+
+    /// Returns the number of frontier discs for the given player: the pieces belonging to that
+    /// player that are adjacent to at least one empty tile
+    ///
+    /// Frontier discs are usually a weakness since they give the opponent more moves.
+    pub fn frontier_discs(&self, piece: Piece) -> usize {
+        let grid = &self.grid;
+        let bounds = (grid.col_len(), grid.row_len());
+
+        let mut frontier = 0;
+        for row in 0..grid.col_len() {
+            for col in 0..grid.row_len() {
+                let pos = TilePos {row, col};
+                if grid.tile(pos) != Some(piece) {
+                    continue;
+                }
+
+                let is_frontier = Direction::ALL.iter().any(|dir| {
+                    let (drow, dcol) = dir.delta();
+                    pos.offset(drow, dcol, bounds).is_some_and(|adj| grid.is_open(adj))
+                });
+                if is_frontier {
+                    frontier += 1;
+                }
+            }
+        }
+
+        frontier
+    }
+
+    /// Returns the potential mobility of the given player: the number of open tiles adjacent to at
+    /// least one of the opponent's pieces
+    ///
+    /// These are the tiles that could become valid moves for the player later on, even if they are
+    /// not valid moves right now.
+    pub fn potential_mobility(&self, piece: Piece) -> usize {
+        let grid = &self.grid;
+        let bounds = (grid.col_len(), grid.row_len());
+        let opponent = piece.opposite();
+
+        let mut potential = 0;
+        for row in 0..grid.col_len() {
+            for col in 0..grid.row_len() {
+                let pos = TilePos {row, col};
+                if !grid.is_open(pos) {
+                    continue;
+                }
+
+                let next_to_opponent = Direction::ALL.iter().any(|dir| {
+                    let (drow, dcol) = dir.delta();
+                    pos.offset(drow, dcol, bounds).is_some_and(|adj| grid.tile(adj) == Some(opponent))
+                });
+                if next_to_opponent {
+                    potential += 1;
+                }
+            }
+        }
+
+        potential
+    }
+
+    /// Returns the number of stable discs for the given player: pieces that can never be flipped
+    /// again for the rest of the game
+    ///
+    /// This is a conservative estimate. Every disc counted is definitely stable, but some stable
+    /// discs may not be counted.
+    pub fn stable_discs(&self, piece: Piece) -> usize {
+        let grid = &self.grid;
+        let mut stable = [[false; 8]; 8];
+        let mut count = 0;
+
+        // A disc can only be flipped along a line (e.g. its row) by a piece placed on an open tile
+        // in that line. A disc is safe along a line if the line is full, or if it has the edge of
+        // the board or another stable disc of the same player as a neighbour in that line. Discs
+        // that are safe along all 4 lines are stable, which may in turn make other discs stable.
+        loop {
+            let mut changed = false;
+            for row in 0..grid.col_len() {
+                for col in 0..grid.row_len() {
+                    let pos = TilePos {row, col};
+                    if stable[row][col] || grid.tile(pos) != Some(piece) {
+                        continue;
+                    }
+
+                    // The first 4 directions cover every line through the tile exactly once
+                    let is_stable = Direction::ALL[..4].iter().all(|&dir| {
+                        let sides = [dir, dir.opposite()];
+                        let is_full = sides.iter()
+                            .all(|&side| grid.ray(pos, side).all(|adj| !grid.is_open(adj)));
+                        let is_anchored = sides.iter().any(|&side| match grid.ray(pos, side).next() {
+                            Some(adj) => grid.is_blocked(adj) || stable[adj.row][adj.col],
+                            None => true,
+                        });
+
+                        is_full || is_anchored
+                    });
+                    if is_stable {
+                        stable[row][col] = true;
+                        count += 1;
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                break count;
+            }
+        }
+    }
+
     /// Returns all valid moves for the current player
     pub fn valid_moves(&self) -> &[TilePos] {
         &self.valid_moves
     }
 
+    /// Returns the number of tiles that would be flipped if the current player placed a piece at
+    /// the given position (zero if the move is not valid)
+    pub fn count_flips(&self, pos: TilePos) -> usize {
+        if !self.grid.is_open(pos) {
+            return 0;
+        }
+
+        count_flips(&self.grid, self.current_player, pos)
+    }
+
+    /// Returns an iterator over the legal moves for the given player, computed lazily from the
+    /// current state of the grid
+    ///
+    /// Unlike `valid_moves`, this works for either player and does not allocate. Moves are
+    /// yielded in the same order as `valid_moves`.
+    pub fn legal_moves_iter(&self, player: Piece) -> LegalMoves<'_> {
+        LegalMoves {
+            grid: &self.grid,
+            player,
+            next: 0,
+        }
+    }
+
     /// Advances the turn by changing the current player, leave the board unmodified
     pub fn advance_turn(&mut self) {
-        self.current_player = self.current_player.opposite();
-        self.valid_moves = compute_valid_moves(self.grid(), self.current_player);
+        let next_player = self.current_player.opposite();
+        self.hash ^= zobrist::player_key(self.current_player) ^ zobrist::player_key(next_player);
+        self.current_player = next_player;
+        self.update_valid_moves();
     }
 
     /// Places a tile for the current player at the given position, updating any surrounding tiles
     /// that were affected by this move.
     ///
+    /// Returns the tiles that were flipped by the move. If the game is timed, the time taken is
+    /// charged to the current player. A player that has run out of time loses the game and their
+    /// move is not made, so no tiles are returned.
+    ///
     /// # Panics
     ///
     /// Panics if the move is not valid for the current player.
-    pub fn make_move(&mut self, pos: TilePos) {
-        let flips = compute_flips(self.grid(), self.current_player, pos);
-        assert!(!flips.is_empty(), "bug: attempt to make a move that would result in zero flips");
+    pub fn make_move(&mut self, pos: TilePos) -> Flips {
+        self.play(Move::Place(pos))
+    }
+
+    /// Skips the turn of the current player
+    ///
+    /// If the game is timed, the time taken is charged to the current player the same way as
+    /// with `make_move`.
+    pub fn pass(&mut self) {
+        let _ = self.play(Move::Pass);
+    }
 
-        let player = self.current_player();
-        for flip_pos in flips {
-            self.grid.place(flip_pos, player.clone());
+    /// Ends the game with the given player resigning, even if it is not currently their turn
+    ///
+    /// Does nothing if the game has already ended.
+    pub fn resign(&mut self, piece: Piece) {
+        if self.is_game_over() {
+            return;
+        }
+
+        if let Some(clock) = &mut self.clock {
+            if let Some((player, think_time)) = clock.stop() {
+                self.think_times.push((player, think_time));
+            }
         }
-        self.grid.place(pos.clone(), player.clone());
 
-        self.advance_turn();
+        self.resigned = Some(piece);
+        self.notify_game_over();
+    }
+
+    /// Makes the given move for the current player, keeping track of the time it took, and returns
+    /// the tiles it flipped
+    fn play(&mut self, pmove: Move) -> Flips {
+        let player = self.current_player;
+
+        if let Some(clock) = &mut self.clock {
+            let think_time = clock.stop().map_or(Duration::from_secs(0), |(_, elapsed)| elapsed);
+            self.think_times.push((player, think_time));
+
+            if clock.flagged().is_some() {
+                self.notify_game_over();
+                return Flips::default();
+            }
+        }
+
+        let UndoToken {flips, ..} = self.apply(pmove);
+
+        let game_over = self.is_game_over();
+        if let Some(clock) = &mut self.clock {
+            if !game_over {
+                clock.start(self.current_player);
+            }
+        }
+
+        flips
+    }
+
+    /// Makes the given move for the current player and returns a token that can be passed to
+    /// `unapply` to restore the game to its state before the move
+    ///
+    /// Unlike cloning the game before every move, this reuses the game's existing allocations,
+    /// which makes it well suited for searching through many possible moves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a piece is placed on a tile where it would not result in any flips.
+    pub fn apply(&mut self, pmove: Move) -> UndoToken {
+        let player = self.current_player;
+
+        let flips = match pmove {
+            Move::Place(pos) => {
+                let mut flips = Flips::default();
+                compute_flips(self.grid(), player, pos, &mut flips);
+                assert!(!flips.is_empty(), "bug: attempt to make a move that would result in zero flips");
+
+                let opponent = player.opposite();
+                for &flip_pos in flips.iter() {
+                    self.grid.place(flip_pos, player);
+                    self.hash ^= zobrist::piece_key(flip_pos, opponent) ^ zobrist::piece_key(flip_pos, player);
+                }
+                self.grid.place(pos, player);
+                self.hash ^= zobrist::piece_key(pos, player);
+
+                flips
+            },
+
+            Move::Pass => Flips::default(),
+
+            Move::Resign => {
+                self.resigned = Some(player);
+                Flips::default()
+            },
+        };
+
+        // Resigning ends the game, so there is no next turn to advance to
+        if pmove != Move::Resign {
+            self.advance_turn();
+        }
+        self.history.push(pmove);
+        let flip_mask = flips.iter().fold(0, |mask, flip_pos| mask | 1 << (flip_pos.row * 8 + flip_pos.col));
+        self.flip_history.push(flip_mask);
+
+        if !self.observers.0.is_empty() {
+            self.notify(pmove, player, &flips);
+        }
+
+        UndoToken {pmove, player, flips}
+    }
+
+    /// Notifies every observer about a move that was just made
+    fn notify(&mut self, pmove: Move, player: Piece, flips: &[TilePos]) {
+        // Observers are taken out of the game so that they can be given a reference to it
+        let mut observers = mem::take(&mut self.observers);
+
+        let game_over = self.is_game_over();
+        let winner = self.winner();
+        for observer in &mut observers.0 {
+            match pmove {
+                Move::Place(pos) => {
+                    observer.on_move(self, player, pos);
+                    observer.on_flips(self, player, flips);
+                },
+
+                Move::Pass => observer.on_pass(self, player),
+
+                Move::Resign => {},
+            }
+
+            if game_over {
+                observer.on_game_over(self, winner);
+            }
+        }
+
+        self.observers = observers;
+    }
+
+    /// Notifies every observer that the game has ended without a move being made
+    fn notify_game_over(&mut self) {
+        let mut observers = mem::take(&mut self.observers);
+
+        let winner = self.winner();
+        for observer in &mut observers.0 {
+            observer.on_game_over(self, winner);
+        }
+
+        self.observers = observers;
+    }
+
+    /// Undoes a move previously made with `apply`, restoring the game to the state it was in
+    /// before that move
+    pub fn unapply(&mut self, token: UndoToken) {
+        let UndoToken {pmove, player, flips} = token;
+        self.history.pop();
+        self.flip_history.pop();
+
+        match pmove {
+            Move::Place(pos) => {
+                // Every flipped tile must have previously belonged to the opponent
+                let opponent = player.opposite();
+                for &flip_pos in flips.iter() {
+                    self.grid.place(flip_pos, opponent);
+                    self.hash ^= zobrist::piece_key(flip_pos, player) ^ zobrist::piece_key(flip_pos, opponent);
+                }
+                self.grid.clear(pos);
+                self.hash ^= zobrist::piece_key(pos, player);
+            },
+
+            Move::Pass => {},
+
+            Move::Resign => self.resigned = None,
+        }
+
+        self.hash ^= zobrist::player_key(self.current_player) ^ zobrist::player_key(player);
+        self.current_player = player;
+        self.update_valid_moves();
+    }
+
+    /// Recomputes the cached valid moves for the current player, reusing the existing buffer
+    fn update_valid_moves(&mut self) {
+        compute_valid_moves(&self.grid, self.current_player, &mut self.valid_moves);
+    }
+}
+
+impl Game for Reversi {
+    type Move = Move;
+
+    fn current_player(&self) -> Piece {
+        self.current_player
+    }
+
+    /// Returns the valid moves of the current player, or a pass if they have none
+    ///
+    /// Resigning is always allowed but never returned, so that searches do not consider it.
+    fn legal_moves(&self) -> Vec<Move> {
+        if self.is_game_over() {
+            return Vec::new();
+        }
+        if self.valid_moves.is_empty() {
+            return vec![Move::Pass];
+        }
+
+        self.valid_moves.iter().map(|&pos| Move::Place(pos)).collect()
+    }
+
+    fn play_move(&mut self, pmove: Move) {
+        match pmove {
+            Move::Place(pos) => {
+                self.make_move(pos);
+            },
+            Move::Pass => self.pass(),
+            Move::Resign => self.resign(self.current_player),
+        }
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.termination().is_some()
+    }
+
+    fn winner(&self) -> Option<Piece> {
+        if !self.is_game_over() {
+            return None;
+        }
+
+        Reversi::winner(self)
+    }
+
+    fn is_pass(&self, pmove: &Move) -> bool {
+        *pmove == Move::Pass
+    }
+}
+
+/// An iterator over the legal moves of a player
+///
+/// Created with `Reversi::legal_moves_iter`
+#[derive(Debug, Clone)]
+pub struct LegalMoves<'a> {
+    grid: &'a Grid,
+    player: Piece,
+    /// The index (in row-by-row order) of the next tile to check
+    next: usize,
+}
+
+impl<'a> Iterator for LegalMoves<'a> {
+    type Item = TilePos;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ncols = self.grid.row_len();
+        let ntiles = self.grid.col_len() * ncols;
+        while self.next < ntiles {
+            let pos = TilePos {row: self.next / ncols, col: self.next % ncols};
+            self.next += 1;
+
+            if self.grid.is_open(pos) && has_flips(self.grid, self.player, pos) {
+                return Some(pos);
+            }
+        }
+
+        None
     }
 }
 
-fn compute_valid_moves(grid: &Grid, player: Piece) -> Vec<TilePos> {
+/// Parses a position drawn as 8 lines of 8 tiles, with `X` and `O` for pieces, `.` for empty
+/// tiles and `#` for blocked tiles, e.g. a position copied from a book:
+///
+/// ```text
+///   A B C D E F G H
+/// 1 . . . . . . . .
+/// 2 . . . . . . . .
+/// 3 . . . . X . . .
+/// 4 . . . X X . . .
+/// 5 . . . O X . . .
+/// 6 . . . . . . . .
+/// 7 . . . . . . . .
+/// 8 . . . . . . . .
+/// O to move
+/// ```
+///
+/// The tiles are read as they are drawn by this game, with row 1 at the top and column A on the
+/// left. Tiles may be separated by spaces or by `|`, in which case an empty cell is an empty
+/// tile, so the boards printed by `to_board_string` with plain ASCII characters can be pasted
+/// back in. Row numbers, a line of column letters and lines of border characters (e.g.
+/// `---+---`) are ignored, and a line like `X to move` gives the player to move.
+pub fn parse_ascii_board(s: &str) -> Result<AsciiBoard, ParseAsciiBoardError> {
+    let mut grid = Grid::default();
+    let mut player = None;
+    let mut rows = 0;
+    for (index, line) in s.lines().enumerate() {
+        let line_num = index + 1;
+        let text = line.trim();
+        // A line of only bars is a row of empty cells rather than a border
+        let is_border = text.chars().all(|ch| ch.is_whitespace() || BOARD_BORDERS.contains(&ch))
+            && text.chars().any(|ch| !ch.is_whitespace() && ch != '|' && ch != '│');
+        if text.is_empty() || is_border {
+            continue;
+        }
+
+        // The line of column letters (X and O are after H, so they are never column letters)
+        let is_column_letter = |ch: char| ('A'..='H').contains(&ch.to_ascii_uppercase());
+        if text.chars().any(is_column_letter)
+            && text.chars().all(|ch| ch.is_whitespace() || ch == '|' || ch == '│' || is_column_letter(ch)) {
+            continue;
+        }
+
+        let words: Vec<_> = text.split_whitespace().collect();
+        if let [piece, to, moves] = words[..] {
+            if to.eq_ignore_ascii_case("to") && moves.eq_ignore_ascii_case("move") {
+                if player.is_some() {
+                    return Err(ParseAsciiBoardError::DuplicatePlayer {line: line_num});
+                }
+                player = match piece {
+                    "X" | "x" => Some(Piece::X),
+                    "O" | "o" => Some(Piece::O),
+                    _ => return Err(ParseAsciiBoardError::InvalidTile {line: line_num, tile: piece.to_string()}),
+                };
+                continue;
+            }
+        }
+
+        let tiles = parse_ascii_row(text, line_num)?;
+        if tiles.len() != 8 {
+            return Err(ParseAsciiBoardError::WrongRowLength {line: line_num, found: tiles.len()});
+        }
+        if rows < 8 {
+            for (col, tile) in tiles.into_iter().enumerate() {
+                let pos = TilePos {row: rows, col};
+                match tile {
+                    Some('X') => grid.place(pos, Piece::X),
+                    Some('O') => grid.place(pos, Piece::O),
+                    Some('#') => grid.block(pos),
+                    _ => {},
+                }
+            }
+        }
+        rows += 1;
+    }
+
+    if rows != 8 {
+        return Err(ParseAsciiBoardError::WrongRowCount(rows));
+    }
+    Ok(AsciiBoard {grid, player})
+}
+
+/// Parses the tiles of one row of a drawn board, returning `X`, `O` or `#` for each tile, or
+/// `None` for an empty tile
+fn parse_ascii_row(text: &str, line: usize) -> Result<Vec<Option<char>>, ParseAsciiBoardError> {
+    let is_bar = |ch: char| ch == '|' || ch == '│';
+    // Row numbers may be written on either side of the row
+    let text = text.trim_start_matches(|ch: char| ch.is_ascii_digit())
+        .trim_end_matches(|ch: char| ch.is_ascii_digit())
+        .trim();
+
+    let parse_tile = |tile: &str| match tile {
+        "" | "." => Ok(None),
+        "X" | "x" => Ok(Some('X')),
+        "O" | "o" => Ok(Some('O')),
+        "#" => Ok(Some('#')),
+        _ => Err(ParseAsciiBoardError::InvalidTile {line, tile: tile.to_string()}),
+    };
+
+    if text.contains(is_bar) {
+        let mut cells = text;
+        if let Some(rest) = cells.strip_prefix(is_bar) {
+            cells = rest;
+        }
+        if let Some(rest) = cells.strip_suffix(is_bar) {
+            cells = rest;
+        }
+        cells.split(is_bar).map(|cell| parse_tile(cell.trim())).collect()
+    } else {
+        text.chars()
+            .filter(|ch| !ch.is_whitespace())
+            .map(|ch| parse_tile(ch.encode_utf8(&mut [0; 4])))
+            .collect()
+    }
+}
+
+/// Computes the valid moves for the given player, replacing the contents of `valid_moves`
+fn compute_valid_moves(grid: &Grid, player: Piece, valid_moves: &mut Vec<TilePos>) {
     // Algorithm: Find all tiles that are empty and would result in at least one flip if the
     // current piece was placed there.
 
-    let mut valid_moves = Vec::new();
-    for (row, row_tiles) in grid.rows().iter().enumerate() {
-        for (col, tile) in row_tiles.iter().enumerate() {
+    valid_moves.clear();
+    for row in 0..grid.col_len() {
+        for col in 0..grid.row_len() {
+            let pmove = TilePos {row, col};
             // Only empty tiles can be valid moves
-            if tile.is_some() {
+            if !grid.is_open(pmove) {
                 continue;
             }
 
-            let pmove = TilePos {row, col};
-            if !compute_flips(grid, player, pmove).is_empty() {
+            if has_flips(grid, player, pmove) {
                 valid_moves.push(pmove);
             }
         }
     }
+}
+
+/// Returns true if placing the given player's piece at the given position would flip at least
+/// one tile
+///
+/// This is the same as checking if `compute_flips` finds any flips, but stops searching as soon
+/// as the first flip is found and never allocates.
+fn has_flips(grid: &Grid, player: Piece, pos: TilePos) -> bool {
+    let opponent = player.opposite();
 
-    valid_moves
+    Direction::ALL.iter().any(|&direction| {
+        let mut found_opponent = false;
+        for current_pos in grid.ray(pos, direction) {
+            match grid.tile(current_pos) {
+                Some(piece) if piece == opponent => found_opponent = true,
+                // Flips only happen if there was at least one opponent piece in between
+                Some(_) => return found_opponent,
+                None => return false,
+            }
+        }
+
+        false
+    })
+}
+
+/// Returns the number of tiles that would have to flip if the given player's piece was placed at
+/// the given position, without recording which tiles they are
+fn count_flips(grid: &Grid, player: Piece, pos: TilePos) -> usize {
+    let opponent = player.opposite();
+
+    let mut count = 0;
+    for &direction in &Direction::ALL {
+        let mut found_opponents = 0;
+        for current_pos in grid.ray(pos, direction) {
+            match grid.tile(current_pos) {
+                Some(piece) if piece == opponent => found_opponents += 1,
+                Some(_) => {
+                    count += found_opponents;
+                    break;
+                },
+                None => break,
+            }
+        }
+    }
+
+    count
 }
 
 /// Computes the tiles that would have to flip if the current piece was placed at the given
-/// position
-fn compute_flips(grid: &Grid, player: Piece, pos: TilePos) -> Vec<TilePos> {
+/// position, adding them to the end of `flips`
+fn compute_flips(grid: &Grid, player: Piece, pos: TilePos, flips: &mut Flips) {
     // Algorithm: Search each of the 8 cardinal directions. A tile is considered a valid move
     // if it is empty and if while searching in a direction we find at least one opponent piece
     // and then a player piece with no empty tiles in between. The "flips" are all opponent
@@ -127,51 +1074,164 @@ fn compute_flips(grid: &Grid, player: Piece, pos: TilePos) -> Vec<TilePos> {
     //     Finding "oo x" is *not* a valid move for x
     //     Finding "x" is *not* a valid move for x
 
-    debug_assert!(grid.tile(pos).is_none(),
-        "bug: cannot compute flips for a tile that is non-empty");
+    // Blocked tiles are treated the same as empty tiles when searching
+    debug_assert!(grid.is_open(pos),
+        "bug: cannot compute flips for a tile that is non-empty or blocked");
 
     let opponent = player.opposite();
 
-    let nrows = grid.col_len() as isize;
-    let ncols = grid.row_len() as isize;
-
-    let mut flips = Vec::new();
-    let directions = &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
-    for &(drow, dcol) in directions {
-        // Opponents that can potentially be flipped
-        let mut found_opponents = Vec::new();
-        for i in 1.. {
-            let row = pos.row as isize + drow * i;
-            let col = pos.col as isize + dcol * i;
-            if row >= 0 && row < nrows && col >= 0 && col < ncols {
-                let current_pos = TilePos {
-                    row: row as usize,
-                    col: col as usize,
-                };
+    for &direction in &Direction::ALL {
+        // Opponents that can potentially be flipped are added optimistically and then removed
+        // if the search does not end at one of the player's pieces
+        let start_len = flips.len();
+        let mut found_player = false;
+        for current_pos in grid.ray(pos, direction) {
+            match grid.tile(current_pos) {
+                Some(piece) => {
+                    if piece == opponent {
+                        flips.push(current_pos);
+
+                    } else if piece == player {
+                        // If we didn't find any opponent pieces, this will not add any flips
+                        found_player = true;
+                        // Stop searching
+                        break;
+                    }
+                },
+
+                // Found empty, stop searching and do not add found opponents
+                None => break,
+            }
+        }
+
+        if !found_player {
+            flips.truncate(start_len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use crate::zobrist_hash;
+
+    /// Returns a random move for the current player, or a pass if they have no valid moves
+    fn random_move(game: &Reversi, rng: &mut StdRng) -> Move {
+        match game.valid_moves() {
+            [] => Move::Pass,
+            moves => Move::Place(moves[rng.gen_range(0, moves.len())]),
+        }
+    }
+
+    #[test]
+    fn apply_and_unapply_round_trip() {
+        let mut rng = StdRng::seed_from_u64(1523);
+        let starts = [Reversi::new(Variant::Standard), Reversi::new(Variant::Anti), Reversi::with_board_size(Variant::Standard, 6)];
+        for start in &starts {
+            for _ in 0..20 {
+                let mut game = start.clone();
+                while !game.is_game_over() {
+                    let before = (game.grid().clone(), game.current_player(), game.valid_moves().to_vec(), game.hash(), game.history().len());
+
+                    for pmove in [random_move(&game, &mut rng), Move::Resign] {
+                        let token = game.apply(pmove);
+                        assert_eq!(game.hash(), zobrist_hash(game.grid(), game.current_player()));
+                        game.unapply(token);
+
+                        let after = (game.grid().clone(), game.current_player(), game.valid_moves().to_vec(), game.hash(), game.history().len());
+                        assert_eq!(after, before, "{:?} was not undone", pmove);
+                        assert!(!game.is_game_over());
+                    }
 
-                match grid.tile(current_pos) {
-                    Some(piece) => {
-                        if piece == opponent {
-                            found_opponents.push(current_pos);
-
-                        } else if piece == player {
-                            // If we didn't find any opponent pieces, this will not add any flips
-                            flips.extend(found_opponents);
-                            // Stop searching
-                            break;
-                        }
-                    },
-
-                    // Found empty, stop searching and do not add found opponents
-                    None => break,
+                    let _ = game.apply(random_move(&game, &mut rng));
                 }
+            }
+        }
+    }
+
+    #[test]
+    fn last_move_flips_follow_apply_and_unapply() {
+        let mut rng = StdRng::seed_from_u64(1575);
+        let mut game = Reversi::new(Variant::Standard);
+        assert_eq!(game.last_move_flips(), None);
+
+        while !game.is_game_over() {
+            let before = game.last_move_flips();
+            let pmove = random_move(&game, &mut rng);
+            let token = game.apply(pmove);
+            game.unapply(token);
+            assert_eq!(game.last_move_flips(), before);
 
-            } else {
-                // hit one of the boundaries of the board
-                break;
+            // The tokens of moves that are not undone can be dropped
+            let mut expected = game.apply(pmove).flips.to_vec();
+            expected.sort_by_key(|pos| (pos.row, pos.col));
+            match pmove {
+                Move::Place(pos) => assert_eq!(game.last_move_flips(), Some((pos, expected))),
+                _ => assert_eq!(game.last_move_flips(), None),
             }
         }
     }
 
-    flips
+    #[test]
+    fn position_strings() {
+        let mut game = Reversi::with_board_size(Variant::Standard, 6);
+        game.make_move(game.valid_moves()[0]);
+        let position = game.to_position_str();
+        assert_eq!(position.len(), 66);
+        assert!(position.ends_with(" O"));
+
+        let copy = Reversi::from_position_str(Variant::Standard, &position).unwrap();
+        assert_eq!(copy.grid(), game.grid());
+        assert_eq!(copy.current_player(), Piece::O);
+        assert_eq!(copy.valid_moves(), game.valid_moves());
+
+        // The space before the player is optional
+        let compact = position.replace(' ', "");
+        assert_eq!(Reversi::from_position_str(Variant::Anti, &compact).unwrap().to_position_str(), position);
+
+        for invalid in &["", "X", &position[..65], &position.replace(" O", " Z"), &position[2..]] {
+            assert!(Reversi::from_position_str(Variant::Standard, invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn ascii_boards() {
+        let mut game = Reversi::default();
+        game.make_move("E3".parse().unwrap());
+
+        let sketch = "
+              A B C D E F G H
+            1 . . . . . . . .
+            2 . . . . . . . .
+            3 . . . . X . . .
+            4 . . . X X . . .
+            5 . . . O X . . .
+            6 . . . . . . . .
+            7 . . . . . . . .
+            8 . . . . . . . .
+            O to move
+        ";
+        let copy = Reversi::from_ascii_board(Variant::Standard, sketch).unwrap();
+        assert_eq!(copy.grid(), game.grid());
+        assert_eq!(copy.current_player(), Piece::O);
+
+        // Boards printed by the game can be pasted back in, but do not say who is to move
+        let printed = parse_ascii_board(&game.to_board_string(true)).unwrap();
+        assert_eq!(printed.grid, *game.grid());
+        assert_eq!(printed.player, None);
+        // Without row numbers, an empty row drawn with bars is still a row
+        let barred = "| X | X | X | X | X | X | X | X |\n".repeat(6) + "|   |   |   |   |   |   |   |   |\n| O |   |   |   |   |   |   | # |";
+        assert_eq!(parse_ascii_board(&barred).unwrap().grid.to_position_string(), "X".repeat(48) + &"-".repeat(8) + "O------#");
+        let compact = "XXXXXXXX\n".repeat(7) + "OOOO##..";
+        assert_eq!(parse_ascii_board(&compact).unwrap().grid.to_position_string(), "X".repeat(56) + "OOOO##--");
+
+        let err = |s: &str| parse_ascii_board(s).unwrap_err();
+        assert_eq!(err(&sketch.replace("3 . . . . X", "3 . . . . Z")), ParseAsciiBoardError::InvalidTile {line: 5, tile: "Z".to_string()});
+        assert_eq!(err(&sketch.replace("4 . . . X X . . .", "4 . . . X X . .")), ParseAsciiBoardError::WrongRowLength {line: 6, found: 7});
+        assert_eq!(err(&sketch.replace("8 . . . . . . . .", "")), ParseAsciiBoardError::WrongRowCount(7));
+        assert_eq!(err(&(sketch.to_string() + "X to move")), ParseAsciiBoardError::DuplicatePlayer {line: 12});
+    }
 }
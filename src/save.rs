@@ -0,0 +1,273 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Reversi, Piece, Move, Grid, Variant, Clock, TimeControl, TerminationReason, AiDifficulty};
+
+/// The version of the save file format written by `Reversi::save`
+const SAVE_VERSION: u32 = 1;
+
+/// Who is playing each side of a game, saved along with it so that it can be resumed with the
+/// same players
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerConfig {
+    /// Which players are played by the AI, X first
+    pub ai_players: [bool; 2],
+    /// How strong the AI plays
+    pub difficulty: AiDifficulty,
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+    #[error("Invalid save file: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("Invalid save file: {0}")]
+    InvalidGame(String),
+}
+
+/// A game as it is written to a save file
+///
+/// Everything is stored in the same notation as it is shown to players (e.g. moves like "D3"), so
+/// that the files are easy to read and do not depend on how the game is stored in memory.
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveFile {
+    /// The version of the format, which is checked when loading
+    version: u32,
+    /// The rules the game is played with, e.g. "standard"
+    variant: String,
+    /// The grid before any moves were made (see `Grid::to_position_string`)
+    initial_position: String,
+    /// The player who made the first move, "X" or "O"
+    first_player: String,
+    /// Every move made so far, in order
+    moves: Vec<String>,
+    /// The player who resigned, if any
+    resigned: Option<String>,
+    /// The clock, if the game is timed
+    clock: Option<SavedClock>,
+    /// The player who made each timed move and how long it took, in milliseconds
+    think_times: Vec<(String, u64)>,
+    /// Who is playing each side, if known
+    players: Option<SavedPlayers>,
+}
+
+/// A clock as it is written to a save file, with every time in milliseconds
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedClock {
+    initial: u64,
+    increment: u64,
+    byo_yomi: Option<u64>,
+    /// The main time remaining for each player, X first
+    remaining: [u64; 2],
+    /// The player who ran out of time, if any
+    flagged: Option<String>,
+}
+
+/// The players of a game as they are written to a save file
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedPlayers {
+    /// Which players are played by the AI, X first
+    ai_players: [bool; 2],
+    /// How strong the AI plays, e.g. "medium"
+    difficulty: String,
+}
+
+impl Reversi {
+    /// Saves the game to the given file as JSON: its rules, starting position and every move
+    /// made so far, along with its clock and players, if any
+    ///
+    /// The time spent on the current turn is charged to the current player.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveError> {
+        fs::write(path, self.to_json() + "\n")?;
+        Ok(())
+    }
+
+    /// Loads a game saved with `save`, with the clock of the current player running if the game
+    /// is timed and not over
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SaveError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_json(&text)
+    }
+
+    /// Returns the game in the format written by `save`
+    pub fn to_json(&self) -> String {
+        let start = self.at_move(0);
+        let millis = |duration: Duration| duration.as_millis() as u64;
+        let file = SaveFile {
+            version: SAVE_VERSION,
+            variant: self.variant().to_string(),
+            initial_position: start.grid().to_position_string(),
+            first_player: piece_name(start.current_player()),
+            moves: self.history().iter().map(Move::to_string).collect(),
+            resigned: match self.termination() {
+                // Resigning on your own turn is a move, so it is already in the history
+                Some(TerminationReason::Resignation(piece)) if self.history().last() != Some(&Move::Resign) => Some(piece_name(piece)),
+                _ => None,
+            },
+            clock: self.clock().map(|clock| {
+                let control = clock.control();
+                SavedClock {
+                    initial: millis(control.initial),
+                    increment: millis(control.increment),
+                    byo_yomi: control.byo_yomi.map(millis),
+                    remaining: [millis(clock.remaining(Piece::X)), millis(clock.remaining(Piece::O))],
+                    flagged: clock.flagged().map(piece_name),
+                }
+            }),
+            think_times: self.think_times().iter().map(|&(piece, time)| (piece_name(piece), millis(time))).collect(),
+            players: self.players().map(|players| SavedPlayers {
+                ai_players: players.ai_players,
+                difficulty: players.difficulty.to_string(),
+            }),
+        };
+
+        serde_json::to_string_pretty(&file).expect("bug: a save file should always be serializable")
+    }
+
+    /// Parses a game in the format written by `save`, replaying its moves to check that they are
+    /// all valid
+    pub fn from_json(text: &str) -> Result<Self, SaveError> {
+        let file: SaveFile = serde_json::from_str(text)?;
+        let invalid = |message: String| SaveError::InvalidGame(message);
+        if file.version != SAVE_VERSION {
+            return Err(invalid(format!("unsupported version {}", file.version)));
+        }
+
+        let variant: Variant = file.variant.parse().map_err(|err| invalid(format!("{}", err)))?;
+        let grid: Grid = file.initial_position.parse().map_err(|err| invalid(format!("{}", err)))?;
+        let mut game = Reversi::from_grid(variant, grid, parse_piece(&file.first_player)?);
+
+        for (index, text) in file.moves.iter().enumerate() {
+            let pmove = match text.as_str() {
+                "pass" => Move::Pass,
+                "resign" => Move::Resign,
+                _ => Move::Place(text.parse().map_err(|err| invalid(format!("{}", err)))?),
+            };
+            let legal = !game.is_game_over() && match pmove {
+                Move::Place(pos) => game.valid_moves().contains(&pos),
+                Move::Pass => game.valid_moves().is_empty(),
+                Move::Resign => true,
+            };
+            if !legal {
+                return Err(invalid(format!("move {} ({}) is not a valid move", index + 1, text)));
+            }
+            let _ = game.apply(pmove);
+        }
+        if let Some(piece) = &file.resigned {
+            game.resign(parse_piece(piece)?);
+        }
+
+        if let Some(players) = &file.players {
+            game.set_players(PlayerConfig {
+                ai_players: players.ai_players,
+                difficulty: players.difficulty.parse().map_err(|err| invalid(format!("{}", err)))?,
+            });
+        }
+
+        let clock = match &file.clock {
+            Some(clock) => {
+                let control = TimeControl {
+                    initial: Duration::from_millis(clock.initial),
+                    increment: Duration::from_millis(clock.increment),
+                    byo_yomi: clock.byo_yomi.map(Duration::from_millis),
+                };
+                let remaining = clock.remaining.map(Duration::from_millis);
+                let flagged = clock.flagged.as_deref().map(parse_piece).transpose()?;
+                Some(Clock::resume(control, remaining, flagged))
+            },
+            None => None,
+        };
+        let think_times = file.think_times.iter()
+            .map(|(piece, time)| Ok((parse_piece(piece)?, Duration::from_millis(*time))))
+            .collect::<Result<_, SaveError>>()?;
+        game.resume_timing(clock, think_times);
+
+        Ok(game)
+    }
+}
+
+/// Returns the name a piece is saved with, which does not depend on whether colors are enabled
+fn piece_name(piece: Piece) -> String {
+    format!("{:?}", piece)
+}
+
+/// Parses a piece saved with `piece_name`
+fn parse_piece(name: &str) -> Result<Piece, SaveError> {
+    match name {
+        "X" => Ok(Piece::X),
+        "O" => Ok(Piece::O),
+        _ => Err(SaveError::InvalidGame(format!("unknown player `{}`", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::TilePos;
+
+    fn pos(s: &str) -> TilePos {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn saves_history_and_players() {
+        let mut game = Reversi::with_board_size(Variant::Anti, 6);
+        game.set_players(PlayerConfig {ai_players: [false, true], difficulty: AiDifficulty::Hard});
+        for _ in 0..3 {
+            game.make_move(game.valid_moves()[0]);
+        }
+
+        let loaded = Reversi::from_json(&game.to_json()).unwrap();
+        assert_eq!(loaded.variant(), Variant::Anti);
+        assert_eq!(loaded.history(), game.history());
+        assert_eq!(loaded.grid(), game.grid());
+        assert_eq!(loaded.at_move(0).grid(), game.at_move(0).grid());
+        assert_eq!(loaded.current_player(), game.current_player());
+        assert_eq!(loaded.players(), game.players());
+        assert!(loaded.clock().is_none());
+    }
+
+    #[test]
+    fn saves_clock_and_resignation() {
+        let mut game = Reversi::default();
+        game.set_clock(Clock::new(TimeControl {
+            initial: Duration::from_secs(60),
+            increment: Duration::from_secs(2),
+            byo_yomi: None,
+        }));
+        game.make_move(pos("E3"));
+        game.resign(Piece::X);
+
+        let loaded = Reversi::from_json(&game.to_json()).unwrap();
+        let (clock, loaded_clock) = (game.clock().unwrap(), loaded.clock().unwrap());
+        assert_eq!(loaded_clock.control(), clock.control());
+        // Times are saved to the millisecond
+        let millis = |clock: &Clock| clock.remaining(Piece::O).as_millis();
+        assert_eq!(millis(loaded_clock), millis(clock));
+        assert_eq!(loaded.think_times().len(), game.think_times().len());
+        assert_eq!(loaded.termination(), Some(TerminationReason::Resignation(Piece::X)));
+        // The game is over, so no clock is running
+        assert_eq!(loaded_clock.running(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_saves() {
+        let game = Reversi::default();
+        let json = game.to_json();
+        assert!(matches!(Reversi::from_json("{"), Err(SaveError::InvalidJson(_))));
+
+        let illegal = json.replace("\"moves\": []", "\"moves\": [\"A1\"]");
+        assert_ne!(illegal, json);
+        assert!(matches!(Reversi::from_json(&illegal), Err(SaveError::InvalidGame(_))));
+
+        let future = json.replace("\"version\": 1", "\"version\": 2");
+        assert!(matches!(Reversi::from_json(&future), Err(SaveError::InvalidGame(_))));
+    }
+}
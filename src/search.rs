@@ -0,0 +1,299 @@
+use crate::{Game, Piece};
+
+/// The score of a position that the player has won, before taking away the number of moves it
+/// took to win (see `negamax`)
+pub const WIN_SCORE: i32 = 1_000_000;
+
+/// A score that is better than any other
+const INFINITY: i32 = i32::MAX;
+
+/// Evaluates the positions of a game for `negamax`
+pub trait Heuristic<G: Game> {
+    /// Returns how good the given position is for the given player, with positive scores being
+    /// good for them
+    ///
+    /// The scores should stay well below `WIN_SCORE` so that they are never mistaken for a win.
+    fn evaluate(&self, game: &G, player: Piece) -> i32;
+
+    /// Sorts the legal moves of the given position so that the moves most likely to be best come
+    /// first, which lets the search skip more of the others. Moves that are not worth searching
+    /// can also be removed, as long as at least one is left.
+    ///
+    /// By default, the moves are searched in the order they are returned by `Game::legal_moves`.
+    fn order_moves(&self, _game: &G, _moves: &mut Vec<G::Move>) {}
+
+    /// Returns the moves to search in the given position, most likely to be best first
+    ///
+    /// By default, this is every legal move, sorted by `order_moves`. Games with too many legal
+    /// moves to list them all in every position (e.g. a move made of several stones) can build
+    /// only the moves worth searching instead.
+    fn candidate_moves(&self, game: &G) -> Vec<G::Move> {
+        let mut moves = game.legal_moves();
+        self.order_moves(game, &mut moves);
+        moves
+    }
+}
+
+/// Searches the given number of moves ahead with alpha-beta pruning, returning the best move for
+/// the current player along with its score from their perspective, or `None` if the game is over
+///
+/// Wins are scored as `WIN_SCORE` minus the number of moves it takes to get there, so that the
+/// quickest win (or the slowest loss) is chosen. Players are allowed to move more than once in a
+/// row (see `Game::current_player`).
+///
+/// Chance nodes are searched as if the current player chose the outcome, so games of chance should
+/// be searched with `expectiminimax` instead.
+pub fn negamax<G: Game, H: Heuristic<G>>(game: &G, depth: usize, heuristic: &H) -> Option<(G::Move, i32)> {
+    let player = game.current_player();
+    let moves = heuristic.candidate_moves(game);
+
+    let mut best = None;
+    let mut alpha = -INFINITY;
+    for pmove in moves {
+        let mut child = game.clone();
+        child.play_move(pmove.clone());
+        let score = score_after_move(&child, player, depth.saturating_sub(1), 1, alpha, INFINITY, heuristic);
+        if best.is_none() || score > alpha {
+            alpha = score;
+            best = Some(pmove);
+        }
+    }
+
+    best.map(|pmove| (pmove, alpha))
+}
+
+/// Returns the score of the given position for the player that just moved, who may or may not be
+/// the current player of the position
+fn score_after_move<G: Game, H: Heuristic<G>>(
+    game: &G,
+    player: Piece,
+    depth: usize,
+    ply: i32,
+    alpha: i32,
+    beta: i32,
+    heuristic: &H,
+) -> i32 {
+    if game.current_player() == player {
+        search(game, depth, ply, alpha, beta, heuristic)
+    } else {
+        -search(game, depth, ply, -beta, -alpha, heuristic)
+    }
+}
+
+/// Returns the score of the given position for its current player
+fn search<G: Game, H: Heuristic<G>>(
+    game: &G,
+    depth: usize,
+    ply: i32,
+    mut alpha: i32,
+    beta: i32,
+    heuristic: &H,
+) -> i32 {
+    let player = game.current_player();
+    if game.is_game_over() {
+        return match game.winner() {
+            Some(winner) if winner == player => WIN_SCORE - ply,
+            Some(_) => -(WIN_SCORE - ply),
+            None => 0,
+        };
+    }
+
+    if depth == 0 {
+        return heuristic.evaluate(game, player);
+    }
+    let moves = heuristic.candidate_moves(game);
+    if moves.is_empty() {
+        return heuristic.evaluate(game, player);
+    }
+
+    let mut best = -INFINITY;
+    for pmove in moves {
+        let mut child = game.clone();
+        child.play_move(pmove);
+        let score = score_after_move(&child, player, depth - 1, ply + 1, alpha, beta, heuristic);
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best
+}
+
+/// Searches the given number of moves ahead in a game of chance, returning the best move for the
+/// current player along with its expected score from their perspective, or `None` if the game is
+/// over or the next thing to happen is a random event
+///
+/// Each chance node (see `Game::is_chance_node`) is scored as the average score of its outcomes,
+/// weighted by how likely they are. Random events do not count towards the depth. Positions are
+/// scored the same way as in `negamax`, but without alpha-beta pruning, since no outcome can be
+/// skipped without knowing the others.
+pub fn expectiminimax<G: Game, H: Heuristic<G>>(game: &G, depth: usize, heuristic: &H) -> Option<(G::Move, i32)> {
+    if game.is_chance_node() {
+        return None;
+    }
+
+    let player = game.current_player();
+    let mut best: Option<(G::Move, f64)> = None;
+    for pmove in heuristic.candidate_moves(game) {
+        let mut child = game.clone();
+        child.play_move(pmove.clone());
+        let score = expected_score_for(&child, player, depth.saturating_sub(1), 1, heuristic);
+        if best.as_ref().is_none_or(|&(_, best_score)| score > best_score) {
+            best = Some((pmove, score));
+        }
+    }
+
+    best.map(|(pmove, score)| (pmove, score.round() as i32))
+}
+
+/// Returns the expected score of the given position for the given player, who may or may not be
+/// its current player
+fn expected_score_for<G: Game, H: Heuristic<G>>(game: &G, player: Piece, depth: usize, ply: i32, heuristic: &H) -> f64 {
+    let score = expected_score(game, depth, ply, heuristic);
+    if game.current_player() == player {
+        score
+    } else {
+        -score
+    }
+}
+
+/// Returns the expected score of the given position for its current player
+fn expected_score<G: Game, H: Heuristic<G>>(game: &G, depth: usize, ply: i32, heuristic: &H) -> f64 {
+    let player = game.current_player();
+    if game.is_game_over() {
+        return match game.winner() {
+            Some(winner) if winner == player => (WIN_SCORE - ply) as f64,
+            Some(_) => -(WIN_SCORE - ply) as f64,
+            None => 0.0,
+        };
+    }
+
+    if game.is_chance_node() {
+        return game.chance_outcomes().into_iter()
+            .map(|(outcome, probability)| {
+                let mut child = game.clone();
+                child.play_move(outcome);
+                probability * expected_score_for(&child, player, depth, ply, heuristic)
+            })
+            .sum();
+    }
+
+    if depth == 0 {
+        return heuristic.evaluate(game, player) as f64;
+    }
+    let moves = heuristic.candidate_moves(game);
+    if moves.is_empty() {
+        return heuristic.evaluate(game, player) as f64;
+    }
+
+    moves.into_iter()
+        .map(|pmove| {
+            let mut child = game.clone();
+            child.play_move(pmove);
+            expected_score_for(&child, player, depth - 1, ply + 1, heuristic)
+        })
+        .fold(f64::NEG_INFINITY, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use super::*;
+
+    /// A game where X either takes a tie or flips a coin that wins with the given probability
+    #[derive(Debug, Clone)]
+    struct CoinFlip {
+        win_chance: f64,
+        state: CoinState,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum CoinState {
+        Choosing,
+        Flipping,
+        Over(Option<Piece>),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum CoinMove {
+        Tie,
+        Flip,
+        Heads,
+        Tails,
+    }
+
+    impl fmt::Display for CoinMove {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl Game for CoinFlip {
+        type Move = CoinMove;
+
+        fn current_player(&self) -> Piece {
+            Piece::X
+        }
+
+        fn legal_moves(&self) -> Vec<CoinMove> {
+            match self.state {
+                CoinState::Choosing => vec![CoinMove::Tie, CoinMove::Flip],
+                CoinState::Flipping => vec![CoinMove::Heads, CoinMove::Tails],
+                CoinState::Over(_) => Vec::new(),
+            }
+        }
+
+        fn play_move(&mut self, pmove: CoinMove) {
+            self.state = match pmove {
+                CoinMove::Tie => CoinState::Over(None),
+                CoinMove::Flip => CoinState::Flipping,
+                CoinMove::Heads => CoinState::Over(Some(Piece::X)),
+                CoinMove::Tails => CoinState::Over(Some(Piece::O)),
+            };
+        }
+
+        fn is_game_over(&self) -> bool {
+            matches!(self.state, CoinState::Over(_))
+        }
+
+        fn winner(&self) -> Option<Piece> {
+            match self.state {
+                CoinState::Over(winner) => winner,
+                _ => None,
+            }
+        }
+
+        fn is_chance_node(&self) -> bool {
+            self.state == CoinState::Flipping
+        }
+
+        fn chance_outcomes(&self) -> Vec<(CoinMove, f64)> {
+            vec![(CoinMove::Heads, self.win_chance), (CoinMove::Tails, 1.0 - self.win_chance)]
+        }
+    }
+
+    struct NoHeuristic;
+
+    impl Heuristic<CoinFlip> for NoHeuristic {
+        fn evaluate(&self, _game: &CoinFlip, _player: Piece) -> i32 {
+            0
+        }
+    }
+
+    #[test]
+    fn expectiminimax_weighs_outcomes() {
+        let unlikely = CoinFlip {win_chance: 0.25, state: CoinState::Choosing};
+        assert_eq!(expectiminimax(&unlikely, 2, &NoHeuristic).map(|(pmove, _)| pmove), Some(CoinMove::Tie));
+
+        let likely = CoinFlip {win_chance: 0.75, ..unlikely};
+        let (pmove, score) = expectiminimax(&likely, 2, &NoHeuristic).unwrap();
+        assert_eq!(pmove, CoinMove::Flip);
+        assert_eq!(score, (0.5 * (WIN_SCORE - 1) as f64).round() as i32);
+
+        // Negamax assumes the coin lands however is best for X
+        assert_eq!(negamax(&unlikely, 2, &NoHeuristic).map(|(pmove, _)| pmove), Some(CoinMove::Flip));
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+
+use crate::Piece;
+
+/// One of the players of a game, numbered from 0 in the order they take their turns
+///
+/// Two-player games use `Piece` for their players, which converts to the first two seats:
+/// `Piece::X` is seat 0 and `Piece::O` is seat 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Seat(pub usize);
+
+impl Seat {
+    /// Returns the index of the seat, starting at 0 for the player who moves first
+    pub fn index(self) -> usize {
+        self.0
+    }
+
+    /// Returns the seat that takes its turn after this one in a game with the given number of
+    /// players
+    pub fn next(self, players: usize) -> Self {
+        Seat((self.0 + 1) % players)
+    }
+
+    /// Returns the piece of the player in this seat, or `None` if the seat is only found in games
+    /// with more than two players
+    pub fn piece(self) -> Option<Piece> {
+        match self.0 {
+            0 => Some(Piece::X),
+            1 => Some(Piece::O),
+            _ => None,
+        }
+    }
+}
+
+impl From<Piece> for Seat {
+    fn from(piece: Piece) -> Self {
+        match piece {
+            Piece::X => Seat(0),
+            Piece::O => Seat(1),
+        }
+    }
+}
+
+/// Writes the seat as its number, starting at 1, e.g. "Player 3"
+impl fmt::Display for Seat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Player {}", self.0 + 1)
+    }
+}
@@ -0,0 +1,350 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Reversi, Piece, AiDifficulty};
+
+/// The environment variable that overrides the file returned by `PlayerStats::default_path`
+pub const STATS_FILE_VAR: &str = "BOARD_GAMES_STATS";
+
+/// The version of the stats file format written by `PlayerStats::save`
+const STATS_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum StatsError {
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+    #[error("Invalid stats file: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("Invalid stats file: {0}")]
+    InvalidStats(String),
+}
+
+/// Who a player played a game against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opponent {
+    /// Another player at the same computer
+    Human,
+    /// The AI at the given difficulty
+    Ai(AiDifficulty),
+}
+
+impl fmt::Display for Opponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Opponent::Human => write!(f, "Human"),
+            Opponent::Ai(difficulty) => write!(f, "AI ({})", difficulty),
+        }
+    }
+}
+
+/// The results of every game played against one opponent
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordStats {
+    pub wins: u32,
+    pub losses: u32,
+    pub ties: u32,
+    /// The sum of the number of discs the player had at the end of each game minus the number
+    /// their opponent had
+    pub disc_differential: i64,
+}
+
+impl RecordStats {
+    /// Returns the number of games played
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.ties
+    }
+
+    /// Returns the percentage of games that were won, or `None` if no games were played
+    pub fn win_percentage(&self) -> Option<f64> {
+        match self.games() {
+            0 => None,
+            games => Some(self.wins as f64 * 100.0 / games as f64),
+        }
+    }
+
+    /// Returns how many more discs than their opponent the player had at the end of a game on
+    /// average, or `None` if no games were played
+    pub fn average_disc_differential(&self) -> Option<f64> {
+        match self.games() {
+            0 => None,
+            games => Some(self.disc_differential as f64 / games as f64),
+        }
+    }
+
+    fn add(&mut self, other: &RecordStats) {
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.ties += other.ties;
+        self.disc_differential += other.disc_differential;
+    }
+}
+
+/// The results of the games played at this computer, kept in a stats file that is updated at the
+/// end of every game
+///
+/// Games are recorded from the point of view of each human player, so a game between two people
+/// at the same computer counts as both a win and a loss against a human. Win streaks only count
+/// games against the AI, since those are the games a player can climb the difficulty levels with.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PlayerStats {
+    /// The results of games against another human
+    human: RecordStats,
+    /// The results of games against the AI, in the order of `AiDifficulty::ALL`
+    ai: [RecordStats; AiDifficulty::ALL.len()],
+    /// The number of games in a row the player has won against the AI, up to their last game
+    pub current_streak: u32,
+    /// The most games in a row the player has ever won against the AI
+    pub longest_streak: u32,
+}
+
+/// The stats as they are written to a stats file
+#[derive(Debug, Serialize, Deserialize)]
+struct StatsFile {
+    /// The version of the format, which is checked when loading
+    version: u32,
+    human: RecordStats,
+    /// The results against the AI at each difficulty it has been played at, e.g. "medium"
+    ai: BTreeMap<String, RecordStats>,
+    current_streak: u32,
+    longest_streak: u32,
+}
+
+impl PlayerStats {
+    /// Creates stats with no games played
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the file the stats are kept in unless another one is chosen: the file given by the
+    /// `BOARD_GAMES_STATS` environment variable, or `.board-games/stats.json` in the user's home
+    /// directory
+    ///
+    /// Returns `None` if neither the variable nor the home directory is set.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os(STATS_FILE_VAR).filter(|path| !path.is_empty()) {
+            return Some(PathBuf::from(path));
+        }
+
+        let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+        Some(Path::new(&home).join(".board-games").join("stats.json"))
+    }
+
+    /// Loads the stats from the given file, or returns empty stats if the file does not exist yet
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, StatsError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::from_json(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Saves the stats to the given file as JSON, creating its directory if needed
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), StatsError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_json() + "\n")?;
+        Ok(())
+    }
+
+    /// Returns the stats in the format written by `save`
+    pub fn to_json(&self) -> String {
+        let file = StatsFile {
+            version: STATS_VERSION,
+            human: self.human,
+            ai: AiDifficulty::ALL.iter().zip(&self.ai)
+                .filter(|(_, record)| record.games() > 0)
+                .map(|(difficulty, record)| (difficulty.to_string(), *record))
+                .collect(),
+            current_streak: self.current_streak,
+            longest_streak: self.longest_streak,
+        };
+
+        serde_json::to_string_pretty(&file).expect("bug: a stats file should always be serializable")
+    }
+
+    /// Parses stats in the format written by `save`
+    pub fn from_json(text: &str) -> Result<Self, StatsError> {
+        let file: StatsFile = serde_json::from_str(text)?;
+        if file.version != STATS_VERSION {
+            return Err(StatsError::InvalidStats(format!("unsupported version {}", file.version)));
+        }
+
+        let mut stats = Self {
+            human: file.human,
+            current_streak: file.current_streak,
+            longest_streak: file.longest_streak,
+            ..Self::default()
+        };
+        for (name, record) in file.ai {
+            let difficulty: AiDifficulty = name.parse().map_err(|err| StatsError::InvalidStats(format!("{}", err)))?;
+            *stats.record_mut(Opponent::Ai(difficulty)) = record;
+        }
+
+        Ok(stats)
+    }
+
+    /// Returns the results of every game played against the given opponent
+    pub fn record(&self, opponent: Opponent) -> RecordStats {
+        match opponent {
+            Opponent::Human => self.human,
+            Opponent::Ai(difficulty) => self.ai[difficulty_index(difficulty)],
+        }
+    }
+
+    /// Returns the results against every opponent that has been played at least once, with
+    /// humans first and then the AI from its easiest difficulty to its hardest
+    pub fn records(&self) -> Vec<(Opponent, RecordStats)> {
+        let opponents = Some(Opponent::Human).into_iter()
+            .chain(AiDifficulty::ALL.iter().map(|&difficulty| Opponent::Ai(difficulty)));
+        opponents
+            .map(|opponent| (opponent, self.record(opponent)))
+            .filter(|(_, record)| record.games() > 0)
+            .collect()
+    }
+
+    /// Returns the results of every game against every opponent
+    pub fn total(&self) -> RecordStats {
+        let mut total = self.human;
+        for record in &self.ai {
+            total.add(record);
+        }
+        total
+    }
+
+    /// Records the result of the given finished game for each of its human players, returning
+    /// who each of them played against
+    ///
+    /// Nothing is recorded if the game is not over or if it is not known who played it (see
+    /// `Reversi::set_players`), as is the case for games between two AIs.
+    pub fn record_game(&mut self, game: &Reversi) -> Vec<Opponent> {
+        let players = match game.players() {
+            Some(players) if game.is_game_over() => players,
+            _ => return Vec::new(),
+        };
+
+        let (x_score, o_score) = game.scores();
+        let mut opponents = Vec::new();
+        for (piece, &is_ai) in [Piece::X, Piece::O].iter().zip(&players.ai_players) {
+            if is_ai {
+                continue;
+            }
+
+            let opponent_is_ai = players.ai_players[if *piece == Piece::X { 1 } else { 0 }];
+            let opponent = if opponent_is_ai { Opponent::Ai(players.difficulty) } else { Opponent::Human };
+            let won = game.winner().map(|winner| winner == *piece);
+
+            let record = self.record_mut(opponent);
+            match won {
+                Some(true) => record.wins += 1,
+                Some(false) => record.losses += 1,
+                None => record.ties += 1,
+            }
+            record.disc_differential += match piece {
+                Piece::X => x_score as i64 - o_score as i64,
+                Piece::O => o_score as i64 - x_score as i64,
+            };
+
+            if opponent_is_ai {
+                if won == Some(true) {
+                    self.current_streak += 1;
+                    self.longest_streak = self.longest_streak.max(self.current_streak);
+                } else {
+                    self.current_streak = 0;
+                }
+            }
+
+            opponents.push(opponent);
+        }
+
+        opponents
+    }
+
+    fn record_mut(&mut self, opponent: Opponent) -> &mut RecordStats {
+        match opponent {
+            Opponent::Human => &mut self.human,
+            Opponent::Ai(difficulty) => &mut self.ai[difficulty_index(difficulty)],
+        }
+    }
+}
+
+/// Returns the position of the given difficulty in `AiDifficulty::ALL`
+fn difficulty_index(difficulty: AiDifficulty) -> usize {
+    AiDifficulty::ALL.iter()
+        .position(|&level| level == difficulty)
+        .expect("bug: every difficulty should be in `AiDifficulty::ALL`")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Variant, PlayerConfig};
+
+    /// Returns a finished game that X won by resignation, played by the given players
+    fn game_won_by_x(ai_players: [bool; 2], difficulty: AiDifficulty) -> Reversi {
+        let mut game = Reversi::new(Variant::Standard);
+        game.set_players(PlayerConfig {ai_players, difficulty});
+        game.make_move(game.valid_moves()[0]);
+        game.resign(Piece::O);
+        game
+    }
+
+    #[test]
+    fn records_games_and_streaks() {
+        let mut stats = PlayerStats::new();
+        let hard = Opponent::Ai(AiDifficulty::Hard);
+
+        assert_eq!(stats.record_game(&game_won_by_x([false, true], AiDifficulty::Hard)), vec![hard]);
+        assert_eq!(stats.record_game(&game_won_by_x([false, true], AiDifficulty::Easy)).len(), 1);
+        assert_eq!((stats.current_streak, stats.longest_streak), (2, 2));
+        // The human lost this one
+        stats.record_game(&game_won_by_x([true, false], AiDifficulty::Hard));
+        assert_eq!((stats.current_streak, stats.longest_streak), (0, 2));
+
+        let record = stats.record(hard);
+        assert_eq!((record.wins, record.losses, record.ties), (1, 1, 0));
+        // X has 4 discs and O has 1 after the first move
+        assert_eq!(record.average_disc_differential(), Some(0.0));
+        assert_eq!(record.win_percentage(), Some(50.0));
+        assert_eq!(stats.records().iter().map(|(opponent, _)| *opponent).collect::<Vec<_>>(),
+            vec![Opponent::Ai(AiDifficulty::Easy), hard]);
+
+        // Both sides of a game between humans are recorded, without affecting the streak
+        let opponents = stats.record_game(&game_won_by_x([false, false], AiDifficulty::Medium));
+        assert_eq!(opponents, vec![Opponent::Human, Opponent::Human]);
+        assert_eq!(stats.record(Opponent::Human), RecordStats {wins: 1, losses: 1, ties: 0, disc_differential: 0});
+        assert_eq!(stats.total().games(), 5);
+
+        // Games between AIs and games that are not over are not recorded
+        assert!(stats.record_game(&game_won_by_x([true, true], AiDifficulty::Medium)).is_empty());
+        assert!(stats.record_game(&Reversi::default()).is_empty());
+        assert_eq!(stats.total().games(), 5);
+
+        let loaded = PlayerStats::from_json(&stats.to_json()).unwrap();
+        assert_eq!(loaded, stats);
+        assert_eq!(PlayerStats::new().record(hard).average_disc_differential(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_stats() {
+        let json = PlayerStats::new().to_json();
+        assert!(matches!(PlayerStats::from_json("{"), Err(StatsError::InvalidJson(_))));
+
+        let future = json.replace("\"version\": 1", "\"version\": 2");
+        assert_ne!(future, json);
+        assert!(matches!(PlayerStats::from_json(&future), Err(StatsError::InvalidStats(_))));
+
+        let unknown = json.replace("\"ai\": {}", "\"ai\": {\"impossible\": {\"wins\": 1, \"losses\": 0, \"ties\": 0, \"disc_differential\": 3}}");
+        assert_ne!(unknown, json);
+        assert!(matches!(PlayerStats::from_json(&unknown), Err(StatsError::InvalidStats(_))));
+    }
+}
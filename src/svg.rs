@@ -0,0 +1,186 @@
+use std::fmt::Write;
+
+use crate::{Reversi, Piece, TilePos};
+
+/// The color of the board behind the tiles
+const BOARD_COLOR: &str = "#2e7d4f";
+/// The color of the lines between tiles and around the board
+const LINE_COLOR: &str = "#1b4a2f";
+/// The color of the tiles that cannot be played on
+const BLOCKED_COLOR: &str = "#5f6b66";
+/// The color of the pieces of `Piece::X`, which moves first like black does in Othello
+const X_COLOR: &str = "#1a1a1a";
+/// The color of the pieces of `Piece::O`
+const O_COLOR: &str = "#f4f4f4";
+/// The color of the marker on the piece placed by the last move
+const LAST_MOVE_COLOR: &str = "#e53935";
+/// The color of the row and column labels
+const LABEL_COLOR: &str = "#333333";
+
+/// Options for how `render_svg` draws the board
+#[derive(Debug, Clone, Copy)]
+pub struct SvgOptions {
+    /// The width and height of each tile in pixels
+    pub tile_size: usize,
+    /// If true, the columns are labeled with letters and the rows with numbers around the board
+    pub coordinates: bool,
+    /// If true, the piece placed by the last move is marked with a dot
+    pub last_move: bool,
+    /// If true, the valid moves of the current player are marked with small dots
+    pub valid_moves: bool,
+    /// If true, the board is drawn from the perspective of `Piece::O` (see
+    /// `DisplayOptions::flip_board`)
+    pub flip_board: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            tile_size: 48,
+            coordinates: true,
+            last_move: true,
+            valid_moves: false,
+            flip_board: false,
+        }
+    }
+}
+
+/// Renders the current position of the given game as a standalone SVG image
+///
+/// The image only uses basic shapes and text, so it can be embedded in a web page or converted to
+/// another format with any SVG tool.
+pub fn render_svg(game: &Reversi, options: &SvgOptions) -> String {
+    let grid = game.grid();
+    let (rows, cols) = (grid.rows().len(), grid.row_len());
+    let tile = options.tile_size as f64;
+    // The labels go in a margin around the board, which is kept on every side so that the board
+    // stays centered
+    let margin = if options.coordinates { tile / 2.0 } else { 0.0 };
+    let board_width = cols as f64 * tile;
+    let board_height = rows as f64 * tile;
+    let width = board_width + 2.0 * margin;
+    let height = board_height + 2.0 * margin;
+
+    // Where each row and column is drawn, taking the orientation of the board into account
+    let x_of = |col: usize| {
+        let col = if options.flip_board { cols - 1 - col } else { col };
+        margin + col as f64 * tile
+    };
+    let y_of = |row: usize| {
+        let row = if options.flip_board { rows - 1 - row } else { row };
+        margin + row as f64 * tile
+    };
+
+    // Writing to a String never fails, so the results of `write!` are ignored
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height,
+    );
+    let _ = writeln!(svg, r##"  <rect width="{}" height="{}" fill="#ffffff"/>"##, width, height);
+    let _ = writeln!(
+        svg,
+        r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}" stroke-width="2"/>"#,
+        margin, margin, board_width, board_height, BOARD_COLOR, LINE_COLOR,
+    );
+
+    for row in 0..rows {
+        for col in 0..cols {
+            if grid.is_blocked(TilePos {row, col}) {
+                let _ = writeln!(
+                    svg,
+                    r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                    x_of(col), y_of(row), tile, tile, BLOCKED_COLOR,
+                );
+            }
+        }
+    }
+
+    for i in 1..cols {
+        let x = margin + i as f64 * tile;
+        let _ = writeln!(
+            svg,
+            r#"  <line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="{}" stroke-width="1"/>"#,
+            margin, margin + board_height, LINE_COLOR, x = x,
+        );
+    }
+    for i in 1..rows {
+        let y = margin + i as f64 * tile;
+        let _ = writeln!(
+            svg,
+            r#"  <line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="{}" stroke-width="1"/>"#,
+            margin, margin + board_width, LINE_COLOR, y = y,
+        );
+    }
+
+    if options.coordinates {
+        let font_size = tile * 0.3;
+        let label = |svg: &mut String, x: f64, y: f64, text: &str| {
+            let _ = writeln!(
+                svg,
+                r#"  <text x="{}" y="{}" font-family="sans-serif" font-size="{:.1}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x, y, font_size, LABEL_COLOR, text,
+            );
+        };
+
+        for col in 0..cols {
+            let x = x_of(col) + tile / 2.0;
+            let text = ((b'A' + col as u8) as char).to_string();
+            label(&mut svg, x, margin / 2.0, &text);
+            label(&mut svg, x, height - margin / 2.0, &text);
+        }
+        for row in 0..rows {
+            let y = y_of(row) + tile / 2.0;
+            let text = (row + 1).to_string();
+            label(&mut svg, margin / 2.0, y, &text);
+            label(&mut svg, width - margin / 2.0, y, &text);
+        }
+    }
+
+    let center = |pos: TilePos| (x_of(pos.col) + tile / 2.0, y_of(pos.row) + tile / 2.0);
+    for (row, row_tiles) in grid.rows().iter().enumerate() {
+        for (col, &piece) in row_tiles.iter().enumerate() {
+            let piece = match piece {
+                Some(piece) => piece,
+                None => continue,
+            };
+            let (x, y) = center(TilePos {row, col});
+            let fill = match piece {
+                Piece::X => X_COLOR,
+                Piece::O => O_COLOR,
+            };
+            let _ = writeln!(
+                svg,
+                r#"  <circle cx="{}" cy="{}" r="{:.1}" fill="{}" stroke="{}" stroke-width="1"/>"#,
+                x, y, tile * 0.42, fill, X_COLOR,
+            );
+        }
+    }
+
+    if options.valid_moves {
+        for &pos in game.valid_moves() {
+            let (x, y) = center(pos);
+            let _ = writeln!(
+                svg,
+                r#"  <circle cx="{}" cy="{}" r="{:.1}" fill="{}" fill-opacity="0.35"/>"#,
+                x, y, tile * 0.08, X_COLOR,
+            );
+        }
+    }
+
+    if options.last_move {
+        if let Some((pos, _)) = game.last_move_flips() {
+            let (x, y) = center(pos);
+            let _ = writeln!(
+                svg,
+                r#"  <circle cx="{}" cy="{}" r="{:.1}" fill="{}"/>"#,
+                x, y, tile * 0.1, LAST_MOVE_COLOR,
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::{Reversi, Grid, Piece, TilePos, Move, Variant, Evaluator, Material, ParseGridError, ParseVariantError};
+
+/// The score given by `Tablebase` as an evaluator to positions that are won with perfect play,
+/// larger than any score the normal evaluation can produce
+pub const TABLEBASE_WIN_SCORE: i32 = 1_000_000;
+
+/// The number of empty tiles at or below which a tablebase solves positions that are not in the
+/// table yet, since positions with more empty tiles can take too long to solve
+pub const TABLEBASE_SOLVE_EMPTIES: usize = 20;
+
+#[derive(Debug, Error)]
+pub enum TablebaseError {
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+    #[error("Missing the board size and variant at the start of the tablebase")]
+    MissingHeader,
+    #[error("Line {line}: invalid board size `{text}`")]
+    InvalidSize {
+        line: usize,
+        text: String,
+    },
+    #[error("Line {line}: {error}")]
+    InvalidVariant {
+        line: usize,
+        error: ParseVariantError,
+    },
+    #[error("Line {line}: {error}")]
+    InvalidPosition {
+        line: usize,
+        error: ParseGridError,
+    },
+    #[error("Line {line}: expected a position, the player to move and a result, found `{text}`")]
+    InvalidLine {
+        line: usize,
+        text: String,
+    },
+}
+
+/// The result of a game with perfect play from both players, from the perspective of the player
+/// to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Wdl {
+    /// Returns the same result from the perspective of the opponent
+    pub fn opposite(self) -> Self {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+
+    fn to_letter(self) -> char {
+        match self {
+            Wdl::Loss => 'L',
+            Wdl::Draw => 'D',
+            Wdl::Win => 'W',
+        }
+    }
+
+    fn from_letter(s: &str) -> Option<Self> {
+        match s {
+            "L" => Some(Wdl::Loss),
+            "D" => Some(Wdl::Draw),
+            "W" => Some(Wdl::Win),
+            _ => None,
+        }
+    }
+}
+
+/// A database of positions on a small board (e.g. 4x4 or 6x6) that have been solved for a win,
+/// draw or loss with perfect play
+///
+/// Positions are stored in their canonical (symmetry-reduced) form, so a single entry covers all
+/// 8 rotations and reflections of a position. Positions that are not in the table yet can be
+/// solved and added to it with `solve`.
+#[derive(Debug, Clone)]
+pub struct Tablebase {
+    /// The number of tiles on each side of the board
+    size: usize,
+    variant: Variant,
+    /// The result for each canonical grid and player to move
+    entries: HashMap<(Grid, Piece), Wdl>,
+}
+
+impl Tablebase {
+    /// Creates an empty tablebase for the board of the given size (see `Reversi::with_board_size`)
+    pub fn new(variant: Variant, size: usize) -> Self {
+        Self {size, variant, entries: HashMap::new()}
+    }
+
+    /// Solves every position that can be reached from the start of a game on the board of the
+    /// given size
+    ///
+    /// This takes a fraction of a second for 4x4 boards. 6x6 boards have far too many positions
+    /// to generate in a reasonable amount of time this way.
+    pub fn generate(variant: Variant, size: usize) -> Self {
+        let mut tablebase = Self::new(variant, size);
+        tablebase.solve_all(&mut Reversi::with_board_size(variant, size));
+        tablebase
+    }
+
+    /// Loads a tablebase from a file written by `write`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TablebaseError> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    /// Parses a tablebase in the format written by `write`
+    ///
+    /// The first line has the board size and variant (e.g. "4 standard"). Every other line has a
+    /// position (as written by `Grid::to_position_string`), the player to move and the result for
+    /// that player (`W`, `D` or `L`). Empty lines are ignored. There are no comments, since the
+    /// positions of small boards start with the `#` of their blocked tiles.
+    pub fn parse(text: &str) -> Result<Self, TablebaseError> {
+        let mut lines = text.lines().enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let (line_num, header) = lines.next().ok_or(TablebaseError::MissingHeader)?;
+        let (size, variant) = match header.split_whitespace().collect::<Vec<_>>()[..] {
+            [size, variant] => (size, variant),
+            _ => return Err(TablebaseError::MissingHeader),
+        };
+        let size = size.parse()
+            .map_err(|_| TablebaseError::InvalidSize {line: line_num, text: size.to_string()})?;
+        let variant = variant.parse()
+            .map_err(|error| TablebaseError::InvalidVariant {line: line_num, error})?;
+
+        let mut tablebase = Self::new(variant, size);
+        for (line_num, line) in lines {
+            let invalid = || TablebaseError::InvalidLine {line: line_num, text: line.to_string()};
+            let (position, player, wdl) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [position, player, wdl] => (position, player, wdl),
+                _ => return Err(invalid()),
+            };
+
+            let grid: Grid = position.parse()
+                .map_err(|error| TablebaseError::InvalidPosition {line: line_num, error})?;
+            let player = match player {
+                "X" => Piece::X,
+                "O" => Piece::O,
+                _ => return Err(invalid()),
+            };
+            let wdl = Wdl::from_letter(wdl).ok_or_else(invalid)?;
+            tablebase.entries.insert((grid.canonical(), player), wdl);
+        }
+
+        Ok(tablebase)
+    }
+
+    /// Writes the tablebase in the format read by `parse`
+    pub fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{} {}", self.size, self.variant)?;
+
+        // Sorted so that the same tablebase is always written the same way
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort();
+        for ((grid, player), wdl) in entries {
+            let player = match player {
+                Piece::X => 'X',
+                Piece::O => 'O',
+            };
+            writeln!(out, "{} {} {}", grid.to_position_string(), player, wdl.to_letter())?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the tablebase to a file in the format read by `load`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        self.write(&mut out)?;
+        out.flush()
+    }
+
+    /// Returns the number of tiles on each side of the board this tablebase is for
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of positions in the tablebase
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the tablebase has no positions
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns true if the given game is played on the board and with the rules this tablebase is
+    /// for
+    pub fn covers(&self, game: &Reversi) -> bool {
+        let grid = game.grid();
+        let playable = (0..8)
+            .flat_map(|row| (0..8).map(move |col| TilePos {row, col}))
+            .filter(|&pos| !grid.is_blocked(pos))
+            .count();
+        game.variant() == self.variant && playable == self.size * self.size
+    }
+
+    /// Returns the result of the given game with perfect play, or `None` if the position is not
+    /// in the tablebase
+    pub fn probe(&self, game: &Reversi) -> Option<Wdl> {
+        if game.is_game_over() {
+            return Some(final_result(game));
+        }
+
+        self.entries.get(&(game.grid().canonical(), game.current_player())).copied()
+    }
+
+    /// Returns the result of the given game with perfect play, solving it and adding it to the
+    /// tablebase (along with every position searched along the way) if it is not there yet
+    pub fn solve(&mut self, game: &Reversi) -> Wdl {
+        self.solve_all(&mut game.clone())
+    }
+
+    /// Returns the best move for the current player of the given game and its result with perfect
+    /// play, or `None` if the game is over or the position cannot be solved
+    ///
+    /// Positions that are not in the tablebase are solved if they have at most
+    /// `TABLEBASE_SOLVE_EMPTIES` empty tiles. Moves that win are preferred over moves that draw.
+    pub fn best_move(&mut self, game: &Reversi) -> Option<(Move, Wdl)> {
+        if game.is_game_over() || !self.covers(game) {
+            return None;
+        }
+        if self.probe(game).is_none() && game.grid().open_count() > TABLEBASE_SOLVE_EMPTIES {
+            return None;
+        }
+
+        let mut game = game.clone();
+        if game.valid_moves().is_empty() {
+            let wdl = self.solve_all(&mut game);
+            return Some((Move::Pass, wdl));
+        }
+
+        let mut best = None;
+        for pmove in game.valid_moves().to_vec() {
+            let token = game.apply(Move::Place(pmove));
+            let wdl = self.solve_all(&mut game).opposite();
+            game.unapply(token);
+
+            if best.is_none_or(|(_, best_wdl)| wdl > best_wdl) {
+                best = Some((Move::Place(pmove), wdl));
+            }
+        }
+
+        best
+    }
+
+    /// Solves the given position with a depth-first search, remembering the result of every
+    /// position that is searched
+    ///
+    /// The game is left in the same state it was in when this function was called.
+    fn solve_all(&mut self, game: &mut Reversi) -> Wdl {
+        if game.is_game_over() {
+            return final_result(game);
+        }
+
+        let key = (game.grid().canonical(), game.current_player());
+        if let Some(&wdl) = self.entries.get(&key) {
+            return wdl;
+        }
+
+        let wdl = if game.valid_moves().is_empty() {
+            let token = game.apply(Move::Pass);
+            let wdl = self.solve_all(game).opposite();
+            game.unapply(token);
+            wdl
+        } else {
+            let mut best = Wdl::Loss;
+            for pmove in game.valid_moves().to_vec() {
+                let token = game.apply(Move::Place(pmove));
+                let wdl = self.solve_all(game).opposite();
+                game.unapply(token);
+
+                best = best.max(wdl);
+                if best == Wdl::Win {
+                    break;
+                }
+            }
+            best
+        };
+
+        self.entries.insert(key, wdl);
+        wdl
+    }
+}
+
+/// Returns the result of a finished game from the perspective of the player to move
+fn final_result(game: &Reversi) -> Wdl {
+    match game.winner() {
+        Some(winner) if winner == game.current_player() => Wdl::Win,
+        Some(_) => Wdl::Loss,
+        None => Wdl::Draw,
+    }
+}
+
+/// Scores positions in the tablebase as won, drawn or lost, falling back to the difference in
+/// material for positions that are not in the tablebase
+impl Evaluator for Tablebase {
+    fn evaluate(&self, game: &Reversi, player: Piece) -> i32 {
+        let wdl = match self.probe(game) {
+            Some(wdl) if game.current_player() == player => wdl,
+            Some(wdl) => wdl.opposite(),
+            None => return Material.evaluate(game, player),
+        };
+
+        match wdl {
+            Wdl::Win => TABLEBASE_WIN_SCORE,
+            Wdl::Draw => 0,
+            Wdl::Loss => -TABLEBASE_WIN_SCORE,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "tablebase"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_the_4x4_board() {
+        let tablebase = Tablebase::generate(Variant::Standard, 4);
+        let game = Reversi::with_board_size(Variant::Standard, 4);
+        assert!(tablebase.covers(&game));
+        assert!(!tablebase.covers(&Reversi::new(Variant::Standard)));
+
+        // The second player wins 4x4 Othello with perfect play
+        assert_eq!(tablebase.probe(&game), Some(Wdl::Loss));
+
+        // Solving a single position gives the same result as solving the whole board
+        let mut solved = Tablebase::new(Variant::Standard, 4);
+        assert_eq!(solved.probe(&game), None);
+        assert_eq!(solved.solve(&game), Wdl::Loss);
+        assert_eq!(solved.probe(&game), Some(Wdl::Loss));
+        assert_eq!(solved.clone().best_move(&game).map(|(_, wdl)| wdl), Some(Wdl::Loss));
+    }
+
+    #[test]
+    fn symmetric_positions_share_results() {
+        let tablebase = Tablebase::generate(Variant::Standard, 4);
+        let mut game = Reversi::with_board_size(Variant::Standard, 4);
+        let pmove = game.valid_moves()[0];
+        let _ = game.apply(Move::Place(pmove));
+
+        let wdl = tablebase.probe(&game);
+        assert!(wdl.is_some());
+        for grid in game.grid().symmetries() {
+            let symmetric = Reversi::from_grid(Variant::Standard, grid, game.current_player());
+            assert_eq!(tablebase.probe(&symmetric), wdl);
+        }
+    }
+
+    #[test]
+    fn write_and_parse_round_trip() {
+        let tablebase = Tablebase::generate(Variant::Anti, 4);
+        let mut text = Vec::new();
+        tablebase.write(&mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.starts_with("4 anti\n"));
+
+        let parsed = Tablebase::parse(&text).unwrap();
+        assert_eq!(parsed.size(), 4);
+        assert_eq!(parsed.len(), tablebase.len());
+        assert_eq!(parsed.entries, tablebase.entries);
+        let game = Reversi::with_board_size(Variant::Anti, 4);
+        assert_eq!(parsed.probe(&game), tablebase.probe(&game));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(Tablebase::parse("\n"), Err(TablebaseError::MissingHeader)));
+        assert!(matches!(Tablebase::parse("four standard"), Err(TablebaseError::InvalidSize {line: 1, ..})));
+        assert!(matches!(Tablebase::parse("4 standard\nX W"), Err(TablebaseError::InvalidLine {line: 2, ..})));
+    }
+}
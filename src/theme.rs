@@ -0,0 +1,190 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+use yansi::{Color, Paint, Style};
+
+use crate::Piece;
+
+/// The glyphs, colors and grid characters used to draw the board
+///
+/// Every glyph should be a single character wide so that the columns of the board line up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// The name of the theme, as accepted by `FromStr`
+    pub name: &'static str,
+    /// The glyph and style of the pieces of `Piece::X`
+    pub x_piece: (&'static str, Style),
+    /// The glyph and style of the pieces of `Piece::O`
+    pub o_piece: (&'static str, Style),
+    /// The glyph and style of the tiles the current player can move to
+    pub valid_move: (&'static str, Style),
+    /// The glyph and style of a suggested move (e.g. a hint from the AI)
+    pub hint: (&'static str, Style),
+    /// The glyph and style of the tiles that cannot be played on
+    pub blocked: (&'static str, Style),
+    /// The background behind the piece placed by the last move, or `Color::Unset` to mark it with
+    /// brackets instead
+    pub last_move: Color,
+    /// The background behind the pieces flipped by the last move, or `Color::Unset` to mark them
+    /// with parentheses instead
+    pub flipped: Color,
+    /// The background behind the cursor when it is on a valid move, or `Color::Unset` to mark it
+    /// with `>` and `<` instead
+    pub cursor: Color,
+    /// The background behind the cursor when it is not on a valid move, or `Color::Unset` to mark
+    /// it with `!` instead
+    pub invalid_cursor: Color,
+    /// The glyphs that flipped pieces are drawn with, in order, while a flip is being animated
+    pub flip_frames: &'static [&'static str],
+    /// The line drawn between the columns of the board
+    pub vertical: char,
+    /// The line drawn between the rows of the board
+    pub horizontal: char,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+impl Theme {
+    /// Colored discs on a board drawn with box-drawing characters
+    pub fn classic() -> Self {
+        Self {
+            name: "classic",
+            x_piece: ("\u{25CF}", Style::new(Color::Red)),
+            o_piece: ("\u{25CF}", Style::new(Color::Blue)),
+            valid_move: ("\u{25CB}", Style::new(Color::Yellow)),
+            hint: ("\u{25C9}", Style::new(Color::Green).bold()),
+            blocked: ("\u{2573}", Style::default().dimmed()),
+            last_move: Color::Fixed(240),
+            flipped: Color::Fixed(236),
+            cursor: Color::Green,
+            invalid_cursor: Color::Red,
+            flip_frames: &["\u{25D0}", "\u{2503}", "\u{25D1}"],
+            vertical: '\u{2502}',
+            horizontal: '\u{2500}',
+        }
+    }
+
+    /// Letters on a board drawn with plain ASCII characters and no colors, for terminals and
+    /// fonts that cannot show anything else
+    pub fn minimal() -> Self {
+        Self {
+            name: "minimal",
+            x_piece: ("X", Style::default()),
+            o_piece: ("O", Style::default()),
+            valid_move: (".", Style::default()),
+            hint: ("*", Style::default()),
+            blocked: ("#", Style::default()),
+            last_move: Color::Unset,
+            flipped: Color::Unset,
+            cursor: Color::Unset,
+            invalid_cursor: Color::Unset,
+            flip_frames: &["o", "|", "o"],
+            vertical: '|',
+            horizontal: '-',
+        }
+    }
+
+    /// Filled and hollow discs in bright colors, so that the pieces can be told apart by their
+    /// shape as well as their color
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast",
+            x_piece: ("\u{25CF}", Style::new(Color::White).bold()),
+            o_piece: ("\u{25CB}", Style::new(Color::Cyan).bold()),
+            valid_move: ("\u{00B7}", Style::new(Color::Yellow).bold()),
+            hint: ("+", Style::new(Color::Green).bold()),
+            blocked: ("\u{2573}", Style::new(Color::Magenta)),
+            last_move: Color::Red,
+            flipped: Color::Blue,
+            cursor: Color::Green,
+            invalid_cursor: Color::Magenta,
+            flip_frames: &["\u{25D0}", "\u{2503}", "\u{25D1}"],
+            vertical: '\u{2502}',
+            horizontal: '\u{2500}',
+        }
+    }
+
+    /// Orange and blue discs, which can be told apart with the most common kinds of color
+    /// blindness
+    pub fn colorblind() -> Self {
+        Self {
+            name: "colorblind",
+            x_piece: ("\u{25CF}", Style::new(Color::Fixed(208))),
+            o_piece: ("\u{25CF}", Style::new(Color::Fixed(33))),
+            valid_move: ("\u{25CB}", Style::new(Color::Fixed(250))),
+            hint: ("\u{25C9}", Style::new(Color::White).bold()),
+            blocked: ("\u{2573}", Style::default().dimmed()),
+            last_move: Color::Fixed(240),
+            flipped: Color::Fixed(236),
+            cursor: Color::Fixed(246),
+            invalid_cursor: Color::Fixed(52),
+            flip_frames: &["\u{25D0}", "\u{2503}", "\u{25D1}"],
+            vertical: '\u{2502}',
+            horizontal: '\u{2500}',
+        }
+    }
+
+    /// Filled and hollow discs with no colors at all, so that the pieces are only told apart by
+    /// their shape
+    pub fn shapes() -> Self {
+        Self {
+            name: "shapes",
+            x_piece: ("\u{25CF}", Style::default()),
+            o_piece: ("\u{25CB}", Style::default()),
+            valid_move: ("\u{00B7}", Style::default()),
+            hint: ("+", Style::default()),
+            blocked: ("\u{2573}", Style::default()),
+            last_move: Color::Unset,
+            flipped: Color::Unset,
+            cursor: Color::Unset,
+            invalid_cursor: Color::Unset,
+            flip_frames: &["\u{25D0}", "\u{2503}", "\u{25D1}"],
+            vertical: '\u{2502}',
+            horizontal: '\u{2500}',
+        }
+    }
+
+    /// Returns the glyph and style of the given piece
+    pub fn piece(&self, piece: Piece) -> (&'static str, Style) {
+        match piece {
+            Piece::X => self.x_piece,
+            Piece::O => self.o_piece,
+        }
+    }
+
+    /// Returns the given piece painted with its glyph and style
+    pub fn paint_piece(&self, piece: Piece) -> Paint<&'static str> {
+        let (glyph, style) = self.piece(piece);
+        style.paint(glyph)
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Unknown theme: `{0}`. Expected `classic`, `minimal`, `high-contrast`, `colorblind` or `shapes`")]
+pub struct ParseThemeError(pub String);
+
+impl FromStr for Theme {
+    type Err = ParseThemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Theme::classic()),
+            "minimal" => Ok(Theme::minimal()),
+            "high-contrast" => Ok(Theme::high_contrast()),
+            "colorblind" => Ok(Theme::colorblind()),
+            "shapes" => Ok(Theme::shapes()),
+            _ => Err(ParseThemeError(s.to_string())),
+        }
+    }
+}
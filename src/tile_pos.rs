@@ -1,14 +1,91 @@
 use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
 
 /// Represents the position of a tile on the grid
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TilePos {
     pub row: usize,
     pub col: usize,
 }
 
+impl TilePos {
+    /// Returns the position offset from this one by the given number of rows and columns, or
+    /// `None` if the resulting position would be outside of the given bounds
+    ///
+    /// The bounds are given as (number of rows, number of columns).
+    pub fn offset(self, drow: isize, dcol: isize, bounds: (usize, usize)) -> Option<TilePos> {
+        let (nrows, ncols) = bounds;
+        let row = self.row as isize + drow;
+        let col = self.col as isize + dcol;
+        if row < 0 || row >= nrows as isize || col < 0 || col >= ncols as isize {
+            return None;
+        }
+
+        Some(TilePos {row: row as usize, col: col as usize})
+    }
+}
+
 impl fmt::Display for TilePos {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}{}", (b'A' + self.col as u8) as char, self.row + 1)
     }
 }
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid tile position: `{0}`")]
+pub struct ParseTilePosError(pub String);
+
+impl FromStr for TilePos {
+    type Err = ParseTilePosError;
+
+    /// Parses a position in the format "A1" or "1A" where "A" is the column and "1" is the row.
+    /// The column letter is not case-sensitive and the row may have multiple digits (e.g. "A10").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseTilePosError(s.to_string());
+
+        // The column letter is either at the start or at the end of the string
+        let (col_byte, row_str) = match s.as_bytes() {
+            [col, ..] if col.is_ascii_alphabetic() => (*col, &s[1..]),
+            [.., col] if col.is_ascii_alphabetic() => (*col, &s[..s.len()-1]),
+            _ => return Err(err()),
+        };
+
+        if row_str.is_empty() || !row_str.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(err());
+        }
+        // Rows are numbered starting at 1
+        let row = match row_str.parse::<usize>() {
+            Ok(row) if row >= 1 => row - 1,
+            _ => return Err(err()),
+        };
+        let col = (col_byte.to_ascii_uppercase() - b'A') as usize;
+
+        Ok(TilePos {row, col})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_within_bounds() {
+        let pos = TilePos {row: 3, col: 4};
+        assert_eq!(pos.offset(0, 0, (8, 8)), Some(pos));
+        assert_eq!(pos.offset(-3, 3, (8, 8)), Some(TilePos {row: 0, col: 7}));
+        assert_eq!(pos.offset(4, -4, (8, 8)), Some(TilePos {row: 7, col: 0}));
+    }
+
+    #[test]
+    fn offset_out_of_bounds() {
+        let pos = TilePos {row: 0, col: 7};
+        assert_eq!(pos.offset(-1, 0, (8, 8)), None);
+        assert_eq!(pos.offset(0, 1, (8, 8)), None);
+        assert_eq!(pos.offset(8, -7, (8, 8)), None);
+        assert_eq!(pos.offset(0, -8, (8, 8)), None);
+        // Bounds do not have to be square
+        assert_eq!(pos.offset(1, 0, (1, 8)), None);
+    }
+}
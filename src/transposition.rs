@@ -0,0 +1,88 @@
+use crate::TilePos;
+
+/// How the score stored in a transposition table entry relates to the real score of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is the exact score of the position
+    Exact,
+    /// The real score is at least this score (the search was cut off by beta)
+    Lower,
+    /// The real score is at most this score (no move could raise alpha)
+    Upper,
+}
+
+/// The result of searching a single position, as stored in the transposition table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableEntry {
+    /// The full Zobrist hash of the position, used to detect collisions between positions that
+    /// map to the same slot of the table
+    pub hash: u64,
+    /// The number of moves that were searched below this position
+    pub depth: usize,
+    /// The score of the position from the perspective of the player to move
+    pub score: i32,
+    /// How `score` relates to the real score of the position
+    pub bound: Bound,
+    /// The best move found for this position (if any)
+    pub best_move: Option<TilePos>,
+}
+
+/// A fixed-size hash table of previously searched positions, keyed on their Zobrist hashes
+///
+/// Many different orders of moves lead to the same position. Storing the result of each search
+/// means that the work only needs to be done once.
+#[derive(Debug, Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<TableEntry>>,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        // 2^18 entries is around ten megabytes, which is plenty for the depths the AI searches to
+        Self::new(1 << 18)
+    }
+}
+
+impl TranspositionTable {
+    /// Creates a new table with space for the given number of entries
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of entries is not a power of two
+    pub fn new(size: usize) -> Self {
+        assert!(size.is_power_of_two(), "bug: transposition table size must be a power of two");
+
+        Self {
+            entries: vec![None; size],
+        }
+    }
+
+    /// Returns the entry for the position with the given hash, if that position has been stored
+    pub fn probe(&self, hash: u64) -> Option<&TableEntry> {
+        self.entries[self.index(hash)].as_ref()
+            .filter(|entry| entry.hash == hash)
+    }
+
+    /// Stores the given entry, replacing any entry previously stored in the same slot unless that
+    /// entry is for the same position and was searched more deeply
+    pub fn store(&mut self, entry: TableEntry) {
+        let index = self.index(entry.hash);
+        let slot = &mut self.entries[index];
+        match slot {
+            Some(prev) if prev.hash == entry.hash && prev.depth > entry.depth => {},
+            _ => *slot = Some(entry),
+        }
+    }
+
+    /// Removes every entry from the table
+    pub fn clear(&mut self) {
+        for entry in &mut self.entries {
+            *entry = None;
+        }
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        // The table size is a power of two, so this is the same as `hash % len`
+        (hash as usize) & (self.entries.len() - 1)
+    }
+}
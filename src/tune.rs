@@ -0,0 +1,215 @@
+use crate::{Reversi, WeightedEvaluator};
+use crate::eval::total_moves;
+
+/// A position from a recorded game, labelled with how that game ended
+#[derive(Debug, Clone)]
+pub struct TrainingPosition {
+    /// The position, with the player to move being the player the result is relative to
+    pub game: Reversi,
+    /// The final result of the game for the current player: 1.0 for a win, 0.5 for a draw and
+    /// 0.0 for a loss
+    pub result: f64,
+}
+
+/// The options used to tune the weights of a `WeightedEvaluator`
+#[derive(Debug, Clone)]
+pub struct TuneOptions {
+    /// The number of passes of gradient descent to make over all of the positions
+    pub iterations: usize,
+    /// The largest amount a weight can change by in a single iteration
+    pub learning_rate: f64,
+    /// The evaluation that corresponds to a ~73% chance of winning. Larger values make the
+    /// predicted win probability less sensitive to the evaluation.
+    pub scale: f64,
+}
+
+impl Default for TuneOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 500,
+            learning_rate: 0.5,
+            scale: 200.0,
+        }
+    }
+}
+
+/// Adjusts the weights of the given evaluator to better predict the results of the given positions
+///
+/// This is Texel-style tuning: the evaluation of each position is turned into a predicted result
+/// with a logistic function and the mean squared error between the predicted and actual results
+/// is minimized with gradient descent (using Adam to adapt the step size of each weight). Since
+/// the evaluation is linear in the weights of the evaluator, the value of every term is only
+/// computed once per position.
+///
+/// Returns the mean squared error of the evaluator after tuning.
+pub fn tune(
+    evaluator: &mut WeightedEvaluator,
+    positions: &[TrainingPosition],
+    options: &TuneOptions,
+) -> f64 {
+    if positions.is_empty() {
+        return 0.0;
+    }
+
+    let all_features: Vec<_> = positions.iter().map(|pos| features(evaluator, &pos.game)).collect();
+    // The weights are laid out as [opening, endgame] pairs, in the same order as the features
+    let mut weights = evaluator.weights();
+
+    // The first and second moment estimates used by Adam
+    let mut moment = vec![0.0; weights.len()];
+    let mut velocity = vec![0.0; weights.len()];
+    let (beta1, beta2, epsilon): (f64, f64, f64) = (0.9, 0.999, 1e-8);
+
+    let mut gradient = vec![0.0; weights.len()];
+    for iteration in 1..=options.iterations {
+        gradient.iter_mut().for_each(|grad| *grad = 0.0);
+        for (pos, pos_features) in positions.iter().zip(&all_features) {
+            let predicted = sigmoid(dot(&weights, pos_features) / options.scale);
+            // Derivative of (result - predicted)^2 with respect to the evaluation
+            let error = 2.0 * (predicted - pos.result) * predicted * (1.0 - predicted) / options.scale;
+            for (grad, feature) in gradient.iter_mut().zip(pos_features) {
+                *grad += error * feature;
+            }
+        }
+
+        let count = positions.len() as f64;
+        for i in 0..weights.len() {
+            let grad = gradient[i] / count;
+            moment[i] = beta1 * moment[i] + (1.0 - beta1) * grad;
+            velocity[i] = beta2 * velocity[i] + (1.0 - beta2) * grad * grad;
+
+            let moment_hat = moment[i] / (1.0 - beta1.powi(iteration as i32));
+            let velocity_hat = velocity[i] / (1.0 - beta2.powi(iteration as i32));
+            weights[i] -= options.learning_rate * moment_hat / (velocity_hat.sqrt() + epsilon);
+        }
+    }
+
+    for (term, pair) in evaluator.terms_mut().iter_mut().zip(weights.chunks(2)) {
+        term.opening_weight = pair[0].round() as i32;
+        term.endgame_weight = pair[1].round() as i32;
+    }
+
+    prediction_error(evaluator, positions, options.scale)
+}
+
+/// Returns the mean squared error between the results predicted by the given evaluator and the
+/// actual results of the given positions
+pub fn prediction_error(evaluator: &WeightedEvaluator, positions: &[TrainingPosition], scale: f64) -> f64 {
+    if positions.is_empty() {
+        return 0.0;
+    }
+
+    let total: f64 = positions.iter().map(|pos| {
+        let score = evaluator.evaluate_f64(&pos.game);
+        let predicted = sigmoid(score / scale);
+        (pos.result - predicted).powi(2)
+    }).sum();
+
+    total / positions.len() as f64
+}
+
+/// Returns the contribution of each weight of the evaluator to the evaluation of the given
+/// position, so that the evaluation is the dot product of the weights and these features
+fn features(evaluator: &WeightedEvaluator, game: &Reversi) -> Vec<f64> {
+    let total_moves = total_moves(game.grid()) as f64;
+    let empties = (game.grid().open_count() as f64).min(total_moves);
+    let filled = total_moves - empties;
+
+    let player = game.current_player();
+    evaluator.terms().iter().flat_map(|term| {
+        let value = term.evaluator.evaluate(game, player) as f64;
+        vec![value * empties / total_moves, value * filled / total_moves]
+    }).collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+impl WeightedEvaluator {
+    /// Evaluates the position for the current player without rounding, as seen by the tuner
+    fn evaluate_f64(&self, game: &Reversi) -> f64 {
+        dot(&self.weights(), &features(self, game))
+    }
+
+    /// Returns the weights of every term as [opening, endgame] pairs
+    fn weights(&self) -> Vec<f64> {
+        self.terms().iter()
+            .flat_map(|term| vec![term.opening_weight as f64, term.endgame_weight as f64])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cmp::Ordering;
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use crate::{Evaluator, Piece, Variant};
+
+    /// Returns positions from random games, labelled by whether the player to move has more pieces
+    /// than their opponent
+    fn synthetic_positions(rng: &mut StdRng, count: usize) -> Vec<TrainingPosition> {
+        let mut positions = Vec::new();
+        while positions.len() < count {
+            let mut game = Reversi::new(Variant::Standard);
+            for _ in 0..rng.gen_range(4, 56) {
+                match game.valid_moves() {
+                    [] if game.is_game_over() => break,
+                    [] => game.pass(),
+                    moves => {
+                        let pos = moves[rng.gen_range(0, moves.len())];
+                        game.make_move(pos);
+                    },
+                }
+            }
+
+            let (x_score, o_score) = game.scores();
+            let (mine, theirs) = match game.current_player() {
+                Piece::X => (x_score, o_score),
+                Piece::O => (o_score, x_score),
+            };
+            let result = match mine.cmp(&theirs) {
+                Ordering::Greater => 1.0,
+                Ordering::Equal => 0.5,
+                Ordering::Less => 0.0,
+            };
+            positions.push(TrainingPosition {game, result});
+        }
+
+        positions
+    }
+
+    #[test]
+    fn features_match_evaluation() {
+        let evaluator = WeightedEvaluator::default();
+        let mut rng = StdRng::seed_from_u64(1560);
+        for pos in synthetic_positions(&mut rng, 100) {
+            let game = &pos.game;
+            let expected = evaluator.evaluate(game, game.current_player());
+            let score = evaluator.evaluate_f64(game);
+            // The evaluation rounds towards zero when it divides by the number of moves
+            assert!((score - expected as f64).abs() < 1.0, "{} != {}", score, expected);
+        }
+    }
+
+    #[test]
+    fn tuning_lowers_prediction_error() {
+        let mut rng = StdRng::seed_from_u64(1560);
+        let positions = synthetic_positions(&mut rng, 200);
+        let options = TuneOptions {iterations: 100, ..TuneOptions::default()};
+
+        let mut evaluator = WeightedEvaluator::default();
+        let before = prediction_error(&evaluator, &positions, options.scale);
+        let after = tune(&mut evaluator, &positions, &options);
+        assert!(after < before, "{} >= {}", after, before);
+        assert_eq!(after, prediction_error(&evaluator, &positions, options.scale));
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+/// The different rule variants that a game of reversi can be played with
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The normal rules: the player with the most pieces at the end of the game wins
+    #[default]
+    Standard,
+    /// Anti-reversi (misère): the player with the *fewest* pieces at the end of the game wins
+    Anti,
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Variant::Standard => write!(f, "standard"),
+            Variant::Anti => write!(f, "anti"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Unknown variant: `{0}`. Expected `standard` or `anti`")]
+pub struct ParseVariantError(pub String);
+
+impl FromStr for Variant {
+    type Err = ParseVariantError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Variant::Standard),
+            "anti" => Ok(Variant::Anti),
+            _ => Err(ParseVariantError(s.to_string())),
+        }
+    }
+}
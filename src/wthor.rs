@@ -0,0 +1,303 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::{Reversi, Move, Piece, Variant, TilePos, LineOutcome, TrainingPosition, from_standard_notation};
+
+/// The length of the header at the start of every WTHOR file
+const HEADER_LEN: usize = 16;
+/// The length of each game in a WTHOR game file for an 8x8 board
+const GAME_LEN: usize = 68;
+/// The number of moves stored for each game, whether or not they were all played
+const MAX_MOVES: usize = 60;
+
+#[derive(Debug, Error)]
+pub enum WthorError {
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+    #[error("File is too short to be a WTHOR game file")]
+    TruncatedHeader,
+    #[error("Unsupported board size: {0}x{0}. Only games on 8x8 boards can be imported")]
+    UnsupportedBoardSize(u8),
+    #[error("The file should have {expected} games, but only has room for {found}")]
+    TruncatedGames {
+        expected: usize,
+        found: usize,
+    },
+    #[error("Game {game}: move {index} is not a square (code {code})")]
+    InvalidSquare {
+        game: usize,
+        index: usize,
+        code: u8,
+    },
+    /// The move is given in standard Othello notation, as it is written in the file
+    #[error("Game {game}: move {index} ({pmove}) is not a valid move")]
+    IllegalMove {
+        game: usize,
+        index: usize,
+        pmove: TilePos,
+    },
+}
+
+/// A game from a WTHOR database, the binary format used by the French Othello federation (FFO)
+/// for its public archive of tournament games
+///
+/// The names of the players and tournaments are stored in separate files (`WTHOR.JOU` and
+/// `WTHOR.TRN`), so only their indexes into those files are kept here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WthorGame {
+    /// The year the game was played, which is stored once for every game in a file
+    pub year: u16,
+    /// The index of the tournament the game was played in
+    pub tournament: u16,
+    /// The index of the player with the black discs, who moves first (X)
+    pub black_player: u16,
+    /// The index of the player with the white discs (O)
+    pub white_player: u16,
+    /// The number of discs black had at the end of the game, with the empty squares counted for
+    /// the winner
+    pub black_score: u8,
+    /// The score black would have gotten with perfect play by both players from a position near
+    /// the end of the game
+    pub theoretical_score: u8,
+    /// Every move of the game on the board used by `Reversi`, including the passes that are not
+    /// written in the file
+    pub moves: Vec<Move>,
+}
+
+impl WthorGame {
+    /// Returns the game with every one of its moves played from the standard starting position
+    pub fn to_game(&self) -> Reversi {
+        let mut game = Reversi::new(Variant::Standard);
+        for &pmove in &self.moves {
+            let _ = game.apply(pmove);
+        }
+        game
+    }
+
+    /// Returns how the game ended according to its recorded score
+    ///
+    /// Games are often recorded without their last few moves (e.g. when a player resigned), so
+    /// this is not always the same as the outcome of the game returned by `to_game`.
+    pub fn outcome(&self) -> LineOutcome {
+        use std::cmp::Ordering::*;
+        match self.black_score.cmp(&32) {
+            Greater => LineOutcome::Won(Piece::X),
+            Less => LineOutcome::Won(Piece::O),
+            Equal => LineOutcome::Drawn,
+        }
+    }
+
+    /// Returns the moves of the game up to its first pass, which can be added to an opening book
+    /// with `OpeningBook::add_line_with_outcome`
+    pub fn line(&self) -> Vec<TilePos> {
+        self.moves.iter()
+            .map_while(|&pmove| match pmove {
+                Move::Place(pos) => Some(pos),
+                Move::Pass | Move::Resign => None,
+            })
+            .collect()
+    }
+
+    /// Returns every position in the game where a piece was placed, labelled with the recorded
+    /// outcome of the game for the player to move, for tuning an evaluator with `tune`
+    pub fn training_positions(&self) -> Vec<TrainingPosition> {
+        let outcome = self.outcome();
+        let mut positions = Vec::new();
+        let mut game = Reversi::new(Variant::Standard);
+        for &pmove in &self.moves {
+            if let Move::Place(_) = pmove {
+                let player = game.current_player();
+                let result = match outcome {
+                    LineOutcome::Won(winner) if winner == player => 1.0,
+                    LineOutcome::Won(_) => 0.0,
+                    LineOutcome::Drawn => 0.5,
+                };
+                positions.push(TrainingPosition {
+                    game: Reversi::from_grid(Variant::Standard, game.grid().clone(), player),
+                    result,
+                });
+            }
+            let _ = game.apply(pmove);
+        }
+
+        positions
+    }
+}
+
+/// Loads every game from a WTHOR game file (e.g. `WTH_2001.wtb`)
+pub fn load_wthor_games<P: AsRef<Path>>(path: P) -> Result<Vec<WthorGame>, WthorError> {
+    parse_wthor_games(&fs::read(path)?)
+}
+
+/// Parses every game in the contents of a WTHOR game file, replaying each game to check its moves
+/// and to find where its players passed
+///
+/// The file starts with a 16 byte header giving the number of games, the year they were played
+/// and the size of the board. Each game is 68 bytes: the tournament and player indexes (16-bit
+/// little endian), black's actual and theoretical scores and then 60 moves. Each move is written
+/// as `10 * row + column` in standard Othello notation, with both starting from 1 (e.g. 56 is F5),
+/// and the unplayed moves at the end are 0.
+pub fn parse_wthor_games(bytes: &[u8]) -> Result<Vec<WthorGame>, WthorError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(WthorError::TruncatedHeader);
+    }
+    let u16_at = |bytes: &[u8], offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+
+    let count = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+    let year = u16_at(bytes, 10);
+    // Older files use 0 for the usual 8x8 board
+    match bytes[12] {
+        0 | 8 => {},
+        size => return Err(WthorError::UnsupportedBoardSize(size)),
+    }
+
+    let records = &bytes[HEADER_LEN..];
+    if records.len() / GAME_LEN < count {
+        return Err(WthorError::TruncatedGames {expected: count, found: records.len() / GAME_LEN});
+    }
+
+    records.chunks_exact(GAME_LEN).take(count).enumerate().map(|(i, record)| {
+        Ok(WthorGame {
+            year,
+            tournament: u16_at(record, 0),
+            black_player: u16_at(record, 2),
+            white_player: u16_at(record, 4),
+            black_score: record[6],
+            theoretical_score: record[7],
+            moves: parse_moves(&record[8..8 + MAX_MOVES], i + 1)?,
+        })
+    }).collect()
+}
+
+/// Parses the moves of the game with the given number, inserting a pass wherever the player to
+/// move has no valid moves
+fn parse_moves(codes: &[u8], game_num: usize) -> Result<Vec<Move>, WthorError> {
+    let mut moves = Vec::new();
+    let mut game = Reversi::new(Variant::Standard);
+    for (i, &code) in codes.iter().enumerate() {
+        if code == 0 {
+            break;
+        }
+
+        let (row, col) = (code / 10, code % 10);
+        if !(1..=8).contains(&row) || !(1..=8).contains(&col) {
+            return Err(WthorError::InvalidSquare {game: game_num, index: i + 1, code});
+        }
+        let standard_pos = TilePos {row: row as usize - 1, col: col as usize - 1};
+        let pos = from_standard_notation(standard_pos);
+
+        if game.valid_moves().is_empty() && !game.is_game_over() {
+            moves.push(Move::Pass);
+            let _ = game.apply(Move::Pass);
+        }
+        if !game.valid_moves().contains(&pos) {
+            return Err(WthorError::IllegalMove {game: game_num, index: i + 1, pmove: standard_pos});
+        }
+        moves.push(Move::Place(pos));
+        let _ = game.apply(Move::Place(pos));
+    }
+
+    Ok(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a WTHOR file from 2001 with a game for each of the given black scores and moves
+    fn wthor_file(games: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = vec![20, 1, 6, 15];
+        bytes.extend_from_slice(&(games.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes.extend_from_slice(&2001u16.to_le_bytes());
+        bytes.extend_from_slice(&[8, 0, 22, 0]);
+        for (i, (score, moves)) in games.iter().enumerate() {
+            bytes.extend_from_slice(&7u16.to_le_bytes());
+            bytes.extend_from_slice(&(i as u16 * 2).to_le_bytes());
+            bytes.extend_from_slice(&(i as u16 * 2 + 1).to_le_bytes());
+            bytes.extend_from_slice(&[*score, *score]);
+            let mut codes = moves.clone();
+            codes.resize(MAX_MOVES, 0);
+            bytes.extend_from_slice(&codes);
+        }
+        bytes
+    }
+
+    /// Returns the code of each placement in the given game, in standard Othello notation
+    fn move_codes(game: &Reversi) -> Vec<u8> {
+        game.history().iter().filter_map(|&pmove| match pmove {
+            Move::Place(pos) => {
+                let standard_pos = from_standard_notation(pos);
+                Some((standard_pos.row as u8 + 1) * 10 + standard_pos.col as u8 + 1)
+            },
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn reads_games_and_their_details() {
+        // The tiger opening: F5 D6 C3 D3 C4
+        let bytes = wthor_file(&[(40, vec![56, 64, 33, 34, 43])]);
+        let games = parse_wthor_games(&bytes).unwrap();
+        assert_eq!(games.len(), 1);
+
+        let wthor_game = &games[0];
+        assert_eq!(wthor_game.year, 2001);
+        assert_eq!((wthor_game.tournament, wthor_game.black_player, wthor_game.white_player), (7, 0, 1));
+        assert_eq!(wthor_game.outcome(), LineOutcome::Won(Piece::X));
+        assert_eq!(wthor_game.line().len(), 5);
+        assert_eq!(wthor_game.line()[0], from_standard_notation("F5".parse().unwrap()));
+
+        let positions = wthor_game.training_positions();
+        assert_eq!(positions.len(), 5);
+        assert_eq!(positions[0].game.grid(), Reversi::default().grid());
+        assert_eq!(positions.iter().map(|pos| pos.result).collect::<Vec<_>>(), vec![1.0, 0.0, 1.0, 0.0, 1.0]);
+
+        let game = wthor_game.to_game();
+        assert_eq!(game.history().len(), 5);
+        assert_eq!(game.current_player(), Piece::O);
+    }
+
+    #[test]
+    fn inserts_passes() {
+        let mut game = Reversi::default();
+        while !game.is_game_over() {
+            match game.valid_moves().last() {
+                Some(&pos) => { game.make_move(pos); },
+                None => game.pass(),
+            }
+        }
+        let (x_score, _) = game.scores();
+
+        let bytes = wthor_file(&[(x_score as u8, move_codes(&game)), (32, vec![])]);
+        let games = parse_wthor_games(&bytes).unwrap();
+        assert_eq!(games[0].moves, game.history());
+        assert_eq!(games[0].to_game().grid(), game.grid());
+        assert!(games[1].moves.is_empty());
+        assert_eq!(games[1].outcome(), LineOutcome::Drawn);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(matches!(parse_wthor_games(&[20, 1]), Err(WthorError::TruncatedHeader)));
+
+        let mut bytes = wthor_file(&[(32, vec![56])]);
+        bytes[12] = 10;
+        assert!(matches!(parse_wthor_games(&bytes), Err(WthorError::UnsupportedBoardSize(10))));
+
+        let bytes = wthor_file(&[(32, vec![56])]);
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(matches!(parse_wthor_games(truncated), Err(WthorError::TruncatedGames {expected: 1, found: 0})));
+
+        let bytes = wthor_file(&[(32, vec![56]), (32, vec![56, 90])]);
+        assert!(matches!(parse_wthor_games(&bytes), Err(WthorError::InvalidSquare {game: 2, index: 2, code: 90})));
+
+        let bytes = wthor_file(&[(32, vec![11])]);
+        let err = parse_wthor_games(&bytes).unwrap_err();
+        assert!(matches!(err, WthorError::IllegalMove {game: 1, index: 1, ..}));
+        assert_eq!(err.to_string(), "Game 1: move 1 (A1) is not a valid move");
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::OnceLock;
+
+use rand::{Rng, seq::SliceRandom};
+use thiserror::Error;
+
+use crate::{Reversi, TilePos, Variant, ParseTilePosError};
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum OpeningError {
+    #[error("Line {line}: {error}")]
+    InvalidPosition {
+        line: usize,
+        error: ParseTilePosError,
+    },
+    #[error("Line {line}: incomplete move `{text}`")]
+    IncompleteMove {
+        line: usize,
+        text: String,
+    },
+    #[error("Move {index} of the opening ({pmove}) is not a valid move")]
+    IllegalMove {
+        index: usize,
+        pmove: TilePos,
+    },
+    #[error("No openings to choose from")]
+    NoOpenings,
+}
+
+/// The balanced 8-move openings that are compiled into the crate, in the format read by
+/// `parse_openings`
+const BUILTIN_OPENINGS: &str = include_str!("openings.txt");
+
+/// Returns the balanced 8-move openings that are compiled into the crate
+///
+/// These are not the official XOT list. They were picked with this crate's own search: every
+/// position reached after 8 moves (up to symmetry) that the AI scores as close to even. A different
+/// list (e.g. the official one) can be loaded with `parse_openings` and given to
+/// `Reversi::new_xot` instead.
+pub fn builtin_openings() -> &'static [Vec<TilePos>] {
+    static OPENINGS: OnceLock<Vec<Vec<TilePos>>> = OnceLock::new();
+    OPENINGS.get_or_init(|| {
+        parse_openings(BUILTIN_OPENINGS).expect("bug: builtin openings are invalid")
+    })
+}
+
+/// Parses a list of openings, one opening per line
+///
+/// Each opening is a sequence of moves written like "f5d6c3d3c4f4f6f3" (the format used by the
+/// XOT opening lists). Moves may optionally be separated by whitespace. Empty lines and lines
+/// starting with `#` are ignored.
+///
+/// Standard Othello notation places the first player's starting discs on D5 and E4, which is the
+/// mirror image of the starting position used by `Reversi`. Moves are mirrored horizontally while
+/// parsing so that the openings can be played on this board.
+///
+/// The openings are not checked for legality until they are played with `Reversi::from_opening`.
+pub fn parse_openings(text: &str) -> Result<Vec<Vec<TilePos>>, OpeningError> {
+    let mut openings = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        openings.push(parse_opening_line(line, i + 1)?);
+    }
+
+    Ok(openings)
+}
+
+/// Parses a single opening in the format described in `parse_openings`, reporting errors at the
+/// given line number
+pub(crate) fn parse_opening_line(line: &str, line_num: usize) -> Result<Vec<TilePos>, OpeningError> {
+    let chars: Vec<char> = line.chars().filter(|ch| !ch.is_whitespace()).collect();
+    let mut opening = Vec::new();
+    // Every move on an 8x8 board is exactly two characters long
+    for chunk in chars.chunks(2) {
+        let text: String = chunk.iter().collect();
+        if chunk.len() != 2 {
+            return Err(OpeningError::IncompleteMove {line: line_num, text});
+        }
+
+        let pmove = text.parse()
+            .map_err(|error| OpeningError::InvalidPosition {line: line_num, error})?;
+        opening.push(from_standard_notation(pmove));
+    }
+
+    Ok(opening)
+}
+
+impl Reversi {
+    /// Creates a new game and plays the given sequence of opening moves
+    pub fn from_opening(variant: Variant, opening: &[TilePos]) -> Result<Self, OpeningError> {
+        let mut game = Self::new(variant);
+        for (index, &pmove) in opening.iter().enumerate() {
+            if !game.valid_moves().contains(&pmove) {
+                return Err(OpeningError::IllegalMove {index: index + 1, pmove});
+            }
+
+            game.make_move(pmove);
+        }
+
+        Ok(game)
+    }
+
+    /// Creates a new game starting from an opening chosen at random from the given list, or from
+    /// `builtin_openings` if no list is given
+    ///
+    /// This is how XOT (eXtended Othello Tournament) games are played. The official XOT list of
+    /// balanced 8-move openings can be loaded with `parse_openings`.
+    pub fn new_xot<R: Rng + ?Sized>(
+        rng: &mut R,
+        variant: Variant,
+        openings: Option<&[Vec<TilePos>]>,
+    ) -> Result<Self, OpeningError> {
+        let openings = openings.unwrap_or_else(|| builtin_openings());
+        let opening = openings.choose(rng).ok_or(OpeningError::NoOpenings)?;
+        Self::from_opening(variant, opening)
+    }
+}
+
+/// Converts a move from standard Othello notation to the equivalent position on the board used by
+/// `Reversi` by mirroring it horizontally
+pub fn from_standard_notation(pos: TilePos) -> TilePos {
+    // Standard notation is always for an 8x8 board
+    TilePos {row: pos.row, col: 7 - pos.col.min(7)}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn pos(text: &str) -> TilePos {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn standard_notation_is_mirrored() {
+        // The first player's usual first move in standard notation
+        assert_eq!(from_standard_notation(pos("F5")), pos("C5"));
+        assert_eq!(from_standard_notation(pos("A1")), pos("H1"));
+        assert_eq!(from_standard_notation(pos("H8")), pos("A8"));
+        for row in 0..8 {
+            for col in 0..8 {
+                let pos = TilePos {row, col};
+                assert_eq!(from_standard_notation(from_standard_notation(pos)), pos);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_openings_skips_comments_and_blank_lines() {
+        let text = "# Two openings\n\nf5d6c3d3c4f4f6f3\n  f5 f6 e6 f4  \n";
+        let openings = parse_openings(text).unwrap();
+        assert_eq!(openings.len(), 2);
+        assert_eq!(openings[0].len(), 8);
+        assert_eq!(openings[0][..2], [pos("C5"), pos("E6")]);
+        assert_eq!(openings[1], [pos("C5"), pos("C6"), pos("D6"), pos("C4")]);
+    }
+
+    #[test]
+    fn parse_openings_errors() {
+        assert_eq!(
+            parse_openings("f5d6\nf5d6c"),
+            Err(OpeningError::IncompleteMove {line: 2, text: "c".to_string()}),
+        );
+        match parse_openings("# comment\nf5d0") {
+            Err(OpeningError::InvalidPosition {line: 2, ..}) => {},
+            result => panic!("expected an invalid position, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn play_openings() {
+        let openings = parse_openings("f5d6c3d3c4f4f6f3\nf5f5").unwrap();
+        let game = Reversi::from_opening(Variant::Standard, &openings[0]).unwrap();
+        assert_eq!(game.history().len(), 8);
+        let (x_score, o_score) = game.scores();
+        assert_eq!(x_score + o_score, 12);
+
+        assert_eq!(
+            Reversi::from_opening(Variant::Standard, &openings[1]).unwrap_err(),
+            OpeningError::IllegalMove {index: 2, pmove: pos("C5")},
+        );
+        assert_eq!(
+            Reversi::new_xot(&mut StdRng::seed_from_u64(1533), Variant::Standard, Some(&[])).unwrap_err(),
+            OpeningError::NoOpenings,
+        );
+    }
+
+    #[test]
+    fn builtin_openings_are_legal() {
+        let openings = builtin_openings();
+        assert!(openings.len() > 1000);
+        for opening in openings {
+            assert_eq!(opening.len(), 8);
+            let game = Reversi::from_opening(Variant::Standard, opening).unwrap();
+            assert!(!game.valid_moves().is_empty());
+        }
+
+        let mut rng = StdRng::seed_from_u64(1533);
+        let game = Reversi::new_xot(&mut rng, Variant::Standard, None).unwrap();
+        assert_eq!(game.history().len(), 8);
+    }
+}
@@ -0,0 +1,139 @@
+//! Zobrist hashing of game positions
+//!
+//! Every (tile, piece) combination is assigned a random 64-bit key. The hash of a position is the
+//! XOR of the keys of all of the pieces on the grid, along with an extra key if it is O's turn.
+//! Since XOR is its own inverse, the hash can be updated incrementally as pieces are placed and
+//! flipped.
+//!
+//! Based on: <https://en.wikipedia.org/wiki/Zobrist_hashing>
+
+use crate::{Grid, Piece, TilePos};
+
+/// The number of tiles on the grid
+const NTILES: usize = 8 * 8;
+
+/// The keys for each piece on each tile, indexed by `tile_index` and then by piece
+const PIECE_KEYS: [[u64; 2]; NTILES] = generate_piece_keys();
+
+/// The key XORed into the hash when it is O's turn
+const O_TO_MOVE_KEY: u64 = splitmix64(0x4F5F544F5F4D4F56).1;
+
+/// Returns the Zobrist hash of the given grid with the given player to move
+pub fn zobrist_hash(grid: &Grid, current_player: Piece) -> u64 {
+    let mut hash = player_key(current_player);
+    for (row, row_tiles) in grid.rows().iter().enumerate() {
+        for (col, tile) in row_tiles.iter().enumerate() {
+            if let &Some(piece) = tile {
+                hash ^= piece_key(TilePos {row, col}, piece);
+            }
+        }
+    }
+
+    hash
+}
+
+/// Returns the key for the given piece being on the tile at the given position
+pub(crate) fn piece_key(pos: TilePos, piece: Piece) -> u64 {
+    let piece_index = match piece {
+        Piece::X => 0,
+        Piece::O => 1,
+    };
+
+    PIECE_KEYS[pos.row * 8 + pos.col][piece_index]
+}
+
+/// Returns the key for the given player being the player to move
+pub(crate) fn player_key(piece: Piece) -> u64 {
+    match piece {
+        Piece::X => 0,
+        Piece::O => O_TO_MOVE_KEY,
+    }
+}
+
+/// Generates the keys with a fixed seed so that hashes are the same every time the program runs
+const fn generate_piece_keys() -> [[u64; 2]; NTILES] {
+    let mut keys = [[0; 2]; NTILES];
+    let mut state = 0x5EEDB0A2D6A3E5;
+    let mut i = 0;
+    while i < NTILES {
+        let (next_state, x_key) = splitmix64(state);
+        let (next_state, o_key) = splitmix64(next_state);
+        keys[i] = [x_key, o_key];
+        state = next_state;
+        i += 1;
+    }
+
+    keys
+}
+
+/// A small, fast pseudo-random number generator. Returns the next state and the generated value.
+///
+/// Based on: https://prng.di.unimi.it/splitmix64.c
+pub(crate) const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    (state, z ^ (z >> 31))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use crate::{Reversi, Variant};
+
+    #[test]
+    fn incremental_hash_matches_full_hash() {
+        let mut rng = StdRng::seed_from_u64(1539);
+        for variant in [Variant::Standard, Variant::Anti] {
+            for _ in 0..50 {
+                let mut game = Reversi::new(variant);
+                assert_eq!(game.hash(), zobrist_hash(game.grid(), game.current_player()));
+                while !game.is_game_over() {
+                    match game.valid_moves() {
+                        [] => game.pass(),
+                        moves => {
+                            let pos = moves[rng.gen_range(0, moves.len())];
+                            game.make_move(pos);
+                        },
+                    }
+                    assert_eq!(game.hash(), zobrist_hash(game.grid(), game.current_player()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transpositions_have_the_same_hash() {
+        // Every position after three moves, along with the moves that reached it
+        let mut games = vec![Reversi::default()];
+        for _ in 0..3 {
+            games = games.iter().flat_map(|game| game.valid_moves().iter().map(move |&pos| {
+                let mut next = game.clone();
+                next.make_move(pos);
+                next
+            })).collect();
+        }
+
+        let mut transpositions = 0;
+        for (i, first) in games.iter().enumerate() {
+            for second in &games[i + 1..] {
+                if first.grid() == second.grid() {
+                    assert_ne!(first.history(), second.history());
+                    assert_eq!(first.hash(), second.hash());
+                    transpositions += 1;
+                } else {
+                    assert_ne!(first.hash(), second.hash());
+                }
+            }
+        }
+        assert!(transpositions > 0);
+
+        // The same grid with the other player to move is a different position
+        let grid = games[0].grid();
+        assert_ne!(zobrist_hash(grid, Piece::X), zobrist_hash(grid, Piece::O));
+    }
+}